@@ -0,0 +1,142 @@
+//! Approving tool use from the app before Claude Code goes ahead with it.
+//!
+//! Claude Code waits for the output of its PreToolUse hook, and that output can
+//! carry a permission decision. With approvals enabled the hook server holds
+//! the request for the tools listed in `~/.ccconfig/approval.json`, asks the
+//! user, and answers with their decision. Without an answer before the timeout
+//! it answers with `timeout_decision`.
+
+use serde_json::{json, Value};
+
+use crate::fsio;
+use crate::hooks;
+use crate::paths::Paths;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+const MIN_TIMEOUT_SECS: u64 = 5;
+/// The installed hook gives up a minute after this, see [`crate::hooks::APPROVAL_HOOK_TIMEOUT_SECS`]
+pub const MAX_TIMEOUT_SECS: u64 = 3600;
+const MAX_SUMMARY_CHARS: usize = 120;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionDecision {
+    /// Use the tool without asking in Claude Code
+    Allow,
+    /// Don't use the tool, and tell Claude why
+    Deny,
+    /// Leave it to Claude Code's own permission prompt
+    Ask,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ApprovalSettings {
+    pub enabled: bool,
+    /// Seconds to wait for an answer
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Answer given when the user doesn't answer in time
+    #[serde(default = "default_timeout_decision")]
+    pub timeout_decision: PermissionDecision,
+    /// Tools that need approval, every tool if empty
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+impl Default for ApprovalSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            timeout_decision: default_timeout_decision(),
+            tools: vec![],
+        }
+    }
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+fn default_timeout_decision() -> PermissionDecision {
+    PermissionDecision::Ask
+}
+
+impl ApprovalSettings {
+    /// Whether using `tool_name` has to wait for the user
+    pub fn requires_approval(&self, tool_name: &str) -> bool {
+        self.enabled && (self.tools.is_empty() || self.tools.iter().any(|tool| tool == tool_name))
+    }
+}
+
+pub fn get_approval_settings(paths: &Paths) -> Result<ApprovalSettings, String> {
+    Ok(fsio::read_json(&paths.approval_file(), "approval settings")?.unwrap_or_default())
+}
+
+pub fn update_approval_settings(
+    paths: &Paths,
+    mut settings: ApprovalSettings,
+) -> Result<ApprovalSettings, String> {
+    if !(MIN_TIMEOUT_SECS..=MAX_TIMEOUT_SECS).contains(&settings.timeout_secs) {
+        return Err(format!(
+            "Approval timeout must be between {} and {} seconds",
+            MIN_TIMEOUT_SECS, MAX_TIMEOUT_SECS
+        ));
+    }
+    let mut tools: Vec<String> = vec![];
+    for tool in settings.tools.iter().map(|tool| tool.trim()) {
+        if !tool.is_empty() && !tools.iter().any(|existing| existing == tool) {
+            tools.push(tool.to_string());
+        }
+    }
+    settings.tools = tools;
+
+    fsio::write_json(
+        paths,
+        &paths.approval_file(),
+        "approval settings",
+        &settings,
+    )?;
+
+    // Approvals need the PreToolUse hook even when its notifications are off
+    hooks::sync_claude_code_hook(paths)?;
+    Ok(settings)
+}
+
+/// PreToolUse hook output telling Claude Code what to do with the tool call
+pub fn hook_response(decision: PermissionDecision, reason: &str) -> Value {
+    json!({
+        "hookSpecificOutput": {
+            "hookEventName": "PreToolUse",
+            "permissionDecision": decision,
+            "permissionDecisionReason": reason,
+        }
+    })
+}
+
+/// One line describing a tool call, e.g. `Bash: rm -rf build`
+pub fn describe_tool_use(tool_name: &str, tool_input: &Value) -> String {
+    let detail = [
+        "command",
+        "file_path",
+        "url",
+        "pattern",
+        "query",
+        "description",
+    ]
+    .iter()
+    .find_map(|key| tool_input.get(*key).and_then(|v| v.as_str()))
+    .map(str::to_string)
+    .unwrap_or_else(|| match tool_input {
+        Value::Null => String::new(),
+        input => input.to_string(),
+    });
+    let detail = detail.split_whitespace().collect::<Vec<_>>().join(" ");
+    if detail.is_empty() {
+        return tool_name.to_string();
+    }
+    match detail.char_indices().nth(MAX_SUMMARY_CHARS) {
+        Some((cut, _)) => format!("{}: {}…", tool_name, &detail[..cut]),
+        None => format!("{}: {}", tool_name, detail),
+    }
+}
@@ -0,0 +1,760 @@
+//! Rules that switch the active profile automatically.
+//!
+//! Rules are kept in `~/.ccconfig/auto_switch.json` and evaluated by the
+//! desktop app on an interval. A rule fires when its condition starts to hold,
+//! so a manual switch made while the condition still holds is left alone until
+//! the condition clears and holds again. Every switch attempt is appended to
+//! the audit log `~/.ccconfig/auto_switch_log.jsonl`.
+
+use chrono::{DateTime, Datelike, Duration, Local, Timelike};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+
+use crate::codex;
+use crate::fsio;
+use crate::health::{self, ProbeRequest, ProfileHealth};
+use crate::paths::Paths;
+use crate::stores;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const MIN_INTERVAL_SECS: u64 = 15;
+const DEFAULT_PUBLIC_IP_URL: &str = "https://api.ipify.org";
+const MAX_LOG_ENTRIES: usize = 500;
+
+// Schedules are matched against every minute since the previous evaluation, up to a day
+const MAX_SCHEDULE_WINDOW_MINUTES: i64 = 24 * 60;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct AutoSwitchSettings {
+    pub enabled: bool,
+    /// Seconds between two evaluations of the rules
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    /// Plain text service returning the caller's public IP
+    #[serde(default = "default_public_ip_url")]
+    pub public_ip_url: String,
+    /// Evaluated in order, the first rule to fire for a target wins
+    #[serde(default)]
+    pub rules: Vec<SwitchRule>,
+}
+
+impl Default for AutoSwitchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            public_ip_url: DEFAULT_PUBLIC_IP_URL.to_string(),
+            rules: vec![],
+        }
+    }
+}
+
+fn default_interval_secs() -> u64 {
+    DEFAULT_INTERVAL_SECS
+}
+
+fn default_public_ip_url() -> String {
+    DEFAULT_PUBLIC_IP_URL.to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Which kind of profile a rule switches
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SwitchTarget {
+    Claude,
+    Codex,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct SwitchRule {
+    /// Generated when empty
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub target: SwitchTarget,
+    /// Profile to switch to when the condition starts to hold
+    pub store_id: String,
+    pub condition: RuleCondition,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    /// Cron-like `minute hour day month weekday` expression, in local time
+    Schedule { cron: String },
+    /// The public IP is one of `addresses`, plain IPs or CIDR ranges
+    PublicIp { addresses: Vec<String> },
+    /// The endpoint takes longer than `threshold_ms` to answer, or does not answer.
+    /// Defaults to the active profile's endpoint.
+    Latency {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+        threshold_ms: u64,
+    },
+    /// The endpoint does not answer. Without `url`, the active profile fails an
+    /// authenticated request, see [`health`].
+    HealthCheck {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+    },
+}
+
+/// One automatic switch attempt
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct AuditEntry {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub target: SwitchTarget,
+    /// Profile that was active before
+    pub from_store: Option<String>,
+    pub to_store: String,
+    pub to_title: Option<String>,
+    /// Why the rule fired
+    pub reason: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of probing an endpoint
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+pub struct EndpointProbe {
+    pub healthy: bool,
+    pub latency_ms: u64,
+}
+
+/// What the app measured before evaluating the rules
+#[derive(Debug, Clone, Default)]
+pub struct Observations {
+    pub public_ip: Option<IpAddr>,
+    /// Keyed by endpoint URL, see [`endpoints_to_probe`]
+    pub probes: HashMap<String, EndpointProbe>,
+    /// Health of the active profiles, see [`profiles_to_check`]
+    pub profiles: HashMap<SwitchTarget, ProfileHealth>,
+}
+
+pub fn get_auto_switch_settings(paths: &Paths) -> Result<AutoSwitchSettings, String> {
+    Ok(fsio::read_json(&paths.auto_switch_file(), "auto switch settings")?.unwrap_or_default())
+}
+
+/// Validate and save the rules, generating ids for new ones.
+pub fn update_auto_switch_settings(
+    paths: &Paths,
+    mut settings: AutoSwitchSettings,
+) -> Result<AutoSwitchSettings, String> {
+    for rule in &mut settings.rules {
+        if rule.id.is_empty() {
+            rule.id = nanoid::nanoid!(8);
+        }
+    }
+    ensure_valid_settings(paths, &settings)?;
+
+    fsio::write_json(
+        paths,
+        &paths.auto_switch_file(),
+        "auto switch settings",
+        &settings,
+    )?;
+    Ok(settings)
+}
+
+fn ensure_valid_settings(paths: &Paths, settings: &AutoSwitchSettings) -> Result<(), String> {
+    if settings.interval_secs < MIN_INTERVAL_SECS {
+        return Err(format!(
+            "Rules cannot be checked more often than every {} seconds",
+            MIN_INTERVAL_SECS
+        ));
+    }
+
+    let claude_stores = stores::get_stores(paths)?;
+    let codex_stores = codex::get_codex_stores(paths)?;
+    let mut ids: HashSet<&str> = HashSet::new();
+    for rule in &settings.rules {
+        if !ids.insert(&rule.id) {
+            return Err(format!("Duplicate rule id '{}'", rule.id));
+        }
+
+        let invalid = |message: String| format!("Rule \"{}\": {}", rule.name, message);
+        match &rule.condition {
+            RuleCondition::Schedule { cron } => {
+                CronSchedule::parse(cron).map_err(invalid)?;
+            }
+            RuleCondition::PublicIp { addresses } => {
+                if addresses.is_empty() {
+                    return Err(invalid("no IP addresses given".to_string()));
+                }
+                for address in addresses {
+                    parse_ip_range(address).map_err(invalid)?;
+                }
+            }
+            RuleCondition::Latency { threshold_ms, .. } if *threshold_ms == 0 => {
+                return Err(invalid("latency threshold must be above 0 ms".to_string()));
+            }
+            RuleCondition::Latency { .. } | RuleCondition::HealthCheck { .. } => {}
+        }
+
+        let exists = match rule.target {
+            SwitchTarget::Claude => claude_stores.iter().any(|store| store.id == rule.store_id),
+            SwitchTarget::Codex => codex_stores.iter().any(|store| store.id == rule.store_id),
+        };
+        if !exists {
+            return Err(invalid(format!("profile '{}' not found", rule.store_id)));
+        }
+    }
+    Ok(())
+}
+
+/// Whether any enabled rule needs the public IP
+pub fn needs_public_ip(settings: &AutoSwitchSettings) -> bool {
+    settings
+        .rules
+        .iter()
+        .any(|rule| rule.enabled && matches!(rule.condition, RuleCondition::PublicIp { .. }))
+}
+
+/// Targets whose active profile the enabled health rules check
+pub fn profiles_to_check(settings: &AutoSwitchSettings) -> Vec<SwitchTarget> {
+    let mut targets = Vec::new();
+    for rule in settings.rules.iter().filter(|rule| rule.enabled) {
+        if matches!(rule.condition, RuleCondition::HealthCheck { url: None })
+            && !targets.contains(&rule.target)
+        {
+            targets.push(rule.target);
+        }
+    }
+    targets
+}
+
+/// Health check request for the active profile of `target`
+pub fn active_profile_probe(
+    paths: &Paths,
+    target: SwitchTarget,
+) -> Result<Option<ProbeRequest>, String> {
+    match target {
+        SwitchTarget::Claude => match stores::get_current_store(paths)? {
+            Some(store) => Ok(Some(health::claude_probe(paths, &store.id)?)),
+            None => Ok(None),
+        },
+        SwitchTarget::Codex => match codex::get_current_codex_store(paths)? {
+            Some(store) => Ok(Some(health::codex_probe(paths, &store.id)?)),
+            None => Ok(None),
+        },
+    }
+}
+
+/// Endpoints the enabled latency and health rules watch
+pub fn endpoints_to_probe(paths: &Paths, settings: &AutoSwitchSettings) -> Vec<String> {
+    let mut endpoints: Vec<String> = Vec::new();
+    for rule in settings.rules.iter().filter(|rule| rule.enabled) {
+        if let Some(endpoint) = rule_endpoint(paths, rule) {
+            if !endpoints.contains(&endpoint) {
+                endpoints.push(endpoint);
+            }
+        }
+    }
+    endpoints
+}
+
+fn rule_endpoint(paths: &Paths, rule: &SwitchRule) -> Option<String> {
+    match &rule.condition {
+        RuleCondition::Latency { url, .. } => url
+            .clone()
+            .or_else(|| active_endpoint(paths, rule.target).ok().flatten()),
+        RuleCondition::HealthCheck { url } => url.clone(),
+        _ => None,
+    }
+}
+
+/// Base URL the active profile of `target` sends requests to
+pub fn active_endpoint(paths: &Paths, target: SwitchTarget) -> Result<Option<String>, String> {
+    match target {
+        SwitchTarget::Claude => {
+            let Some(stores_data) = stores::read_stores_data(paths)? else {
+                return Ok(None);
+            };
+            let Some(store) = stores_data.configs.iter().find(|store| store.using) else {
+                return Ok(None);
+            };
+            let settings = stores::resolve_store_settings(&stores_data, &store.id)?;
+            Ok(Some(health::claude_base_url(&settings).to_string()))
+        }
+        SwitchTarget::Codex => Ok(codex::get_current_codex_store(paths)?
+            .map(|store| codex::codex_base_url(&store).to_string())),
+    }
+}
+
+fn active_store_id(paths: &Paths, target: SwitchTarget) -> Option<String> {
+    match target {
+        SwitchTarget::Claude => stores::get_current_store(paths)
+            .ok()
+            .flatten()
+            .map(|s| s.id),
+        SwitchTarget::Codex => codex::get_current_codex_store(paths)
+            .ok()
+            .flatten()
+            .map(|s| s.id),
+    }
+}
+
+/// Remembers which rules held at the previous evaluation, so each fires once per onset
+#[derive(Debug, Default)]
+pub struct RuleEvaluator {
+    holding: HashSet<String>,
+    last_run: Option<DateTime<Local>>,
+}
+
+impl RuleEvaluator {
+    /// Switch profiles for the rules whose condition started to hold since the last evaluation.
+    ///
+    /// Returns the switches attempted, which are also written to the audit log.
+    pub fn evaluate(
+        &mut self,
+        paths: &Paths,
+        settings: &AutoSwitchSettings,
+        now: DateTime<Local>,
+        observations: &Observations,
+    ) -> Vec<AuditEntry> {
+        let mut entries = Vec::new();
+        let mut holding: HashSet<String> = HashSet::new();
+        let mut switched: HashSet<SwitchTarget> = HashSet::new();
+
+        for rule in settings.rules.iter().filter(|rule| rule.enabled) {
+            let Some(reason) = self.condition_holds(paths, rule, now, observations) else {
+                continue;
+            };
+            holding.insert(rule.id.clone());
+            if self.holding.contains(&rule.id) || switched.contains(&rule.target) {
+                continue;
+            }
+
+            let from_store = active_store_id(paths, rule.target);
+            if from_store.as_deref() == Some(rule.store_id.as_str()) {
+                continue;
+            }
+            switched.insert(rule.target);
+            entries.push(switch_profile(
+                paths,
+                (&rule.id, &rule.name),
+                rule.target,
+                &rule.store_id,
+                from_store,
+                reason,
+            ));
+        }
+
+        self.holding = holding;
+        self.last_run = Some(now);
+        entries
+    }
+
+    /// Why the rule's condition holds, `None` if it does not
+    fn condition_holds(
+        &self,
+        paths: &Paths,
+        rule: &SwitchRule,
+        now: DateTime<Local>,
+        observations: &Observations,
+    ) -> Option<String> {
+        match &rule.condition {
+            RuleCondition::Schedule { cron } => {
+                let schedule = CronSchedule::parse(cron).ok()?;
+                schedule
+                    .matches_since(self.last_run, now)
+                    .then(|| format!("schedule \"{}\" matched", cron))
+            }
+            RuleCondition::PublicIp { addresses } => {
+                let ip = observations.public_ip?;
+                addresses
+                    .iter()
+                    .any(|address| ip_in_range(address, ip))
+                    .then(|| format!("public IP {} matched", ip))
+            }
+            RuleCondition::Latency { threshold_ms, .. } => {
+                let endpoint = rule_endpoint(paths, rule)?;
+                let probe = observations.probes.get(&endpoint)?;
+                if !probe.healthy {
+                    Some(format!("{} did not answer", endpoint))
+                } else if probe.latency_ms > *threshold_ms {
+                    Some(format!(
+                        "{} took {} ms (limit {} ms)",
+                        endpoint, probe.latency_ms, threshold_ms
+                    ))
+                } else {
+                    None
+                }
+            }
+            RuleCondition::HealthCheck { url: None } => {
+                let health = observations.profiles.get(&rule.target)?;
+                (!health.healthy()).then(|| {
+                    format!(
+                        "active profile failed its health check ({})",
+                        health.error.as_deref().unwrap_or("no answer")
+                    )
+                })
+            }
+            RuleCondition::HealthCheck { .. } => {
+                let endpoint = rule_endpoint(paths, rule)?;
+                let probe = observations.probes.get(&endpoint)?;
+                (!probe.healthy).then(|| format!("{} failed its health check", endpoint))
+            }
+        }
+    }
+}
+
+/// Switch the active profile of `target` on behalf of `(rule_id, rule_name)` and log the attempt
+pub fn switch_profile(
+    paths: &Paths,
+    (rule_id, rule_name): (&str, &str),
+    target: SwitchTarget,
+    store_id: &str,
+    from_store: Option<String>,
+    reason: String,
+) -> AuditEntry {
+    let to_title = match target {
+        SwitchTarget::Claude => stores::get_store(paths, store_id).map(|store| store.title),
+        SwitchTarget::Codex => codex::get_codex_stores(paths).and_then(|stores| {
+            stores
+                .into_iter()
+                .find(|store| store.id == store_id)
+                .map(|store| store.title)
+                .ok_or_else(|| format!("Codex store with id '{}' not found", store_id))
+        }),
+    };
+    let result = to_title
+        .as_ref()
+        .map_err(Clone::clone)
+        .and_then(|_| match target {
+            SwitchTarget::Claude => stores::set_using_config(paths, store_id),
+            SwitchTarget::Codex => codex::set_using_codex_store(paths, store_id),
+        });
+
+    match &result {
+        Ok(()) => eprintln!(
+            "🔀 Rule \"{}\" switched to {} because {}",
+            rule_name, store_id, reason
+        ),
+        Err(e) => eprintln!("Rule \"{}\" failed to switch: {}", rule_name, e),
+    }
+
+    let entry = AuditEntry {
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        rule_id: rule_id.to_string(),
+        rule_name: rule_name.to_string(),
+        target,
+        from_store,
+        to_store: store_id.to_string(),
+        to_title: to_title.ok(),
+        reason,
+        success: result.is_ok(),
+        error: result.err(),
+    };
+    if let Err(e) = append_audit_entry(paths, &entry) {
+        eprintln!("Warning: Failed to write auto switch log: {}", e);
+    }
+    entry
+}
+
+pub(crate) fn append_audit_entry(paths: &Paths, entry: &AuditEntry) -> Result<(), String> {
+    let path = paths.auto_switch_log_file();
+    let _lock = fsio::lock(paths, &path)?;
+
+    let mut lines: Vec<String> = match std::fs::read_to_string(&path) {
+        Ok(content) => content.lines().map(str::to_string).collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(format!("Failed to read auto switch log: {}", e)),
+    };
+    lines.push(
+        serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize auto switch log entry: {}", e))?,
+    );
+
+    let start = lines.len().saturating_sub(MAX_LOG_ENTRIES);
+    let content = format!("{}\n", lines[start..].join("\n"));
+    fsio::write_atomic(&path, content.as_bytes())
+        .map_err(|e| format!("Failed to write auto switch log: {}", e))
+}
+
+/// Automatic switches, newest first
+pub fn get_auto_switch_log(paths: &Paths, limit: usize) -> Result<Vec<AuditEntry>, String> {
+    let content = match std::fs::read_to_string(paths.auto_switch_log_file()) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("Failed to read auto switch log: {}", e)),
+    };
+
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect())
+}
+
+/// Five-field cron expression: minute, hour, day of month, month and day of week
+/// (0 or 7 is Sunday). Fields take `*`, numbers, ranges `a-b`, steps `*/n`, `a/n`
+/// or `a-b/n`, and comma separated lists of those.
+struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "Invalid schedule \"{}\": expected minute hour day month weekday",
+                expression
+            ));
+        };
+
+        let mut weekdays = parse_cron_field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            minutes: parse_cron_field(minute, 0, 59)?,
+            hours: parse_cron_field(hour, 0, 23)?,
+            days: parse_cron_field(day, 1, 31)?,
+            months: parse_cron_field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn matches(&self, time: &DateTime<Local>) -> bool {
+        let has = |mask: u64, value: u32| mask & (1 << value) != 0;
+        let day = has(self.days, time.day());
+        let weekday = has(self.weekdays, time.weekday().num_days_from_sunday());
+
+        // Like cron, when both day fields are restricted either one matching is enough
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        };
+        has(self.minutes, time.minute())
+            && has(self.hours, time.hour())
+            && has(self.months, time.month())
+            && day_matches
+    }
+
+    /// Whether any minute after `since` up to `now` matches, only `now` without a `since`
+    fn matches_since(&self, since: Option<DateTime<Local>>, now: DateTime<Local>) -> bool {
+        let now = start_of_minute(now);
+        let since = since.map(start_of_minute).filter(|since| {
+            *since < now && now - *since <= Duration::minutes(MAX_SCHEDULE_WINDOW_MINUTES)
+        });
+        let Some(since) = since else {
+            return self.matches(&now);
+        };
+
+        let mut minute = since + Duration::minutes(1);
+        while minute <= now {
+            if self.matches(&minute) {
+                return true;
+            }
+            minute += Duration::minutes(1);
+        }
+        false
+    }
+}
+
+fn start_of_minute(time: DateTime<Local>) -> DateTime<Local> {
+    time.with_second(0)
+        .and_then(|time| time.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("Invalid schedule field \"{}\"", field);
+    let value = |text: &str| {
+        text.parse::<u32>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(invalid)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(invalid)?,
+            ),
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let start = value(range)?;
+            // `a/n` runs from a to the end of the range
+            (start, if step > 1 { max } else { start })
+        };
+        if start > end {
+            return Err(invalid());
+        }
+
+        for value in (start..=end).step_by(step) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// Parse `address` or `address/prefix`
+fn parse_ip_range(pattern: &str) -> Result<(IpAddr, u32), String> {
+    let invalid = || format!("Invalid IP address or range \"{}\"", pattern);
+    let (address, prefix) = match pattern.trim().split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (pattern.trim(), None),
+    };
+
+    let address: IpAddr = address.parse().map_err(|_| invalid())?;
+    let bits = if address.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        Some(prefix) => prefix
+            .parse::<u32>()
+            .ok()
+            .filter(|prefix| *prefix <= bits)
+            .ok_or_else(invalid)?,
+        None => bits,
+    };
+    Ok((address, prefix))
+}
+
+fn ip_in_range(pattern: &str, ip: IpAddr) -> bool {
+    let Ok((network, prefix)) = parse_ip_range(pattern) else {
+        return false;
+    };
+    match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(network) & mask == u32::from(ip) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(network) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // 2025-01-06 is a Monday
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 1, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn schedule(expression: &str) -> CronSchedule {
+        CronSchedule::parse(expression).unwrap()
+    }
+
+    #[test]
+    fn parses_ranges_steps_and_lists() {
+        assert_eq!(parse_cron_field("*", 0, 5).unwrap(), 0b111111);
+        assert_eq!(parse_cron_field("1-3", 0, 59).unwrap(), 0b1110);
+        assert_eq!(
+            parse_cron_field("*/15", 0, 59).unwrap(),
+            1 | 1 << 15 | 1 << 30 | 1 << 45
+        );
+        assert_eq!(parse_cron_field("50/5", 0, 59).unwrap(), 1 << 50 | 1 << 55);
+        assert_eq!(
+            parse_cron_field("0-10/5,30", 0, 59).unwrap(),
+            1 | 1 << 5 | 1 << 10 | 1 << 30
+        );
+
+        for invalid in ["", "60", "5-1", "*/0", "a", "1-"] {
+            assert!(parse_cron_field(invalid, 0, 59).is_err(), "{}", invalid);
+        }
+        assert!(CronSchedule::parse("0 9 * *").is_err());
+        // Ranges don't wrap, an overnight window is written as a list
+        assert!(CronSchedule::parse("0 22-6 * * *").is_err());
+    }
+
+    #[test]
+    fn matches_hours_and_overnight_lists() {
+        let office = schedule("*/30 9-17 * * *");
+        assert!(office.matches(&at(6, 9, 0)));
+        assert!(office.matches(&at(6, 17, 30)));
+        assert!(!office.matches(&at(6, 9, 15)));
+        assert!(!office.matches(&at(6, 18, 0)));
+
+        let night = schedule("0 22-23,0-5 * * *");
+        assert!(night.matches(&at(6, 23, 0)));
+        assert!(night.matches(&at(7, 0, 0)));
+        assert!(night.matches(&at(7, 5, 0)));
+        assert!(!night.matches(&at(7, 6, 0)));
+    }
+
+    #[test]
+    fn matches_weekdays_with_sunday_as_zero_or_seven() {
+        let workdays = schedule("0 9 * * 1-5");
+        assert!(workdays.matches(&at(6, 9, 0)));
+        assert!(workdays.matches(&at(10, 9, 0)));
+        assert!(!workdays.matches(&at(11, 9, 0)));
+        assert!(!workdays.matches(&at(12, 9, 0)));
+
+        for sunday in ["0 9 * * 0", "0 9 * * 7"] {
+            assert!(schedule(sunday).matches(&at(12, 9, 0)), "{}", sunday);
+            assert!(!schedule(sunday).matches(&at(11, 9, 0)), "{}", sunday);
+        }
+
+        // With both day fields restricted, either one is enough, like cron
+        let first_or_friday = schedule("0 9 1 * 5");
+        assert!(first_or_friday.matches(&at(1, 9, 0)));
+        assert!(first_or_friday.matches(&at(10, 9, 0)));
+        assert!(!first_or_friday.matches(&at(9, 9, 0)));
+    }
+
+    #[test]
+    fn matches_every_minute_since_the_last_run_past_midnight() {
+        let midnight = schedule("0 0 * * *");
+        assert!(midnight.matches_since(Some(at(6, 23, 58)), at(7, 0, 2)));
+        assert!(!midnight.matches_since(Some(at(7, 0, 0)), at(7, 0, 2)));
+        // Without a last run, or after too long a gap, only the current minute counts
+        assert!(!midnight.matches_since(None, at(7, 0, 2)));
+        assert!(midnight.matches_since(None, at(7, 0, 0)));
+        assert!(!midnight.matches_since(Some(at(5, 23, 0)), at(7, 0, 2)));
+    }
+
+    #[test]
+    fn matches_ip_addresses_and_ranges() {
+        let ip = |text: &str| text.parse::<IpAddr>().unwrap();
+        assert!(ip_in_range("203.0.113.7", ip("203.0.113.7")));
+        assert!(!ip_in_range("203.0.113.7", ip("203.0.113.8")));
+        assert!(ip_in_range("10.0.0.0/8", ip("10.20.30.40")));
+        assert!(!ip_in_range("10.0.0.0/8", ip("11.0.0.1")));
+        assert!(ip_in_range("0.0.0.0/0", ip("192.0.2.1")));
+        assert!(ip_in_range("2001:db8::/32", ip("2001:db8:1::1")));
+        assert!(!ip_in_range("2001:db8::/32", ip("10.0.0.1")));
+        assert!(!ip_in_range("10.0.0.0/33", ip("10.0.0.1")));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::paths::Paths;
+use crate::vault;
+
+pub fn initialize_app_config(paths: &Paths) -> Result<(), String> {
+    eprintln!("initialize_app_config called");
+
+    let app_config_path = &paths.app_config_dir;
+
+    eprintln!(
+        "Checking if app config directory exists: {}",
+        app_config_path.display()
+    );
+
+    // Create config directory if it doesn't exist
+    if !app_config_path.exists() {
+        eprintln!("App config directory does not exist, creating...");
+        std::fs::create_dir_all(app_config_path)
+            .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+        eprintln!(
+            "App config directory created: {}",
+            app_config_path.display()
+        );
+    } else {
+        eprintln!("App config directory already exists");
+    }
+
+    // Check if we need to backup Claude configs
+    let claude_dir = &paths.claude_dir;
+    eprintln!(
+        "Checking if Claude directory exists: {}",
+        claude_dir.display()
+    );
+
+    if claude_dir.exists() {
+        // Check if we already have a backup
+        if paths.backup_dir().exists() {
+            eprintln!("Claude backup already exists, skipping backup");
+        } else {
+            eprintln!("Claude directory exists but no backup found, backing up...");
+            if let Err(e) = backup_claude_configs_internal(paths) {
+                return Err(format!("Failed to backup Claude configs: {}", e));
+            }
+            eprintln!("Claude configs backed up successfully");
+        }
+    } else {
+        eprintln!("Claude directory does not exist, skipping backup");
+    }
+
+    // Stores written by older versions keep their tokens in plain text
+    match vault::migrate_plaintext_secrets(paths) {
+        Ok(0) => {}
+        Ok(moved) => eprintln!("🔐 Moved {} secrets into the vault", moved),
+        Err(e) => eprintln!("Warning: Failed to move secrets into the vault: {}", e),
+    }
+
+    Ok(())
+}
+
+fn backup_claude_configs_internal(paths: &Paths) -> Result<(), String> {
+    // Create backup directory
+    let backup_dir = paths.backup_dir();
+
+    std::fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    // Copy all files from .claude directory to backup
+    for entry in std::fs::read_dir(&paths.claude_dir)
+        .map_err(|e| format!("Failed to read Claude directory: {}", e))?
+    {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let source_path = entry.path();
+        let file_name = source_path.file_name().ok_or("Invalid file name")?;
+        let dest_path = backup_dir.join(file_name);
+
+        if source_path.is_file() {
+            std::fs::copy(&source_path, &dest_path)
+                .map_err(|e| format!("Failed to copy file {}: {}", source_path.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn backup_claude_configs(paths: &Paths) -> Result<(), String> {
+    if !paths.claude_dir.exists() {
+        return Err("Claude configuration directory does not exist".to_string());
+    }
+
+    // Ensure app config directory exists
+    std::fs::create_dir_all(&paths.app_config_dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+
+    backup_claude_configs_internal(paths)
+}
@@ -0,0 +1,779 @@
+// Headless command line interface for managing Claude Code and Codex profiles.
+// Runs the same store logic as the desktop app against ~/.ccconfig, without a webview.
+
+use serde_json::Value;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use tvcbuddy_core::bindings;
+use tvcbuddy_core::bundle::{self, BundleItemKind, ConflictStrategy, ImportAction};
+use tvcbuddy_core::codex::{self, CodexStore};
+use tvcbuddy_core::drift::{self, DriftKind, ProfileDiff};
+use tvcbuddy_core::history::{self, DiffKind, HistoryFile};
+use tvcbuddy_core::stores::{self, ConfigStore};
+use tvcbuddy_core::vault::{self, KeySource};
+use tvcbuddy_core::Paths;
+
+const USAGE: &str = "Usage: tvcbuddy-cli [--json] <command> [args]
+
+Commands:
+  list                                   List Claude Code profiles
+  show [<id|title>] [--resolved]         Show a profile (defaults to the active one), with
+                                         --resolved its settings merged over its parents
+  use <id|title>                         Activate a profile and write ~/.claude/settings.json
+  create <title> [--id <id>] [--settings <file|->] [--parent <id|title>]
+                                         Create a profile from a JSON settings file or stdin,
+                                         optionally layered on top of a parent profile
+  delete <id|title>                      Delete a profile
+  reset                                  Deactivate all profiles and clear the env block
+  diff [--reapply|--absorb]              Compare the active profile with ~/.claude/settings.json,
+                                         then overwrite the edits or save them into the profile
+  bindings                               List projects bound to a profile
+  bind <project> <id|title>              Write a profile to <project>/.claude/settings.local.json
+  unbind <project>                       Remove a project's profile binding
+  codex list                             List Codex profiles
+  codex show [<id|title>]                Show a Codex profile (defaults to the active one)
+  codex use <id|title>                   Activate a Codex profile and write ~/.codex
+  history                                List files with saved versions
+  history <file>                         List saved versions of a file (key, path or name)
+  history diff <file> <version> [<version>]
+                                         Diff a version against another one or the current file
+  history restore <file> <version>       Restore a saved version of a file
+  vault                                  Show how stored API tokens are protected
+  export <file>                          Export all profiles, MCP servers, commands and agents
+                                         to a bundle; tokens are stripped unless
+                                         TVCBUDDY_BUNDLE_PASSPHRASE is set
+  import <file> [--on-conflict skip|rename|overwrite] [--dry-run]
+                                         Import a bundle, skipping conflicts by default
+
+Options:
+  --json                                 Print machine readable JSON output, before or
+                                         after the command
+  --                                     Treat the remaining arguments as values
+  -h, --help                             Show this help
+
+Environment:
+  TVCBUDDY_VAULT_PASSPHRASE              Passphrase of a passphrase-protected vault
+  TVCBUDDY_BUNDLE_PASSPHRASE             Passphrase sealing the tokens of an exported bundle";
+
+// Seals the tokens of an exported bundle, and opens them on import
+const BUNDLE_PASSPHRASE_ENV: &str = "TVCBUDDY_BUNDLE_PASSPHRASE";
+
+// Exit codes
+const EXIT_ERROR: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Failed(message)
+    }
+}
+
+// Flags every command takes
+const JSON_FLAG: &str = "--json";
+const HELP_FLAGS: [&str; 2] = ["-h", "--help"];
+
+/// Arguments of one command, split into the flags and options it takes
+#[derive(Default)]
+struct Args {
+    positional: Vec<String>,
+    flags: Vec<&'static str>,
+    options: Vec<(&'static str, String)>,
+    json: bool,
+    help: bool,
+}
+
+impl Args {
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| *option == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn get(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(|s| s.as_str())
+    }
+
+    fn at_most(&self, count: usize) -> Result<(), CliError> {
+        match self.positional.get(count) {
+            Some(extra) => Err(CliError::Usage(format!("unexpected argument '{}'", extra))),
+            None => Ok(()),
+        }
+    }
+}
+
+// Flags, and options taking a value, of each command. Anything else starting with `-` is rejected.
+fn command_spec(command: &str) -> (&'static [&'static str], &'static [&'static str]) {
+    match command {
+        "show" => (&["--resolved"], &[]),
+        "create" => (&[], &["--id", "--settings", "--parent"]),
+        "diff" => (&["--reapply", "--absorb"], &[]),
+        "import" => (&["--dry-run"], &["--on-conflict"]),
+        _ => (&[], &[]),
+    }
+}
+
+fn parse_args(
+    args: &[String],
+    flags: &[&'static str],
+    options: &[&'static str],
+) -> Result<Args, CliError> {
+    let mut parsed = Args::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let name = arg.as_str();
+        if name == "--" {
+            parsed.positional.extend(iter.cloned());
+            break;
+        } else if name == JSON_FLAG {
+            parsed.json = true;
+        } else if HELP_FLAGS.contains(&name) {
+            parsed.help = true;
+        } else if let Some(flag) = flags.iter().find(|flag| **flag == name) {
+            parsed.flags.push(flag);
+        } else if let Some(option) = options.iter().find(|option| **option == name) {
+            // Taken as is, even when the value looks like a flag
+            let value = iter
+                .next()
+                .ok_or_else(|| CliError::Usage(format!("{} requires a value", option)))?;
+            parsed.options.push((option, value.clone()));
+        } else if name.starts_with('-') && name != "-" {
+            return Err(CliError::Usage(format!("unknown option '{}'", name)));
+        } else {
+            parsed.positional.push(arg.clone());
+        }
+    }
+    Ok(parsed)
+}
+
+// Global options come before the command, the command's own flags after it.
+// Returns `None` when help was asked for.
+fn parse_command_line(args: &[String]) -> Result<Option<(String, Args)>, CliError> {
+    let start = args
+        .iter()
+        .position(|arg| !arg.starts_with('-'))
+        .unwrap_or(args.len());
+    let global = parse_args(&args[..start], &[], &[])?;
+    let Some(command) = args.get(start) else {
+        return match global.help {
+            true => Ok(None),
+            false => Err(CliError::Usage("missing command".to_string())),
+        };
+    };
+
+    let (flags, options) = command_spec(command);
+    let mut parsed = parse_args(&args[start + 1..], flags, options)?;
+    if global.help || parsed.help {
+        return Ok(None);
+    }
+    parsed.json |= global.json;
+    Ok(Some((command.clone(), parsed)))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut json = false;
+    let result = parse_command_line(&args).and_then(|invocation| match invocation {
+        None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some((command, args)) => {
+            json = args.json;
+            Paths::detect()
+                .map_err(CliError::Failed)
+                .and_then(|paths| run(&paths, &command, &args))
+        }
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(EXIT_USAGE)
+        }
+        Err(CliError::Failed(message)) => {
+            if json {
+                println!("{}", serde_json::json!({ "error": message }));
+            } else {
+                eprintln!("error: {}", message);
+            }
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn run(paths: &Paths, command: &str, args: &Args) -> Result<(), CliError> {
+    let json = args.json;
+    match command {
+        "list" => {
+            args.at_most(0)?;
+            let stores = stores::get_stores(paths)?;
+            if json {
+                print_json(&stores)?;
+            } else {
+                print_table(
+                    stores
+                        .iter()
+                        .map(|s| (s.using, s.id.as_str(), s.title.as_str(), s.created_at))
+                        .collect(),
+                );
+            }
+            Ok(())
+        }
+        "show" => {
+            args.at_most(1)?;
+            let store = match args.get(0) {
+                Some(query) => Some(find_store(paths, query)?),
+                None => stores::get_current_store(paths)?,
+            };
+            match store {
+                Some(store) if args.flag("--resolved") => {
+                    print_json(&stores::get_resolved_store_settings(paths, &store.id)?)
+                }
+                Some(store) => print_json(&store),
+                None => Err(CliError::Failed("No active profile".to_string())),
+            }
+        }
+        "use" => {
+            args.at_most(1)?;
+            let query = required_arg(args, 0, "use <id|title>")?;
+            let store = find_store(paths, query)?;
+            stores::set_using_config(paths, &store.id)?;
+            print_result(
+                json,
+                &store,
+                format!("Switched to \"{}\" ({})", store.title, store.id),
+            )
+        }
+        "create" => {
+            args.at_most(1)?;
+            let title = required_arg(
+                args,
+                0,
+                "create <title> [--id <id>] [--settings <file|->] [--parent <id|title>]",
+            )?
+            .to_string();
+            let settings = read_settings(args.option("--settings"))?;
+            let id = args
+                .option("--id")
+                .map(str::to_string)
+                .unwrap_or_else(|| nanoid::nanoid!(6));
+            let parent = args
+                .option("--parent")
+                .map(|query| find_store(paths, query).map(|store| store.id))
+                .transpose()?;
+            let store = stores::create_config(paths, id, title, settings, parent)?;
+            print_result(
+                json,
+                &store,
+                format!("Created \"{}\" ({})", store.title, store.id),
+            )
+        }
+        "delete" => {
+            args.at_most(1)?;
+            let query = required_arg(args, 0, "delete <id|title>")?;
+            let store = find_store(paths, query)?;
+            stores::delete_config(paths, &store.id)?;
+            print_result(
+                json,
+                &store,
+                format!("Deleted \"{}\" ({})", store.title, store.id),
+            )
+        }
+        "reset" => {
+            args.at_most(0)?;
+            stores::reset_to_original_config(paths)?;
+            print_result(
+                json,
+                &serde_json::json!({ "reset": true }),
+                "Reset to original config".to_string(),
+            )
+        }
+        "diff" => run_diff(paths, args),
+        "bindings" => {
+            args.at_most(0)?;
+            let bindings = bindings::get_project_bindings(paths)?;
+            if json {
+                return print_json(&bindings);
+            }
+            if bindings.is_empty() {
+                println!("No projects are bound to a profile");
+            }
+            let stores = stores::get_stores(paths)?;
+            for binding in bindings {
+                let title = stores
+                    .iter()
+                    .find(|s| s.id == binding.store_id)
+                    .map(|s| s.title.as_str())
+                    .unwrap_or("?");
+                println!("{}  {} ({})", binding.project_path, title, binding.store_id);
+            }
+            Ok(())
+        }
+        "bind" => {
+            let usage = "bind <project> <id|title>";
+            args.at_most(2)?;
+            let project_path = project_arg(required_arg(args, 0, usage)?)?;
+            let store = find_store(paths, required_arg(args, 1, usage)?)?;
+            let binding = bindings::bind_project(paths, &project_path, &store.id)?;
+            print_result(
+                json,
+                &binding,
+                format!(
+                    "Bound {} to \"{}\" ({})",
+                    project_path, store.title, store.id
+                ),
+            )
+        }
+        "unbind" => {
+            args.at_most(1)?;
+            let project_path = project_arg(required_arg(args, 0, "unbind <project>")?)?;
+            bindings::unbind_project(paths, &project_path)?;
+            print_result(
+                json,
+                &serde_json::json!({ "project_path": project_path, "unbound": true }),
+                format!("Unbound {}", project_path),
+            )
+        }
+        "codex" => run_codex(paths, args),
+        "history" => run_history(paths, args),
+        "vault" => {
+            args.at_most(0)?;
+            let status = vault::get_vault_status(paths)?;
+            if json {
+                return print_json(&status);
+            }
+            let protection = match status.key_source {
+                None => "not created yet",
+                Some(KeySource::Passphrase) if status.locked => {
+                    "passphrase (locked, set TVCBUDDY_VAULT_PASSPHRASE)"
+                }
+                Some(KeySource::Passphrase) => "passphrase",
+                Some(KeySource::Keyring) => "OS keyring",
+                Some(KeySource::File) => "local key file",
+            };
+            println!("Vault: {}", protection);
+            println!("Secrets: {}", status.secrets);
+            Ok(())
+        }
+        "export" => {
+            args.at_most(1)?;
+            let file = required_arg(args, 0, "export <file>")?;
+            let passphrase = std::env::var(BUNDLE_PASSPHRASE_ENV).ok();
+            let bundle = bundle::export_profiles(paths, Path::new(file), passphrase.as_deref())?;
+            print_result(
+                json,
+                &serde_json::json!({ "file": file, "encrypted": bundle.secrets.is_some() }),
+                format!(
+                    "Exported {} Claude and {} Codex profiles to {} ({})",
+                    bundle.claude_stores.len(),
+                    bundle.codex_stores.len(),
+                    file,
+                    if bundle.secrets.is_some() {
+                        "tokens encrypted"
+                    } else {
+                        "tokens stripped"
+                    }
+                ),
+            )
+        }
+        "import" => run_import(paths, args),
+        other => Err(CliError::Usage(format!("unknown command '{}'", other))),
+    }
+}
+
+fn run_diff(paths: &Paths, args: &Args) -> Result<(), CliError> {
+    args.at_most(0)?;
+    let diff = match (args.flag("--reapply"), args.flag("--absorb")) {
+        (false, false) => drift::diff_active_profile(paths)?,
+        (true, false) => {
+            drift::reapply_active_profile(paths)?;
+            drift::diff_active_profile(paths)?
+        }
+        (false, true) => drift::absorb_active_profile(paths)?,
+        (true, true) => {
+            return Err(CliError::Usage(
+                "--reapply and --absorb can't be combined".to_string(),
+            ))
+        }
+    };
+    let diff = diff.ok_or_else(|| CliError::Failed("No active profile".to_string()))?;
+    if args.json {
+        return print_json(&diff);
+    }
+    print_profile_diff(&diff);
+    Ok(())
+}
+
+fn print_profile_diff(diff: &ProfileDiff) {
+    if diff.entries.is_empty() {
+        println!(
+            "settings.json matches \"{}\" ({})",
+            diff.store_title, diff.store_id
+        );
+        return;
+    }
+
+    println!(
+        "\"{}\" ({}) vs settings.json",
+        diff.store_title, diff.store_id
+    );
+    let show = |value: &Option<Value>| value.as_ref().map(Value::to_string).unwrap_or_default();
+    for entry in &diff.entries {
+        let at = if entry.item {
+            format!("{}[]", entry.pointer)
+        } else {
+            entry.pointer.clone()
+        };
+        match entry.kind {
+            DriftKind::Added => println!("+ {}  {}", at, show(&entry.actual)),
+            DriftKind::Removed => println!("- {}  {}", at, show(&entry.expected)),
+            DriftKind::Changed => println!(
+                "~ {}  {} → {}",
+                at,
+                show(&entry.expected),
+                show(&entry.actual)
+            ),
+        }
+    }
+}
+
+fn run_import(paths: &Paths, args: &Args) -> Result<(), CliError> {
+    args.at_most(1)?;
+    let file = required_arg(
+        args,
+        0,
+        "import <file> [--on-conflict skip|rename|overwrite] [--dry-run]",
+    )?;
+    let strategy = match args.option("--on-conflict") {
+        None | Some("skip") => ConflictStrategy::Skip,
+        Some("rename") => ConflictStrategy::Rename,
+        Some("overwrite") => ConflictStrategy::Overwrite,
+        Some(_) => {
+            return Err(CliError::Usage(
+                "--on-conflict expects skip, rename or overwrite".to_string(),
+            ))
+        }
+    };
+    let dry_run = args.flag("--dry-run");
+
+    let passphrase = std::env::var(BUNDLE_PASSPHRASE_ENV).ok();
+    let report = bundle::import_profiles(
+        paths,
+        Path::new(file),
+        passphrase.as_deref(),
+        strategy,
+        dry_run,
+    )?;
+    if args.json {
+        return print_json(&report);
+    }
+    for item in &report.items {
+        let action = match item.action {
+            ImportAction::Create => "create",
+            ImportAction::Skip => "skip",
+            ImportAction::Rename => "rename",
+            ImportAction::Overwrite => "overwrite",
+        };
+        let kind = match item.kind {
+            BundleItemKind::ClaudeStore => "profile",
+            BundleItemKind::CodexStore => "codex",
+            BundleItemKind::McpServer => "mcp",
+            BundleItemKind::Command => "command",
+            BundleItemKind::Agent => "agent",
+        };
+        match &item.renamed_to {
+            Some(renamed_to) => {
+                println!(
+                    "{:<9}  {:<8}  {} -> {}",
+                    action, kind, item.name, renamed_to
+                )
+            }
+            None => println!("{:<9}  {:<8}  {}", action, kind, item.name),
+        }
+    }
+    if dry_run {
+        println!("Dry run, nothing was imported");
+    }
+    Ok(())
+}
+
+fn run_codex(paths: &Paths, args: &Args) -> Result<(), CliError> {
+    let json = args.json;
+    let command = args
+        .get(0)
+        .ok_or_else(|| CliError::Usage("missing codex command".to_string()))?;
+    args.at_most(2)?;
+
+    match command {
+        "list" => {
+            let stores = codex::get_codex_stores(paths)?;
+            if json {
+                print_json(&stores)?;
+            } else {
+                print_table(
+                    stores
+                        .iter()
+                        .map(|s| {
+                            (
+                                s.using,
+                                s.id.as_str(),
+                                s.title.as_str(),
+                                s.created_at / 1000,
+                            )
+                        })
+                        .collect(),
+                );
+            }
+            Ok(())
+        }
+        "show" => {
+            let store = match args.get(1) {
+                Some(query) => Some(find_codex_store(paths, query)?),
+                None => codex::get_current_codex_store(paths)?,
+            };
+            match store {
+                Some(store) => print_json(&store),
+                None => Err(CliError::Failed("No active Codex profile".to_string())),
+            }
+        }
+        "use" => {
+            let query = required_arg(args, 1, "codex use <id|title>")?;
+            let store = find_codex_store(paths, query)?;
+            codex::set_using_codex_store(paths, &store.id)?;
+            print_result(
+                json,
+                &store,
+                format!("Switched Codex to \"{}\" ({})", store.title, store.id),
+            )
+        }
+        other => Err(CliError::Usage(format!(
+            "unknown codex command '{}'",
+            other
+        ))),
+    }
+}
+
+fn run_history(paths: &Paths, args: &Args) -> Result<(), CliError> {
+    let json = args.json;
+    match args.get(0) {
+        None => {
+            let files = history::list_history_files(paths)?;
+            if json {
+                return print_json(&files);
+            }
+            if files.is_empty() {
+                println!("No history recorded yet");
+            }
+            for file in files {
+                println!("{}  {} version(s)  {}", file.key, file.versions, file.path);
+            }
+            Ok(())
+        }
+        Some("diff") => {
+            let usage = "history diff <file> <version> [<version>]";
+            args.at_most(4)?;
+            let file = find_history_file(paths, required_arg(args, 1, usage)?)?;
+            let from = required_arg(args, 2, usage)?;
+            let to = args.get(3);
+            let diff = history::diff_history_version(paths, &file.key, from, to)?;
+            if json {
+                return print_json(&diff);
+            }
+            println!("--- {} ({})", diff.path, diff.from);
+            println!("+++ {} ({})", diff.path, to.unwrap_or("current"));
+            for line in diff.lines {
+                let marker = match line.kind {
+                    DiffKind::Equal => ' ',
+                    DiffKind::Added => '+',
+                    DiffKind::Removed => '-',
+                };
+                println!("{}{}", marker, line.text);
+            }
+            Ok(())
+        }
+        Some("restore") => {
+            let usage = "history restore <file> <version>";
+            args.at_most(3)?;
+            let file = find_history_file(paths, required_arg(args, 1, usage)?)?;
+            let version = required_arg(args, 2, usage)?;
+            history::restore_history_version(paths, &file.key, version)?;
+            print_result(
+                json,
+                &serde_json::json!({ "key": file.key, "path": file.path, "restored": version }),
+                format!("Restored {} to version {}", file.path, version),
+            )
+        }
+        Some(query) => {
+            args.at_most(1)?;
+            let file = find_history_file(paths, query)?;
+            let versions = history::list_history_versions(paths, &file.key)?;
+            if json {
+                return print_json(&versions);
+            }
+            println!("{}", file.path);
+            for version in versions {
+                let created = chrono::DateTime::from_timestamp_millis(version.created_at as i64)
+                    .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+                println!("  {}  {}  {} bytes", version.id, created, version.size);
+            }
+            Ok(())
+        }
+    }
+}
+
+// Resolve a history file by key, full path, then unique file name
+fn find_history_file(paths: &Paths, query: &str) -> Result<HistoryFile, CliError> {
+    let files = history::list_history_files(paths)?;
+    if let Some(file) = files.iter().find(|f| f.key == query || f.path == query) {
+        return Ok(file.clone());
+    }
+
+    let mut by_name: Vec<HistoryFile> = files.into_iter().filter(|f| f.name == query).collect();
+    match by_name.len() {
+        0 => Err(CliError::Failed(format!(
+            "No history found for '{}'",
+            query
+        ))),
+        1 => Ok(by_name.remove(0)),
+        n => Err(CliError::Failed(format!(
+            "{} files are named '{}', use the key or full path instead",
+            n, query
+        ))),
+    }
+}
+
+// Project paths are keyed the way Claude Code records them: absolute, without a trailing slash
+fn project_arg(path: &str) -> Result<String, CliError> {
+    let absolute = std::path::absolute(path)
+        .map_err(|e| format!("Failed to resolve project path {}: {}", path, e))?;
+    let absolute = absolute.to_string_lossy();
+    Ok(match absolute.trim_end_matches(std::path::MAIN_SEPARATOR) {
+        "" => absolute.to_string(),
+        trimmed => trimmed.to_string(),
+    })
+}
+
+fn required_arg<'a>(args: &'a Args, index: usize, usage: &str) -> Result<&'a str, CliError> {
+    args.get(index)
+        .ok_or_else(|| CliError::Usage(format!("expected: {}", usage)))
+}
+
+// Read profile settings from a file, from stdin when the path is "-", or default to an empty object
+fn read_settings(path: Option<&str>) -> Result<Value, CliError> {
+    let content = match path {
+        None => return Ok(Value::Object(serde_json::Map::new())),
+        Some("-") => std::io::read_to_string(std::io::stdin())
+            .map_err(|e| format!("Failed to read settings from stdin: {}", e))?,
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read settings file {}: {}", path, e))?,
+    };
+
+    let settings: Value =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings: {}", e))?;
+    if !settings.is_object() {
+        return Err(CliError::Failed(
+            "Settings must be a JSON object".to_string(),
+        ));
+    }
+    Ok(settings)
+}
+
+// Resolve a profile by exact id first, then by unique title
+fn find_store(paths: &Paths, query: &str) -> Result<ConfigStore, CliError> {
+    let stores = stores::get_stores(paths)?;
+    resolve(stores, query, |s| (&s.id, &s.title))
+}
+
+fn find_codex_store(paths: &Paths, query: &str) -> Result<CodexStore, CliError> {
+    let stores = codex::get_codex_stores(paths)?;
+    resolve(stores, query, |s| (&s.id, &s.title))
+}
+
+fn resolve<T>(
+    stores: Vec<T>,
+    query: &str,
+    key: impl Fn(&T) -> (&String, &String),
+) -> Result<T, CliError> {
+    let (by_id, rest): (Vec<T>, Vec<T>) = stores.into_iter().partition(|s| key(s).0 == query);
+    if let Some(store) = by_id.into_iter().next() {
+        return Ok(store);
+    }
+
+    let mut by_title: Vec<T> = rest.into_iter().filter(|s| key(s).1 == query).collect();
+    match by_title.len() {
+        0 => Err(CliError::Failed(format!("Profile '{}' not found", query))),
+        1 => Ok(by_title.remove(0)),
+        n => Err(CliError::Failed(format!(
+            "{} profiles are titled '{}', use the id instead",
+            n, query
+        ))),
+    }
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<(), CliError> {
+    let output = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    match writeln!(std::io::stdout().lock(), "{}", output) {
+        Ok(()) => Ok(()),
+        // The reader stopped early, e.g. `| head -1`, and has all it wanted
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => std::process::exit(0),
+        Err(e) => Err(CliError::Failed(format!("Failed to write output: {}", e))),
+    }
+}
+
+fn print_result<T: serde::Serialize>(
+    json: bool,
+    value: &T,
+    message: String,
+) -> Result<(), CliError> {
+    if json {
+        print_json(value)
+    } else {
+        println!("{}", message);
+        Ok(())
+    }
+}
+
+// Print (active, id, title, created_at seconds) rows as an aligned table
+fn print_table(rows: Vec<(bool, &str, &str, u64)>) {
+    if rows.is_empty() {
+        println!("No profiles configured");
+        return;
+    }
+
+    let id_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(2);
+    let title_width = rows
+        .iter()
+        .map(|r| r.2.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+
+    println!("  {:<id_width$}  {:<title_width$}  CREATED", "ID", "TITLE");
+    for (using, id, title, created_at) in rows {
+        let created = chrono::DateTime::from_timestamp(created_at as i64, 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        println!(
+            "{} {:<id_width$}  {:<title_width$}  {}",
+            if using { "*" } else { " " },
+            id,
+            title,
+            created
+        );
+    }
+}
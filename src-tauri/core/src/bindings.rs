@@ -0,0 +1,278 @@
+//! Per-project profile bindings.
+//!
+//! A store bound to a project is merged into `<project>/.claude/settings.local.json`,
+//! which Claude Code layers over `~/.claude/settings.json` for that project only.
+//! As with the global switch, the changes each binding made are recorded in
+//! stores.json so rebinding or unbinding reverts exactly those.
+
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::fsio;
+use crate::merge::{self, MergeChange, MergeStrategies};
+use crate::paths::Paths;
+use crate::relay;
+use crate::settings_schema;
+use crate::stores;
+use crate::vault;
+
+/// Project settings file, relative to the project root
+pub const PROJECT_SETTINGS_FILE: &str = ".claude/settings.local.json";
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ProjectBinding {
+    pub project_path: String,
+    pub store_id: String,
+    pub bound_at: u64,
+    /// What the store contributed to the project's settings.local.json
+    #[serde(default)]
+    pub changes: Vec<MergeChange>,
+    /// settings.local.json did not exist before the first bind
+    #[serde(default)]
+    pub created_file: bool,
+}
+
+/// Path of the settings file a binding writes for `project_path`
+pub fn project_settings_file(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(PROJECT_SETTINGS_FILE)
+}
+
+pub fn get_project_bindings(paths: &Paths) -> Result<Vec<ProjectBinding>, String> {
+    Ok(stores::read_stores_data(paths)?
+        .map(|stores_data| stores_data.bindings)
+        .unwrap_or_default())
+}
+
+pub fn get_project_binding(
+    paths: &Paths,
+    project_path: &str,
+) -> Result<Option<ProjectBinding>, String> {
+    Ok(get_project_bindings(paths)?
+        .into_iter()
+        .find(|binding| binding.project_path == project_path))
+}
+
+/// Bind `store_id` to a project, replacing the project's previous binding.
+pub fn bind_project(
+    paths: &Paths,
+    project_path: &str,
+    store_id: &str,
+) -> Result<ProjectBinding, String> {
+    let project_dir = Path::new(project_path);
+    if !project_dir.is_dir() {
+        return Err(format!(
+            "Project directory does not exist: {}",
+            project_path
+        ));
+    }
+
+    let stores_data =
+        stores::read_stores_data(paths)?.ok_or_else(|| "Stores file does not exist".to_string())?;
+    let store = stores_data
+        .configs
+        .iter()
+        .find(|store| store.id == store_id)
+        .ok_or_else(|| format!("Store with id '{}' not found", store_id))?;
+
+    // Refuse to write settings that would break Claude Code in this project
+    let store_settings = stores::resolve_store_settings(&stores_data, &store.id)?;
+    settings_schema::ensure_valid_settings(&store_settings)?;
+
+    let strategies = stores::merge_strategies(&stores_data);
+    let previous = stores_data
+        .bindings
+        .iter()
+        .find(|binding| binding.project_path == project_path);
+    let settings_file = project_settings_file(project_path);
+    let created_file = match previous {
+        Some(previous) => previous.created_file,
+        None => !settings_file.exists(),
+    };
+
+    // Secrets are only resolved for the write; the recorded changes keep their handles
+    let (mut store_settings, secrets) = vault::reveal_with_secrets(paths, &store_settings)?;
+    relay::route_env(paths, &mut store_settings, Some(store_id))?;
+    let previous_changes = vault::reveal_changes(
+        paths,
+        previous
+            .map(|binding| binding.changes.as_slice())
+            .unwrap_or(&[]),
+    )?;
+    let mut changes = switch_project_settings(
+        paths,
+        &settings_file,
+        &previous_changes,
+        Some(&store_settings),
+        &strategies,
+    )?;
+    vault::conceal_changes(paths, &mut changes, &secrets)?;
+
+    let binding = ProjectBinding {
+        project_path: project_path.to_string(),
+        store_id: store.id.clone(),
+        bound_at: now_secs()?,
+        changes,
+        created_file,
+    };
+    stores::update_stores_data(paths, |stores_data| {
+        stores_data
+            .bindings
+            .retain(|existing| existing.project_path != project_path);
+        stores_data.bindings.push(binding.clone());
+        Ok(())
+    })?;
+
+    // Secrets the previous binding moved into the vault are held by the new one now
+    if let Err(e) = vault::prune_secrets(paths) {
+        eprintln!("Warning: Failed to remove unused secrets: {}", e);
+    }
+
+    // settings.local.json may hold API keys, keep it out of commits
+    if let Err(e) = exclude_from_git(project_dir) {
+        eprintln!("Warning: Failed to add settings file to git exclude: {}", e);
+    }
+
+    eprintln!(
+        "✅ Bound project {} to config \"{}\"",
+        project_path, store.title
+    );
+    Ok(binding)
+}
+
+/// Remove a project's binding and revert what it wrote to settings.local.json.
+pub fn unbind_project(paths: &Paths, project_path: &str) -> Result<(), String> {
+    let binding = get_project_binding(paths, project_path)?
+        .ok_or_else(|| format!("Project is not bound to a config: {}", project_path))?;
+
+    // A project that was moved or deleted has nothing left to revert
+    if Path::new(project_path).is_dir() {
+        let settings_file = project_settings_file(project_path);
+        switch_project_settings(
+            paths,
+            &settings_file,
+            &vault::reveal_changes(paths, &binding.changes)?,
+            None,
+            &MergeStrategies::new(),
+        )?;
+        if binding.created_file {
+            remove_if_empty(paths, &settings_file)?;
+        }
+    }
+
+    stores::update_stores_data(paths, |stores_data| {
+        stores_data
+            .bindings
+            .retain(|existing| existing.project_path != project_path);
+        Ok(())
+    })?;
+
+    if let Err(e) = vault::prune_secrets(paths) {
+        eprintln!("Warning: Failed to remove unused secrets: {}", e);
+    }
+
+    eprintln!("✅ Unbound project {}", project_path);
+    Ok(())
+}
+
+/// Re-apply every project bound to `store_id` or a store inheriting from it, e.g. after it was edited.
+pub fn refresh_store_bindings(paths: &Paths, store_id: &str) -> Result<(), String> {
+    let Some(stores_data) = stores::read_stores_data(paths)? else {
+        return Ok(());
+    };
+    for binding in stores_data
+        .bindings
+        .iter()
+        .filter(|binding| stores::inherits_from(&stores_data.configs, &binding.store_id, store_id))
+    {
+        if !Path::new(&binding.project_path).is_dir() {
+            eprintln!(
+                "Warning: Skipping missing bound project {}",
+                binding.project_path
+            );
+            continue;
+        }
+        bind_project(paths, &binding.project_path, &binding.store_id)?;
+    }
+    Ok(())
+}
+
+/// Unbind every project bound to `store_id`, e.g. before the store is deleted.
+pub fn unbind_store(paths: &Paths, store_id: &str) -> Result<(), String> {
+    for binding in get_project_bindings(paths)?
+        .into_iter()
+        .filter(|binding| binding.store_id == store_id)
+    {
+        unbind_project(paths, &binding.project_path)?;
+    }
+    Ok(())
+}
+
+/// Revert `previous` in a project's settings file and merge `settings` in their place.
+fn switch_project_settings(
+    paths: &Paths,
+    settings_file: &Path,
+    previous: &[MergeChange],
+    settings: Option<&Value>,
+    strategies: &MergeStrategies,
+) -> Result<Vec<MergeChange>, String> {
+    let mut changes = Vec::new();
+    fsio::update_json_object(paths, settings_file, "project settings", |existing| {
+        let original = existing.clone();
+        merge::revert(existing, previous);
+        changes = match settings {
+            Some(settings) => merge::apply(existing, settings, strategies)?,
+            None => vec![],
+        };
+        Ok(*existing != original)
+    })?;
+    Ok(changes)
+}
+
+/// Delete a settings file we created once nothing is left in it.
+fn remove_if_empty(paths: &Paths, settings_file: &Path) -> Result<(), String> {
+    let _lock = fsio::lock(paths, settings_file)?;
+    let content: Option<Value> = fsio::read_json(settings_file, "project settings")?;
+    if content.is_some_and(|content| content.as_object().is_some_and(|obj| obj.is_empty())) {
+        std::fs::remove_file(settings_file)
+            .map_err(|e| format!("Failed to remove project settings: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Add the settings file to `.git/info/exclude` if the project is a git checkout.
+fn exclude_from_git(project_dir: &Path) -> Result<(), String> {
+    let git_dir = project_dir.join(".git");
+    if !git_dir.is_dir() {
+        return Ok(());
+    }
+
+    let exclude_file = git_dir.join("info").join("exclude");
+    let existing = std::fs::read_to_string(&exclude_file).unwrap_or_default();
+    let pattern = format!("/{}", PROJECT_SETTINGS_FILE);
+    if existing.lines().any(|line| line.trim() == pattern) {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(git_dir.join("info"))
+        .map_err(|e| format!("Failed to create git info directory: {}", e))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&exclude_file)
+        .map_err(|e| format!("Failed to open git exclude file: {}", e))?;
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    writeln!(file, "{}{}", separator, pattern)
+        .map_err(|e| format!("Failed to write git exclude file: {}", e))
+}
+
+fn now_secs() -> Result<u64, String> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| format!("Failed to get timestamp: {}", e))?
+        .as_secs())
+}
@@ -0,0 +1,657 @@
+//! Profile bundles for moving stores between machines or sharing a team baseline.
+//!
+//! A bundle is a single versioned JSON file holding the Claude and Codex stores,
+//! the global MCP servers, slash commands and agents. API tokens are either
+//! stripped or sealed together with a passphrase chosen at export, so the file
+//! never contains them in plain text. Importing matches items against the
+//! existing ones by id or title and skips, renames or overwrites conflicts.
+
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::claude_files;
+use crate::codex::{self, CodexStore};
+use crate::fsio;
+use crate::mcp;
+use crate::paths::Paths;
+use crate::pointer;
+use crate::stores::{self, ConfigStore};
+use crate::vault::{self, PassphraseSealed};
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+const SECRETS_AAD: &[u8] = b"tvcbuddy-bundle-secrets";
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ProfileBundle {
+    pub version: u32,
+    pub exported_at: u64,
+    #[serde(default)]
+    pub claude_stores: Vec<ConfigStore>,
+    #[serde(default)]
+    pub codex_stores: Vec<CodexStore>,
+    #[serde(default)]
+    pub mcp_servers: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub commands: Vec<BundleFile>,
+    #[serde(default)]
+    pub agents: Vec<BundleFile>,
+    /// Tokens by the placeholder that replaced them, absent when they were stripped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<PassphraseSealed>,
+}
+
+/// A slash command or agent definition
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct BundleFile {
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    Skip,
+    Rename,
+    Overwrite,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleItemKind {
+    ClaudeStore,
+    CodexStore,
+    McpServer,
+    Command,
+    Agent,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Create,
+    Skip,
+    Rename,
+    Overwrite,
+}
+
+/// What importing one bundle item does, or did
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ImportItem {
+    pub kind: BundleItemKind,
+    pub name: String,
+    pub action: ImportAction,
+    /// New title or name of a renamed item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_to: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub items: Vec<ImportItem>,
+}
+
+/// Write everything to a bundle at `file`, sealing tokens with `passphrase` or stripping them.
+pub fn export_profiles(
+    paths: &Paths,
+    file: &Path,
+    passphrase: Option<&str>,
+) -> Result<ProfileBundle, String> {
+    let bundle = build_bundle(paths, passphrase)?;
+    let content = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize bundle: {}", e))?;
+    fsio::write_atomic(file, content.as_bytes())?;
+
+    eprintln!(
+        "📦 Exported {} Claude and {} Codex profiles to {}",
+        bundle.claude_stores.len(),
+        bundle.codex_stores.len(),
+        file.display()
+    );
+    Ok(bundle)
+}
+
+pub fn build_bundle(paths: &Paths, passphrase: Option<&str>) -> Result<ProfileBundle, String> {
+    let mut secrets = BundleSecrets::new(paths, passphrase.is_some());
+
+    let mut claude_stores = stores::get_stores(paths)?;
+    for store in &mut claude_stores {
+        store.using = false;
+        let pointers = store_secret_pointers(&store.settings);
+        secrets.take(&mut store.settings, &pointers)?;
+    }
+
+    let mut codex_stores = codex::get_codex_stores(paths)?;
+    for store in &mut codex_stores {
+        store.using = false;
+        secrets.take(&mut store.config, &vault::codex_secret_pointers())?;
+    }
+
+    let mut mcp_servers: BTreeMap<String, Value> = mcp::get_global_mcp_servers(paths)?
+        .into_iter()
+        .map(|(name, server)| (name, server.config))
+        .collect();
+    for config in mcp_servers.values_mut() {
+        let pointers = vault::mcp_server_secret_pointers(config);
+        secrets.take(config, &pointers)?;
+    }
+
+    let commands = claude_files::read_claude_commands(paths)?
+        .into_iter()
+        .map(|command| BundleFile {
+            name: command.name,
+            content: command.content,
+        })
+        .collect();
+    let agents = claude_files::read_claude_agents(paths)?
+        .into_iter()
+        .map(|agent| BundleFile {
+            name: agent.name,
+            content: agent.content,
+        })
+        .collect();
+
+    let secrets = match passphrase {
+        Some(passphrase) => {
+            let plain = serde_json::to_vec(&secrets.values)
+                .map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+            Some(vault::seal_with_passphrase(
+                passphrase,
+                &plain,
+                SECRETS_AAD,
+            )?)
+        }
+        None => None,
+    };
+
+    Ok(ProfileBundle {
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis() as u64,
+        claude_stores,
+        codex_stores,
+        mcp_servers,
+        commands,
+        agents,
+        secrets,
+    })
+}
+
+/// Import the bundle at `file`, or with `dry_run` only report what importing would do.
+pub fn import_profiles(
+    paths: &Paths,
+    file: &Path,
+    passphrase: Option<&str>,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+) -> Result<ImportReport, String> {
+    let bundle: ProfileBundle = fsio::read_json(file, "profile bundle")?
+        .ok_or_else(|| format!("Bundle file does not exist: {}", file.display()))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is newer than this app supports ({})",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+
+    // Checked up front so a dry run also catches a wrong passphrase
+    let secrets: BTreeMap<String, String> = match (&bundle.secrets, passphrase) {
+        (Some(sealed), Some(passphrase)) => {
+            let plain = vault::open_with_passphrase(passphrase, sealed, SECRETS_AAD)?;
+            serde_json::from_slice(&plain).map_err(|e| format!("Invalid bundle secrets: {}", e))?
+        }
+        (Some(_), None) => {
+            return Err("Bundle tokens are encrypted, enter its passphrase".to_string())
+        }
+        (None, _) => BTreeMap::new(),
+    };
+    let stripped = bundle.secrets.is_none();
+
+    let mut items = Vec::new();
+    import_claude_stores(
+        paths, &bundle, &secrets, stripped, strategy, dry_run, &mut items,
+    )?;
+    import_codex_stores(
+        paths, &bundle, &secrets, stripped, strategy, dry_run, &mut items,
+    )?;
+    import_mcp_servers(
+        paths, &bundle, &secrets, stripped, strategy, dry_run, &mut items,
+    )?;
+    import_files(
+        paths,
+        BundleItemKind::Command,
+        &bundle.commands,
+        strategy,
+        dry_run,
+        &mut items,
+    )?;
+    import_files(
+        paths,
+        BundleItemKind::Agent,
+        &bundle.agents,
+        strategy,
+        dry_run,
+        &mut items,
+    )?;
+
+    if !dry_run {
+        eprintln!("📦 Imported profile bundle {}", file.display());
+    }
+    Ok(ImportReport { dry_run, items })
+}
+
+fn import_claude_stores(
+    paths: &Paths,
+    bundle: &ProfileBundle,
+    secrets: &BTreeMap<String, String>,
+    stripped: bool,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+    items: &mut Vec<ImportItem>,
+) -> Result<(), String> {
+    let existing = stores::get_stores(paths)?;
+    let mut taken_ids: BTreeSet<String> = existing.iter().map(|s| s.id.clone()).collect();
+    let mut taken_titles: BTreeSet<String> = existing.iter().map(|s| s.title.clone()).collect();
+    // Bundle store id -> id it ends up with here, for remapping parents
+    let mut imported_ids: BTreeMap<String, String> = BTreeMap::new();
+
+    for store in parents_first(&bundle.claude_stores) {
+        let conflict = existing
+            .iter()
+            .find(|s| s.id == store.id)
+            .or_else(|| existing.iter().find(|s| s.title == store.title));
+        let mut settings = store.settings.clone();
+        restore_secrets(&mut settings, secrets);
+        let parent = store
+            .parent
+            .as_ref()
+            .map(|parent| imported_ids.get(parent).unwrap_or(parent).clone());
+
+        let item = match (conflict, strategy) {
+            (None, _) => {
+                taken_ids.insert(store.id.clone());
+                taken_titles.insert(store.title.clone());
+                imported_ids.insert(store.id.clone(), store.id.clone());
+                if !dry_run {
+                    stores::import_config(
+                        paths,
+                        store.id.clone(),
+                        store.title.clone(),
+                        settings,
+                        parent,
+                    )?;
+                }
+                import_item(
+                    BundleItemKind::ClaudeStore,
+                    &store.title,
+                    ImportAction::Create,
+                )
+            }
+            (Some(existing), ConflictStrategy::Skip) => {
+                imported_ids.insert(store.id.clone(), existing.id.clone());
+                import_item(
+                    BundleItemKind::ClaudeStore,
+                    &store.title,
+                    ImportAction::Skip,
+                )
+            }
+            (Some(_), ConflictStrategy::Rename) => {
+                let id = unique_id(&store.id, &taken_ids);
+                let title = unique_title(&store.title, &taken_titles);
+                taken_ids.insert(id.clone());
+                taken_titles.insert(title.clone());
+                imported_ids.insert(store.id.clone(), id.clone());
+                if !dry_run {
+                    stores::import_config(paths, id, title.clone(), settings, parent)?;
+                }
+                ImportItem {
+                    renamed_to: Some(title),
+                    ..import_item(
+                        BundleItemKind::ClaudeStore,
+                        &store.title,
+                        ImportAction::Rename,
+                    )
+                }
+            }
+            (Some(existing), ConflictStrategy::Overwrite) => {
+                imported_ids.insert(store.id.clone(), existing.id.clone());
+                if stripped {
+                    keep_existing_secrets(
+                        &mut settings,
+                        &existing.settings,
+                        &store_secret_pointers(&existing.settings),
+                    )?;
+                }
+                if !dry_run {
+                    stores::update_config(
+                        paths,
+                        &existing.id,
+                        store.title.clone(),
+                        settings,
+                        parent,
+                    )?;
+                }
+                import_item(
+                    BundleItemKind::ClaudeStore,
+                    &store.title,
+                    ImportAction::Overwrite,
+                )
+            }
+        };
+        items.push(item);
+    }
+    Ok(())
+}
+
+fn import_codex_stores(
+    paths: &Paths,
+    bundle: &ProfileBundle,
+    secrets: &BTreeMap<String, String>,
+    stripped: bool,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+    items: &mut Vec<ImportItem>,
+) -> Result<(), String> {
+    let existing = codex::get_codex_stores(paths)?;
+    let mut taken_titles: BTreeSet<String> = existing.iter().map(|s| s.title.clone()).collect();
+
+    for store in &bundle.codex_stores {
+        let conflict = existing
+            .iter()
+            .find(|s| s.id == store.id)
+            .or_else(|| existing.iter().find(|s| s.title == store.title));
+        let mut config = store.config.clone();
+        restore_secrets(&mut config, secrets);
+
+        let item = match (conflict, strategy) {
+            (None, _) => {
+                taken_titles.insert(store.title.clone());
+                if !dry_run {
+                    codex::create_codex_store(paths, store.title.clone(), config)?;
+                }
+                import_item(
+                    BundleItemKind::CodexStore,
+                    &store.title,
+                    ImportAction::Create,
+                )
+            }
+            (Some(_), ConflictStrategy::Skip) => {
+                import_item(BundleItemKind::CodexStore, &store.title, ImportAction::Skip)
+            }
+            (Some(_), ConflictStrategy::Rename) => {
+                let title = unique_title(&store.title, &taken_titles);
+                taken_titles.insert(title.clone());
+                if !dry_run {
+                    codex::create_codex_store(paths, title.clone(), config)?;
+                }
+                ImportItem {
+                    renamed_to: Some(title),
+                    ..import_item(
+                        BundleItemKind::CodexStore,
+                        &store.title,
+                        ImportAction::Rename,
+                    )
+                }
+            }
+            (Some(existing), ConflictStrategy::Overwrite) => {
+                if stripped {
+                    keep_existing_secrets(
+                        &mut config,
+                        &existing.config,
+                        &vault::codex_secret_pointers(),
+                    )?;
+                }
+                if !dry_run {
+                    codex::update_codex_store(paths, &existing.id, store.title.clone(), config)?;
+                }
+                import_item(
+                    BundleItemKind::CodexStore,
+                    &store.title,
+                    ImportAction::Overwrite,
+                )
+            }
+        };
+        items.push(item);
+    }
+    Ok(())
+}
+
+fn import_mcp_servers(
+    paths: &Paths,
+    bundle: &ProfileBundle,
+    secrets: &BTreeMap<String, String>,
+    stripped: bool,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+    items: &mut Vec<ImportItem>,
+) -> Result<(), String> {
+    let existing = mcp::get_global_mcp_servers(paths)?;
+    let mut taken: BTreeSet<String> = existing.keys().cloned().collect();
+
+    for (name, config) in &bundle.mcp_servers {
+        let mut config = config.clone();
+        restore_secrets(&mut config, secrets);
+
+        let (action, target) = resolve_name_conflict(name, taken.contains(name), strategy, &taken);
+        if let (ImportAction::Overwrite, true, Some(server)) =
+            (action, stripped, existing.get(name))
+        {
+            keep_existing_secrets(
+                &mut config,
+                &server.config,
+                &vault::mcp_server_secret_pointers(&server.config),
+            )?;
+        }
+        if let Some(target) = &target {
+            taken.insert(target.clone());
+            if !dry_run {
+                mcp::update_global_mcp_server(paths, target.clone(), config)?;
+            }
+        }
+        items.push(ImportItem {
+            renamed_to: (action == ImportAction::Rename)
+                .then(|| target.clone())
+                .flatten(),
+            ..import_item(BundleItemKind::McpServer, name, action)
+        });
+    }
+    Ok(())
+}
+
+fn import_files(
+    paths: &Paths,
+    kind: BundleItemKind,
+    files: &[BundleFile],
+    strategy: ConflictStrategy,
+    dry_run: bool,
+    items: &mut Vec<ImportItem>,
+) -> Result<(), String> {
+    let mut taken: BTreeSet<String> = match kind {
+        BundleItemKind::Command => claude_files::read_claude_commands(paths)?
+            .into_iter()
+            .map(|command| command.name)
+            .collect(),
+        _ => claude_files::read_claude_agents(paths)?
+            .into_iter()
+            .map(|agent| agent.name)
+            .collect(),
+    };
+
+    for file in files {
+        let (action, target) =
+            resolve_name_conflict(&file.name, taken.contains(&file.name), strategy, &taken);
+        if let Some(target) = &target {
+            taken.insert(target.clone());
+            if !dry_run {
+                match kind {
+                    BundleItemKind::Command => {
+                        claude_files::write_claude_command(paths, target, &file.content)?
+                    }
+                    _ => claude_files::write_claude_agent(paths, target, &file.content)?,
+                }
+            }
+        }
+        items.push(ImportItem {
+            renamed_to: (action == ImportAction::Rename)
+                .then(|| target.clone())
+                .flatten(),
+            ..import_item(kind, &file.name, action)
+        });
+    }
+    Ok(())
+}
+
+/// Action for an item matched by name, and the name it is written under unless skipped
+fn resolve_name_conflict(
+    name: &str,
+    exists: bool,
+    strategy: ConflictStrategy,
+    taken: &BTreeSet<String>,
+) -> (ImportAction, Option<String>) {
+    match (exists, strategy) {
+        (false, _) => (ImportAction::Create, Some(name.to_string())),
+        (true, ConflictStrategy::Skip) => (ImportAction::Skip, None),
+        (true, ConflictStrategy::Rename) => (ImportAction::Rename, Some(unique_id(name, taken))),
+        (true, ConflictStrategy::Overwrite) => (ImportAction::Overwrite, Some(name.to_string())),
+    }
+}
+
+fn import_item(kind: BundleItemKind, name: &str, action: ImportAction) -> ImportItem {
+    ImportItem {
+        kind,
+        name: name.to_string(),
+        action,
+        renamed_to: None,
+    }
+}
+
+/// Stores ordered so that every parent comes before the stores inheriting from it
+fn parents_first(configs: &[ConfigStore]) -> Vec<&ConfigStore> {
+    let mut ordered: Vec<&ConfigStore> = Vec::new();
+    let mut remaining: Vec<&ConfigStore> = configs.iter().collect();
+
+    while !remaining.is_empty() {
+        let (ready, waiting): (Vec<&ConfigStore>, Vec<&ConfigStore>) =
+            remaining.into_iter().partition(|store| {
+                store.parent.as_ref().is_none_or(|parent| {
+                    ordered.iter().any(|s| &s.id == parent)
+                        || !configs.iter().any(|s| &s.id == parent)
+                })
+            });
+        // A cycle in a hand-edited bundle, import_config rejects those stores
+        if ready.is_empty() {
+            ordered.extend(waiting);
+            break;
+        }
+        ordered.extend(ready);
+        remaining = waiting;
+    }
+    ordered
+}
+
+fn unique_title(title: &str, taken: &BTreeSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", title, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| title.to_string())
+}
+
+fn unique_id(id: &str, taken: &BTreeSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", id, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Pointers of the tokens in Claude store settings, including those of its MCP servers
+fn store_secret_pointers(settings: &Value) -> Vec<String> {
+    let mut pointers = vault::settings_secret_pointers(settings);
+    if let Some(servers) = settings.get("mcpServers").and_then(Value::as_object) {
+        for (name, config) in servers {
+            let server = pointer::child("/mcpServers", name);
+            pointers.extend(
+                vault::mcp_server_secret_pointers(config)
+                    .into_iter()
+                    .map(|at| format!("{}{}", server, at)),
+            );
+        }
+    }
+    pointers
+}
+
+/// Replace bundle placeholders with the tokens they stand for
+fn restore_secrets(value: &mut Value, secrets: &BTreeMap<String, String>) {
+    match value {
+        Value::String(text) => {
+            if let Some(secret) = secrets.get(text.as_str()) {
+                *text = secret.clone();
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| restore_secrets(item, secrets)),
+        Value::Object(object) => object
+            .values_mut()
+            .for_each(|item| restore_secrets(item, secrets)),
+        _ => {}
+    }
+}
+
+/// Keep the existing tokens of an overwritten store when the bundle had them stripped
+fn keep_existing_secrets(
+    value: &mut Value,
+    existing: &Value,
+    pointers: &[String],
+) -> Result<(), String> {
+    for at in pointers {
+        if value.pointer(at).is_none() {
+            if let Some(secret) = existing.pointer(at) {
+                pointer::set(value, at, secret.clone())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Tokens taken out of the exported items, by the placeholder left in their place
+struct BundleSecrets<'a> {
+    paths: &'a Paths,
+    keep: bool,
+    values: BTreeMap<String, String>,
+}
+
+impl<'a> BundleSecrets<'a> {
+    fn new(paths: &'a Paths, keep: bool) -> Self {
+        Self {
+            paths,
+            keep,
+            values: BTreeMap::new(),
+        }
+    }
+
+    fn take(&mut self, value: &mut Value, pointers: &[String]) -> Result<(), String> {
+        for at in pointers {
+            let Some(Value::String(text)) = value.pointer(at).cloned() else {
+                continue;
+            };
+            if !self.keep {
+                pointer::remove(value, at);
+                continue;
+            }
+            if text.is_empty() {
+                continue;
+            }
+
+            let secret = if vault::is_handle(&text) {
+                vault::resolve_secret(self.paths, &text)?
+            } else {
+                text
+            };
+            let placeholder = format!("{}{}", vault::HANDLE_PREFIX, nanoid::nanoid!(12));
+            self.values.insert(placeholder.clone(), secret);
+            pointer::set(value, at, Value::String(placeholder))?;
+        }
+        Ok(())
+    }
+}
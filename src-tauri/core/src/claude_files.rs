@@ -0,0 +1,123 @@
+// Slash command and agent definitions stored as markdown under ~/.claude
+
+use std::path::{Path, PathBuf};
+
+use crate::fsio;
+use crate::paths::Paths;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct CommandFile {
+    pub name: String,
+    pub content: String,
+    pub exists: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct AgentFile {
+    pub name: String,
+    pub content: String,
+    pub exists: bool,
+}
+
+fn commands_dir(paths: &Paths) -> PathBuf {
+    paths.claude_dir.join("commands")
+}
+
+fn agents_dir(paths: &Paths) -> PathBuf {
+    paths.claude_dir.join("agents")
+}
+
+/// Read all .md files in a directory as (name, content) pairs sorted by name
+fn read_markdown_dir(dir: &Path, kind: &str) -> Result<Vec<(String, String)>, String> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}s directory: {}", kind, e))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+
+        if path.is_file() && path.extension().map(|ext| ext == "md").unwrap_or(false) {
+            let file_name = path
+                .file_stem()
+                .and_then(|name| name.to_str())
+                .unwrap_or("unknown")
+                .to_string();
+
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {} file {}: {}", kind, path.display(), e))?;
+
+            files.push((file_name, content));
+        }
+    }
+
+    // Sort alphabetically by name
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(files)
+}
+
+fn write_markdown_file(dir: &Path, name: &str, content: &str, kind: &str) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {} directory: {}", dir.display(), e))?;
+
+    fsio::write_atomic(&dir.join(format!("{}.md", name)), content.as_bytes())
+        .map_err(|e| format!("Failed to write {} file: {}", kind, e))
+}
+
+fn delete_markdown_file(dir: &Path, name: &str, kind: &str) -> Result<(), String> {
+    let file_path = dir.join(format!("{}.md", name));
+
+    if file_path.exists() {
+        std::fs::remove_file(&file_path)
+            .map_err(|e| format!("Failed to delete {} file: {}", kind, e))?;
+    }
+
+    Ok(())
+}
+
+pub fn read_claude_commands(paths: &Paths) -> Result<Vec<CommandFile>, String> {
+    Ok(read_markdown_dir(&commands_dir(paths), "command")?
+        .into_iter()
+        .map(|(name, content)| CommandFile {
+            name,
+            content,
+            exists: true,
+        })
+        .collect())
+}
+
+pub fn write_claude_command(
+    paths: &Paths,
+    command_name: &str,
+    content: &str,
+) -> Result<(), String> {
+    write_markdown_file(&commands_dir(paths), command_name, content, "command")
+}
+
+pub fn delete_claude_command(paths: &Paths, command_name: &str) -> Result<(), String> {
+    delete_markdown_file(&commands_dir(paths), command_name, "command")
+}
+
+pub fn read_claude_agents(paths: &Paths) -> Result<Vec<AgentFile>, String> {
+    Ok(read_markdown_dir(&agents_dir(paths), "agent")?
+        .into_iter()
+        .map(|(name, content)| AgentFile {
+            name,
+            content,
+            exists: true,
+        })
+        .collect())
+}
+
+pub fn write_claude_agent(paths: &Paths, agent_name: &str, content: &str) -> Result<(), String> {
+    write_markdown_file(&agents_dir(paths), agent_name, content, "agent")
+}
+
+pub fn delete_claude_agent(paths: &Paths, agent_name: &str) -> Result<(), String> {
+    delete_markdown_file(&agents_dir(paths), agent_name, "agent")
+}
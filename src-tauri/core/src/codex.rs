@@ -0,0 +1,285 @@
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::fsio;
+use crate::paths::Paths;
+use crate::vault;
+
+const DEFAULT_CODEX_URL: &str = "https://api.lightai.io/v1";
+const DEFAULT_CODEX_MODEL: &str = "gpt-5-codex";
+const DEFAULT_WIRE_API: &str = "responses";
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CodexStore {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: u64,
+    pub config: Value,
+    pub using: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct CodexStoresData {
+    pub configs: Vec<CodexStore>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CodexGlobalSettings {
+    pub enabled: bool,
+    pub root_path: String,
+}
+
+impl CodexGlobalSettings {
+    /// Default settings, with Codex enabled and rooted at `paths.codex_root`.
+    pub fn default_for(paths: &Paths) -> Self {
+        Self {
+            enabled: true,
+            root_path: paths.codex_root.to_string_lossy().to_string(),
+        }
+    }
+}
+
+pub fn read_codex_stores(paths: &Paths) -> Result<CodexStoresData, String> {
+    Ok(
+        fsio::read_json(&paths.codex_stores_file(), "codex config file")?
+            .unwrap_or(CodexStoresData { configs: vec![] }),
+    )
+}
+
+/// Locked read-modify-write of codexcc.json
+pub fn update_codex_stores<R>(
+    paths: &Paths,
+    mut apply: impl FnMut(&mut CodexStoresData) -> Result<R, String>,
+) -> Result<R, String> {
+    fsio::update_json(
+        paths,
+        &paths.codex_stores_file(),
+        "codex config file",
+        |current| {
+            let mut data = current.unwrap_or(CodexStoresData { configs: vec![] });
+            let result = apply(&mut data)?;
+            Ok((Some(data), result))
+        },
+    )
+}
+
+pub fn get_codex_stores(paths: &Paths) -> Result<Vec<CodexStore>, String> {
+    Ok(read_codex_stores(paths)?.configs)
+}
+
+pub fn get_current_codex_store(paths: &Paths) -> Result<Option<CodexStore>, String> {
+    Ok(read_codex_stores(paths)?
+        .configs
+        .into_iter()
+        .find(|s| s.using))
+}
+
+pub fn create_codex_store(
+    paths: &Paths,
+    title: String,
+    mut config: Value,
+) -> Result<CodexStore, String> {
+    // The API key goes into the vault, the store keeps a handle
+    vault::seal_secrets(paths, &mut config, &vault::codex_secret_pointers(), None)?;
+
+    let new_store = CodexStore {
+        id: nanoid::nanoid!(),
+        title,
+        created_at: chrono::Utc::now().timestamp_millis() as u64,
+        config,
+        using: false,
+    };
+
+    update_codex_stores(paths, |data| {
+        data.configs.push(new_store.clone());
+        Ok(())
+    })?;
+
+    Ok(new_store)
+}
+
+pub fn update_codex_store(
+    paths: &Paths,
+    id: &str,
+    title: String,
+    mut config: Value,
+) -> Result<CodexStore, String> {
+    // A masked key sent back unchanged keeps its handle
+    let previous_config = get_codex_stores(paths)?
+        .into_iter()
+        .find(|s| s.id == id)
+        .map(|s| s.config);
+    vault::seal_secrets(
+        paths,
+        &mut config,
+        &vault::codex_secret_pointers(),
+        previous_config.as_ref(),
+    )?;
+
+    let updated_store = update_codex_stores(paths, |data| {
+        let store = data
+            .configs
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or("Store not found")?;
+        store.title = title.clone();
+        store.config = config.clone();
+        Ok(store.clone())
+    })?;
+
+    if let Err(e) = vault::prune_secrets(paths) {
+        eprintln!("Warning: Failed to remove unused secrets: {}", e);
+    }
+    Ok(updated_store)
+}
+
+pub fn delete_codex_store(paths: &Paths, id: &str) -> Result<(), String> {
+    update_codex_stores(paths, |data| {
+        data.configs.retain(|s| s.id != id);
+        Ok(())
+    })?;
+
+    if let Err(e) = vault::prune_secrets(paths) {
+        eprintln!("Warning: Failed to remove unused secrets: {}", e);
+    }
+    Ok(())
+}
+
+/// Provider base URL of a Codex store
+pub fn codex_base_url(store: &CodexStore) -> &str {
+    store
+        .config
+        .get("url")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_CODEX_URL)
+}
+
+/// Model a Codex store asks for
+pub fn codex_model(store: &CodexStore) -> &str {
+    store
+        .config
+        .get("model")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_CODEX_MODEL)
+}
+
+/// Wire API of a Codex store's provider, `responses` or `chat`
+pub fn codex_wire_api(store: &CodexStore) -> &str {
+    store
+        .config
+        .get("wire_api")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_WIRE_API)
+}
+
+/// Render `config.toml` for a Codex store, using the store title as provider name
+pub fn render_config_toml(store: &CodexStore) -> Result<String, String> {
+    store.config.as_object().ok_or("Invalid config format")?;
+
+    let model = codex_model(store);
+    let url = codex_base_url(store);
+    let wire_api = codex_wire_api(store);
+    let name = &store.title; // Use configuration name as provider name
+
+    Ok(format!(
+        r#"model_provider = "{name}"
+model = "{model}"
+model_reasoning_effort = "high"
+disable_response_storage = true
+windows_wsl_setup_acknowledged = true
+
+[model_providers.{name}]
+name = "{name}"
+base_url = "{url}"
+wire_api = "{wire_api}"
+requires_openai_auth = true
+"#
+    ))
+}
+
+/// Write auth.json and config.toml for a Codex store into the Codex root
+pub fn write_codex_files(
+    paths: &Paths,
+    root_path: &std::path::Path,
+    store: &CodexStore,
+) -> Result<(), String> {
+    // Ensure .codex directory exists
+    std::fs::create_dir_all(root_path)
+        .map_err(|e| format!("Failed to create codex root directory: {}", e))?;
+
+    let config_obj = store.config.as_object().ok_or("Invalid config format")?;
+
+    // 1. Write auth.json, resolving the key from the vault only now
+    if let Some(api_key) = config_obj.get("api_key").and_then(|v| v.as_str()) {
+        let api_key = if vault::is_handle(api_key) {
+            vault::resolve_secret(paths, api_key)?
+        } else {
+            api_key.to_string()
+        };
+        let auth_content = serde_json::json!({
+            "OPENAI_API_KEY": api_key
+        });
+        let auth_json = serde_json::to_string_pretty(&auth_content)
+            .map_err(|e| format!("Failed to serialize auth.json: {}", e))?;
+        fsio::write_private_text(paths, &root_path.join("auth.json"), "auth.json", &auth_json)?;
+    }
+
+    // 2. Write config.toml
+    let config_toml_content = render_config_toml(store)?;
+    fsio::write_text(
+        paths,
+        &root_path.join("config.toml"),
+        "config.toml",
+        &config_toml_content,
+    )?;
+
+    Ok(())
+}
+
+pub fn set_using_codex_store(paths: &Paths, id: &str) -> Result<(), String> {
+    let selected_store = update_codex_stores(paths, |data| {
+        let mut selected_store: Option<CodexStore> = None;
+
+        for store in &mut data.configs {
+            if store.id == id {
+                store.using = true;
+                selected_store = Some(store.clone());
+            } else {
+                store.using = false;
+            }
+        }
+        Ok(selected_store)
+    })?;
+
+    // If a store was selected, update the .codex directory
+    if let Some(store) = selected_store {
+        let settings = get_codex_global_settings(paths)?;
+
+        // Only proceed if Codex is enabled
+        if settings.enabled {
+            write_codex_files(paths, &PathBuf::from(&settings.root_path), &store)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn get_codex_global_settings(paths: &Paths) -> Result<CodexGlobalSettings, String> {
+    Ok(
+        fsio::read_json(&paths.codex_settings_file(), "codex settings")?
+            .unwrap_or_else(|| CodexGlobalSettings::default_for(paths)),
+    )
+}
+
+pub fn update_codex_global_settings(
+    paths: &Paths,
+    settings: &CodexGlobalSettings,
+) -> Result<(), String> {
+    fsio::write_json(
+        paths,
+        &paths.codex_settings_file(),
+        "codex settings",
+        settings,
+    )
+}
@@ -0,0 +1,531 @@
+//! Detects and reconciles edits that make `~/.claude/settings.json` diverge from the active profile.
+//!
+//! The active profile is expected to be fully present in settings.json. Its
+//! resolved settings are walked with the same merge strategies used to apply
+//! them, and every value that is missing, different, or extra inside a merged
+//! object or list was edited outside the app. The edits can then be undone by
+//! re-applying the profile, or absorbed into it.
+
+use serde_json::{Map, Value};
+
+use crate::merge::{self, AppliedSettings, MergeChange, MergeStrategies, MergeStrategy};
+use crate::paths::Paths;
+use crate::pointer;
+use crate::relay;
+use crate::settings;
+use crate::stores::{self, ConfigStore};
+use crate::vault;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ActiveDrift {
+    pub store_id: String,
+    pub store_title: String,
+    /// Pointers where settings.json no longer holds the profile's value
+    pub pointers: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DriftKind {
+    /// In settings.json but not in the profile
+    Added,
+    /// In the profile but missing from settings.json
+    Removed,
+    /// In both with different values
+    Changed,
+}
+
+/// One difference between the active profile and settings.json
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct DriftEntry {
+    pub pointer: String,
+    pub kind: DriftKind,
+    /// `expected` and `actual` are one item of the list at `pointer` rather than its whole value
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub item: bool,
+    /// Value in the profile, absent for added keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Value>,
+    /// Value in settings.json, absent for removed keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<Value>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ProfileDiff {
+    pub store_id: String,
+    pub store_title: String,
+    /// Empty when settings.json matches the profile
+    pub entries: Vec<DriftEntry>,
+}
+
+/// Where settings.json diverges from the store marked `using`, `None` if it matches.
+///
+/// Keys only added to settings.json are not drift, the profile is still fully applied.
+pub fn check_active_drift(paths: &Paths) -> Result<Option<ActiveDrift>, String> {
+    let Some(diff) = diff_active_profile(paths)? else {
+        return Ok(None);
+    };
+
+    let mut pointers: Vec<String> = Vec::new();
+    for entry in diff.entries {
+        if entry.kind != DriftKind::Added && !pointers.contains(&entry.pointer) {
+            pointers.push(entry.pointer);
+        }
+    }
+
+    if pointers.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(ActiveDrift {
+        store_id: diff.store_id,
+        store_title: diff.store_title,
+        pointers,
+    }))
+}
+
+/// Structured diff between the active store and settings.json, `None` if no store is active.
+///
+/// Tokens are masked in the returned values.
+pub fn diff_active_profile(paths: &Paths) -> Result<Option<ProfileDiff>, String> {
+    let Some(active) = ActiveProfile::read(paths)? else {
+        return Ok(None);
+    };
+
+    let entries = active
+        .diff(paths)?
+        .into_iter()
+        .map(|entry| DriftEntry {
+            expected: entry.expected.map(|value| masked(&entry.pointer, value)),
+            actual: entry.actual.map(|value| masked(&entry.pointer, value)),
+            ..entry
+        })
+        .collect();
+
+    Ok(Some(ProfileDiff {
+        store_id: active.store.id,
+        store_title: active.store.title,
+        entries,
+    }))
+}
+
+/// Undo edits made outside the app by writing the active store into settings.json again.
+///
+/// Keys that were only added to settings.json are kept.
+pub fn reapply_active_profile(paths: &Paths) -> Result<(), String> {
+    let active = ActiveProfile::read(paths)?.ok_or_else(|| "No active config".to_string())?;
+    restore_applied(paths, active.applied.as_ref())?;
+    stores::set_using_config(paths, &active.store.id)
+}
+
+/// Save edits made outside the app into the active store, so it matches settings.json.
+///
+/// Values the store inherits from its parents can be overridden but not removed,
+/// those are left in the returned diff.
+pub fn absorb_active_profile(paths: &Paths) -> Result<Option<ProfileDiff>, String> {
+    let active = ActiveProfile::read(paths)?.ok_or_else(|| "No active config".to_string())?;
+    let entries = active.diff(paths)?;
+    if entries.is_empty() {
+        return diff_active_profile(paths);
+    }
+
+    let mut store_settings = active.store.settings.clone();
+    for entry in &entries {
+        absorb_entry(&mut store_settings, entry)?;
+    }
+
+    // Added values may have been the user's own before the store was applied,
+    // they stay in settings.json when switching away
+    restore_applied(paths, active.applied.as_ref())?;
+
+    // Re-applies the store, tokens go back into the vault
+    stores::update_config(
+        paths,
+        &active.store.id,
+        active.store.title.clone(),
+        store_settings,
+        active.store.parent.clone(),
+    )?;
+    eprintln!(
+        "✅ Absorbed settings.json edits into \"{}\"",
+        active.store.title
+    );
+
+    diff_active_profile(paths)
+}
+
+/// The store marked `using` and what it contributed to settings.json
+struct ActiveProfile {
+    store: ConfigStore,
+    resolved: Value,
+    strategies: MergeStrategies,
+    applied: Option<AppliedSettings>,
+}
+
+impl ActiveProfile {
+    fn read(paths: &Paths) -> Result<Option<Self>, String> {
+        let Some(stores_data) = stores::read_stores_data(paths)? else {
+            return Ok(None);
+        };
+        let Some(store) = stores_data.configs.iter().find(|store| store.using) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            store: store.clone(),
+            resolved: stores::resolve_store_settings(&stores_data, &store.id)?,
+            strategies: stores::merge_strategies(&stores_data),
+            applied: stores_data
+                .applied
+                .clone()
+                .filter(|applied| applied.store_id == store.id),
+        }))
+    }
+
+    /// Unmasked differences, for comparing and absorbing
+    fn diff(&self, paths: &Paths) -> Result<Vec<DriftEntry>, String> {
+        // A locked vault leaves the tokens out of the comparison
+        let mut expected = vault::reveal(paths, &self.resolved)
+            .unwrap_or_else(|_| without_handles(self.resolved.clone()));
+        relay::route_env(paths, &mut expected, None)?;
+        let current = settings::read_user_settings(paths)?;
+
+        let mut entries = Vec::new();
+        diff_at(&current, "", &expected, &self.strategies, &mut entries);
+        Ok(entries)
+    }
+}
+
+fn diff_at(
+    current: &Value,
+    at: &str,
+    expected: &Value,
+    strategies: &MergeStrategies,
+    entries: &mut Vec<DriftEntry>,
+) {
+    let Some(actual) = current.pointer(at) else {
+        entries.push(DriftEntry {
+            pointer: at.to_string(),
+            kind: DriftKind::Removed,
+            item: false,
+            expected: Some(expected.clone()),
+            actual: None,
+        });
+        return;
+    };
+
+    match (merge::strategy_for(strategies, at), expected, actual) {
+        (MergeStrategy::DeepMerge, Value::Object(object), Value::Object(existing)) => {
+            for (key, value) in object {
+                diff_at(
+                    current,
+                    &pointer::child(at, key),
+                    value,
+                    strategies,
+                    entries,
+                );
+            }
+            // The root also holds the user's own settings, only merged objects are owned by the profile
+            if at.is_empty() {
+                return;
+            }
+            for (key, value) in existing
+                .iter()
+                .filter(|(key, _)| !object.contains_key(*key))
+            {
+                entries.push(DriftEntry {
+                    pointer: pointer::child(at, key),
+                    kind: DriftKind::Added,
+                    item: false,
+                    expected: None,
+                    actual: Some(value.clone()),
+                });
+            }
+        }
+        (MergeStrategy::ArrayUnion, Value::Array(items), Value::Array(existing)) => {
+            for item in items.iter().filter(|item| !existing.contains(item)) {
+                entries.push(DriftEntry {
+                    pointer: at.to_string(),
+                    kind: DriftKind::Removed,
+                    item: true,
+                    expected: Some(item.clone()),
+                    actual: None,
+                });
+            }
+            for item in existing.iter().filter(|item| !items.contains(item)) {
+                entries.push(DriftEntry {
+                    pointer: at.to_string(),
+                    kind: DriftKind::Added,
+                    item: true,
+                    expected: None,
+                    actual: Some(item.clone()),
+                });
+            }
+        }
+        _ if actual != expected => entries.push(DriftEntry {
+            pointer: at.to_string(),
+            kind: DriftKind::Changed,
+            item: false,
+            expected: Some(expected.clone()),
+            actual: Some(actual.clone()),
+        }),
+        _ => {}
+    }
+}
+
+/// Make the store's own settings hold the value settings.json has at the entry.
+fn absorb_entry(store_settings: &mut Value, entry: &DriftEntry) -> Result<(), String> {
+    let at = entry.pointer.as_str();
+    match (entry.kind, entry.item) {
+        (DriftKind::Added, true) => {
+            let Some(item) = &entry.actual else {
+                return Ok(());
+            };
+            if !matches!(store_settings.pointer(at), Some(Value::Array(_))) {
+                pointer::set(store_settings, at, Value::Array(vec![]))?;
+            }
+            if let Some(Value::Array(items)) = store_settings.pointer_mut(at) {
+                if !items.contains(item) {
+                    items.push(item.clone());
+                }
+            }
+        }
+        (DriftKind::Removed, true) => {
+            if let Some(Value::Array(items)) = store_settings.pointer_mut(at) {
+                items.retain(|item| Some(item) != entry.expected.as_ref());
+            }
+        }
+        (DriftKind::Removed, false) => {
+            pointer::remove(store_settings, at);
+        }
+        (DriftKind::Added | DriftKind::Changed, _) => {
+            if let Some(value) = &entry.actual {
+                pointer::set(store_settings, at, value.clone())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Put the values the store wrote back into settings.json where they were edited,
+/// so switching reverts them to the user's base settings and re-applies cleanly.
+fn restore_applied(paths: &Paths, applied: Option<&AppliedSettings>) -> Result<(), String> {
+    let changes = match applied {
+        Some(applied) => vault::reveal_changes(paths, &applied.changes)?,
+        None => vec![],
+    };
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    settings::update_user_settings(paths, |current| {
+        for change in &changes {
+            match change {
+                MergeChange::Set {
+                    pointer: at, value, ..
+                } => {
+                    if current.pointer(at) != Some(value) {
+                        pointer::set(current, at, value.clone())?;
+                    }
+                }
+                MergeChange::Append { pointer: at, value } => match current.pointer_mut(at) {
+                    Some(Value::Array(items)) => {
+                        if !items.contains(value) {
+                            items.push(value.clone());
+                        }
+                    }
+                    _ => pointer::set(current, at, Value::Array(vec![value.clone()]))?,
+                },
+                MergeChange::Create { .. } => {}
+            }
+        }
+        Ok(true)
+    })?;
+    Ok(())
+}
+
+fn without_handles(mut settings: Value) -> Value {
+    for at in vault::settings_secret_pointers(&settings) {
+        if settings
+            .pointer(&at)
+            .and_then(Value::as_str)
+            .is_some_and(vault::is_handle)
+        {
+            pointer::remove(&mut settings, &at);
+        }
+    }
+    settings
+}
+
+/// `value` with the tokens it holds masked, as found at `at` in a settings file
+fn masked(at: &str, value: Value) -> Value {
+    let mut root = Value::Object(Map::new());
+    if pointer::set(&mut root, at, value.clone()).is_err() {
+        return value;
+    }
+    for secret in vault::settings_secret_pointers(&root) {
+        if let Some(Value::String(text)) = root.pointer_mut(&secret) {
+            if !text.is_empty() {
+                *text = vault::mask(text);
+            }
+        }
+    }
+    root.pointer(at).cloned().unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn diff(current: Value, expected: Value) -> Vec<DriftEntry> {
+        let mut entries = Vec::new();
+        diff_at(
+            &current,
+            "",
+            &expected,
+            &merge::default_strategies(),
+            &mut entries,
+        );
+        entries
+    }
+
+    fn entry(
+        pointer: &str,
+        kind: DriftKind,
+        expected: Option<Value>,
+        actual: Option<Value>,
+    ) -> DriftEntry {
+        DriftEntry {
+            pointer: pointer.to_string(),
+            kind,
+            item: false,
+            expected,
+            actual,
+        }
+    }
+
+    fn item(pointer: &str, kind: DriftKind, value: Value) -> DriftEntry {
+        let (expected, actual) = match kind {
+            DriftKind::Removed => (Some(value), None),
+            _ => (None, Some(value)),
+        };
+        DriftEntry {
+            item: true,
+            ..entry(pointer, kind, expected, actual)
+        }
+    }
+
+    fn profile() -> Value {
+        json!({
+            "model": "opus",
+            "env": { "ANTHROPIC_BASE_URL": "https://api.example.com", "API_TIMEOUT_MS": "60000" },
+            "permissions": { "allow": ["Read", "Edit"] }
+        })
+    }
+
+    #[test]
+    fn a_fully_applied_profile_has_no_drift_whatever_the_user_added_at_the_root() {
+        let mut current = profile();
+        current["theme"] = json!("dark");
+        assert!(diff(current, profile()).is_empty());
+    }
+
+    #[test]
+    fn reports_changed_removed_and_added_values() {
+        let current = json!({
+            "model": "sonnet",
+            "env": { "ANTHROPIC_BASE_URL": "https://api.example.com", "DEBUG": "1" },
+            "permissions": { "allow": ["Edit", "Bash"] }
+        });
+        assert_eq!(
+            diff(current, profile()),
+            [
+                entry(
+                    "/env/API_TIMEOUT_MS",
+                    DriftKind::Removed,
+                    Some(json!("60000")),
+                    None
+                ),
+                entry("/env/DEBUG", DriftKind::Added, None, Some(json!("1"))),
+                entry(
+                    "/model",
+                    DriftKind::Changed,
+                    Some(json!("opus")),
+                    Some(json!("sonnet"))
+                ),
+                item("/permissions/allow", DriftKind::Removed, json!("Read")),
+                item("/permissions/allow", DriftKind::Added, json!("Bash")),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_replaced_merged_object_is_one_change() {
+        let current =
+            json!({ "model": "opus", "env": "oops", "permissions": { "allow": ["Read", "Edit"] } });
+        assert_eq!(
+            diff(current, profile()),
+            [entry(
+                "/env",
+                DriftKind::Changed,
+                Some(profile()["env"].clone()),
+                Some(json!("oops"))
+            )]
+        );
+    }
+
+    #[test]
+    fn absorbing_every_entry_makes_the_profile_match() {
+        let current = json!({
+            "model": "sonnet",
+            "env": { "ANTHROPIC_BASE_URL": "https://api.example.com", "DEBUG": "1" },
+            "permissions": { "allow": ["Edit", "Bash"], "deny": ["WebFetch"] },
+            "theme": "dark"
+        });
+        let mut store_settings = profile();
+        for entry in diff(current, profile()) {
+            absorb_entry(&mut store_settings, &entry).unwrap();
+        }
+        assert_eq!(
+            store_settings,
+            json!({
+                "model": "sonnet",
+                "env": { "ANTHROPIC_BASE_URL": "https://api.example.com", "DEBUG": "1" },
+                "permissions": { "allow": ["Edit", "Bash"], "deny": ["WebFetch"] }
+            })
+        );
+    }
+
+    #[test]
+    fn list_items_are_absorbed_into_a_list_the_store_did_not_have() {
+        let mut store_settings = json!({ "permissions": {} });
+        let added = item(
+            "/permissions/ask",
+            DriftKind::Added,
+            json!("Bash(git push:*)"),
+        );
+        absorb_entry(&mut store_settings, &added).unwrap();
+        absorb_entry(&mut store_settings, &added).unwrap();
+        assert_eq!(
+            store_settings,
+            json!({ "permissions": { "ask": ["Bash(git push:*)"] } })
+        );
+    }
+
+    #[test]
+    fn tokens_are_masked_wherever_the_value_sits() {
+        let token = "sk-ant-0123456789abcdef";
+        let whole = masked(
+            "/env",
+            json!({ "ANTHROPIC_AUTH_TOKEN": token, "DEBUG": "1" }),
+        );
+        assert_ne!(whole["ANTHROPIC_AUTH_TOKEN"], token);
+        assert_eq!(whole["DEBUG"], "1");
+        assert_ne!(
+            masked("/env/ANTHROPIC_AUTH_TOKEN", json!(token)),
+            json!(token)
+        );
+        assert_eq!(masked("/model", json!("opus")), json!("opus"));
+    }
+}
@@ -0,0 +1,335 @@
+//! Failover between Claude Code profiles when the active endpoint goes down.
+//!
+//! The group is an ordered list of store ids kept in `~/.ccconfig/failover.json`.
+//! The desktop app health-checks the active member on an interval. After
+//! `failure_threshold` failed checks in a row it switches to the next member
+//! that passes a check, and with `switch_back` it returns to the first member
+//! once that one passes as many checks in a row. Switches are written to the
+//! automatic switching audit log.
+
+use std::collections::HashSet;
+
+use crate::auto_switch::{self, AuditEntry, SwitchTarget};
+use crate::fsio;
+use crate::paths::Paths;
+use crate::stores;
+
+pub const FAILOVER_RULE_ID: &str = "failover";
+const FAILOVER_RULE_NAME: &str = "Failover";
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const MIN_INTERVAL_SECS: u64 = 15;
+const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct FailoverSettings {
+    pub enabled: bool,
+    /// Seconds between two health checks
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    /// Failed checks in a row before switching away, and passed checks before switching back
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Return to the first member once it recovers
+    #[serde(default)]
+    pub switch_back: bool,
+    /// Store ids, in order of preference
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+impl Default for FailoverSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            switch_back: false,
+            members: vec![],
+        }
+    }
+}
+
+fn default_interval_secs() -> u64 {
+    DEFAULT_INTERVAL_SECS
+}
+
+fn default_failure_threshold() -> u32 {
+    DEFAULT_FAILURE_THRESHOLD
+}
+
+/// The saved group, leaving out profiles deleted since
+pub fn get_failover_settings(paths: &Paths) -> Result<FailoverSettings, String> {
+    let mut settings: FailoverSettings =
+        fsio::read_json(&paths.failover_file(), "failover settings")?.unwrap_or_default();
+    let configs = stores::get_stores(paths)?;
+    settings
+        .members
+        .retain(|member| configs.iter().any(|store| &store.id == member));
+    Ok(settings)
+}
+
+pub fn update_failover_settings(
+    paths: &Paths,
+    settings: FailoverSettings,
+) -> Result<FailoverSettings, String> {
+    if settings.interval_secs < MIN_INTERVAL_SECS {
+        return Err(format!(
+            "Profiles cannot be checked more often than every {} seconds",
+            MIN_INTERVAL_SECS
+        ));
+    }
+    if settings.failure_threshold == 0 {
+        return Err("Failure threshold must be at least 1".to_string());
+    }
+
+    let configs = stores::get_stores(paths)?;
+    let mut seen: HashSet<&str> = HashSet::new();
+    for member in &settings.members {
+        if !seen.insert(member) {
+            return Err(format!("Profile '{}' is in the group twice", member));
+        }
+        if !configs.iter().any(|store| &store.id == member) {
+            return Err(format!("Profile '{}' not found", member));
+        }
+    }
+
+    fsio::write_json(
+        paths,
+        &paths.failover_file(),
+        "failover settings",
+        &settings,
+    )?;
+    Ok(settings)
+}
+
+/// Profiles to health-check in one round
+#[derive(Debug, Clone)]
+pub struct FailoverCheck {
+    pub active: String,
+    /// The first member, checked while waiting to switch back to it
+    pub primary: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailoverAction {
+    Stay,
+    /// Switch to the first healthy member of [`candidates`]
+    FailOver,
+    /// Switch back to the first member
+    SwitchBack,
+}
+
+/// Counts consecutive check results for the active member and the primary
+#[derive(Debug, Default)]
+pub struct FailoverMonitor {
+    watched: Option<String>,
+    failures: u32,
+    recoveries: u32,
+    // Set once a failover found no healthy member, so it is only reported once
+    stranded: bool,
+}
+
+impl FailoverMonitor {
+    /// What to check this round, `None` when failover is off or the active profile is not a member
+    pub fn next_check(
+        &mut self,
+        paths: &Paths,
+        settings: &FailoverSettings,
+    ) -> Result<Option<FailoverCheck>, String> {
+        let active = match stores::get_current_store(paths)? {
+            Some(store) if settings.enabled && settings.members.contains(&store.id) => store.id,
+            _ => {
+                *self = Self::default();
+                return Ok(None);
+            }
+        };
+
+        // Counts start over whenever the active profile changes, however it changed
+        if self.watched.as_ref() != Some(&active) {
+            *self = Self {
+                watched: Some(active.clone()),
+                ..Self::default()
+            };
+        }
+
+        let primary = settings
+            .members
+            .first()
+            .filter(|primary| settings.switch_back && **primary != active)
+            .cloned();
+        Ok(Some(FailoverCheck { active, primary }))
+    }
+
+    /// Record the results of a round's checks and decide what to do
+    pub fn record(
+        &mut self,
+        settings: &FailoverSettings,
+        active_healthy: bool,
+        primary_healthy: Option<bool>,
+    ) -> FailoverAction {
+        if active_healthy {
+            self.failures = 0;
+            self.stranded = false;
+        } else {
+            self.failures += 1;
+        }
+        if primary_healthy == Some(true) {
+            self.recoveries += 1;
+        } else {
+            self.recoveries = 0;
+        }
+
+        if self.failures >= settings.failure_threshold {
+            FailoverAction::FailOver
+        } else if self.recoveries >= settings.failure_threshold {
+            FailoverAction::SwitchBack
+        } else {
+            FailoverAction::Stay
+        }
+    }
+
+    /// Log that no member could take over, once per outage
+    pub fn no_candidate(
+        &mut self,
+        paths: &Paths,
+        active: &str,
+        reason: String,
+    ) -> Option<AuditEntry> {
+        if self.stranded {
+            return None;
+        }
+        self.stranded = true;
+
+        eprintln!("Failover found no healthy profile: {}", reason);
+        let entry = AuditEntry {
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            rule_id: FAILOVER_RULE_ID.to_string(),
+            rule_name: FAILOVER_RULE_NAME.to_string(),
+            target: SwitchTarget::Claude,
+            from_store: Some(active.to_string()),
+            to_store: active.to_string(),
+            to_title: None,
+            reason,
+            success: false,
+            error: Some("No healthy profile in the failover group".to_string()),
+        };
+        if let Err(e) = auto_switch::append_audit_entry(paths, &entry) {
+            eprintln!("Warning: Failed to write auto switch log: {}", e);
+        }
+        Some(entry)
+    }
+}
+
+/// Members to try when failing over from `active`: the ones after it, then the ones before
+pub fn candidates(settings: &FailoverSettings, active: &str) -> Vec<String> {
+    let position = settings
+        .members
+        .iter()
+        .position(|member| member == active)
+        .unwrap_or(0);
+    settings
+        .members
+        .iter()
+        .cycle()
+        .skip(position + 1)
+        .take(settings.members.len())
+        .filter(|member| *member != active)
+        .cloned()
+        .collect()
+}
+
+/// Make `to` the active profile and log the switch
+pub fn switch_to(paths: &Paths, from: &str, to: &str, reason: String) -> AuditEntry {
+    auto_switch::switch_profile(
+        paths,
+        (FAILOVER_RULE_ID, FAILOVER_RULE_NAME),
+        SwitchTarget::Claude,
+        to,
+        Some(from.to_string()),
+        reason,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(members: &[&str], switch_back: bool) -> FailoverSettings {
+        FailoverSettings {
+            enabled: true,
+            failure_threshold: 2,
+            switch_back,
+            members: members.iter().map(|member| member.to_string()).collect(),
+            ..FailoverSettings::default()
+        }
+    }
+
+    #[test]
+    fn fails_over_after_enough_failures_in_a_row() {
+        let settings = group(&["a", "b"], false);
+        let mut monitor = FailoverMonitor::default();
+        assert_eq!(monitor.record(&settings, false, None), FailoverAction::Stay);
+        // A passed check in between starts the count over
+        assert_eq!(monitor.record(&settings, true, None), FailoverAction::Stay);
+        assert_eq!(monitor.record(&settings, false, None), FailoverAction::Stay);
+        assert_eq!(
+            monitor.record(&settings, false, None),
+            FailoverAction::FailOver
+        );
+        // Until a switch resets the monitor, every further failure asks again
+        assert_eq!(
+            monitor.record(&settings, false, None),
+            FailoverAction::FailOver
+        );
+    }
+
+    #[test]
+    fn switches_back_once_the_primary_passes_enough_checks() {
+        let settings = group(&["a", "b"], true);
+        let mut monitor = FailoverMonitor::default();
+        assert_eq!(
+            monitor.record(&settings, true, Some(true)),
+            FailoverAction::Stay
+        );
+        assert_eq!(
+            monitor.record(&settings, true, Some(false)),
+            FailoverAction::Stay
+        );
+        assert_eq!(
+            monitor.record(&settings, true, Some(true)),
+            FailoverAction::Stay
+        );
+        assert_eq!(
+            monitor.record(&settings, true, Some(true)),
+            FailoverAction::SwitchBack
+        );
+
+        // Failing over wins when both are due
+        let mut monitor = FailoverMonitor::default();
+        monitor.record(&settings, false, Some(true));
+        assert_eq!(
+            monitor.record(&settings, false, Some(true)),
+            FailoverAction::FailOver
+        );
+    }
+
+    #[test]
+    fn candidates_follow_the_active_member_and_wrap_around() {
+        let settings = group(&["a", "b", "c", "d"], false);
+        assert_eq!(candidates(&settings, "a"), ["b", "c", "d"]);
+        assert_eq!(candidates(&settings, "c"), ["d", "a", "b"]);
+        assert_eq!(candidates(&settings, "d"), ["a", "b", "c"]);
+        // Not a member, so every member is worth a try, in order
+        assert_eq!(candidates(&settings, "x"), ["b", "c", "d", "a"]);
+        assert!(candidates(&group(&["a"], false), "a").is_empty());
+    }
+
+    #[test]
+    fn saved_settings_fill_in_defaults() {
+        let settings: FailoverSettings = serde_json::from_str(r#"{ "enabled": true }"#).unwrap();
+        assert_eq!(settings.interval_secs, DEFAULT_INTERVAL_SECS);
+        assert_eq!(settings.failure_threshold, DEFAULT_FAILURE_THRESHOLD);
+        assert!(!settings.switch_back);
+        assert!(settings.members.is_empty());
+    }
+}
@@ -0,0 +1,319 @@
+//! Shared file-access layer for every file the app manages.
+//!
+//! Writes go to a temporary file in the same directory, are fsynced and then
+//! renamed over the target, so readers never observe a truncated file.
+//! Read-modify-write cycles hold an advisory lock under `~/.ccconfig/locks`
+//! so the GUI, the tray and the CLI serialize against each other. Writers
+//! that do not take the lock (Claude Code rewriting `~/.claude.json`, or a
+//! user editing a file by hand) are detected by re-checking the file's
+//! fingerprint just before the rename, and the update is retried against the
+//! fresh content instead of overwriting it. The previous content of every
+//! file written here is kept in the history store, see [`crate::history`].
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::history;
+use crate::paths::Paths;
+
+/// How many times an update is re-applied when the file changes underneath it
+const MAX_UPDATE_ATTEMPTS: usize = 5;
+
+/// Identity of a file's content at a point in time, `None` when it does not exist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint(Option<(u64, Option<SystemTime>, u64)>);
+
+impl Fingerprint {
+    pub fn of(path: &Path) -> Result<Self, String> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(Self::of_bytes(path, &bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Fingerprint(None)),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    fn of_bytes(path: &Path, bytes: &[u8]) -> Self {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Fingerprint(Some((bytes.len() as u64, modified, hasher.finish())))
+    }
+}
+
+/// Exclusive advisory lock on a managed file, released on drop
+pub struct FileLock {
+    file: File,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// Stable, filesystem-safe identifier for a managed file, e.g. `settings.json-<hash>`
+pub fn file_key(target: &Path) -> String {
+    let file_name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_start_matches('.').to_string())
+        .unwrap_or_else(|| "file".to_string());
+
+    // Different directories can hold files with the same name, so include the full path
+    let mut hasher = DefaultHasher::new();
+    target.hash(&mut hasher);
+
+    format!("{}-{:016x}", file_name, hasher.finish())
+}
+
+fn lock_path(paths: &Paths, target: &Path) -> PathBuf {
+    paths.lock_dir().join(format!("{}.lock", file_key(target)))
+}
+
+/// Snapshot the current content into the history store before it is replaced.
+/// A failed snapshot is logged but never blocks the write itself.
+fn record_history(paths: &Paths, path: &Path) {
+    if let Err(e) = history::record(paths, path) {
+        eprintln!(
+            "Warning: Failed to record history for {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Take the cross-process advisory lock for `target`, blocking until it is free.
+pub fn lock(paths: &Paths, target: &Path) -> Result<FileLock, String> {
+    let lock_dir = paths.lock_dir();
+    std::fs::create_dir_all(&lock_dir)
+        .map_err(|e| format!("Failed to create lock directory: {}", e))?;
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(lock_path(paths, target))
+        .map_err(|e| format!("Failed to open lock file for {}: {}", target.display(), e))?;
+
+    file.lock()
+        .map_err(|e| format!("Failed to lock {}: {}", target.display(), e))?;
+
+    Ok(FileLock { file })
+}
+
+/// Write `contents` to `path` via a fsynced temporary file and an atomic rename.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    write_atomic_file(path, contents, false)
+}
+
+/// Like [`write_atomic`], but the file is created readable only by the user
+/// (0600 on Unix) before anything is written to it.
+pub fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    write_atomic_file(path, contents, true)
+}
+
+fn write_atomic_file(path: &Path, contents: &[u8], private: bool) -> Result<(), String> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create directory {}: {}", dir.display(), e))?;
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?
+        .to_string_lossy();
+    let tmp_path = dir.join(format!(
+        ".{}.tmp-{}-{}",
+        file_name,
+        std::process::id(),
+        nanoid::nanoid!(8)
+    ));
+
+    let result = (|| {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if private {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut tmp = options
+            .open(&tmp_path)
+            .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+        tmp.write_all(contents)
+            .map_err(|e| format!("Failed to write temporary file: {}", e))?;
+        tmp.sync_all()
+            .map_err(|e| format!("Failed to sync temporary file: {}", e))?;
+
+        // Keep the permissions of the file being replaced (e.g. 0600 credentials)
+        if let Some(metadata) = std::fs::metadata(path).ok().filter(|_| !private) {
+            let _ = std::fs::set_permissions(&tmp_path, metadata.permissions());
+        }
+
+        std::fs::rename(&tmp_path, path)
+            .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Ok(dir_handle) = File::open(&dir) {
+        let _ = dir_handle.sync_all();
+    }
+
+    Ok(())
+}
+
+/// Read and parse a JSON file, returning `None` if it does not exist.
+pub fn read_json<T: DeserializeOwned>(path: &Path, label: &str) -> Result<Option<T>, String> {
+    read_json_with_fingerprint(path, label).map(|(value, _)| value)
+}
+
+fn read_json_with_fingerprint<T: DeserializeOwned>(
+    path: &Path,
+    label: &str,
+) -> Result<(Option<T>, Fingerprint), String> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((None, Fingerprint(None))),
+        Err(e) => return Err(format!("Failed to read {}: {}", label, e)),
+    };
+
+    let fingerprint = Fingerprint::of_bytes(path, &bytes);
+    let value =
+        serde_json::from_slice(&bytes).map_err(|e| format!("Failed to parse {}: {}", label, e))?;
+
+    Ok((Some(value), fingerprint))
+}
+
+fn to_pretty_json<T: Serialize>(value: &T, label: &str) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| format!("Failed to serialize {}: {}", label, e))
+}
+
+/// Serialize `value` as pretty JSON and write it atomically under the file's lock.
+pub fn write_json<T: Serialize>(
+    paths: &Paths,
+    path: &Path,
+    label: &str,
+    value: &T,
+) -> Result<(), String> {
+    let content = to_pretty_json(value, label)?;
+    let _lock = lock(paths, path)?;
+    record_history(paths, path);
+    write_atomic(path, &content).map_err(|e| format!("Failed to write {}: {}", label, e))
+}
+
+/// Locked, atomic read-modify-write of a JSON file.
+///
+/// `apply` receives the current content (`None` if the file does not exist),
+/// mutates it and returns a result. If the file changes between the read and
+/// the write, `apply` is run again on the new content. Returning `Ok((None, r))`
+/// from `apply` leaves the file untouched.
+pub fn update_json<T, R, F>(paths: &Paths, path: &Path, label: &str, apply: F) -> Result<R, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnMut(Option<T>) -> Result<(Option<T>, R), String>,
+{
+    let lock = lock(paths, path)?;
+    update_json_locked(&lock, paths, path, label, apply)
+}
+
+/// Like [`update_json`], for a caller that already holds the file's [`lock`]
+/// across a longer sequence of reads and writes.
+pub fn update_json_locked<T, R, F>(
+    _lock: &FileLock,
+    paths: &Paths,
+    path: &Path,
+    label: &str,
+    mut apply: F,
+) -> Result<R, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnMut(Option<T>) -> Result<(Option<T>, R), String>,
+{
+    for attempt in 1..=MAX_UPDATE_ATTEMPTS {
+        let (current, fingerprint) = read_json_with_fingerprint::<T>(path, label)?;
+        let (updated, result) = apply(current)?;
+
+        let Some(updated) = updated else {
+            return Ok(result);
+        };
+        let content = to_pretty_json(&updated, label)?;
+
+        // Someone outside our lock wrote the file while we were merging: start over
+        if Fingerprint::of(path)? != fingerprint {
+            eprintln!(
+                "Warning: {} changed during update (attempt {}), retrying merge",
+                label, attempt
+            );
+            continue;
+        }
+
+        record_history(paths, path);
+        write_atomic(path, &content).map_err(|e| format!("Failed to write {}: {}", label, e))?;
+        return Ok(result);
+    }
+
+    Err(format!(
+        "Failed to write {}: file kept changing during update",
+        label
+    ))
+}
+
+/// Atomically write a text file under its lock.
+pub fn write_text(paths: &Paths, path: &Path, label: &str, content: &str) -> Result<(), String> {
+    write_bytes(paths, path, label, content.as_bytes())
+}
+
+/// Like [`write_text`], but a newly created file is readable only by the user,
+/// for credentials such as the Codex auth.json.
+pub fn write_private_text(
+    paths: &Paths,
+    path: &Path,
+    label: &str,
+    content: &str,
+) -> Result<(), String> {
+    let _lock = lock(paths, path)?;
+    record_history(paths, path);
+    write_private(path, content.as_bytes()).map_err(|e| format!("Failed to write {}: {}", label, e))
+}
+
+/// Atomically write raw bytes under the file's lock.
+pub fn write_bytes(paths: &Paths, path: &Path, label: &str, content: &[u8]) -> Result<(), String> {
+    let _lock = lock(paths, path)?;
+    record_history(paths, path);
+    write_atomic(path, content).map_err(|e| format!("Failed to write {}: {}", label, e))
+}
+
+/// Locked, atomic read-modify-write of a JSON object file such as settings.json.
+///
+/// A missing file starts out as `{}`. `apply` returns whether it changed
+/// anything; the file is only rewritten when it did.
+pub fn update_json_object<F>(
+    paths: &Paths,
+    path: &Path,
+    label: &str,
+    mut apply: F,
+) -> Result<bool, String>
+where
+    F: FnMut(&mut Value) -> Result<bool, String>,
+{
+    update_json(paths, path, label, |current: Option<Value>| {
+        let mut value = current.unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+        let changed = apply(&mut value)?;
+        Ok((changed.then_some(value), changed))
+    })
+}
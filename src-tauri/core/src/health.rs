@@ -0,0 +1,446 @@
+//! Health checks that send a minimal authenticated request for a profile.
+//!
+//! Building the request and reading the answer live here, sending it is left
+//! to the caller. Every URL comes from the profile itself, so pointing a
+//! profile at a local mock server exercises the same code as a real provider.
+
+use serde_json::{json, Value};
+
+use crate::codex;
+use crate::model_aliases;
+use crate::paths::Paths;
+use crate::relay;
+use crate::stores;
+use crate::translate::{self, WireApi};
+use crate::vault;
+
+const DEFAULT_ANTHROPIC_URL: &str = "https://api.anthropic.com";
+const DEFAULT_ANTHROPIC_MODEL: &str = "claude-sonnet-4-5";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+// Error bodies are kept for display, cut to a readable length
+const MAX_ERROR_CHARS: usize = 500;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeApi {
+    AnthropicMessages,
+    OpenaiResponses,
+    OpenaiChat,
+}
+
+/// Request to send for a health check
+#[derive(Debug, Clone)]
+pub struct ProbeRequest {
+    pub api: ProbeApi,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+    pub model: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ProfileHealth {
+    pub api: ProbeApi,
+    pub url: String,
+    pub model: String,
+    /// Whether the server answered at all
+    pub reachable: bool,
+    pub status: Option<u16>,
+    /// `None` when the answer does not tell
+    pub auth_valid: Option<bool>,
+    pub model_available: Option<bool>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+impl ProfileHealth {
+    /// The request went through, the profile can be used as is
+    pub fn healthy(&self) -> bool {
+        self.status
+            .is_some_and(|status| (200..300).contains(&status))
+    }
+}
+
+/// Base URL a Claude Code profile sends requests to
+pub fn claude_base_url(settings: &Value) -> &str {
+    settings
+        .pointer("/env/ANTHROPIC_BASE_URL")
+        .and_then(|v| v.as_str())
+        .filter(|url| !url.is_empty())
+        .unwrap_or(DEFAULT_ANTHROPIC_URL)
+}
+
+/// Header Claude Code authenticates with for these settings, secrets already revealed
+pub fn claude_auth_header(settings: &Value) -> Option<(String, String)> {
+    let env = |key: &str| {
+        settings
+            .pointer(&format!("/env/{}", key))
+            .and_then(|v| v.as_str())
+            .filter(|value| !value.is_empty())
+    };
+
+    // The auth token is sent as a bearer token, the API key as x-api-key
+    if let Some(token) = env("ANTHROPIC_AUTH_TOKEN") {
+        Some(("authorization".to_string(), format!("Bearer {}", token)))
+    } else {
+        env("ANTHROPIC_API_KEY").map(|key| ("x-api-key".to_string(), key.to_string()))
+    }
+}
+
+/// The auth token, or else the API key, of Claude Code settings
+pub fn claude_api_key(settings: &Value) -> Option<&str> {
+    ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"]
+        .iter()
+        .filter_map(|key| settings.pointer(&format!("/env/{}", key)))
+        .filter_map(|v| v.as_str())
+        .find(|value| !value.is_empty())
+}
+
+/// Health check request for a Claude Code store, using its resolved settings.
+/// A profile with an OpenAI endpoint is checked with the model the relay would send.
+pub fn claude_probe(paths: &Paths, store_id: &str) -> Result<ProbeRequest, String> {
+    let resolved = stores::get_resolved_store_settings(paths, store_id)?;
+    let settings = vault::reveal(paths, &resolved)?;
+    let api = match relay::get_relay_settings(paths)?.wire_api(store_id) {
+        WireApi::Anthropic => return claude_probe_for_settings(&settings),
+        WireApi::Chat => ProbeApi::OpenaiChat,
+        WireApi::Responses => ProbeApi::OpenaiResponses,
+    };
+
+    let model = claude_model(&settings);
+    let aliases = model_aliases::get_store_model_aliases(paths, store_id)?;
+    let model = model_aliases::map_model(&aliases, &model).unwrap_or(model);
+    Ok(openai_probe(
+        api,
+        claude_base_url(&settings),
+        claude_api_key(&settings),
+        model,
+    ))
+}
+
+fn claude_model(settings: &Value) -> String {
+    ["ANTHROPIC_MODEL", "ANTHROPIC_SMALL_FAST_MODEL"]
+        .iter()
+        .filter_map(|key| settings.pointer(&format!("/env/{}", key)))
+        .filter_map(|v| v.as_str())
+        .find(|value| !value.is_empty())
+        .unwrap_or(DEFAULT_ANTHROPIC_MODEL)
+        .to_string()
+}
+
+/// Health check request for Claude Code settings whose secrets are already revealed
+pub fn claude_probe_for_settings(settings: &Value) -> Result<ProbeRequest, String> {
+    let model = claude_model(settings);
+
+    let mut headers = vec![(
+        "anthropic-version".to_string(),
+        ANTHROPIC_VERSION.to_string(),
+    )];
+    headers.extend(claude_auth_header(settings));
+
+    Ok(ProbeRequest {
+        api: ProbeApi::AnthropicMessages,
+        url: join_url(claude_base_url(settings), "v1/messages"),
+        headers,
+        body: json!({
+            "model": model,
+            "max_tokens": 1,
+            "messages": [{ "role": "user", "content": "ping" }],
+        }),
+        model,
+    })
+}
+
+/// Health check request for a Codex store
+pub fn codex_probe(paths: &Paths, store_id: &str) -> Result<ProbeRequest, String> {
+    let store = codex::get_codex_stores(paths)?
+        .into_iter()
+        .find(|s| s.id == store_id)
+        .ok_or("Store not found")?;
+    let config = vault::reveal(paths, &store.config)?;
+    codex_probe_for_store(&codex::CodexStore { config, ..store })
+}
+
+/// Health check request for a Codex store whose API key is already revealed
+pub fn codex_probe_for_store(store: &codex::CodexStore) -> Result<ProbeRequest, String> {
+    let api = match codex::codex_wire_api(store) {
+        "chat" => ProbeApi::OpenaiChat,
+        "responses" => ProbeApi::OpenaiResponses,
+        other => return Err(format!("Unsupported wire API: {}", other)),
+    };
+    let api_key = store
+        .config
+        .get("api_key")
+        .and_then(|v| v.as_str())
+        .filter(|key| !key.is_empty());
+    Ok(openai_probe(
+        api,
+        codex::codex_base_url(store),
+        api_key,
+        codex::codex_model(store).to_string(),
+    ))
+}
+
+fn openai_probe(
+    api: ProbeApi,
+    base_url: &str,
+    api_key: Option<&str>,
+    model: String,
+) -> ProbeRequest {
+    let mut headers = Vec::new();
+    if let Some(key) = api_key {
+        headers.push(("authorization".to_string(), format!("Bearer {}", key)));
+    }
+
+    let (url, body) = match api {
+        ProbeApi::OpenaiResponses => (
+            join_url(base_url, "responses"),
+            // The Responses API rejects fewer than 16 output tokens
+            json!({
+                "model": model,
+                "max_output_tokens": 16,
+                "input": "ping",
+            }),
+        ),
+        _ => {
+            let mut body = json!({
+                "model": model,
+                "messages": [{ "role": "user", "content": "ping" }],
+            });
+            body[translate::chat_max_tokens_field(&model)] = json!(1);
+            (join_url(base_url, "chat/completions"), body)
+        }
+    };
+
+    ProbeRequest {
+        api,
+        url,
+        headers,
+        body,
+        model,
+    }
+}
+
+/// Interpret the answer to a health check request
+pub fn read_response(
+    probe: &ProbeRequest,
+    status: u16,
+    body: &str,
+    latency_ms: u64,
+) -> ProfileHealth {
+    let error_body: Option<Value> = serde_json::from_str(body).ok();
+    let error_type = error_body.as_ref().and_then(error_type);
+    let message = error_body
+        .as_ref()
+        .and_then(error_message)
+        .unwrap_or_else(|| truncate(body.trim()));
+
+    // Anthropic answers an unknown model with a not_found_error about `model: <name>`, OpenAI
+    // and compatible servers with a model_not_found code. Any other 404 is a wrong URL, and
+    // any other 400 a request the server rejected for some other reason.
+    let model_missing = match error_type.as_deref() {
+        Some("model_not_found" | "invalid_model") => true,
+        Some("not_found_error") => message.starts_with("model:"),
+        _ => false,
+    };
+
+    let (auth_valid, model_available) = match status {
+        200..=299 => (Some(true), Some(true)),
+        401 | 403 => (Some(false), None),
+        400 | 404 | 422 if model_missing => (Some(true), Some(false)),
+        400 | 422 | 429 => (Some(true), None),
+        _ => (None, None),
+    };
+
+    ProfileHealth {
+        api: probe.api,
+        url: probe.url.clone(),
+        model: probe.model.clone(),
+        reachable: true,
+        status: Some(status),
+        auth_valid,
+        model_available,
+        latency_ms,
+        error: if (200..300).contains(&status) {
+            None
+        } else if message.is_empty() {
+            Some(format!("HTTP {}", status))
+        } else {
+            Some(format!("HTTP {}: {}", status, message))
+        },
+    }
+}
+
+/// Result of a health check request that got no answer
+pub fn unreachable(probe: &ProbeRequest, error: String, latency_ms: u64) -> ProfileHealth {
+    ProfileHealth {
+        api: probe.api,
+        url: probe.url.clone(),
+        model: probe.model.clone(),
+        reachable: false,
+        status: None,
+        auth_valid: None,
+        model_available: None,
+        latency_ms,
+        error: Some(error),
+    }
+}
+
+pub(crate) fn join_url(base: &str, path: &str) -> String {
+    // Base URLs given with the version prefix already included are common
+    let base = base.trim_end_matches('/');
+    match path.strip_prefix("v1/") {
+        Some(rest) if base.ends_with("/v1") => format!("{}/{}", base, rest),
+        _ => format!("{}/{}", base, path),
+    }
+}
+
+// Anthropic: {"error": {"type", "message"}}, OpenAI: {"error": {"type", "code", "message"}}
+fn error_type(body: &Value) -> Option<String> {
+    let error = body.get("error")?;
+    error
+        .get("code")
+        .and_then(|v| v.as_str())
+        .or_else(|| error.get("type").and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
+
+fn error_message(body: &Value) -> Option<String> {
+    body.pointer("/error/message")
+        .or_else(|| body.get("error").filter(|v| v.is_string()))
+        .or_else(|| body.get("message"))
+        .and_then(|v| v.as_str())
+        .map(truncate)
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_ERROR_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_ERROR_CHARS).collect();
+    format!("{}…", cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe() -> ProbeRequest {
+        claude_probe_for_settings(&json!({ "env": { "ANTHROPIC_API_KEY": "sk-key" } })).unwrap()
+    }
+
+    fn answer(status: u16, body: Value) -> (Option<bool>, Option<bool>) {
+        let health = read_response(&probe(), status, &body.to_string(), 0);
+        (health.auth_valid, health.model_available)
+    }
+
+    fn error_body(error_type: &str, message: &str) -> Value {
+        json!({ "type": "error", "error": { "type": error_type, "message": message } })
+    }
+
+    #[test]
+    fn builds_requests_from_the_profile() {
+        let probe = claude_probe_for_settings(&json!({ "env": {
+            "ANTHROPIC_BASE_URL": "http://127.0.0.1:8080/v1/",
+            "ANTHROPIC_AUTH_TOKEN": "sk-token",
+            "ANTHROPIC_MODEL": "claude-opus-4-1",
+        } }))
+        .unwrap();
+        assert_eq!(probe.url, "http://127.0.0.1:8080/v1/messages");
+        assert_eq!(probe.model, "claude-opus-4-1");
+        assert!(probe
+            .headers
+            .contains(&("authorization".to_string(), "Bearer sk-token".to_string())));
+
+        let chat = openai_probe(
+            ProbeApi::OpenaiChat,
+            "http://127.0.0.1:8080",
+            None,
+            "gpt-5".to_string(),
+        );
+        assert_eq!(chat.url, "http://127.0.0.1:8080/chat/completions");
+        assert!(chat.headers.is_empty());
+        assert_eq!(chat.body["max_completion_tokens"], 1);
+    }
+
+    #[test]
+    fn reads_auth_and_model_from_the_status() {
+        let ok = read_response(&probe(), 200, r#"{"id":"msg_1"}"#, 12);
+        assert!(ok.healthy());
+        assert_eq!(
+            (ok.auth_valid, ok.model_available),
+            (Some(true), Some(true))
+        );
+        assert_eq!((ok.latency_ms, ok.error), (12, None));
+
+        let bad_key = read_response(
+            &probe(),
+            401,
+            &error_body("authentication_error", "invalid x-api-key").to_string(),
+            0,
+        );
+        assert!(!bad_key.healthy());
+        assert_eq!(
+            (bad_key.auth_valid, bad_key.model_available),
+            (Some(false), None)
+        );
+        assert_eq!(
+            bad_key.error.as_deref(),
+            Some("HTTP 401: invalid x-api-key")
+        );
+
+        assert_eq!(
+            answer(429, error_body("rate_limit_error", "slow down")),
+            (Some(true), None)
+        );
+        assert_eq!(answer(500, json!("oops")), (None, None));
+    }
+
+    #[test]
+    fn a_missing_model_is_told_by_the_error_type() {
+        // Anthropic
+        assert_eq!(
+            answer(404, error_body("not_found_error", "model: claude-gone")),
+            (Some(true), Some(false))
+        );
+        // OpenAI and compatible servers
+        let openai = |status, code: &str| {
+            answer(
+                status,
+                json!({ "error": {
+                    "type": "invalid_request_error",
+                    "code": code,
+                    "message": "The model `gpt-gone` does not exist",
+                } }),
+            )
+        };
+        assert_eq!(openai(404, "model_not_found"), (Some(true), Some(false)));
+        assert_eq!(openai(400, "model_not_found"), (Some(true), Some(false)));
+        assert_eq!(openai(422, "invalid_model"), (Some(true), Some(false)));
+    }
+
+    #[test]
+    fn other_errors_mentioning_the_model_are_not_a_missing_model() {
+        // A wrong URL
+        assert_eq!(
+            answer(404, error_body("not_found_error", "Not Found")),
+            (None, None)
+        );
+        // Rejected requests whose message happens to name the model
+        assert_eq!(
+            answer(
+                400,
+                error_body(
+                    "invalid_request_error",
+                    "max_tokens: 1 is below the minimum for this model"
+                )
+            ),
+            (Some(true), None)
+        );
+        assert_eq!(
+            answer(422, json!({ "detail": "model field is required" })),
+            (Some(true), None)
+        );
+    }
+}
@@ -0,0 +1,427 @@
+//! Versioned snapshots of the files the app writes.
+//!
+//! Before [`crate::fsio`] replaces a managed file (settings.json, .claude.json,
+//! stores.json, the Codex files, ...) its previous content is copied to
+//! `~/.ccconfig/history/<file key>/<version id>`. The `source` file next to
+//! the snapshots records which path they belong to. Only the newest
+//! `max_versions` snapshots per file are kept.
+
+use std::path::{Path, PathBuf};
+
+use crate::fsio;
+use crate::paths::Paths;
+
+const DEFAULT_MAX_VERSIONS: usize = 20;
+
+// Records the original path of the snapshots in a history directory
+const SOURCE_FILE: &str = "source";
+
+// Larger diffs fall back to "everything removed, everything added"
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct HistorySettings {
+    /// Snapshots kept per file; 0 disables history
+    pub max_versions: usize,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            max_versions: DEFAULT_MAX_VERSIONS,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct HistoryFile {
+    pub key: String,
+    pub name: String,
+    pub path: String,
+    pub exists: bool,
+    pub versions: usize,
+    pub latest_at: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct HistoryVersion {
+    pub id: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: u64,
+    pub size: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    Equal,
+    Added,
+    Removed,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct HistoryDiff {
+    pub key: String,
+    pub path: String,
+    /// Version id the diff starts from
+    pub from: String,
+    /// Version id the diff goes to, `None` for the current file
+    pub to: Option<String>,
+    pub added: usize,
+    pub removed: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+pub fn get_history_settings(paths: &Paths) -> Result<HistorySettings, String> {
+    Ok(fsio::read_json(&paths.history_settings_file(), "history settings")?.unwrap_or_default())
+}
+
+pub fn update_history_settings(paths: &Paths, settings: &HistorySettings) -> Result<(), String> {
+    fsio::write_json(
+        paths,
+        &paths.history_settings_file(),
+        "history settings",
+        settings,
+    )?;
+
+    // Apply a lowered limit to the existing history right away
+    for key in history_keys(paths)? {
+        prune(&paths.history_dir().join(key), settings.max_versions)?;
+    }
+
+    Ok(())
+}
+
+/// Snapshot the current content of `path` before it is overwritten.
+///
+/// Called by [`crate::fsio`] while it holds the file's lock. Does nothing if
+/// the file does not exist yet or is identical to the newest snapshot.
+pub fn record(paths: &Paths, path: &Path) -> Result<(), String> {
+    let max_versions = get_history_settings(paths)?.max_versions;
+    if max_versions == 0 {
+        return Ok(());
+    }
+
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    let dir = paths.history_dir().join(fsio::file_key(path));
+    // Snapshots hold whatever the files held, tokens included
+    create_private_dir(&paths.history_dir())?;
+    create_private_dir(&dir)?;
+
+    // Skip if nothing changed since the last snapshot
+    if let Some(latest) = version_ids(&dir)?.last() {
+        if std::fs::read(dir.join(latest)).ok().as_deref() == Some(content.as_slice()) {
+            return Ok(());
+        }
+    }
+
+    if !dir.join(SOURCE_FILE).exists() {
+        fsio::write_atomic(&dir.join(SOURCE_FILE), path.to_string_lossy().as_bytes())?;
+    }
+
+    let id = format!(
+        "{:013}-{}",
+        chrono::Utc::now().timestamp_millis(),
+        nanoid::nanoid!(6)
+    );
+    fsio::write_private(&dir.join(&id), &content)?;
+
+    prune(&dir, max_versions)
+}
+
+/// Create `dir` readable only by the user (0700 on Unix), tightening it if it exists
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create directory {}: {}", dir.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to restrict {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// All files that have at least one snapshot, sorted by name.
+pub fn list_history_files(paths: &Paths) -> Result<Vec<HistoryFile>, String> {
+    let mut files = Vec::new();
+
+    for key in history_keys(paths)? {
+        let dir = paths.history_dir().join(&key);
+        let Ok(source) = std::fs::read_to_string(dir.join(SOURCE_FILE)) else {
+            continue;
+        };
+        let source = PathBuf::from(source.trim());
+        let versions = version_ids(&dir)?;
+
+        files.push(HistoryFile {
+            name: source
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: source.to_string_lossy().to_string(),
+            exists: source.exists(),
+            versions: versions.len(),
+            latest_at: versions.last().and_then(|id| parse_created_at(id)),
+            key,
+        });
+    }
+
+    files.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    Ok(files)
+}
+
+/// Snapshots of one file, newest first.
+pub fn list_history_versions(paths: &Paths, key: &str) -> Result<Vec<HistoryVersion>, String> {
+    let (dir, _) = history_entry(paths, key)?;
+
+    let mut versions = Vec::new();
+    for id in version_ids(&dir)?.into_iter().rev() {
+        let size = std::fs::metadata(dir.join(&id))
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        versions.push(HistoryVersion {
+            created_at: parse_created_at(&id).unwrap_or(0),
+            id,
+            size,
+        });
+    }
+
+    Ok(versions)
+}
+
+/// Content of one snapshot.
+pub fn read_history_version(paths: &Paths, key: &str, version_id: &str) -> Result<String, String> {
+    let (dir, _) = history_entry(paths, key)?;
+    let content = std::fs::read(version_path(&dir, version_id)?)
+        .map_err(|e| format!("Failed to read history version: {}", e))?;
+    Ok(String::from_utf8_lossy(&content).to_string())
+}
+
+/// Line diff from snapshot `from` to snapshot `to`, or to the current file when `to` is `None`.
+pub fn diff_history_version(
+    paths: &Paths,
+    key: &str,
+    from: &str,
+    to: Option<&str>,
+) -> Result<HistoryDiff, String> {
+    let (_, source) = history_entry(paths, key)?;
+
+    let old = read_history_version(paths, key, from)?;
+    let new = match to {
+        Some(to) => read_history_version(paths, key, to)?,
+        None => match std::fs::read(&source) {
+            Ok(content) => String::from_utf8_lossy(&content).to_string(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Failed to read {}: {}", source.display(), e)),
+        },
+    };
+
+    let lines = diff_lines(&old, &new);
+    Ok(HistoryDiff {
+        key: key.to_string(),
+        path: source.to_string_lossy().to_string(),
+        from: from.to_string(),
+        to: to.map(|to| to.to_string()),
+        added: lines.iter().filter(|l| l.kind == DiffKind::Added).count(),
+        removed: lines.iter().filter(|l| l.kind == DiffKind::Removed).count(),
+        lines,
+    })
+}
+
+/// Write a snapshot back to its original path.
+///
+/// The content being replaced is itself snapshotted first, so a restore can be undone.
+pub fn restore_history_version(paths: &Paths, key: &str, version_id: &str) -> Result<(), String> {
+    let (dir, source) = history_entry(paths, key)?;
+    let content = std::fs::read(version_path(&dir, version_id)?)
+        .map_err(|e| format!("Failed to read history version: {}", e))?;
+
+    let label = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    fsio::write_bytes(paths, &source, &label, &content)?;
+
+    eprintln!("⏪ Restored {} to version {}", source.display(), version_id);
+    Ok(())
+}
+
+/// Replace text in every snapshot of `path`, e.g. to scrub secrets that moved elsewhere.
+pub fn redact(paths: &Paths, path: &Path, replacements: &[(String, String)]) -> Result<(), String> {
+    let dir = paths.history_dir().join(fsio::file_key(path));
+    for id in version_ids(&dir)? {
+        let version = dir.join(&id);
+        let Ok(content) = std::fs::read_to_string(&version) else {
+            continue;
+        };
+        let redacted = replacements
+            .iter()
+            .fold(content.clone(), |content, (from, to)| {
+                content.replace(from, to)
+            });
+        if redacted != content {
+            fsio::write_atomic(&version, redacted.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn history_keys(paths: &Paths) -> Result<Vec<String>, String> {
+    let history_dir = paths.history_dir();
+    if !history_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let entries = std::fs::read_dir(&history_dir)
+        .map_err(|e| format!("Failed to read history directory: {}", e))?;
+
+    let mut keys = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        if entry.path().is_dir() {
+            keys.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    Ok(keys)
+}
+
+/// Resolve a history key to its directory and the file it tracks
+fn history_entry(paths: &Paths, key: &str) -> Result<(PathBuf, PathBuf), String> {
+    if key.is_empty() || key.contains(['/', '\\']) || key.starts_with('.') {
+        return Err(format!("Invalid history key '{}'", key));
+    }
+
+    let dir = paths.history_dir().join(key);
+    let source = std::fs::read_to_string(dir.join(SOURCE_FILE))
+        .map_err(|_| format!("No history found for '{}'", key))?;
+
+    Ok((dir, PathBuf::from(source.trim())))
+}
+
+fn version_path(dir: &Path, version_id: &str) -> Result<PathBuf, String> {
+    if version_ids(dir)?.iter().any(|id| id == version_id) {
+        Ok(dir.join(version_id))
+    } else {
+        Err(format!("History version '{}' not found", version_id))
+    }
+}
+
+/// Snapshot ids in a history directory, oldest first
+fn version_ids(dir: &Path) -> Result<Vec<String>, String> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read history directory: {}", e))?;
+
+    let mut ids: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        // Skip the source marker and temporary files from interrupted writes
+        .filter(|name| name != SOURCE_FILE && !name.starts_with('.'))
+        .collect();
+
+    // Ids start with a zero-padded millisecond timestamp, so they sort chronologically
+    ids.sort();
+    Ok(ids)
+}
+
+fn parse_created_at(id: &str) -> Option<u64> {
+    id.split('-').next()?.parse().ok()
+}
+
+fn prune(dir: &Path, max_versions: usize) -> Result<(), String> {
+    let ids = version_ids(dir)?;
+    if ids.len() <= max_versions {
+        return Ok(());
+    }
+
+    for id in &ids[..ids.len() - max_versions] {
+        std::fs::remove_file(dir.join(id))
+            .map_err(|e| format!("Failed to remove old history version: {}", e))?;
+    }
+    Ok(())
+}
+
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let line = |kind, text: &str| DiffLine {
+        kind,
+        text: text.to_string(),
+    };
+
+    // Common prefix and suffix need no alignment
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|text| line(DiffKind::Equal, text))
+        .collect();
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        lines.extend(old_mid.iter().map(|text| line(DiffKind::Removed, text)));
+        lines.extend(new_mid.iter().map(|text| line(DiffKind::Added, text)));
+    } else {
+        // Longest common subsequence table, lcs[i][j] for old_mid[i..] and new_mid[j..]
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                lines.push(line(DiffKind::Equal, old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                lines.push(line(DiffKind::Removed, old_mid[i]));
+                i += 1;
+            } else {
+                lines.push(line(DiffKind::Added, new_mid[j]));
+                j += 1;
+            }
+        }
+    }
+
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|text| line(DiffKind::Equal, text)),
+    );
+    lines
+}
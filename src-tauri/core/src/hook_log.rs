@@ -0,0 +1,583 @@
+//! Persistent log of the hook events Claude Code sends the app.
+//!
+//! Every event the hook server receives is recorded in the SQLite database
+//! `~/.ccconfig/hook_events.db`, with its session, working directory, event
+//! name, tool name and input, and the rest of its payload. The log can be read
+//! back as a list of sessions, the timeline of one session, or events filtered
+//! by project, tool and time range. What tool rules decided about tool calls,
+//! or would have in dry-run mode, is kept next to the events.
+
+use rusqlite::{params, Connection, Row};
+use serde_json::Value;
+
+use crate::paths::Paths;
+use crate::tool_rules::{RuleAction, RuleMatch};
+
+// Oldest events are dropped beyond this
+const MAX_LOG_EVENTS: i64 = 200_000;
+// Longer strings in a payload, such as a tool's whole output, are cut off
+const MAX_STRING_CHARS: usize = 16 * 1024;
+const DEFAULT_LIMIT: usize = 500;
+// Payload fields kept in their own columns
+const COLUMN_FIELDS: &[&str] = &[
+    "session_id",
+    "cwd",
+    "hook_event_name",
+    "tool_name",
+    "tool_input",
+];
+
+/// One event Claude Code sent to the hook server
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct HookEventRecord {
+    pub id: i64,
+    /// Milliseconds since the epoch, when the event was received
+    pub timestamp: u64,
+    pub session_id: String,
+    pub cwd: String,
+    pub hook_event_name: String,
+    pub tool_name: Option<String>,
+    pub tool_input: Option<Value>,
+    /// The other fields of the event, such as `message`, `prompt` or `tool_response`
+    pub payload: Value,
+}
+
+/// The events of one Claude Code session
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct HookSession {
+    pub session_id: String,
+    /// Working directory of the session's latest event
+    pub cwd: String,
+    pub started_at: u64,
+    pub last_event_at: u64,
+    pub last_event_name: String,
+    pub events: u64,
+    pub tool_uses: u64,
+}
+
+/// A tool call a rule matched
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RuleDecisionRecord {
+    pub id: i64,
+    /// Milliseconds since the epoch
+    pub timestamp: u64,
+    pub session_id: String,
+    pub cwd: String,
+    pub tool_name: String,
+    /// One line describing the call, see [`crate::approval::describe_tool_use`]
+    pub summary: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub action: RuleAction,
+    pub reason: String,
+    /// Only recorded, the call went on as if no rule matched
+    pub dry_run: bool,
+}
+
+/// Which events to read, every field narrowing them down
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct HookEventFilter {
+    pub session_id: Option<String>,
+    /// Working directory of the events, including its subdirectories
+    pub project: Option<String>,
+    pub tool_name: Option<String>,
+    pub hook_event_name: Option<String>,
+    /// Milliseconds since the epoch, inclusive
+    pub since: Option<u64>,
+    /// Milliseconds since the epoch, exclusive
+    pub until: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+fn open(paths: &Paths) -> Result<Connection, String> {
+    std::fs::create_dir_all(&paths.app_config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let connection = Connection::open(paths.hook_events_db())
+        .map_err(|e| format!("Failed to open hook event log: {}", e))?;
+    connection
+        .busy_timeout(std::time::Duration::from_secs(5))
+        .map_err(|e| format!("Failed to open hook event log: {}", e))?;
+    connection
+        .execute_batch(
+            "CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                session_id TEXT NOT NULL,
+                cwd TEXT NOT NULL,
+                hook_event_name TEXT NOT NULL,
+                tool_name TEXT,
+                tool_input TEXT,
+                payload TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS events_session ON events (session_id, id);
+            CREATE INDEX IF NOT EXISTS events_timestamp ON events (timestamp);
+            CREATE INDEX IF NOT EXISTS events_tool ON events (tool_name, timestamp);
+            CREATE TABLE IF NOT EXISTS rule_decisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                session_id TEXT NOT NULL,
+                cwd TEXT NOT NULL,
+                tool_name TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                rule_id TEXT NOT NULL,
+                rule_name TEXT NOT NULL,
+                action TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                dry_run INTEGER NOT NULL
+            );",
+        )
+        .map_err(|e| format!("Failed to create hook event log tables: {}", e))?;
+    Ok(connection)
+}
+
+/// Record an event as Claude Code sent it, returning its id
+pub fn record_hook_event(paths: &Paths, event: &Value) -> Result<i64, String> {
+    let text = |key: &str| event.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+    let mut payload = event.as_object().cloned().unwrap_or_default();
+    payload.retain(|key, _| !COLUMN_FIELDS.contains(&key.as_str()));
+    let mut payload = Value::Object(payload);
+    shorten_strings(&mut payload);
+    let mut tool_input = event.get("tool_input").cloned();
+    if let Some(input) = &mut tool_input {
+        shorten_strings(input);
+    }
+
+    let connection = open(paths)?;
+    connection
+        .execute(
+            "INSERT INTO events (
+                timestamp, session_id, cwd, hook_event_name, tool_name, tool_input, payload
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                chrono::Utc::now().timestamp_millis(),
+                text("session_id"),
+                text("cwd"),
+                text("hook_event_name"),
+                event.get("tool_name").and_then(|v| v.as_str()),
+                tool_input.map(|input| input.to_string()),
+                payload.to_string(),
+            ],
+        )
+        .map_err(|e| format!("Failed to write hook event log: {}", e))?;
+
+    let id = connection.last_insert_rowid();
+    connection
+        .execute(
+            "DELETE FROM events WHERE id <= ?1",
+            params![id - MAX_LOG_EVENTS],
+        )
+        .map_err(|e| format!("Failed to trim hook event log: {}", e))?;
+    Ok(id)
+}
+
+/// Record what a rule decided about a tool call, or would have in dry-run mode
+pub fn record_rule_decision(
+    paths: &Paths,
+    session_id: &str,
+    cwd: &str,
+    tool_name: &str,
+    summary: &str,
+    matched: &RuleMatch,
+    dry_run: bool,
+) -> Result<i64, String> {
+    let action = serde_json::to_value(matched.action)
+        .ok()
+        .and_then(|action| action.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    let connection = open(paths)?;
+    connection
+        .execute(
+            "INSERT INTO rule_decisions (
+                timestamp, session_id, cwd, tool_name, summary,
+                rule_id, rule_name, action, reason, dry_run
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                chrono::Utc::now().timestamp_millis(),
+                session_id,
+                cwd,
+                tool_name,
+                summary,
+                matched.rule_id,
+                matched.rule_name,
+                action,
+                matched.reason,
+                dry_run,
+            ],
+        )
+        .map_err(|e| format!("Failed to write rule decision: {}", e))?;
+
+    let id = connection.last_insert_rowid();
+    connection
+        .execute(
+            "DELETE FROM rule_decisions WHERE id <= ?1",
+            params![id - MAX_LOG_EVENTS],
+        )
+        .map_err(|e| format!("Failed to trim rule decisions: {}", e))?;
+    Ok(id)
+}
+
+/// Latest rule decisions, newest first
+pub fn get_rule_decisions(
+    paths: &Paths,
+    limit: Option<usize>,
+) -> Result<Vec<RuleDecisionRecord>, String> {
+    let connection = open(paths)?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT id, timestamp, session_id, cwd, tool_name, summary,
+                rule_id, rule_name, action, reason, dry_run
+             FROM rule_decisions ORDER BY id DESC LIMIT {}",
+            limit.unwrap_or(DEFAULT_LIMIT)
+        ))
+        .map_err(|e| format!("Failed to read rule decisions: {}", e))?;
+    let decisions = statement
+        .query_map([], |row| {
+            let action: String = row.get(8)?;
+            Ok(RuleDecisionRecord {
+                id: row.get(0)?,
+                timestamp: row.get::<_, i64>(1)? as u64,
+                session_id: row.get(2)?,
+                cwd: row.get(3)?,
+                tool_name: row.get(4)?,
+                summary: row.get(5)?,
+                rule_id: row.get(6)?,
+                rule_name: row.get(7)?,
+                action: serde_json::from_value(Value::String(action)).unwrap_or(RuleAction::Ask),
+                reason: row.get(9)?,
+                dry_run: row.get(10)?,
+            })
+        })
+        .map_err(|e| format!("Failed to read rule decisions: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read rule decisions: {}", e))?;
+    Ok(decisions)
+}
+
+fn shorten_strings(value: &mut Value) {
+    match value {
+        Value::String(text) => {
+            if let Some((cut, _)) = text.char_indices().nth(MAX_STRING_CHARS) {
+                text.truncate(cut);
+                text.push_str("… [truncated]");
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(shorten_strings),
+        Value::Object(object) => object.values_mut().for_each(shorten_strings),
+        _ => {}
+    }
+}
+
+// The WHERE clause for a filter, with its parameters numbered from 1
+fn filter_clause(filter: &HookEventFilter) -> (String, Vec<rusqlite::types::Value>) {
+    use rusqlite::types::Value as Param;
+
+    let mut conditions = vec!["1 = 1".to_string()];
+    let mut values = Vec::new();
+    let mut add = |condition: &str, value: Param| {
+        values.push(value);
+        conditions.push(condition.replace('?', &format!("?{}", values.len())));
+    };
+    if let Some(session_id) = &filter.session_id {
+        add("session_id = ?", Param::Text(session_id.clone()));
+    }
+    if let Some(project) = &filter.project {
+        let project = project.trim_end_matches(['/', '\\']).to_string();
+        // The project itself, or a directory inside it
+        add(
+            "(cwd = ? OR substr(cwd, 1, length(?) + 1) IN (? || '/', ? || '\\'))",
+            Param::Text(project),
+        );
+    }
+    if let Some(tool_name) = &filter.tool_name {
+        add("tool_name = ?", Param::Text(tool_name.clone()));
+    }
+    if let Some(hook_event_name) = &filter.hook_event_name {
+        add("hook_event_name = ?", Param::Text(hook_event_name.clone()));
+    }
+    if let Some(since) = filter.since {
+        add("timestamp >= ?", Param::Integer(since as i64));
+    }
+    if let Some(until) = filter.until {
+        add("timestamp < ?", Param::Integer(until as i64));
+    }
+    (conditions.join(" AND "), values)
+}
+
+const EVENT_COLUMNS: &str =
+    "id, timestamp, session_id, cwd, hook_event_name, tool_name, tool_input, payload";
+
+fn read_event(row: &Row) -> rusqlite::Result<HookEventRecord> {
+    let json = |text: Option<String>| text.and_then(|text| serde_json::from_str(&text).ok());
+    Ok(HookEventRecord {
+        id: row.get(0)?,
+        timestamp: row.get::<_, i64>(1)? as u64,
+        session_id: row.get(2)?,
+        cwd: row.get(3)?,
+        hook_event_name: row.get(4)?,
+        tool_name: row.get(5)?,
+        tool_input: json(row.get(6)?),
+        payload: json(row.get(7)?).unwrap_or(Value::Null),
+    })
+}
+
+/// Events matching `filter`, newest first
+pub fn get_hook_events(
+    paths: &Paths,
+    filter: &HookEventFilter,
+) -> Result<Vec<HookEventRecord>, String> {
+    let connection = open(paths)?;
+    let (clause, values) = filter_clause(filter);
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT {} FROM events WHERE {} ORDER BY id DESC LIMIT {}",
+            EVENT_COLUMNS,
+            clause,
+            filter.limit.unwrap_or(DEFAULT_LIMIT)
+        ))
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?;
+    let events = statement
+        .query_map(rusqlite::params_from_iter(values), read_event)
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?;
+    Ok(events)
+}
+
+/// Every event of a session, in the order they happened
+pub fn get_session_timeline(
+    paths: &Paths,
+    session_id: &str,
+) -> Result<Vec<HookEventRecord>, String> {
+    let connection = open(paths)?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT {} FROM events WHERE session_id = ?1 ORDER BY id",
+            EVENT_COLUMNS
+        ))
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?;
+    let events = statement
+        .query_map(params![session_id], read_event)
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?;
+    Ok(events)
+}
+
+/// Sessions with events matching `filter`, most recently active first.
+/// The counts cover all of a session's events, not only the matching ones.
+pub fn get_hook_sessions(
+    paths: &Paths,
+    filter: &HookEventFilter,
+) -> Result<Vec<HookSession>, String> {
+    let connection = open(paths)?;
+    let (clause, values) = filter_clause(filter);
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT session_id,
+                (SELECT cwd FROM events latest WHERE latest.session_id = events.session_id
+                    ORDER BY id DESC LIMIT 1),
+                MIN(timestamp), MAX(timestamp),
+                (SELECT hook_event_name FROM events latest
+                    WHERE latest.session_id = events.session_id ORDER BY id DESC LIMIT 1),
+                COUNT(*),
+                SUM(CASE WHEN hook_event_name = 'PreToolUse' THEN 1 ELSE 0 END)
+             FROM events
+             WHERE session_id IN (SELECT DISTINCT session_id FROM events WHERE {})
+             GROUP BY session_id
+             ORDER BY MAX(id) DESC
+             LIMIT {}",
+            clause,
+            filter.limit.unwrap_or(DEFAULT_LIMIT)
+        ))
+        .map_err(|e| format!("Failed to read hook sessions: {}", e))?;
+    let sessions = statement
+        .query_map(rusqlite::params_from_iter(values), |row| {
+            Ok(HookSession {
+                session_id: row.get(0)?,
+                cwd: row.get(1)?,
+                started_at: row.get::<_, i64>(2)? as u64,
+                last_event_at: row.get::<_, i64>(3)? as u64,
+                last_event_name: row.get(4)?,
+                events: row.get::<_, i64>(5)? as u64,
+                tool_uses: row.get::<_, i64>(6)? as u64,
+            })
+        })
+        .map_err(|e| format!("Failed to read hook sessions: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read hook sessions: {}", e))?;
+    Ok(sessions)
+}
+
+/// Tools used in the logged events, for filtering by tool
+pub fn get_hook_tool_names(paths: &Paths) -> Result<Vec<String>, String> {
+    let connection = open(paths)?;
+    let mut statement = connection
+        .prepare("SELECT DISTINCT tool_name FROM events WHERE tool_name IS NOT NULL ORDER BY 1")
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?;
+    let names = statement
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read hook event log: {}", e))?;
+    Ok(names)
+}
+
+/// Delete logged events and rule decisions, only the ones older than `before` milliseconds when given
+pub fn clear_hook_events(paths: &Paths, before: Option<u64>) -> Result<usize, String> {
+    let connection = open(paths)?;
+    let before = before.map(|before| before as i64);
+    let deleted = connection
+        .execute(
+            "DELETE FROM events WHERE ?1 IS NULL OR timestamp < ?1",
+            params![before],
+        )
+        .map_err(|e| format!("Failed to clear hook event log: {}", e))?;
+    connection
+        .execute(
+            "DELETE FROM rule_decisions WHERE ?1 IS NULL OR timestamp < ?1",
+            params![before],
+        )
+        .map_err(|e| format!("Failed to clear rule decisions: {}", e))?;
+    eprintln!("🧹 Deleted {} hook events", deleted);
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // A throwaway home for the database, removed when dropped
+    struct TestHome {
+        dir: std::path::PathBuf,
+        paths: Paths,
+    }
+
+    impl TestHome {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "tvcbuddy-hook-log-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            let paths = Paths::from_home(&dir);
+            Self { dir, paths }
+        }
+    }
+
+    impl Drop for TestHome {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn record(paths: &Paths, session_id: &str, cwd: &str, event: &str, tool: Option<&str>) -> i64 {
+        let mut payload = json!({ "session_id": session_id, "cwd": cwd, "hook_event_name": event });
+        if let Some(tool) = tool {
+            payload["tool_name"] = json!(tool);
+            payload["tool_input"] = json!({ "command": "ls" });
+        }
+        record_hook_event(paths, &payload).unwrap()
+    }
+
+    #[test]
+    fn sessions_count_all_their_events_and_follow_the_latest_one() {
+        let home = TestHome::new("sessions");
+        let paths = &home.paths;
+        record(paths, "one", "/repo", "SessionStart", None);
+        record(paths, "one", "/repo", "PreToolUse", Some("Bash"));
+        record(paths, "two", "/other", "UserPromptSubmit", None);
+        record(paths, "one", "/repo/src", "PreToolUse", Some("Read"));
+        record(paths, "one", "/repo/src", "Stop", None);
+
+        let sessions = get_hook_sessions(paths, &HookEventFilter::default()).unwrap();
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["one", "two"]);
+        assert_eq!(sessions[0].cwd, "/repo/src");
+        assert_eq!(sessions[0].last_event_name, "Stop");
+        assert_eq!((sessions[0].events, sessions[0].tool_uses), (4, 2));
+        assert!(sessions[0].started_at <= sessions[0].last_event_at);
+
+        // A filter picks the sessions, but the counts still cover every event
+        let bash = HookEventFilter {
+            tool_name: Some("Bash".to_string()),
+            ..HookEventFilter::default()
+        };
+        let sessions = get_hook_sessions(paths, &bash).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].events, 4);
+
+        let timeline = get_session_timeline(paths, "one").unwrap();
+        let events: Vec<&str> = timeline
+            .iter()
+            .map(|event| event.hook_event_name.as_str())
+            .collect();
+        assert_eq!(events, ["SessionStart", "PreToolUse", "PreToolUse", "Stop"]);
+        assert_eq!(timeline[1].tool_input, Some(json!({ "command": "ls" })));
+        assert_eq!(get_hook_tool_names(paths).unwrap(), ["Bash", "Read"]);
+    }
+
+    #[test]
+    fn project_filter_covers_subdirectories_but_not_siblings() {
+        let home = TestHome::new("project");
+        let paths = &home.paths;
+        record(paths, "a", "/work/app", "Stop", None);
+        record(paths, "b", "/work/app/web", "Stop", None);
+        record(paths, "c", "/work/app-old", "Stop", None);
+        record(paths, "d", "C:\\work\\app\\api", "Stop", None);
+
+        let sessions_in = |project: &str| {
+            let filter = HookEventFilter {
+                project: Some(project.to_string()),
+                ..HookEventFilter::default()
+            };
+            let mut ids: Vec<String> = get_hook_events(paths, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|event| event.session_id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(sessions_in("/work/app/"), ["a", "b"]);
+        assert_eq!(sessions_in("C:\\work\\app"), ["d"]);
+    }
+
+    #[test]
+    fn payload_keeps_other_fields_and_cuts_long_strings() {
+        let home = TestHome::new("payload");
+        let paths = &home.paths;
+        let output = "x".repeat(MAX_STRING_CHARS + 10);
+        record_hook_event(
+            paths,
+            &json!({
+                "session_id": "s",
+                "cwd": "/repo",
+                "hook_event_name": "PostToolUse",
+                "tool_name": "Bash",
+                "tool_input": { "command": output },
+                "tool_response": { "stdout": output },
+            }),
+        )
+        .unwrap();
+
+        let event = &get_session_timeline(paths, "s").unwrap()[0];
+        let stdout = event.payload["tool_response"]["stdout"].as_str().unwrap();
+        assert!(stdout.ends_with("… [truncated]"));
+        assert_eq!(
+            stdout.chars().count(),
+            MAX_STRING_CHARS + "… [truncated]".chars().count()
+        );
+        assert!(event.payload.get("session_id").is_none());
+        assert!(event.tool_input.as_ref().unwrap()["command"]
+            .as_str()
+            .unwrap()
+            .ends_with("… [truncated]"));
+
+        assert_eq!(clear_hook_events(paths, Some(0)).unwrap(), 0);
+        assert_eq!(clear_hook_events(paths, None).unwrap(), 1);
+        assert!(get_session_timeline(paths, "s").unwrap().is_empty());
+    }
+}
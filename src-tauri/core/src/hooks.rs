@@ -0,0 +1,342 @@
+// Hook management functions
+
+use crate::approval;
+use crate::fsio;
+use crate::paths::Paths;
+use crate::settings;
+use crate::stores;
+use crate::tool_rules;
+
+/// Every hook event Claude Code sends, in the order of its documentation
+pub const HOOK_EVENTS: [&str; 9] = [
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+/// Seconds Claude Code waits for the PreToolUse hook, long enough for the slowest approval
+pub const APPROVAL_HOOK_TIMEOUT_SECS: u64 = approval::MAX_TIMEOUT_SECS + 60;
+
+/// Seconds Claude Code waits for the hook of every other event, which the server answers right away
+pub const HOOK_TIMEOUT_SECS: u64 = 10;
+
+/// How long Claude Code waits for the hook of `event`
+fn hook_timeout_secs(event: &str) -> u64 {
+    if event == "PreToolUse" {
+        APPROVAL_HOOK_TIMEOUT_SECS
+    } else {
+        HOOK_TIMEOUT_SECS
+    }
+}
+
+/// Header the installed hook sends its token in, so only it can post to the hook server
+pub const HOOK_TOKEN_HEADER: &str = "X-TVCBuddy-Hook-Token";
+
+/// Hook events the app installs its hook for: those enabled in the notification
+/// settings, plus PreToolUse while tool use needs approval or tool rules are on
+pub fn enabled_hook_events(paths: &Paths) -> Result<Vec<&'static str>, String> {
+    let settings = stores::get_notification_settings(paths)?.unwrap_or_default();
+    let decides_tool_use = approval::get_approval_settings(paths)?.enabled
+        || tool_rules::get_tool_rule_settings(paths)?.enabled;
+    Ok(HOOK_EVENTS
+        .into_iter()
+        .filter(|event| {
+            settings.enabled_hooks.iter().any(|hook| hook == event)
+                || (decides_tool_use && *event == "PreToolUse")
+        })
+        .collect())
+}
+
+/// The token of this install, created on first use and readable only by the user
+pub fn hook_token(paths: &Paths) -> Result<String, String> {
+    let file = paths.hook_token_file();
+    let _lock = fsio::lock(paths, &file)?;
+    match std::fs::read_to_string(&file) {
+        Ok(token) if !token.trim().is_empty() => return Ok(token.trim().to_string()),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to read hook token: {}", e)),
+    }
+
+    // Written directly so the token never ends up in the history
+    let token = format!("tvcbuddy-hook-{}", nanoid::nanoid!(32));
+    fsio::write_private(&file, token.as_bytes())?;
+    Ok(token)
+}
+
+/// Get the latest hook command of `event` based on the current operating system
+///
+/// The command prints the server's response, which Claude Code reads as the hook output.
+pub fn get_latest_hook_command(event: &str, token: &str) -> serde_json::Value {
+    if cfg!(target_os = "windows") {
+        serde_json::json!({
+            "__ccmate__": true,
+            "type": "command",
+            "command": format!("powershell -Command \"try {{ (Invoke-WebRequest -Uri http://localhost:59948/claude_code/hooks -Method POST -ContentType 'application/json' -Headers @{{'{}'='{}'}} -Body $input -UseBasicParsing -ErrorAction Stop).Content }} catch {{ '' }}\"", HOOK_TOKEN_HEADER, token),
+            "timeout": hook_timeout_secs(event)
+        })
+    } else {
+        serde_json::json!({
+            "__ccmate__": true,
+            "type": "command",
+            "command": format!("curl -s -X POST http://localhost:59948/claude_code/hooks -H 'Content-Type: application/json' -H '{}: {}' --data-binary @- 2>/dev/null || echo", HOOK_TOKEN_HEADER, token),
+            "timeout": hook_timeout_secs(event)
+        })
+    }
+}
+
+/// Update existing ccmate hooks for specified events (doesn't add new ones)
+pub fn update_existing_hooks(
+    hooks_obj: &mut serde_json::Map<String, serde_json::Value>,
+    events: &[&str],
+    token: &str,
+) -> bool {
+    let mut hook_updated = false;
+
+    for event in events {
+        let latest_hook_command = get_latest_hook_command(event, token);
+        let latest_command_str = latest_hook_command
+            .get("command")
+            .and_then(|cmd| cmd.as_str())
+            .unwrap_or("");
+
+        if let Some(event_hooks) = hooks_obj.get_mut(*event).and_then(|h| h.as_array_mut()) {
+            // Find and update existing ccmate hooks only
+            for entry in event_hooks.iter_mut() {
+                if let Some(hooks_array) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) {
+                    for hook in hooks_array.iter_mut() {
+                        if hook.get("__ccmate__").is_some() {
+                            // Compare only the command string, not the entire JSON object
+                            if let Some(existing_command) =
+                                hook.get("command").and_then(|cmd| cmd.as_str())
+                            {
+                                if existing_command != latest_command_str {
+                                    // Update only the command field, preserve other properties
+                                    hook["command"] =
+                                        serde_json::Value::String(latest_command_str.to_string());
+                                    hook_updated = true;
+                                    // The command carries the hook token, keep it out of the log
+                                    eprintln!("🔄 Updated {} hook command", event);
+                                }
+                            }
+                            // Older hooks used Claude Code's default timeout, too short for approvals,
+                            // or the approval timeout on every event
+                            if hook.get("timeout") != latest_hook_command.get("timeout") {
+                                hook["timeout"] = latest_hook_command["timeout"].clone();
+                                hook_updated = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    hook_updated
+}
+
+/// Update or add ccmate hooks for specified events
+pub fn update_or_add_hooks(
+    hooks_obj: &mut serde_json::Map<String, serde_json::Value>,
+    events: &[&str],
+    token: &str,
+) -> bool {
+    let mut hook_updated = false;
+
+    for event in events {
+        let latest_hook_command = get_latest_hook_command(event, token);
+        if let Some(event_hooks) = hooks_obj.get_mut(*event).and_then(|h| h.as_array_mut()) {
+            // Find and update existing ccmate hooks
+            for entry in event_hooks.iter_mut() {
+                if let Some(hooks_array) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) {
+                    for hook in hooks_array.iter_mut() {
+                        if hook.get("__ccmate__").is_some() {
+                            // Update the command and its timeout to the latest version
+                            if *hook != latest_hook_command {
+                                *hook = latest_hook_command.clone();
+                                hook_updated = true;
+                            }
+                        }
+                    }
+                }
+            }
+
+            // If no ccmate hooks found, add one
+            let ccmate_hook_exists = event_hooks.iter().any(|entry| {
+                if let Some(hooks_array) = entry.get("hooks").and_then(|h| h.as_array()) {
+                    hooks_array
+                        .iter()
+                        .any(|hook| hook.get("__ccmate__").is_some())
+                } else {
+                    false
+                }
+            });
+
+            if !ccmate_hook_exists {
+                let ccmate_hook_entry = serde_json::json!({
+                    "hooks": [latest_hook_command.clone()]
+                });
+                event_hooks.push(ccmate_hook_entry);
+                hook_updated = true;
+            }
+        } else {
+            // Create event hooks array with ccmate hook
+            let ccmate_hook_entry = serde_json::json!({
+                "hooks": [latest_hook_command.clone()]
+            });
+            hooks_obj.insert(
+                event.to_string(),
+                serde_json::Value::Array(vec![ccmate_hook_entry]),
+            );
+            hook_updated = true;
+        }
+    }
+
+    hook_updated
+}
+
+/// Ensure the settings value is an object and return its hooks object, creating it if needed
+fn hooks_object_mut(
+    settings: &mut serde_json::Value,
+) -> Result<&mut serde_json::Map<String, serde_json::Value>, String> {
+    settings
+        .as_object_mut()
+        .ok_or("Invalid settings.json format")?
+        .entry("hooks".to_string())
+        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+        .as_object_mut()
+        .ok_or_else(|| "Invalid hooks format in settings.json".to_string())
+}
+
+pub fn update_claude_code_hook(paths: &Paths) -> Result<(), String> {
+    if !paths.user_settings().exists() {
+        // If settings file doesn't exist, just add the hooks
+        return add_claude_code_hook(paths);
+    }
+
+    // Update existing hooks (only update, don't add new ones)
+    let token = hook_token(paths)?;
+    let hook_updated = settings::update_user_settings(paths, |settings| {
+        Ok(update_existing_hooks(
+            hooks_object_mut(settings)?,
+            &HOOK_EVENTS,
+            &token,
+        ))
+    })?;
+
+    if hook_updated {
+        eprintln!("✅ Claude Code hooks updated successfully");
+    } else {
+        eprintln!("ℹ️  Claude Code hooks are already up to date - no updates needed");
+    }
+
+    Ok(())
+}
+
+pub fn add_claude_code_hook(paths: &Paths) -> Result<(), String> {
+    let enabled = enabled_hook_events(paths)?;
+    let disabled: Vec<&str> = HOOK_EVENTS
+        .into_iter()
+        .filter(|event| !enabled.contains(event))
+        .collect();
+
+    // Read existing settings or create new structure
+    let token = hook_token(paths)?;
+    settings::update_user_settings(paths, |settings| {
+        update_or_add_hooks(hooks_object_mut(settings)?, &enabled, &token);
+        remove_ccmate_hooks(settings, &disabled);
+        Ok(true)
+    })?;
+
+    eprintln!("✅ Claude Code hooks added successfully");
+    Ok(())
+}
+
+pub fn remove_claude_code_hook(paths: &Paths) -> Result<(), String> {
+    if !paths.user_settings().exists() {
+        return Ok(()); // Settings file doesn't exist, nothing to remove
+    }
+
+    settings::update_user_settings(paths, |settings| {
+        remove_ccmate_hooks(settings, &HOOK_EVENTS);
+        Ok(true)
+    })?;
+
+    eprintln!("✅ Claude Code hooks removed successfully");
+    Ok(())
+}
+
+/// Install the hook for the enabled events and drop it from the others, if it is installed at all
+pub fn sync_claude_code_hook(paths: &Paths) -> Result<(), String> {
+    let installed = settings::read_user_settings(paths)?
+        .get("hooks")
+        .and_then(|hooks| hooks.as_object())
+        .is_some_and(|hooks| hooks.values().any(has_ccmate_hook));
+    if installed {
+        add_claude_code_hook(paths)?;
+    }
+    Ok(())
+}
+
+fn has_ccmate_hook(event_hooks: &serde_json::Value) -> bool {
+    event_hooks.as_array().is_some_and(|entries| {
+        entries.iter().any(|entry| {
+            entry
+                .get("hooks")
+                .and_then(|h| h.as_array())
+                .is_some_and(|hooks| hooks.iter().any(|hook| hook.get("__ccmate__").is_some()))
+        })
+    })
+}
+
+/// Remove the ccmate hook of `events` from the settings value, dropping emptied events and hooks objects
+fn remove_ccmate_hooks(settings: &mut serde_json::Value, events: &[&str]) {
+    // Check if hooks object exists
+    if let Some(hooks_obj) = settings.get_mut("hooks").and_then(|h| h.as_object_mut()) {
+        for event in events {
+            if let Some(event_hooks) = hooks_obj.get_mut(*event).and_then(|h| h.as_array_mut()) {
+                // Remove hooks that have __ccmate__ key from nested hooks arrays
+                let mut new_event_hooks = Vec::new();
+                for entry in event_hooks.iter() {
+                    if let Some(hooks_array) = entry.get("hooks").and_then(|h| h.as_array()) {
+                        // Filter out hooks that have __ccmate__ key
+                        let filtered_hooks: Vec<serde_json::Value> = hooks_array
+                            .iter()
+                            .filter(|hook| hook.get("__ccmate__").is_none())
+                            .cloned()
+                            .collect();
+
+                        // Keep the entry only if it still has hooks
+                        if !filtered_hooks.is_empty() {
+                            let mut new_entry = entry.clone();
+                            new_entry["hooks"] = serde_json::Value::Array(filtered_hooks);
+                            new_event_hooks.push(new_entry);
+                        }
+                    } else {
+                        // Keep entries that don't have a hooks array
+                        new_event_hooks.push(entry.clone());
+                    }
+                }
+                *event_hooks = new_event_hooks;
+
+                // If the event hooks array is empty, remove the entire event entry
+                if event_hooks.is_empty() {
+                    hooks_obj.remove(*event);
+                }
+            }
+        }
+
+        // If hooks object is empty, remove it entirely
+        if hooks_obj.is_empty() {
+            if let Some(settings_obj) = settings.as_object_mut() {
+                settings_obj.remove("hooks");
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! GUI-independent logic behind TVCBuddy.
+//!
+//! Everything here operates on an explicit [`Paths`] context instead of the
+//! real home directory, so it can be shared by the desktop app, the CLI and
+//! tests running against temporary directories.
+
+pub mod approval;
+pub mod auto_switch;
+pub mod backup;
+pub mod bindings;
+pub mod bundle;
+pub mod claude_files;
+pub mod codex;
+pub mod drift;
+pub mod failover;
+pub mod fsio;
+pub mod health;
+pub mod history;
+pub mod hook_log;
+pub mod hooks;
+pub mod live_sessions;
+pub mod mcp;
+pub mod merge;
+pub mod model_aliases;
+pub mod paths;
+pub mod pointer;
+pub mod relay;
+pub mod relay_log;
+pub mod relay_policy;
+pub mod settings;
+pub mod settings_schema;
+pub mod stores;
+pub mod tool_rules;
+pub mod translate;
+pub mod usage;
+pub mod vault;
+
+pub use paths::Paths;
@@ -0,0 +1,252 @@
+//! Live state of the Claude Code sessions sending hook events.
+//!
+//! Each hook event moves its session along: prompts and tool use mean it is
+//! running, a notification that it waits for the user, and `Stop` that it
+//! finished its turn. `SessionEnd` removes it. Sessions that went quiet without
+//! ending, e.g. because the terminal was closed, are dropped after a while.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+// A session without events for this long is no longer shown
+const IDLE_TIMEOUT_MS: u64 = 12 * 60 * 60 * 1000;
+const MAX_MESSAGE_CHARS: usize = 200;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// Working on a prompt
+    Running,
+    /// Asking the user for permission or input
+    WaitingForInput,
+    /// Done with its turn
+    Stopped,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct LiveSession {
+    pub session_id: String,
+    pub cwd: String,
+    pub status: SessionStatus,
+    /// Tool being used right now
+    pub current_tool: Option<String>,
+    /// Latest prompt or notification, shortened
+    pub last_message: Option<String>,
+    /// Milliseconds since the epoch of the first event seen
+    pub started_at: u64,
+    /// When the session got its current status
+    pub status_since: u64,
+    pub updated_at: u64,
+}
+
+/// What an event did to the live sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionChange {
+    /// A session started, or its status changed
+    Status,
+    /// Only details of a session changed, such as its current tool
+    Details,
+    Ended,
+}
+
+#[derive(Debug, Default)]
+pub struct LiveSessions {
+    sessions: BTreeMap<String, LiveSession>,
+}
+
+impl LiveSessions {
+    pub const fn new() -> Self {
+        Self {
+            sessions: BTreeMap::new(),
+        }
+    }
+
+    /// Update the sessions with an event as Claude Code sent it, `now` in milliseconds
+    pub fn apply(&mut self, event: &Value, now: u64) -> SessionChange {
+        let text = |key: &str| event.get(key).and_then(|v| v.as_str());
+        let session_id = text("session_id").unwrap_or_default().to_string();
+        let event_name = text("hook_event_name").unwrap_or_default();
+        if event_name == "SessionEnd" {
+            self.sessions.remove(&session_id);
+            return SessionChange::Ended;
+        }
+
+        let mut change = SessionChange::Details;
+        let session = self.sessions.entry(session_id.clone()).or_insert_with(|| {
+            change = SessionChange::Status;
+            LiveSession {
+                session_id,
+                cwd: String::new(),
+                status: SessionStatus::Running,
+                current_tool: None,
+                last_message: None,
+                started_at: now,
+                status_since: now,
+                updated_at: now,
+            }
+        });
+        if let Some(cwd) = text("cwd").filter(|cwd| !cwd.is_empty()) {
+            session.cwd = cwd.to_string();
+        }
+        session.updated_at = now;
+
+        let status = match event_name {
+            "Notification" => {
+                session.last_message = text("message").map(shorten);
+                SessionStatus::WaitingForInput
+            }
+            "Stop" => {
+                session.current_tool = None;
+                SessionStatus::Stopped
+            }
+            "UserPromptSubmit" => {
+                session.current_tool = None;
+                session.last_message = text("prompt").map(shorten);
+                SessionStatus::Running
+            }
+            "PreToolUse" => {
+                session.current_tool = text("tool_name").map(str::to_string);
+                SessionStatus::Running
+            }
+            "PostToolUse" => {
+                session.current_tool = None;
+                SessionStatus::Running
+            }
+            // A new or resumed session waits for its first prompt
+            "SessionStart" => SessionStatus::Stopped,
+            _ => session.status,
+        };
+        if status != session.status {
+            session.status = status;
+            session.status_since = now;
+            change = SessionChange::Status;
+        }
+        change
+    }
+
+    /// Sessions still going at `now`, most recently active first
+    pub fn active(&mut self, now: u64) -> Vec<LiveSession> {
+        self.sessions
+            .retain(|_, session| now.saturating_sub(session.updated_at) < IDLE_TIMEOUT_MS);
+        let mut sessions: Vec<LiveSession> = self.sessions.values().cloned().collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+        sessions
+    }
+}
+
+fn shorten(text: &str) -> String {
+    match text.char_indices().nth(MAX_MESSAGE_CHARS) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(session_id: &str, name: &str) -> Value {
+        json!({ "session_id": session_id, "cwd": "/repo", "hook_event_name": name })
+    }
+
+    fn only(sessions: &mut LiveSessions, now: u64) -> LiveSession {
+        let active = sessions.active(now);
+        assert_eq!(active.len(), 1);
+        active.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn events_move_a_session_through_its_statuses() {
+        let mut sessions = LiveSessions::new();
+        assert_eq!(
+            sessions.apply(&event("s", "SessionStart"), 1),
+            SessionChange::Status
+        );
+        assert_eq!(only(&mut sessions, 1).status, SessionStatus::Stopped);
+
+        let mut prompt = event("s", "UserPromptSubmit");
+        prompt["prompt"] = json!("fix the tests");
+        assert_eq!(sessions.apply(&prompt, 2), SessionChange::Status);
+        let session = only(&mut sessions, 2);
+        assert_eq!(session.status, SessionStatus::Running);
+        assert_eq!(session.last_message.as_deref(), Some("fix the tests"));
+        assert_eq!((session.started_at, session.status_since), (1, 2));
+
+        // Picking a tool while running only changes the details
+        let mut tool = event("s", "PreToolUse");
+        tool["tool_name"] = json!("Bash");
+        assert_eq!(sessions.apply(&tool, 3), SessionChange::Details);
+        assert_eq!(only(&mut sessions, 3).current_tool.as_deref(), Some("Bash"));
+
+        let mut notification = event("s", "Notification");
+        notification["message"] = json!("Claude needs your permission to use Bash");
+        assert_eq!(sessions.apply(&notification, 4), SessionChange::Status);
+        let session = only(&mut sessions, 4);
+        assert_eq!(session.status, SessionStatus::WaitingForInput);
+        assert_eq!(session.current_tool.as_deref(), Some("Bash"));
+
+        assert_eq!(
+            sessions.apply(&event("s", "PostToolUse"), 5),
+            SessionChange::Status
+        );
+        assert_eq!(only(&mut sessions, 5).current_tool, None);
+        assert_eq!(
+            sessions.apply(&event("s", "Stop"), 6),
+            SessionChange::Status
+        );
+        let session = only(&mut sessions, 6);
+        assert_eq!(session.status, SessionStatus::Stopped);
+        assert_eq!((session.status_since, session.updated_at), (6, 6));
+
+        assert_eq!(
+            sessions.apply(&event("s", "SessionEnd"), 7),
+            SessionChange::Ended
+        );
+        assert!(sessions.active(7).is_empty());
+    }
+
+    #[test]
+    fn unknown_events_keep_the_status_and_an_empty_cwd_is_ignored() {
+        let mut sessions = LiveSessions::new();
+        sessions.apply(&event("s", "UserPromptSubmit"), 1);
+        let change = sessions.apply(
+            &json!({ "session_id": "s", "cwd": "", "hook_event_name": "PreCompact" }),
+            2,
+        );
+        assert_eq!(change, SessionChange::Details);
+        let session = only(&mut sessions, 2);
+        assert_eq!(session.status, SessionStatus::Running);
+        assert_eq!(session.cwd, "/repo");
+        assert_eq!(session.updated_at, 2);
+    }
+
+    #[test]
+    fn quiet_sessions_are_dropped_and_the_rest_sorted_by_activity() {
+        let mut sessions = LiveSessions::new();
+        sessions.apply(&event("old", "Stop"), 0);
+        sessions.apply(&event("a", "Stop"), 10);
+        sessions.apply(&event("b", "Stop"), 20);
+        sessions.apply(&event("a", "UserPromptSubmit"), 30);
+
+        let ids = |sessions: Vec<LiveSession>| -> Vec<String> {
+            sessions.into_iter().map(|s| s.session_id).collect()
+        };
+        assert_eq!(ids(sessions.active(IDLE_TIMEOUT_MS - 1)), ["a", "b", "old"]);
+        assert_eq!(ids(sessions.active(IDLE_TIMEOUT_MS)), ["a", "b"]);
+        // Dropped for good, not only hidden
+        assert_eq!(ids(sessions.active(0)), ["a", "b"]);
+    }
+
+    #[test]
+    fn long_messages_are_shortened() {
+        let mut sessions = LiveSessions::new();
+        let mut prompt = event("s", "UserPromptSubmit");
+        prompt["prompt"] = json!("é".repeat(MAX_MESSAGE_CHARS + 1));
+        sessions.apply(&prompt, 1);
+        let message = only(&mut sessions, 1).last_message.unwrap();
+        assert_eq!(message, format!("{}…", "é".repeat(MAX_MESSAGE_CHARS)));
+        assert_eq!(shorten("short"), "short");
+    }
+}
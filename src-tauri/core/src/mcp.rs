@@ -0,0 +1,153 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::fsio;
+use crate::paths::Paths;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct McpServer {
+    #[serde(flatten)]
+    pub config: serde_json::Value,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ProjectConfig {
+    pub path: String,
+    pub config: serde_json::Value,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ClaudeConfigFile {
+    pub path: String,
+    pub content: Value,
+    pub exists: bool,
+}
+
+/// Read `~/.claude.json`, returning `None` if it does not exist yet.
+fn read_claude_json(paths: &Paths) -> Result<Option<Value>, String> {
+    fsio::read_json(&paths.claude_json, ".claude.json")
+}
+
+/// Locked read-modify-write of `~/.claude.json`. Claude Code rewrites this file
+/// itself, so the update is re-applied if it changes while we are merging.
+fn update_claude_json(
+    paths: &Paths,
+    apply: impl FnMut(&mut Value) -> Result<bool, String>,
+) -> Result<bool, String> {
+    fsio::update_json_object(paths, &paths.claude_json, ".claude.json", apply)
+}
+
+pub fn get_global_mcp_servers(paths: &Paths) -> Result<HashMap<String, McpServer>, String> {
+    let Some(json_value) = read_claude_json(paths)? else {
+        return Ok(HashMap::new());
+    };
+
+    let mcp_servers_obj = json_value
+        .get("mcpServers")
+        .and_then(|servers| servers.as_object())
+        .cloned()
+        .unwrap_or_default();
+
+    Ok(mcp_servers_obj
+        .into_iter()
+        .map(|(name, config)| (name, McpServer { config }))
+        .collect())
+}
+
+pub fn check_mcp_server_exists(paths: &Paths, server_name: &str) -> Result<bool, String> {
+    Ok(get_global_mcp_servers(paths)?.contains_key(server_name))
+}
+
+pub fn update_global_mcp_server(
+    paths: &Paths,
+    server_name: String,
+    server_config: Value,
+) -> Result<(), String> {
+    // Read existing .claude.json or create new structure
+    update_claude_json(paths, |json_value| {
+        // Update mcpServers object
+        let mcp_servers = json_value
+            .as_object_mut()
+            .ok_or("Invalid .claude.json format")?
+            .entry("mcpServers".to_string())
+            .or_insert_with(|| Value::Object(serde_json::Map::new()))
+            .as_object_mut()
+            .ok_or("Invalid mcpServers format in .claude.json")?;
+
+        // Update the specific server
+        mcp_servers.insert(server_name.clone(), server_config.clone());
+        Ok(true)
+    })?;
+    Ok(())
+}
+
+pub fn delete_global_mcp_server(paths: &Paths, server_name: &str) -> Result<(), String> {
+    if !paths.claude_json.exists() {
+        return Err("Claude configuration file does not exist".to_string());
+    }
+
+    update_claude_json(paths, |json_value| {
+        let root = json_value
+            .as_object_mut()
+            .ok_or("Invalid .claude.json format")?;
+
+        // Check if mcpServers exists
+        let mcp_servers = match root
+            .get_mut("mcpServers")
+            .and_then(|servers| servers.as_object_mut())
+        {
+            Some(servers) => servers,
+            None => return Err("No mcpServers found in .claude.json".to_string()),
+        };
+
+        // Remove the server
+        if mcp_servers.remove(server_name).is_none() {
+            return Err(format!("MCP server '{}' not found", server_name));
+        }
+
+        // If mcpServers is now empty, we can optionally remove the entire mcpServers object
+        if mcp_servers.is_empty() {
+            root.remove("mcpServers");
+        }
+        Ok(true)
+    })?;
+    Ok(())
+}
+
+pub fn read_claude_projects(paths: &Paths) -> Result<Vec<ProjectConfig>, String> {
+    let Some(json_value) = read_claude_json(paths)? else {
+        return Ok(vec![]);
+    };
+
+    let projects_obj = json_value
+        .get("projects")
+        .and_then(|projects| projects.as_object())
+        .cloned()
+        .unwrap_or_default();
+
+    Ok(projects_obj
+        .into_iter()
+        .map(|(path, config)| ProjectConfig { path, config })
+        .collect())
+}
+
+pub fn read_claude_config_file(paths: &Paths) -> Result<ClaudeConfigFile, String> {
+    let path_str = paths.claude_json.to_string_lossy().to_string();
+
+    match read_claude_json(paths)? {
+        Some(content) => Ok(ClaudeConfigFile {
+            path: path_str,
+            content,
+            exists: true,
+        }),
+        None => Ok(ClaudeConfigFile {
+            path: path_str,
+            content: Value::Object(serde_json::Map::new()),
+            exists: false,
+        }),
+    }
+}
+
+pub fn write_claude_config_file(paths: &Paths, content: &Value) -> Result<(), String> {
+    fsio::write_json(paths, &paths.claude_json, ".claude.json", content)
+}
@@ -0,0 +1,415 @@
+//! Merge engine that layers a profile's settings on top of `~/.claude/settings.json`.
+//!
+//! Each key is merged with a strategy looked up by JSON pointer: `replace`
+//! overwrites the value, `deep-merge` recurses into objects and `array-union`
+//! appends the items that are missing. Every change made while applying a
+//! profile is recorded, so switching to another profile first reverts exactly
+//! what the previous one contributed and the file ends up as the user's own
+//! base settings plus the active profile.
+
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::pointer;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    Replace,
+    DeepMerge,
+    ArrayUnion,
+}
+
+/// One change made to settings.json while applying a profile
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum MergeChange {
+    /// `value` was written at `pointer`, replacing `previous` (absent when `None`)
+    Set {
+        pointer: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        previous: Option<Value>,
+        value: Value,
+    },
+    /// `value` was appended to the array at `pointer`
+    Append { pointer: String, value: Value },
+    /// An empty object or array was created at `pointer` to hold merged values
+    Create { pointer: String },
+}
+
+/// What the active profile contributed to settings.json
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct AppliedSettings {
+    pub store_id: String,
+    pub changes: Vec<MergeChange>,
+}
+
+/// Strategy overrides keyed by JSON pointer; `*` matches any single token
+pub type MergeStrategies = BTreeMap<String, MergeStrategy>;
+
+/// Built-in strategies: maps are merged key by key, permission and hook lists are unioned
+pub fn default_strategies() -> MergeStrategies {
+    [
+        ("/env", MergeStrategy::DeepMerge),
+        ("/permissions", MergeStrategy::DeepMerge),
+        ("/permissions/allow", MergeStrategy::ArrayUnion),
+        ("/permissions/ask", MergeStrategy::ArrayUnion),
+        ("/permissions/deny", MergeStrategy::ArrayUnion),
+        (
+            "/permissions/additionalDirectories",
+            MergeStrategy::ArrayUnion,
+        ),
+        ("/hooks", MergeStrategy::DeepMerge),
+        ("/hooks/*", MergeStrategy::ArrayUnion),
+        ("/enabledMcpjsonServers", MergeStrategy::ArrayUnion),
+        ("/disabledMcpjsonServers", MergeStrategy::ArrayUnion),
+    ]
+    .into_iter()
+    .map(|(pointer, strategy)| (pointer.to_string(), strategy))
+    .collect()
+}
+
+/// Strategy for the value at `pointer`. The document root is always deep-merged,
+/// anything without a matching rule is replaced.
+pub fn strategy_for(strategies: &MergeStrategies, pointer: &str) -> MergeStrategy {
+    if pointer.is_empty() {
+        return MergeStrategy::DeepMerge;
+    }
+    if let Some(strategy) = strategies.get(pointer) {
+        return *strategy;
+    }
+
+    let tokens = pointer::tokens(pointer);
+    strategies
+        .iter()
+        .find(|(pattern, _)| {
+            let pattern_tokens = pointer::tokens(pattern);
+            pattern_tokens.len() == tokens.len()
+                && pattern_tokens
+                    .iter()
+                    .zip(&tokens)
+                    .all(|(pattern, token)| pattern == "*" || pattern == token)
+        })
+        .map(|(_, strategy)| *strategy)
+        .unwrap_or(MergeStrategy::Replace)
+}
+
+/// Merge `settings` into `target` and return the changes that were made.
+pub fn apply(
+    target: &mut Value,
+    settings: &Value,
+    strategies: &MergeStrategies,
+) -> Result<Vec<MergeChange>, String> {
+    if !target.is_object() {
+        return Err("Invalid settings.json format".to_string());
+    }
+    if !settings.is_object() {
+        return Err("Store settings must be a JSON object".to_string());
+    }
+
+    let mut changes = Vec::new();
+    apply_at(target, "", settings, strategies, &mut changes)?;
+    Ok(changes)
+}
+
+fn apply_at(
+    target: &mut Value,
+    at: &str,
+    value: &Value,
+    strategies: &MergeStrategies,
+    changes: &mut Vec<MergeChange>,
+) -> Result<(), String> {
+    let current = target.pointer(at);
+
+    match (strategy_for(strategies, at), value) {
+        (MergeStrategy::DeepMerge, Value::Object(object))
+            if current.is_none_or(|current| current.is_object()) =>
+        {
+            if current.is_none() {
+                pointer::set(target, at, Value::Object(Map::new()))?;
+                changes.push(MergeChange::Create {
+                    pointer: at.to_string(),
+                });
+            }
+            for (key, value) in object {
+                apply_at(target, &pointer::child(at, key), value, strategies, changes)?;
+            }
+        }
+        (MergeStrategy::ArrayUnion, Value::Array(items))
+            if current.is_none_or(|current| current.is_array()) =>
+        {
+            if current.is_none() {
+                pointer::set(target, at, Value::Array(vec![]))?;
+                changes.push(MergeChange::Create {
+                    pointer: at.to_string(),
+                });
+            }
+            for item in items {
+                let Some(Value::Array(existing)) = target.pointer_mut(at) else {
+                    break;
+                };
+                if !existing.contains(item) {
+                    existing.push(item.clone());
+                    changes.push(MergeChange::Append {
+                        pointer: at.to_string(),
+                        value: item.clone(),
+                    });
+                }
+            }
+        }
+        // Replace, or the existing value has a different shape than the profile's
+        _ => {
+            if current == Some(value) {
+                return Ok(());
+            }
+            let previous = current.cloned();
+            pointer::set(target, at, value.clone())?;
+            changes.push(MergeChange::Set {
+                pointer: at.to_string(),
+                previous,
+                value: value.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Undo recorded changes, newest first.
+///
+/// Values the user has edited since they were applied are left alone.
+pub fn revert(target: &mut Value, changes: &[MergeChange]) {
+    for change in changes.iter().rev() {
+        match change {
+            MergeChange::Set {
+                pointer: at,
+                previous,
+                value,
+            } => {
+                if target.pointer(at) != Some(value) {
+                    continue;
+                }
+                match previous {
+                    Some(previous) => {
+                        let _ = pointer::set(target, at, previous.clone());
+                    }
+                    None => {
+                        pointer::remove(target, at);
+                    }
+                }
+            }
+            MergeChange::Append { pointer: at, value } => {
+                if let Some(Value::Array(items)) = target.pointer_mut(at) {
+                    if let Some(index) = items.iter().position(|item| item == value) {
+                        items.remove(index);
+                    }
+                }
+            }
+            MergeChange::Create { pointer: at } => {
+                let empty = match target.pointer(at) {
+                    Some(Value::Object(object)) => object.is_empty(),
+                    Some(Value::Array(items)) => items.is_empty(),
+                    _ => false,
+                };
+                if empty {
+                    pointer::remove(target, at);
+                }
+            }
+        }
+    }
+}
+
+/// Reconstruct the changes a profile most likely made when no record exists,
+/// e.g. for a profile activated by an older version of the app.
+///
+/// Values that still match the profile are treated as contributed by it.
+pub fn infer_changes(
+    target: &Value,
+    settings: &Value,
+    strategies: &MergeStrategies,
+) -> Vec<MergeChange> {
+    let mut changes = Vec::new();
+    infer_at(target, "", settings, strategies, &mut changes);
+    changes
+}
+
+fn infer_at(
+    target: &Value,
+    at: &str,
+    value: &Value,
+    strategies: &MergeStrategies,
+    changes: &mut Vec<MergeChange>,
+) {
+    let Some(current) = target.pointer(at) else {
+        return;
+    };
+
+    match (strategy_for(strategies, at), value, current) {
+        (MergeStrategy::DeepMerge, Value::Object(object), Value::Object(_)) => {
+            for (key, value) in object {
+                infer_at(target, &pointer::child(at, key), value, strategies, changes);
+            }
+        }
+        (MergeStrategy::ArrayUnion, Value::Array(items), Value::Array(existing)) => {
+            for item in items.iter().filter(|item| existing.contains(item)) {
+                changes.push(MergeChange::Append {
+                    pointer: at.to_string(),
+                    value: item.clone(),
+                });
+            }
+        }
+        _ if current == value => changes.push(MergeChange::Set {
+            pointer: at.to_string(),
+            previous: None,
+            value: value.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deep_merge_keeps_sibling_keys_at_every_level() {
+        let strategies: MergeStrategies = [
+            ("/env".to_string(), MergeStrategy::DeepMerge),
+            ("/env/nested".to_string(), MergeStrategy::DeepMerge),
+        ]
+        .into_iter()
+        .collect();
+        let mut target = json!({ "env": { "A": "1", "nested": { "x": 1 } } });
+        let changes = apply(
+            &mut target,
+            &json!({ "env": { "B": "2", "nested": { "y": 2 } } }),
+            &strategies,
+        )
+        .unwrap();
+
+        assert_eq!(
+            target,
+            json!({ "env": { "A": "1", "B": "2", "nested": { "x": 1, "y": 2 } } })
+        );
+        assert_eq!(changes.len(), 2);
+
+        // Without a rule the nested object is replaced as a whole
+        let mut target = json!({ "env": { "nested": { "x": 1 } } });
+        apply(
+            &mut target,
+            &json!({ "env": { "nested": { "y": 2 } } }),
+            &default_strategies(),
+        )
+        .unwrap();
+        assert_eq!(target, json!({ "env": { "nested": { "y": 2 } } }));
+    }
+
+    #[test]
+    fn array_union_skips_items_already_present() {
+        let mut target = json!({ "permissions": { "allow": ["Read", "Bash(ls)"] } });
+        let profile = json!({ "permissions": { "allow": ["Bash(ls)", "Edit", "Edit"] } });
+        let changes = apply(&mut target, &profile, &default_strategies()).unwrap();
+
+        assert_eq!(
+            target,
+            json!({ "permissions": { "allow": ["Read", "Bash(ls)", "Edit"] } })
+        );
+        assert_eq!(
+            changes,
+            vec![MergeChange::Append {
+                pointer: "/permissions/allow".to_string(),
+                value: json!("Edit"),
+            }]
+        );
+
+        // The user's own copy of a shared item survives the revert
+        revert(&mut target, &changes);
+        assert_eq!(
+            target,
+            json!({ "permissions": { "allow": ["Read", "Bash(ls)"] } })
+        );
+    }
+
+    #[test]
+    fn wildcard_matches_exactly_one_token() {
+        let strategies = default_strategies();
+        assert_eq!(
+            strategy_for(&strategies, "/hooks/PreToolUse"),
+            MergeStrategy::ArrayUnion
+        );
+        assert_eq!(
+            strategy_for(&strategies, "/hooks/Stop"),
+            MergeStrategy::ArrayUnion
+        );
+        assert_eq!(
+            strategy_for(&strategies, "/hooks"),
+            MergeStrategy::DeepMerge
+        );
+        assert_eq!(
+            strategy_for(&strategies, "/hooks/Stop/0"),
+            MergeStrategy::Replace
+        );
+        assert_eq!(strategy_for(&strategies, ""), MergeStrategy::DeepMerge);
+        assert_eq!(strategy_for(&strategies, "/model"), MergeStrategy::Replace);
+
+        // An exact rule wins over a wildcard
+        let mut strategies = strategies;
+        strategies.insert("/hooks/Stop".to_string(), MergeStrategy::Replace);
+        assert_eq!(
+            strategy_for(&strategies, "/hooks/Stop"),
+            MergeStrategy::Replace
+        );
+        assert_eq!(
+            strategy_for(&strategies, "/hooks/Notification"),
+            MergeStrategy::ArrayUnion
+        );
+    }
+
+    #[test]
+    fn switching_profiles_reverts_only_what_was_applied() {
+        let strategies = default_strategies();
+        let base = json!({
+            "theme": "dark",
+            "model": "sonnet",
+            "env": { "KEEP": "1" },
+            "permissions": { "allow": ["Read"] }
+        });
+        let first = json!({
+            "model": "opus",
+            "env": { "ANTHROPIC_BASE_URL": "https://a", "ANTHROPIC_MODEL": "a-model" },
+            "permissions": { "allow": ["Bash(git:*)"] },
+            "hooks": { "Stop": [{ "command": "notify" }] }
+        });
+        let second = json!({ "env": { "ANTHROPIC_BASE_URL": "https://b" } });
+
+        let mut target = base.clone();
+        let changes = apply(&mut target, &first, &strategies).unwrap();
+        revert(&mut target, &changes);
+        assert_eq!(target, base);
+
+        // The user edits a key the first profile set before switching away
+        let changes = apply(&mut target, &first, &strategies).unwrap();
+        pointer::set(&mut target, "/env/ANTHROPIC_MODEL", json!("my-model")).unwrap();
+        revert(&mut target, &changes);
+        let changes = apply(&mut target, &second, &strategies).unwrap();
+
+        assert_eq!(
+            target,
+            json!({
+                "theme": "dark",
+                "model": "sonnet",
+                "env": {
+                    "KEEP": "1",
+                    "ANTHROPIC_MODEL": "my-model",
+                    "ANTHROPIC_BASE_URL": "https://b"
+                },
+                "permissions": { "allow": ["Read"] }
+            })
+        );
+
+        revert(&mut target, &changes);
+        assert_eq!(target["env"]["ANTHROPIC_MODEL"], "my-model");
+        assert!(target["env"].get("ANTHROPIC_BASE_URL").is_none());
+    }
+}
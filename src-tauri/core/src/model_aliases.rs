@@ -0,0 +1,318 @@
+//! Per-profile model aliases applied by the local relay.
+//!
+//! A profile's table maps the model names Claude Code asks for to the ones its
+//! endpoint knows, e.g. `claude-sonnet-*` to `glm-4.6`. The relay rewrites the
+//! `model` of each request with the first matching alias, and puts the name
+//! Claude Code asked for back into the response, so Claude Code can keep its
+//! default model names whatever the backend. The tables are kept in
+//! `~/.ccconfig/model_aliases.json`, keyed by store id.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::fsio;
+use crate::paths::Paths;
+use crate::stores;
+
+// A non-streamed response is passed on unchanged past this size
+const MAX_REWRITTEN_RESPONSE_BYTES: usize = 8 * 1024 * 1024;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModelAlias {
+    /// Model Claude Code asks for, with at most one `*` matching any text
+    pub from: String,
+    /// Model sent to the endpoint instead
+    pub to: String,
+}
+
+impl ModelAlias {
+    fn matches(&self, model: &str) -> bool {
+        match self.from.split_once('*') {
+            Some((prefix, suffix)) => {
+                model.len() >= prefix.len() + suffix.len()
+                    && model.starts_with(prefix)
+                    && model.ends_with(suffix)
+            }
+            None => self.from == model,
+        }
+    }
+}
+
+type AliasTables = BTreeMap<String, Vec<ModelAlias>>;
+
+/// Alias tables of all profiles, leaving out profiles deleted since
+pub fn get_model_aliases(paths: &Paths) -> Result<AliasTables, String> {
+    let mut tables: AliasTables =
+        fsio::read_json(&paths.model_aliases_file(), "model aliases")?.unwrap_or_default();
+    let configs = stores::get_stores(paths)?;
+    tables.retain(|store_id, _| configs.iter().any(|store| &store.id == store_id));
+    Ok(tables)
+}
+
+pub fn get_store_model_aliases(paths: &Paths, store_id: &str) -> Result<Vec<ModelAlias>, String> {
+    Ok(get_model_aliases(paths)?
+        .remove(store_id)
+        .unwrap_or_default())
+}
+
+/// Replace the alias table of a profile, removing it when `aliases` is empty
+pub fn update_store_model_aliases(
+    paths: &Paths,
+    store_id: &str,
+    aliases: Vec<ModelAlias>,
+) -> Result<Vec<ModelAlias>, String> {
+    stores::get_store(paths, store_id)?;
+    for alias in &aliases {
+        if alias.from.trim().is_empty() || alias.to.trim().is_empty() {
+            return Err("Model aliases need both a model and its replacement".to_string());
+        }
+        if alias.from.matches('*').count() > 1 {
+            return Err(format!(
+                "Model alias '{}' can contain only one '*'",
+                alias.from
+            ));
+        }
+    }
+
+    let mut tables = get_model_aliases(paths)?;
+    if aliases.is_empty() {
+        tables.remove(store_id);
+    } else {
+        tables.insert(store_id.to_string(), aliases.clone());
+    }
+    fsio::write_json(paths, &paths.model_aliases_file(), "model aliases", &tables)?;
+    eprintln!("✅ Updated {} model aliases of {}", aliases.len(), store_id);
+    Ok(aliases)
+}
+
+/// The model to send instead of `model`, from the first matching alias
+pub fn map_model(aliases: &[ModelAlias], model: &str) -> Option<String> {
+    aliases
+        .iter()
+        .find(|alias| alias.matches(model))
+        .map(|alias| alias.to.clone())
+        .filter(|to| to != model)
+}
+
+/// A request body with its model replaced, and the model it asked for.
+/// `None` when the body names no model or no alias applies.
+pub fn rewrite_request(aliases: &[ModelAlias], body: &[u8]) -> Option<(Vec<u8>, String)> {
+    if aliases.is_empty() {
+        return None;
+    }
+    let mut request: Value = serde_json::from_slice(body).ok()?;
+    let requested = request.get("model")?.as_str()?.to_string();
+    let mapped = map_model(aliases, &requested)?;
+    request["model"] = Value::String(mapped);
+    Some((serde_json::to_vec(&request).ok()?, requested))
+}
+
+/// Puts the model Claude Code asked for back into a response as it streams through
+#[derive(Debug)]
+pub struct ResponseModelRewriter {
+    model: String,
+    streaming: bool,
+    // An incomplete event line, or the non-streamed body so far
+    pending: Vec<u8>,
+    // Set once a non-streamed body turned out too large to rewrite
+    passthrough: bool,
+}
+
+impl ResponseModelRewriter {
+    pub fn new(model: String, content_type: Option<&str>) -> Self {
+        Self {
+            model,
+            streaming: content_type
+                .map(|value| value.starts_with("text/event-stream"))
+                .unwrap_or(false),
+            pending: vec![],
+            passthrough: false,
+        }
+    }
+
+    /// The bytes to pass on for `chunk`, holding back what cannot be rewritten yet
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<u8> {
+        if self.passthrough {
+            return chunk.to_vec();
+        }
+        self.pending.extend_from_slice(chunk);
+
+        if !self.streaming {
+            if self.pending.len() > MAX_REWRITTEN_RESPONSE_BYTES {
+                self.passthrough = true;
+                return std::mem::take(&mut self.pending);
+            }
+            return vec![];
+        }
+
+        let Some(end) = self.pending.iter().rposition(|byte| *byte == b'\n') else {
+            return vec![];
+        };
+        let lines: Vec<u8> = self.pending.drain(..=end).collect();
+        let mut output = Vec::with_capacity(lines.len());
+        for line in lines.split_inclusive(|byte| *byte == b'\n') {
+            output.extend_from_slice(&self.rewrite_event_line(line));
+        }
+        output
+    }
+
+    /// What is still held back once the response has ended
+    pub fn finish(mut self) -> Vec<u8> {
+        let rest = std::mem::take(&mut self.pending);
+        if self.streaming {
+            return self.rewrite_event_line(&rest);
+        }
+        match serde_json::from_slice::<Value>(&rest) {
+            Ok(mut message) if message.get("model").is_some() => {
+                message["model"] = Value::String(self.model.clone());
+                serde_json::to_vec(&message).unwrap_or(rest)
+            }
+            _ => rest,
+        }
+    }
+
+    // Only `message_start` names the model
+    fn rewrite_event_line(&self, line: &[u8]) -> Vec<u8> {
+        let rewritten = std::str::from_utf8(line).ok().and_then(|text| {
+            let data = text.strip_prefix("data:")?;
+            let mut event: Value = serde_json::from_str(data.trim()).ok()?;
+            if event.get("type")?.as_str()? != "message_start" {
+                return None;
+            }
+            event.pointer("/message/model")?;
+            event["message"]["model"] = Value::String(self.model.clone());
+            let ending = &text[text.trim_end_matches(['\r', '\n']).len()..];
+            Some(format!("data: {}{}", event, ending).into_bytes())
+        });
+        rewritten.unwrap_or_else(|| line.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn alias(from: &str, to: &str) -> ModelAlias {
+        ModelAlias {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    fn table() -> Vec<ModelAlias> {
+        vec![
+            alias("claude-opus-4-1", "glm-4.6-max"),
+            alias("claude-*", "glm-4.6"),
+            alias("*-haiku", "glm-4.5-air"),
+        ]
+    }
+
+    #[test]
+    fn first_matching_alias_wins() {
+        let aliases = table();
+        assert_eq!(
+            map_model(&aliases, "claude-opus-4-1").as_deref(),
+            Some("glm-4.6-max")
+        );
+        assert_eq!(
+            map_model(&aliases, "claude-sonnet-4-5").as_deref(),
+            Some("glm-4.6")
+        );
+        assert_eq!(
+            map_model(&aliases, "small-haiku").as_deref(),
+            Some("glm-4.5-air")
+        );
+        assert_eq!(map_model(&aliases, "gpt-5"), None);
+        // Already the model the alias names
+        assert_eq!(map_model(&[alias("glm-*", "glm-4.6")], "glm-4.6"), None);
+    }
+
+    #[test]
+    fn wildcard_prefix_and_suffix_do_not_overlap() {
+        let aliases = [alias("ab*ba", "x")];
+        assert_eq!(map_model(&aliases, "abba").as_deref(), Some("x"));
+        assert_eq!(map_model(&aliases, "ab-ba").as_deref(), Some("x"));
+        assert_eq!(map_model(&aliases, "aba"), None);
+        assert_eq!(
+            map_model(&[alias("*", "x")], "anything").as_deref(),
+            Some("x")
+        );
+    }
+
+    #[test]
+    fn request_model_is_replaced_and_the_rest_kept() {
+        let body = json!({ "model": "claude-sonnet-4-5", "max_tokens": 10, "stream": true });
+        let (rewritten, requested) =
+            rewrite_request(&table(), body.to_string().as_bytes()).unwrap();
+        assert_eq!(requested, "claude-sonnet-4-5");
+        let rewritten: Value = serde_json::from_slice(&rewritten).unwrap();
+        assert_eq!(
+            rewritten,
+            json!({ "model": "glm-4.6", "max_tokens": 10, "stream": true })
+        );
+
+        assert!(rewrite_request(&[], body.to_string().as_bytes()).is_none());
+        assert!(rewrite_request(&table(), br#"{ "max_tokens": 10 }"#).is_none());
+        assert!(rewrite_request(&table(), b"not json").is_none());
+    }
+
+    #[test]
+    fn streamed_response_gets_the_requested_model_back_across_chunks() {
+        let mut rewriter = ResponseModelRewriter::new(
+            "claude-sonnet-4-5".to_string(),
+            Some("text/event-stream; charset=utf-8"),
+        );
+        // Keys in the order serde_json writes them, so a rewritten line compares equal
+        let stream = concat!(
+            "event: message_start\r\n",
+            "data: {\"message\":{\"id\":\"m\",\"model\":\"glm-4.6\"},\"type\":\"message_start\"}\r\n",
+            "\r\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"glm-4.6\"}}\n",
+            "\n",
+        );
+
+        // Split inside the message_start line, nothing of it may go out early
+        let (first, rest) = stream.split_at(40);
+        let mut output = rewriter.feed(first.as_bytes());
+        assert_eq!(output, b"event: message_start\r\n");
+        output.extend(rewriter.feed(rest.as_bytes()));
+        output.extend(rewriter.finish());
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            stream.replace("\"model\":\"glm-4.6\"", "\"model\":\"claude-sonnet-4-5\"")
+        );
+    }
+
+    #[test]
+    fn whole_response_is_rewritten_once_it_ended() {
+        let mut rewriter =
+            ResponseModelRewriter::new("claude-sonnet-4-5".to_string(), Some("application/json"));
+        let body = json!({ "id": "m", "model": "glm-4.6", "content": [] }).to_string();
+        let (first, rest) = body.split_at(10);
+        assert!(rewriter.feed(first.as_bytes()).is_empty());
+        assert!(rewriter.feed(rest.as_bytes()).is_empty());
+        let message: Value = serde_json::from_slice(&rewriter.finish()).unwrap();
+        assert_eq!(message["model"], "claude-sonnet-4-5");
+
+        // An error body without a model is passed on as it came
+        let mut rewriter = ResponseModelRewriter::new("claude-sonnet-4-5".to_string(), None);
+        let error = br#"{"type":"error","error":{"message":"overloaded"}}"#;
+        rewriter.feed(error);
+        assert_eq!(rewriter.finish(), error);
+    }
+
+    #[test]
+    fn oversized_response_is_passed_through() {
+        let mut rewriter = ResponseModelRewriter::new("claude".to_string(), None);
+        let chunk = vec![b' '; MAX_REWRITTEN_RESPONSE_BYTES];
+        assert!(rewriter.feed(&chunk).is_empty());
+        assert_eq!(rewriter.feed(b"{}").len(), MAX_REWRITTEN_RESPONSE_BYTES + 2);
+        assert_eq!(rewriter.feed(b"more"), b"more");
+        assert!(rewriter.finish().is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use std::path::{Path, PathBuf};
+
+// Application configuration directory
+pub const APP_CONFIG_DIR: &str = ".ccconfig";
+
+const STORES_FILE: &str = "stores.json";
+const CODEX_CONFIG_FILE: &str = "codexcc.json";
+const CODEX_SETTINGS_FILE: &str = "codex_settings.json";
+const BACKUP_DIR: &str = "claude_backup";
+const LOCK_DIR: &str = "locks";
+const HISTORY_DIR: &str = "history";
+const HISTORY_SETTINGS_FILE: &str = "history_settings.json";
+const VAULT_FILE: &str = "vault.json";
+const VAULT_KEY_FILE: &str = "vault.key";
+const AUTO_SWITCH_FILE: &str = "auto_switch.json";
+const AUTO_SWITCH_LOG_FILE: &str = "auto_switch_log.jsonl";
+const FAILOVER_FILE: &str = "failover.json";
+const RELAY_FILE: &str = "relay.json";
+const RELAY_LOG_DB: &str = "relay_log.db";
+const MODEL_ALIASES_FILE: &str = "model_aliases.json";
+const RELAY_POLICIES_FILE: &str = "relay_policies.json";
+const HOOK_EVENTS_DB: &str = "hook_events.db";
+const HOOK_TOKEN_FILE: &str = "hook.token";
+const APPROVAL_FILE: &str = "approval.json";
+const TOOL_RULES_FILE: &str = "tool_rules.json";
+
+/// Locations of every file the app reads or writes.
+///
+/// All core functions take a `Paths` instead of resolving the home directory
+/// themselves, so the same logic can run against a temporary directory.
+#[derive(Debug, Clone)]
+pub struct Paths {
+    /// `~/.claude`
+    pub claude_dir: PathBuf,
+    /// `~/.claude.json`
+    pub claude_json: PathBuf,
+    /// `~/.ccconfig`
+    pub app_config_dir: PathBuf,
+    /// Default Codex root, `~/.codex`
+    pub codex_root: PathBuf,
+}
+
+impl Paths {
+    /// Build the standard layout rooted at `home`.
+    pub fn from_home(home: &Path) -> Self {
+        Self {
+            claude_dir: home.join(".claude"),
+            claude_json: home.join(".claude.json"),
+            app_config_dir: home.join(APP_CONFIG_DIR),
+            codex_root: home.join(".codex"),
+        }
+    }
+
+    /// Build the standard layout for the current user's home directory.
+    pub fn detect() -> Result<Self, String> {
+        let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
+        Ok(Self::from_home(&home_dir))
+    }
+
+    pub fn user_settings(&self) -> PathBuf {
+        self.claude_dir.join("settings.json")
+    }
+
+    pub fn claude_config(&self) -> PathBuf {
+        self.claude_dir.join("config.json")
+    }
+
+    pub fn claude_memory(&self) -> PathBuf {
+        self.claude_dir.join("CLAUDE.md")
+    }
+
+    pub fn claude_projects_dir(&self) -> PathBuf {
+        self.claude_dir.join("projects")
+    }
+
+    pub fn stores_file(&self) -> PathBuf {
+        self.app_config_dir.join(STORES_FILE)
+    }
+
+    pub fn codex_stores_file(&self) -> PathBuf {
+        self.app_config_dir.join(CODEX_CONFIG_FILE)
+    }
+
+    pub fn codex_settings_file(&self) -> PathBuf {
+        self.app_config_dir.join(CODEX_SETTINGS_FILE)
+    }
+
+    pub fn backup_dir(&self) -> PathBuf {
+        self.app_config_dir.join(BACKUP_DIR)
+    }
+
+    /// Advisory lock files for the managed files, see [`crate::fsio`]
+    pub fn lock_dir(&self) -> PathBuf {
+        self.app_config_dir.join(LOCK_DIR)
+    }
+
+    /// Snapshots of managed files, see [`crate::history`]
+    pub fn history_dir(&self) -> PathBuf {
+        self.app_config_dir.join(HISTORY_DIR)
+    }
+
+    pub fn history_settings_file(&self) -> PathBuf {
+        self.app_config_dir.join(HISTORY_SETTINGS_FILE)
+    }
+
+    /// Encrypted secrets, see [`crate::vault`]
+    pub fn vault_file(&self) -> PathBuf {
+        self.app_config_dir.join(VAULT_FILE)
+    }
+
+    /// Vault key when neither a passphrase nor the OS keyring is used
+    pub fn vault_key_file(&self) -> PathBuf {
+        self.app_config_dir.join(VAULT_KEY_FILE)
+    }
+
+    /// Automatic switching rules, see [`crate::auto_switch`]
+    pub fn auto_switch_file(&self) -> PathBuf {
+        self.app_config_dir.join(AUTO_SWITCH_FILE)
+    }
+
+    /// Audit log of automatic switches, one JSON entry per line
+    pub fn auto_switch_log_file(&self) -> PathBuf {
+        self.app_config_dir.join(AUTO_SWITCH_LOG_FILE)
+    }
+
+    /// Failover group, see [`crate::failover`]
+    pub fn failover_file(&self) -> PathBuf {
+        self.app_config_dir.join(FAILOVER_FILE)
+    }
+
+    /// Local relay settings, see [`crate::relay`]
+    pub fn relay_file(&self) -> PathBuf {
+        self.app_config_dir.join(RELAY_FILE)
+    }
+
+    /// SQLite database of relayed requests, see [`crate::relay_log`]
+    pub fn relay_log_db(&self) -> PathBuf {
+        self.app_config_dir.join(RELAY_LOG_DB)
+    }
+
+    /// Per-profile model aliases, see [`crate::model_aliases`]
+    pub fn model_aliases_file(&self) -> PathBuf {
+        self.app_config_dir.join(MODEL_ALIASES_FILE)
+    }
+
+    /// Per-profile relay traffic policies, see [`crate::relay_policy`]
+    pub fn relay_policies_file(&self) -> PathBuf {
+        self.app_config_dir.join(RELAY_POLICIES_FILE)
+    }
+
+    /// SQLite database of received hook events, see [`crate::hook_log`]
+    pub fn hook_events_db(&self) -> PathBuf {
+        self.app_config_dir.join(HOOK_EVENTS_DB)
+    }
+
+    /// Token the installed hook authenticates to the hook server with, see [`crate::hooks`]
+    pub fn hook_token_file(&self) -> PathBuf {
+        self.app_config_dir.join(HOOK_TOKEN_FILE)
+    }
+
+    /// Tool use approval settings, see [`crate::approval`]
+    pub fn approval_file(&self) -> PathBuf {
+        self.app_config_dir.join(APPROVAL_FILE)
+    }
+
+    /// Rules deciding tool calls, see [`crate::tool_rules`]
+    pub fn tool_rules_file(&self) -> PathBuf {
+        self.app_config_dir.join(TOOL_RULES_FILE)
+    }
+}
@@ -0,0 +1,57 @@
+//! JSON pointer (RFC 6901) helpers for addressing values inside settings files.
+
+use serde_json::{Map, Value};
+
+/// Append an escaped reference token to a JSON pointer
+pub fn child(pointer: &str, token: &str) -> String {
+    format!(
+        "{}/{}",
+        pointer,
+        token.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+/// Unescaped reference tokens of a pointer, `""` has none
+pub fn tokens(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+/// Split a pointer into its parent pointer and unescaped last token
+pub fn split_last(pointer: &str) -> Option<(&str, String)> {
+    let index = pointer.rfind('/')?;
+    let token = tokens(&pointer[index..]).pop()?;
+    Some((&pointer[..index], token))
+}
+
+/// Set the value at `pointer`, creating missing parent objects.
+pub fn set(root: &mut Value, pointer: &str, value: Value) -> Result<(), String> {
+    let Some((parent, key)) = split_last(pointer) else {
+        *root = value;
+        return Ok(());
+    };
+
+    let mut target = root;
+    for token in tokens(parent) {
+        target = target
+            .as_object_mut()
+            .ok_or_else(|| format!("Cannot set {}: parent is not an object", pointer))?
+            .entry(token)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    target
+        .as_object_mut()
+        .ok_or_else(|| format!("Cannot set {}: parent is not an object", pointer))?
+        .insert(key, value);
+    Ok(())
+}
+
+/// Remove and return the object member at `pointer`.
+pub fn remove(root: &mut Value, pointer: &str) -> Option<Value> {
+    let (parent, key) = split_last(pointer)?;
+    root.pointer_mut(parent)?.as_object_mut()?.remove(&key)
+}
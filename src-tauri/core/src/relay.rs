@@ -0,0 +1,238 @@
+//! Local relay that forwards Claude Code's API requests to the active profile.
+//!
+//! With the relay enabled, applying a profile points `ANTHROPIC_BASE_URL` at
+//! the relay and replaces the profile's credentials with a local relay token.
+//! The desktop app then forwards each request to the upstream of whichever
+//! profile is active at that moment, so switching profiles takes effect for
+//! running sessions too. Profiles whose endpoint speaks an OpenAI API have
+//! their requests translated, see [`crate::translate`].
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::bindings;
+use crate::fsio;
+use crate::health;
+use crate::model_aliases::{self, ModelAlias};
+use crate::paths::Paths;
+use crate::stores;
+use crate::translate::WireApi;
+use crate::vault;
+
+pub const DEFAULT_RELAY_PORT: u16 = 59949;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RelaySettings {
+    pub enabled: bool,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Claude Code authenticates to the relay with this instead of the profile's credentials
+    #[serde(default)]
+    pub token: String,
+    /// Keep request and response bodies in the relay log
+    #[serde(default)]
+    pub log_bodies: bool,
+    /// Profiles whose endpoint does not speak the Anthropic Messages API, by store id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub wire_apis: BTreeMap<String, WireApi>,
+}
+
+impl Default for RelaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_RELAY_PORT,
+            token: String::new(),
+            log_bodies: false,
+            wire_apis: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_port() -> u16 {
+    DEFAULT_RELAY_PORT
+}
+
+impl RelaySettings {
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// Whether a request carries the relay token, as a bearer token or an API key
+    pub fn accepts(&self, authorization: Option<&str>, api_key: Option<&str>) -> bool {
+        !self.token.is_empty()
+            && (authorization.and_then(|value| value.strip_prefix("Bearer "))
+                == Some(self.token.as_str())
+                || api_key == Some(self.token.as_str()))
+    }
+
+    pub fn wire_api(&self, store_id: &str) -> WireApi {
+        self.wire_apis.get(store_id).copied().unwrap_or_default()
+    }
+}
+
+pub fn get_relay_settings(paths: &Paths) -> Result<RelaySettings, String> {
+    Ok(fsio::read_json(&paths.relay_file(), "relay settings")?.unwrap_or_default())
+}
+
+/// Save the relay settings and re-apply the active profile and bound projects,
+/// so their settings point at the relay or back at the profile's own endpoint.
+pub fn update_relay_settings(
+    paths: &Paths,
+    enabled: bool,
+    port: u16,
+    log_bodies: bool,
+) -> Result<RelaySettings, String> {
+    if port == 0 {
+        return Err("Invalid relay port".to_string());
+    }
+
+    let mut settings = get_relay_settings(paths)?;
+    settings.enabled = enabled;
+    settings.port = port;
+    settings.log_bodies = log_bodies;
+    if settings.token.is_empty() {
+        settings.token = format!("tvcbuddy-relay-{}", nanoid::nanoid!(32));
+    }
+    fsio::write_json(paths, &paths.relay_file(), "relay settings", &settings)?;
+
+    if let Some(store) = stores::get_current_store(paths)? {
+        stores::set_using_config(paths, &store.id)?;
+    }
+    for binding in bindings::get_project_bindings(paths)? {
+        if let Err(e) = bindings::bind_project(paths, &binding.project_path, &binding.store_id) {
+            eprintln!(
+                "Warning: Failed to update bound project {}: {}",
+                binding.project_path, e
+            );
+        }
+    }
+    eprintln!(
+        "🔁 Relay {} on {}",
+        if enabled { "enabled" } else { "disabled" },
+        settings.url()
+    );
+    Ok(settings)
+}
+
+/// Set the API a profile's endpoint speaks
+pub fn set_wire_api(
+    paths: &Paths,
+    store_id: &str,
+    wire_api: WireApi,
+) -> Result<RelaySettings, String> {
+    stores::get_store(paths, store_id)?;
+    let mut settings = get_relay_settings(paths)?;
+    if wire_api == WireApi::Anthropic {
+        settings.wire_apis.remove(store_id);
+    } else {
+        settings.wire_apis.insert(store_id.to_string(), wire_api);
+    }
+    fsio::write_json(paths, &paths.relay_file(), "relay settings", &settings)?;
+    eprintln!("✅ Profile {} now uses the {:?} API", store_id, wire_api);
+    Ok(settings)
+}
+
+/// Point Claude Code settings at the relay when it is enabled.
+///
+/// With `store_id` the relay always uses that profile, as project bindings do,
+/// otherwise the active one. Applied to the settings being written and to the
+/// ones drift detection compares against, never to the stored profile.
+pub fn route_env(
+    paths: &Paths,
+    settings: &mut Value,
+    store_id: Option<&str>,
+) -> Result<(), String> {
+    let relay = get_relay_settings(paths)?;
+    if !relay.enabled || relay.token.is_empty() {
+        return Ok(());
+    }
+    let Some(object) = settings.as_object_mut() else {
+        return Ok(());
+    };
+
+    let env = object
+        .entry("env")
+        .or_insert_with(|| Value::Object(serde_json::Map::new()));
+    let Some(env) = env.as_object_mut() else {
+        return Err("Invalid env format in settings".to_string());
+    };
+    let base_url = match store_id {
+        Some(store_id) => format!("{}/profiles/{}", relay.url(), store_id),
+        None => relay.url(),
+    };
+    env.insert("ANTHROPIC_BASE_URL".to_string(), Value::String(base_url));
+    let mut has_credentials = false;
+    for key in ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"] {
+        if let Some(value) = env.get_mut(key) {
+            *value = Value::String(relay.token.clone());
+            has_credentials = true;
+        }
+    }
+    if !has_credentials {
+        env.insert(
+            "ANTHROPIC_AUTH_TOKEN".to_string(),
+            Value::String(relay.token.clone()),
+        );
+    }
+    Ok(())
+}
+
+/// Where the relay sends requests for a profile
+#[derive(Debug, Clone)]
+pub struct Upstream {
+    pub store_id: String,
+    pub store_title: String,
+    pub base_url: String,
+    /// The profile's credentials, replacing the relay token
+    pub auth_header: Option<(String, String)>,
+    pub model_aliases: Vec<ModelAlias>,
+    pub wire_api: WireApi,
+}
+
+impl Upstream {
+    /// Where a request for `path` goes, which for OpenAI APIs is their one endpoint
+    pub fn url(&self, path: &str) -> String {
+        match self.wire_api {
+            WireApi::Anthropic => format!("{}{}", self.base_url, path),
+            other => health::join_url(&self.base_url, other.endpoint()),
+        }
+    }
+}
+
+/// Upstream of `store_id`, or of the active profile when `None`
+pub fn upstream(paths: &Paths, store_id: Option<&str>) -> Result<Upstream, String> {
+    let store = match store_id {
+        Some(store_id) => stores::get_store(paths, store_id)?,
+        None => stores::get_current_store(paths)?
+            .ok_or_else(|| "No active Claude Code profile".to_string())?,
+    };
+    let resolved = stores::get_resolved_store_settings(paths, &store.id)?;
+    let settings = vault::reveal(paths, &resolved)?;
+
+    let relay = get_relay_settings(paths)?;
+    let base_url = health::claude_base_url(&settings)
+        .trim_end_matches('/')
+        .to_string();
+    if base_url.starts_with(&relay.url()) {
+        return Err(format!(
+            "Profile \"{}\" points at the relay itself",
+            store.title
+        ));
+    }
+
+    let wire_api = relay.wire_api(&store.id);
+    Ok(Upstream {
+        auth_header: match wire_api {
+            WireApi::Anthropic => health::claude_auth_header(&settings),
+            _ => health::claude_api_key(&settings)
+                .map(|key| ("authorization".to_string(), format!("Bearer {}", key))),
+        },
+        model_aliases: model_aliases::get_store_model_aliases(paths, &store.id)?,
+        wire_api,
+        store_id: store.id,
+        store_title: store.title,
+        base_url,
+    })
+}
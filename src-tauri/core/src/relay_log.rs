@@ -0,0 +1,624 @@
+//! Request log and token accounting for the local relay.
+//!
+//! Every request the relay forwards is recorded in the SQLite database
+//! `~/.ccconfig/relay_log.db`, with its profile, model, status, latency and the
+//! token usage read from the response, or from the `message_start` and
+//! `message_delta` events of a streamed one. Request and response bodies are
+//! only kept when enabled in the relay settings, with credentials redacted.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::Value;
+
+use crate::paths::Paths;
+
+// Oldest requests are dropped beyond this
+const MAX_LOG_ENTRIES: i64 = 50_000;
+// Bodies are cut off past this many bytes once they are redacted
+const MAX_BODY_BYTES: usize = 256 * 1024;
+// A response is only parsed for usage, or kept for the log, up to this size
+const MAX_PARSED_RESPONSE_BYTES: usize = 8 * 1024 * 1024;
+const REDACTED: &str = "[REDACTED]";
+// Logged in place of a body that can't be parsed, and so can't be redacted
+const UNPARSEABLE_BODY: &str = "<unparseable body redacted>";
+// Words of JSON keys whose values are never written to the log, e.g. `access_token` or `apiKey`
+const SENSITIVE_WORDS: &[&str] = &[
+    "apikey",
+    "authorization",
+    "passphrase",
+    "password",
+    "secret",
+    "token",
+];
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Take over the counts present in an Anthropic `usage` object
+    fn merge(&mut self, usage: &Value) {
+        let fields = [
+            ("input_tokens", &mut self.input_tokens),
+            ("output_tokens", &mut self.output_tokens),
+            (
+                "cache_creation_input_tokens",
+                &mut self.cache_creation_input_tokens,
+            ),
+            ("cache_read_input_tokens", &mut self.cache_read_input_tokens),
+        ];
+        for (key, count) in fields {
+            if let Some(value) = usage.get(key).and_then(|v| v.as_u64()) {
+                *count = value;
+            }
+        }
+    }
+}
+
+/// One request forwarded by the relay
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct RelayLogEntry {
+    #[serde(default)]
+    pub id: i64,
+    /// Milliseconds since the epoch
+    pub timestamp: u64,
+    pub store_id: Option<String>,
+    pub store_title: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub model: Option<String>,
+    /// `None` when no response came back from the upstream
+    pub status: Option<u16>,
+    /// Until the response headers arrived
+    pub latency_ms: u64,
+    /// Until the last byte of the response
+    pub duration_ms: u64,
+    pub streamed: bool,
+    #[serde(flatten)]
+    pub usage: TokenUsage,
+    pub error: Option<String>,
+    pub request_body: Option<String>,
+    pub response_body: Option<String>,
+}
+
+/// Requests and tokens of one profile and model
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RelayUsage {
+    pub store_id: Option<String>,
+    pub store_title: Option<String>,
+    pub model: Option<String>,
+    pub requests: u64,
+    /// Requests without a 2xx response
+    pub errors: u64,
+    #[serde(flatten)]
+    pub usage: TokenUsage,
+}
+
+/// Collects the model and token usage of a response as it streams through
+#[derive(Debug, Default)]
+pub struct ResponseRecorder {
+    streaming: bool,
+    // An incomplete event line, or the whole body of a non-streamed response
+    pending: Vec<u8>,
+    // Kept only when bodies are logged
+    body: Option<Vec<u8>>,
+    usage: TokenUsage,
+    model: Option<String>,
+}
+
+impl ResponseRecorder {
+    pub fn new(content_type: Option<&str>, keep_body: bool) -> Self {
+        Self {
+            streaming: content_type
+                .map(|value| value.starts_with("text/event-stream"))
+                .unwrap_or(false),
+            body: keep_body.then(Vec::new),
+            ..Self::default()
+        }
+    }
+
+    pub fn streaming(&self) -> bool {
+        self.streaming
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        if let Some(body) = &mut self.body {
+            let room = MAX_PARSED_RESPONSE_BYTES.saturating_sub(body.len());
+            body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        }
+
+        if !self.streaming {
+            if self.pending.len() + chunk.len() <= MAX_PARSED_RESPONSE_BYTES {
+                self.pending.extend_from_slice(chunk);
+            }
+            return;
+        }
+
+        self.pending.extend_from_slice(chunk);
+        while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            self.read_event_line(&line);
+        }
+    }
+
+    // `message_start` carries the model and input tokens, `message_delta` the output tokens
+    fn read_event_line(&mut self, line: &[u8]) {
+        let Ok(line) = std::str::from_utf8(line) else {
+            return;
+        };
+        let Some(data) = line.trim_end().strip_prefix("data:") else {
+            return;
+        };
+        let Ok(event) = serde_json::from_str::<Value>(data.trim_start()) else {
+            return;
+        };
+        match event.get("type").and_then(|v| v.as_str()) {
+            Some("message_start") => {
+                if let Some(message) = event.get("message") {
+                    self.read_message(message);
+                }
+            }
+            Some("message_delta") => {
+                if let Some(usage) = event.get("usage") {
+                    self.usage.merge(usage);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_message(&mut self, message: &Value) {
+        if let Some(model) = message.get("model").and_then(|v| v.as_str()) {
+            self.model = Some(model.to_string());
+        }
+        if let Some(usage) = message.get("usage") {
+            self.usage.merge(usage);
+        }
+    }
+
+    /// The model, token usage and the kept body once the response has ended
+    pub fn finish(mut self) -> (Option<String>, TokenUsage, Option<Vec<u8>>) {
+        if self.streaming {
+            let rest = std::mem::take(&mut self.pending);
+            self.read_event_line(&rest);
+        } else if let Ok(message) = serde_json::from_slice::<Value>(&self.pending) {
+            self.read_message(&message);
+        }
+        (self.model, self.usage, self.body)
+    }
+}
+
+/// The `model` a request asked for
+pub fn request_model(body: &[u8]) -> Option<String> {
+    serde_json::from_slice::<Value>(body)
+        .ok()?
+        .get("model")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// A body as it is written to the log: credentials and `secrets` redacted, then cut to size.
+///
+/// JSON bodies and event streams of JSON events are redacted, anything else is
+/// replaced as a whole since it can't be told what in it is a credential.
+pub fn body_text(body: &[u8], secrets: &[String]) -> String {
+    let mut text = match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => match std::str::from_utf8(body).ok().and_then(redact_event_stream) {
+            Some(text) => text,
+            None => UNPARSEABLE_BODY.to_string(),
+        },
+    };
+    for secret in secrets.iter().filter(|secret| !secret.is_empty()) {
+        text = text.replace(secret.as_str(), REDACTED);
+    }
+    if text.len() > MAX_BODY_BYTES {
+        let cut = (0..=MAX_BODY_BYTES)
+            .rev()
+            .find(|index| text.is_char_boundary(*index))
+            .unwrap_or(0);
+        text.truncate(cut);
+    }
+    text
+}
+
+/// A server-sent event stream with the JSON in its `data` lines redacted, `None` if it isn't one
+fn redact_event_stream(text: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut data_lines = 0;
+    for line in text.lines() {
+        if let Some(data) = line.strip_prefix("data:") {
+            data_lines += 1;
+            let data = data.trim_start();
+            let redacted = match serde_json::from_str::<Value>(data) {
+                Ok(mut value) => {
+                    redact_value(&mut value);
+                    value.to_string()
+                }
+                Err(_) if data == "[DONE]" => data.to_string(),
+                Err(_) => UNPARSEABLE_BODY.to_string(),
+            };
+            lines.push(format!("data: {}", redacted));
+        } else if line.is_empty()
+            || line.starts_with(':')
+            || ["event:", "id:", "retry:"]
+                .iter()
+                .any(|field| line.starts_with(field))
+        {
+            lines.push(line.to_string());
+        } else {
+            return None;
+        }
+    }
+    (data_lines > 0).then(|| lines.join("\n"))
+}
+
+/// Redact the values of sensitive keys, whatever their type, at any depth
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if is_sensitive_key(key) {
+                    if !value.is_null() {
+                        *value = Value::String(REDACTED.to_string());
+                    }
+                } else {
+                    redact_value(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Whether a key names a credential, by its words so that counts like `max_tokens` are kept
+fn is_sensitive_key(key: &str) -> bool {
+    let mut words: Vec<String> = vec![];
+    let mut previous_lower = false;
+    for c in key.chars() {
+        if !c.is_ascii_alphanumeric() {
+            words.push(String::new());
+            previous_lower = false;
+            continue;
+        }
+        if words.is_empty() || (c.is_ascii_uppercase() && previous_lower) {
+            words.push(String::new());
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        if let Some(word) = words.last_mut() {
+            word.push(c.to_ascii_lowercase());
+        }
+    }
+    words.retain(|word| !word.is_empty());
+
+    words
+        .iter()
+        .any(|word| SENSITIVE_WORDS.contains(&word.as_str()))
+        || words
+            .windows(2)
+            .any(|pair| pair[0] == "api" && pair[1] == "key")
+}
+
+fn open(paths: &Paths) -> Result<Connection, String> {
+    std::fs::create_dir_all(&paths.app_config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let connection = Connection::open(paths.relay_log_db())
+        .map_err(|e| format!("Failed to open relay log: {}", e))?;
+    connection
+        .busy_timeout(std::time::Duration::from_secs(5))
+        .map_err(|e| format!("Failed to open relay log: {}", e))?;
+    connection
+        .execute_batch(
+            "CREATE TABLE IF NOT EXISTS requests (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                store_id TEXT,
+                store_title TEXT,
+                method TEXT NOT NULL,
+                path TEXT NOT NULL,
+                model TEXT,
+                status INTEGER,
+                latency_ms INTEGER NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                streamed INTEGER NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cache_creation_input_tokens INTEGER NOT NULL,
+                cache_read_input_tokens INTEGER NOT NULL,
+                error TEXT,
+                request_body TEXT,
+                response_body TEXT
+            );
+            CREATE INDEX IF NOT EXISTS requests_timestamp ON requests (timestamp);
+            CREATE INDEX IF NOT EXISTS requests_store ON requests (store_id, timestamp);",
+        )
+        .map_err(|e| format!("Failed to create relay log tables: {}", e))?;
+    Ok(connection)
+}
+
+pub fn record(paths: &Paths, entry: &RelayLogEntry) -> Result<(), String> {
+    let connection = open(paths)?;
+    connection
+        .execute(
+            "INSERT INTO requests (
+                timestamp, store_id, store_title, method, path, model, status,
+                latency_ms, duration_ms, streamed, input_tokens, output_tokens,
+                cache_creation_input_tokens, cache_read_input_tokens, error,
+                request_body, response_body
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+            params![
+                entry.timestamp as i64,
+                entry.store_id,
+                entry.store_title,
+                entry.method,
+                entry.path,
+                entry.model,
+                entry.status,
+                entry.latency_ms as i64,
+                entry.duration_ms as i64,
+                entry.streamed,
+                entry.usage.input_tokens as i64,
+                entry.usage.output_tokens as i64,
+                entry.usage.cache_creation_input_tokens as i64,
+                entry.usage.cache_read_input_tokens as i64,
+                entry.error,
+                entry.request_body,
+                entry.response_body,
+            ],
+        )
+        .map_err(|e| format!("Failed to write relay log: {}", e))?;
+
+    let id = connection.last_insert_rowid();
+    connection
+        .execute(
+            "DELETE FROM requests WHERE id <= ?1",
+            params![id - MAX_LOG_ENTRIES],
+        )
+        .map_err(|e| format!("Failed to trim relay log: {}", e))?;
+    Ok(())
+}
+
+const ENTRY_COLUMNS: &str = "id, timestamp, store_id, store_title, method, path, model, status,
+    latency_ms, duration_ms, streamed, input_tokens, output_tokens,
+    cache_creation_input_tokens, cache_read_input_tokens, error";
+
+fn read_entry(row: &Row) -> rusqlite::Result<RelayLogEntry> {
+    Ok(RelayLogEntry {
+        id: row.get(0)?,
+        timestamp: row.get::<_, i64>(1)? as u64,
+        store_id: row.get(2)?,
+        store_title: row.get(3)?,
+        method: row.get(4)?,
+        path: row.get(5)?,
+        model: row.get(6)?,
+        status: row.get(7)?,
+        latency_ms: row.get::<_, i64>(8)? as u64,
+        duration_ms: row.get::<_, i64>(9)? as u64,
+        streamed: row.get(10)?,
+        usage: TokenUsage {
+            input_tokens: row.get::<_, i64>(11)? as u64,
+            output_tokens: row.get::<_, i64>(12)? as u64,
+            cache_creation_input_tokens: row.get::<_, i64>(13)? as u64,
+            cache_read_input_tokens: row.get::<_, i64>(14)? as u64,
+        },
+        error: row.get(15)?,
+        request_body: None,
+        response_body: None,
+    })
+}
+
+/// Logged requests, newest first and without their bodies, optionally of one profile only
+pub fn get_relay_log(
+    paths: &Paths,
+    limit: usize,
+    store_id: Option<&str>,
+) -> Result<Vec<RelayLogEntry>, String> {
+    let connection = open(paths)?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT {} FROM requests WHERE ?1 IS NULL OR store_id = ?1
+             ORDER BY id DESC LIMIT ?2",
+            ENTRY_COLUMNS
+        ))
+        .map_err(|e| format!("Failed to read relay log: {}", e))?;
+    let entries = statement
+        .query_map(params![store_id, limit as i64], read_entry)
+        .map_err(|e| format!("Failed to read relay log: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read relay log: {}", e))?;
+    Ok(entries)
+}
+
+/// One logged request with its bodies
+pub fn get_relay_log_entry(paths: &Paths, id: i64) -> Result<RelayLogEntry, String> {
+    let connection = open(paths)?;
+    connection
+        .query_row(
+            &format!(
+                "SELECT {}, request_body, response_body FROM requests WHERE id = ?1",
+                ENTRY_COLUMNS
+            ),
+            params![id],
+            |row| {
+                let mut entry = read_entry(row)?;
+                entry.request_body = row.get(16)?;
+                entry.response_body = row.get(17)?;
+                Ok(entry)
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read relay log: {}", e))?
+        .ok_or_else(|| format!("Relay log entry {} not found", id))
+}
+
+/// Requests and tokens per profile and model, since `since` milliseconds when given
+pub fn get_relay_usage(paths: &Paths, since: Option<u64>) -> Result<Vec<RelayUsage>, String> {
+    let connection = open(paths)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT store_id, MAX(store_title), model, COUNT(*),
+                SUM(CASE WHEN status BETWEEN 200 AND 299 THEN 0 ELSE 1 END),
+                SUM(input_tokens), SUM(output_tokens),
+                SUM(cache_creation_input_tokens), SUM(cache_read_input_tokens)
+             FROM requests WHERE timestamp >= ?1
+             GROUP BY store_id, model
+             ORDER BY SUM(input_tokens) + SUM(output_tokens) DESC",
+        )
+        .map_err(|e| format!("Failed to read relay usage: {}", e))?;
+    let usage = statement
+        .query_map(params![since.unwrap_or(0) as i64], |row| {
+            Ok(RelayUsage {
+                store_id: row.get(0)?,
+                store_title: row.get(1)?,
+                model: row.get(2)?,
+                requests: row.get::<_, i64>(3)? as u64,
+                errors: row.get::<_, i64>(4)? as u64,
+                usage: TokenUsage {
+                    input_tokens: row.get::<_, i64>(5)? as u64,
+                    output_tokens: row.get::<_, i64>(6)? as u64,
+                    cache_creation_input_tokens: row.get::<_, i64>(7)? as u64,
+                    cache_read_input_tokens: row.get::<_, i64>(8)? as u64,
+                },
+            })
+        })
+        .map_err(|e| format!("Failed to read relay usage: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read relay usage: {}", e))?;
+    Ok(usage)
+}
+
+/// Drop the stored bodies and keep the rest of the log, returning how many requests had any
+pub fn clear_relay_log_bodies(paths: &Paths) -> Result<usize, String> {
+    let connection = open(paths)?;
+    let cleared = connection
+        .execute(
+            "UPDATE requests SET request_body = NULL, response_body = NULL
+             WHERE request_body IS NOT NULL OR response_body IS NOT NULL",
+            [],
+        )
+        .map_err(|e| format!("Failed to clear relay log bodies: {}", e))?;
+    connection
+        .execute_batch("VACUUM")
+        .map_err(|e| format!("Failed to clear relay log bodies: {}", e))?;
+    eprintln!("🧹 Cleared bodies of {} relay log entries", cleared);
+    Ok(cleared)
+}
+
+pub fn clear_relay_log(paths: &Paths) -> Result<(), String> {
+    let connection = open(paths)?;
+    connection
+        .execute_batch("DELETE FROM requests; VACUUM")
+        .map_err(|e| format!("Failed to clear relay log: {}", e))?;
+    eprintln!("🧹 Relay log cleared");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn logged(body: &Value) -> Value {
+        serde_json::from_str(&body_text(body.to_string().as_bytes(), &[])).unwrap()
+    }
+
+    #[test]
+    fn redacts_sensitive_values_of_any_type_at_any_depth() {
+        let body = json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1024,
+            "usage": { "input_tokens": 12, "cache_read_input_tokens": 3 },
+            "auth": { "access_token": { "value": "abc", "expires": 60 } },
+            "accounts": [{ "apiKey": 12345 }, { "x-api-key": ["one", "two"] }],
+            "client_secret": true,
+            "password": null,
+        });
+        assert_eq!(
+            logged(&body),
+            json!({
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 1024,
+                "usage": { "input_tokens": 12, "cache_read_input_tokens": 3 },
+                "auth": { "access_token": REDACTED },
+                "accounts": [{ "apiKey": REDACTED }, { "x-api-key": REDACTED }],
+                "client_secret": REDACTED,
+                "password": null,
+            })
+        );
+    }
+
+    #[test]
+    fn redacts_before_cutting_to_size() {
+        let padding = "x".repeat(MAX_BODY_BYTES);
+        let secret = "sk-relay-0123456789";
+
+        // A credential past the cut and a known secret across it are both gone
+        let body = json!({ "a_padding": padding, "b": format!("{}{}", "y".repeat(10), secret), "token": "late" });
+        let text = body_text(body.to_string().as_bytes(), &[secret.to_string()]);
+        assert_eq!(text.len(), MAX_BODY_BYTES);
+        assert!(!text.contains("sk-relay"));
+        assert!(!text.contains("late"));
+
+        let text = body_text(
+            format!(
+                "{{\"a\":\"{}{}\"}}",
+                "x".repeat(MAX_BODY_BYTES - 10),
+                secret
+            )
+            .as_bytes(),
+            &[secret.to_string()],
+        );
+        assert!(!text.contains("sk-rel"));
+
+        // Multibyte text is cut on a character boundary
+        let body = json!({ "text": "é".repeat(MAX_BODY_BYTES) });
+        let text = body_text(body.to_string().as_bytes(), &[]);
+        assert!(text.len() <= MAX_BODY_BYTES);
+    }
+
+    #[test]
+    fn unparseable_bodies_are_not_stored() {
+        assert_eq!(body_text(b"token=sk-plain&user=me", &[]), UNPARSEABLE_BODY);
+        assert_eq!(body_text(b"{\"token\": \"sk-cut", &[]), UNPARSEABLE_BODY);
+        assert_eq!(body_text(&[0xff, 0xfe, 0x00], &[]), UNPARSEABLE_BODY);
+    }
+
+    #[test]
+    fn redacts_event_streams_line_by_line() {
+        let stream = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"output_tokens\":1}}}\n",
+            "\n",
+            "event: debug\n",
+            "data: {\"session_token\":\"sk-stream\"}\n",
+            "\n",
+            "data: not json sk-raw\n",
+            "data: [DONE]\n",
+        );
+        let text = body_text(stream.as_bytes(), &[]);
+        assert!(text.contains("\"output_tokens\":1"));
+        assert!(text.contains("event: debug"));
+        assert!(!text.contains("sk-stream"));
+        assert!(!text.contains("sk-raw"));
+        assert!(text.contains(UNPARSEABLE_BODY));
+        assert!(text.ends_with("data: [DONE]"));
+    }
+
+    #[test]
+    fn keeps_the_whole_response_until_it_is_redacted() {
+        let mut recorder = ResponseRecorder::new(Some("application/json"), true);
+        let body = json!({ "padding": "x".repeat(MAX_BODY_BYTES), "token": "sk-late" }).to_string();
+        recorder.feed(body.as_bytes());
+        let (_, _, kept) = recorder.finish();
+        let kept = kept.unwrap();
+        assert_eq!(kept.len(), body.len());
+        assert!(!body_text(&kept, &[]).contains("sk-late"));
+    }
+}
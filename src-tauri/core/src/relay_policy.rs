@@ -0,0 +1,533 @@
+//! Per-profile traffic policies applied by the local relay.
+//!
+//! A policy caps the requests a profile's endpoint gets at once and per minute,
+//! retries requests the endpoint turned away with 429 or a 5xx, waiting as
+//! long as its `retry-after` asks, and stops sending requests for a while once
+//! it kept failing. The policies are kept in `~/.ccconfig/relay_policies.json`,
+//! keyed by store id. A profile without one is relayed as is.
+//!
+//! The relay keeps a [`PolicyGate`] per profile for the counters behind the
+//! policy, which also make up the profile's [`RelayMetrics`].
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::fsio;
+use crate::paths::Paths;
+use crate::stores;
+
+const DEFAULT_RETRY_BASE_MS: u64 = 1000;
+const DEFAULT_RETRY_MAX_MS: u64 = 30_000;
+const DEFAULT_BREAKER_COOLDOWN_SECS: u64 = 30;
+const MAX_RETRIES: u32 = 10;
+// A request is turned away rather than held longer than this by the rate limit
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RelayPolicy {
+    /// Requests sent to the endpoint at once, the others wait for their turn
+    #[serde(default)]
+    pub max_concurrent: Option<u32>,
+    /// Requests sent to the endpoint per minute, on average
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Requests that can go out back to back after a quiet period, a minute's worth by default
+    #[serde(default)]
+    pub burst: Option<u32>,
+    /// Retries of a request the endpoint answered with 429 or a 5xx, or could not be reached for
+    #[serde(default)]
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each one after
+    #[serde(default = "default_retry_base_ms")]
+    pub retry_base_ms: u64,
+    /// Longest wait before a retry. A `retry-after` asking for longer is passed on to Claude Code.
+    #[serde(default = "default_retry_max_ms")]
+    pub retry_max_ms: u64,
+    /// Failed requests in a row after which the endpoint gets no requests for a while
+    #[serde(default)]
+    pub breaker_threshold: Option<u32>,
+    /// Seconds before a request is let through again to see whether the endpoint recovered
+    #[serde(default = "default_breaker_cooldown_secs")]
+    pub breaker_cooldown_secs: u64,
+}
+
+impl Default for RelayPolicy {
+    fn default() -> Self {
+        Self {
+            max_concurrent: None,
+            requests_per_minute: None,
+            burst: None,
+            max_retries: 0,
+            retry_base_ms: DEFAULT_RETRY_BASE_MS,
+            retry_max_ms: DEFAULT_RETRY_MAX_MS,
+            breaker_threshold: None,
+            breaker_cooldown_secs: DEFAULT_BREAKER_COOLDOWN_SECS,
+        }
+    }
+}
+
+fn default_retry_base_ms() -> u64 {
+    DEFAULT_RETRY_BASE_MS
+}
+
+fn default_retry_max_ms() -> u64 {
+    DEFAULT_RETRY_MAX_MS
+}
+
+fn default_breaker_cooldown_secs() -> u64 {
+    DEFAULT_BREAKER_COOLDOWN_SECS
+}
+
+type PolicyTable = BTreeMap<String, RelayPolicy>;
+
+/// Policies of all profiles, leaving out profiles deleted since
+pub fn get_relay_policies(paths: &Paths) -> Result<PolicyTable, String> {
+    let mut policies: PolicyTable =
+        fsio::read_json(&paths.relay_policies_file(), "relay policies")?.unwrap_or_default();
+    let configs = stores::get_stores(paths)?;
+    policies.retain(|store_id, _| configs.iter().any(|store| &store.id == store_id));
+    Ok(policies)
+}
+
+pub fn get_store_relay_policy(paths: &Paths, store_id: &str) -> Result<RelayPolicy, String> {
+    Ok(get_relay_policies(paths)?
+        .remove(store_id)
+        .unwrap_or_default())
+}
+
+/// Replace the policy of a profile, removing it when it is the default one
+pub fn update_store_relay_policy(
+    paths: &Paths,
+    store_id: &str,
+    policy: RelayPolicy,
+) -> Result<RelayPolicy, String> {
+    stores::get_store(paths, store_id)?;
+    let limits = [
+        ("Concurrent requests", policy.max_concurrent),
+        ("Requests per minute", policy.requests_per_minute),
+        ("Burst", policy.burst),
+        (
+            "Failures before breaking the circuit",
+            policy.breaker_threshold,
+        ),
+    ];
+    if let Some((name, _)) = limits.iter().find(|(_, limit)| *limit == Some(0)) {
+        return Err(format!("{} must be at least 1", name));
+    }
+    if policy.max_retries > MAX_RETRIES {
+        return Err(format!(
+            "Requests can be retried at most {} times",
+            MAX_RETRIES
+        ));
+    }
+    if policy.retry_base_ms == 0 || policy.retry_max_ms < policy.retry_base_ms {
+        return Err("The longest retry wait cannot be shorter than the first one".to_string());
+    }
+    if policy.breaker_cooldown_secs == 0 {
+        return Err("The circuit breaker cooldown must be at least 1 second".to_string());
+    }
+
+    let mut policies = get_relay_policies(paths)?;
+    if policy == RelayPolicy::default() {
+        policies.remove(store_id);
+    } else {
+        policies.insert(store_id.to_string(), policy.clone());
+    }
+    fsio::write_json(
+        paths,
+        &paths.relay_policies_file(),
+        "relay policies",
+        &policies,
+    )?;
+    eprintln!("✅ Updated relay policy of {}", store_id);
+    Ok(policy)
+}
+
+/// Answers worth trying again: rate limited, overloaded or failing endpoints
+pub fn is_retryable(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504 | 529)
+}
+
+/// How long to wait before retry number `attempt`, counting from 0, or `None`
+/// when the request should not be retried again. `retry_after` is the
+/// endpoint's header, in seconds or as an HTTP date.
+pub fn retry_delay(
+    policy: &RelayPolicy,
+    attempt: u32,
+    retry_after: Option<&str>,
+) -> Option<Duration> {
+    if attempt >= policy.max_retries {
+        return None;
+    }
+    let max = Duration::from_millis(policy.retry_max_ms);
+    if let Some(wait) = retry_after.and_then(parse_retry_after) {
+        return (wait <= max).then_some(wait);
+    }
+
+    let backoff = Duration::from_millis(policy.retry_base_ms)
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(max);
+    // Up to a fifth more, so sessions that failed together do not retry together
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or(0);
+    Some(backoff + backoff.mul_f64(f64::from(nanos % 1000) / 5000.0))
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return (seconds >= 0.0 && seconds.is_finite()).then(|| Duration::from_secs_f64(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.timestamp_millis() - chrono::Utc::now().timestamp_millis();
+    Some(Duration::from_millis(wait.max(0) as u64))
+}
+
+#[derive(serde::Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests go through
+    #[default]
+    Closed,
+    /// The endpoint kept failing and gets no requests until the cooldown is over
+    Open,
+    /// A request was let through to see whether the endpoint recovered
+    HalfOpen,
+}
+
+/// What the relay did for a profile since it started
+#[derive(serde::Serialize, Debug, Clone, Default)]
+pub struct RelayMetrics {
+    pub store_id: String,
+    pub store_title: Option<String>,
+    /// Requests being answered right now
+    pub in_flight: u32,
+    /// Requests waiting for a free slot or for the rate limit
+    pub queued: u32,
+    pub requests: u64,
+    /// Requests held back by the rate limit
+    pub throttled: u64,
+    /// Requests turned away because the rate limit would hold them too long
+    pub rate_limited: u64,
+    pub retries: u64,
+    /// Requests that failed after their retries
+    pub failures: u64,
+    /// Requests turned away while the circuit was open
+    pub rejected: u64,
+    pub circuit: CircuitState,
+    /// Times the circuit opened
+    pub circuit_opened: u64,
+    pub last_error: Option<String>,
+}
+
+/// Rate limit, circuit breaker and metrics of one profile
+#[derive(Debug)]
+pub struct PolicyGate {
+    policy: RelayPolicy,
+    tokens: f64,
+    refilled: Instant,
+    failures: u32,
+    // When the circuit opened, or when the last request went through to probe it
+    opened: Option<Instant>,
+    metrics: RelayMetrics,
+}
+
+impl PolicyGate {
+    pub fn new(store_id: &str, policy: RelayPolicy, now: Instant) -> Self {
+        let mut gate = Self {
+            policy,
+            tokens: 0.0,
+            refilled: now,
+            failures: 0,
+            opened: None,
+            metrics: RelayMetrics {
+                store_id: store_id.to_string(),
+                ..RelayMetrics::default()
+            },
+        };
+        gate.tokens = gate.capacity();
+        gate
+    }
+
+    pub fn policy(&self) -> &RelayPolicy {
+        &self.policy
+    }
+
+    /// Switch to an edited policy, keeping the counters
+    pub fn set_policy(&mut self, policy: RelayPolicy) {
+        if policy.breaker_threshold.is_none() {
+            self.close();
+        }
+        self.policy = policy;
+        self.tokens = self.tokens.min(self.capacity());
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.metrics.store_title = Some(title.to_string());
+    }
+
+    pub fn metrics(&self) -> RelayMetrics {
+        self.metrics.clone()
+    }
+
+    pub fn circuit(&self) -> CircuitState {
+        self.metrics.circuit
+    }
+
+    /// Whether a request may go to the endpoint, or else how long until the
+    /// circuit lets one through again
+    pub fn admit(&mut self, now: Instant) -> Result<(), Duration> {
+        let Some(opened) = self.opened else {
+            return Ok(());
+        };
+        let cooldown = Duration::from_secs(self.policy.breaker_cooldown_secs);
+        let elapsed = now.duration_since(opened);
+        if elapsed < cooldown {
+            self.metrics.rejected += 1;
+            return Err(cooldown - elapsed);
+        }
+        // One request at a time probes the endpoint, another one only if that one never finished
+        self.opened = Some(now);
+        self.metrics.circuit = CircuitState::HalfOpen;
+        Ok(())
+    }
+
+    /// How long to hold a request back for the rate limit, or `Err` with the
+    /// wait when that would be too long to hold it
+    pub fn reserve(&mut self, now: Instant) -> Result<Duration, Duration> {
+        let Some(per_minute) = self.policy.requests_per_minute else {
+            return Ok(Duration::ZERO);
+        };
+        let per_second = f64::from(per_minute) / 60.0;
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(self.capacity());
+        self.refilled = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(Duration::ZERO);
+        }
+        let wait = Duration::from_secs_f64((1.0 - self.tokens) / per_second);
+        if wait > MAX_RATE_LIMIT_WAIT {
+            self.metrics.rate_limited += 1;
+            return Err(wait);
+        }
+        self.tokens -= 1.0;
+        self.metrics.throttled += 1;
+        Ok(wait)
+    }
+
+    /// Record what one attempt to reach the endpoint got: `Ok` with a status it
+    /// answered with, or `Err` when it could not be reached
+    pub fn record(&mut self, result: Result<u16, String>, now: Instant) {
+        let error = match result {
+            Ok(status) if !is_retryable(status) => {
+                self.failures = 0;
+                self.close();
+                return;
+            }
+            Ok(status) => format!("Endpoint answered {}", status),
+            Err(e) => e,
+        };
+        self.metrics.last_error = Some(error);
+        self.failures += 1;
+
+        let Some(threshold) = self.policy.breaker_threshold else {
+            return;
+        };
+        if self.failures >= threshold || self.metrics.circuit == CircuitState::HalfOpen {
+            if self.metrics.circuit == CircuitState::Closed {
+                self.metrics.circuit_opened += 1;
+                eprintln!(
+                    "🔌 Circuit of {} opened after {} failed requests",
+                    self.metrics.store_id, self.failures
+                );
+            }
+            self.opened = Some(now);
+            self.metrics.circuit = CircuitState::Open;
+        }
+    }
+
+    pub fn queued(&mut self) {
+        self.metrics.queued += 1;
+    }
+
+    pub fn dequeued(&mut self) {
+        self.metrics.queued = self.metrics.queued.saturating_sub(1);
+    }
+
+    pub fn started(&mut self) {
+        self.metrics.in_flight += 1;
+        self.metrics.requests += 1;
+    }
+
+    pub fn retried(&mut self) {
+        self.metrics.retries += 1;
+    }
+
+    /// A request was answered, `failed` when it was still failing after its retries
+    pub fn finished(&mut self, failed: bool) {
+        self.metrics.in_flight = self.metrics.in_flight.saturating_sub(1);
+        if failed {
+            self.metrics.failures += 1;
+        }
+    }
+
+    fn close(&mut self) {
+        if self.opened.take().is_some() {
+            eprintln!("🔌 Circuit of {} closed", self.metrics.store_id);
+        }
+        self.metrics.circuit = CircuitState::Closed;
+    }
+
+    fn capacity(&self) -> f64 {
+        let burst = self.policy.burst.or(self.policy.requests_per_minute);
+        f64::from(burst.unwrap_or(1).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate(policy: RelayPolicy) -> (PolicyGate, Instant) {
+        let start = Instant::now();
+        (PolicyGate::new("work", policy, start), start)
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn rate_limit_spends_the_burst_then_spaces_requests_out() {
+        let (mut gate, start) = gate(RelayPolicy {
+            requests_per_minute: Some(60),
+            burst: Some(2),
+            ..RelayPolicy::default()
+        });
+
+        assert_eq!(gate.reserve(start), Ok(Duration::ZERO));
+        assert_eq!(gate.reserve(start), Ok(Duration::ZERO));
+        // One request a second once the burst is spent, each waiting behind the last
+        assert_eq!(gate.reserve(start), Ok(secs(1)));
+        assert_eq!(gate.reserve(start), Ok(secs(2)));
+        assert_eq!(gate.metrics().throttled, 2);
+
+        // The window refills with time, but never past the burst
+        assert_eq!(gate.reserve(start + secs(2)), Ok(secs(1)));
+        assert_eq!(gate.reserve(start + secs(60)), Ok(Duration::ZERO));
+        assert_eq!(gate.reserve(start + secs(60)), Ok(Duration::ZERO));
+        assert_eq!(gate.reserve(start + secs(60)), Ok(secs(1)));
+    }
+
+    #[test]
+    fn rate_limit_turns_away_requests_it_would_hold_too_long() {
+        let (mut gate, start) = gate(RelayPolicy {
+            requests_per_minute: Some(1),
+            ..RelayPolicy::default()
+        });
+
+        assert_eq!(gate.reserve(start), Ok(Duration::ZERO));
+        assert_eq!(gate.reserve(start), Ok(secs(60)));
+        assert_eq!(gate.reserve(start), Err(secs(120)));
+        assert_eq!(gate.metrics().rate_limited, 1);
+        // A turned away request doesn't take a turn
+        assert_eq!(gate.reserve(start + secs(60)), Ok(secs(60)));
+    }
+
+    #[test]
+    fn counts_queued_and_in_flight_requests() {
+        let (mut gate, _) = gate(RelayPolicy {
+            max_concurrent: Some(1),
+            ..RelayPolicy::default()
+        });
+
+        gate.queued();
+        gate.started();
+        gate.queued();
+        let metrics = gate.metrics();
+        assert_eq!((metrics.in_flight, metrics.queued), (1, 2));
+
+        gate.dequeued();
+        gate.dequeued();
+        gate.finished(false);
+        gate.started();
+        gate.finished(true);
+        gate.dequeued();
+        let metrics = gate.metrics();
+        assert_eq!((metrics.in_flight, metrics.queued), (0, 0));
+        assert_eq!((metrics.requests, metrics.failures), (2, 1));
+    }
+
+    #[test]
+    fn circuit_opens_half_opens_and_closes() {
+        let (mut gate, start) = gate(RelayPolicy {
+            breaker_threshold: Some(2),
+            breaker_cooldown_secs: 30,
+            ..RelayPolicy::default()
+        });
+
+        gate.record(Ok(503), start);
+        assert_eq!(gate.circuit(), CircuitState::Closed);
+        assert_eq!(gate.admit(start), Ok(()));
+        gate.record(Err("connection refused".to_string()), start);
+        assert_eq!(gate.circuit(), CircuitState::Open);
+        assert_eq!(gate.metrics().circuit_opened, 1);
+
+        // Requests are turned away until the cooldown is over
+        assert_eq!(gate.admit(start + secs(10)), Err(secs(20)));
+        assert_eq!(gate.metrics().rejected, 1);
+
+        // Then one probes the endpoint, and a failure opens the circuit again
+        assert_eq!(gate.admit(start + secs(30)), Ok(()));
+        assert_eq!(gate.circuit(), CircuitState::HalfOpen);
+        assert_eq!(gate.admit(start + secs(31)), Err(secs(29)));
+        gate.record(Ok(529), start + secs(32));
+        assert_eq!(gate.circuit(), CircuitState::Open);
+        assert_eq!(gate.admit(start + secs(40)), Err(secs(22)));
+
+        // A probe that gets an answer closes it
+        assert_eq!(gate.admit(start + secs(62)), Ok(()));
+        assert_eq!(gate.circuit(), CircuitState::HalfOpen);
+        gate.record(Ok(200), start + secs(63));
+        assert_eq!(gate.circuit(), CircuitState::Closed);
+        assert_eq!(gate.admit(start + secs(63)), Ok(()));
+        assert_eq!(gate.metrics().circuit_opened, 1);
+
+        // Failures start counting from zero again
+        gate.record(Ok(500), start + secs(64));
+        assert_eq!(gate.circuit(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn probe_that_never_finished_lets_another_one_through() {
+        let (mut gate, start) = gate(RelayPolicy {
+            breaker_threshold: Some(1),
+            breaker_cooldown_secs: 30,
+            ..RelayPolicy::default()
+        });
+        gate.record(Ok(502), start);
+
+        assert_eq!(gate.admit(start + secs(30)), Ok(()));
+        assert_eq!(gate.admit(start + secs(59)), Err(secs(1)));
+        assert_eq!(gate.admit(start + secs(60)), Ok(()));
+        assert_eq!(gate.circuit(), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn turning_the_breaker_off_closes_the_circuit() {
+        let (mut gate, start) = gate(RelayPolicy {
+            breaker_threshold: Some(1),
+            ..RelayPolicy::default()
+        });
+        gate.record(Ok(500), start);
+        assert_eq!(gate.circuit(), CircuitState::Open);
+
+        gate.set_policy(RelayPolicy::default());
+        assert_eq!(gate.circuit(), CircuitState::Closed);
+        assert_eq!(gate.admit(start), Ok(()));
+    }
+}
@@ -0,0 +1,210 @@
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::fsio;
+use crate::merge::{self, MergeChange, MergeStrategies};
+use crate::paths::Paths;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ConfigFile {
+    pub path: String,
+    pub content: Value,
+    pub exists: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct MemoryFile {
+    pub path: String,
+    pub content: String,
+    pub exists: bool,
+}
+
+fn config_file_path(paths: &Paths, config_type: &str) -> Result<PathBuf, String> {
+    let path = match config_type {
+        "user" => paths.user_settings(),
+        "enterprise_macos" => {
+            PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json")
+        }
+        "enterprise_linux" => PathBuf::from("/etc/claude-code/managed-settings.json"),
+        "enterprise_windows" => PathBuf::from("C:\\ProgramData\\ClaudeCode\\managed-settings.json"),
+        "mcp_macos" => PathBuf::from("/Library/Application Support/ClaudeCode/managed-mcp.json"),
+        "mcp_linux" => PathBuf::from("/etc/claude-code/managed-mcp.json"),
+        "mcp_windows" => PathBuf::from("C:\\ProgramData\\ClaudeCode\\managed-mcp.json"),
+        _ => return Err("Invalid configuration type".to_string()),
+    };
+    Ok(path)
+}
+
+pub fn read_config_file(paths: &Paths, config_type: &str) -> Result<ConfigFile, String> {
+    let path = config_file_path(paths, config_type)?;
+    let path_str = path.to_string_lossy().to_string();
+
+    if path.exists() {
+        let content =
+            std::fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+        let json_content: Value =
+            serde_json::from_str(&content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+        Ok(ConfigFile {
+            path: path_str,
+            content: json_content,
+            exists: true,
+        })
+    } else {
+        Ok(ConfigFile {
+            path: path_str,
+            content: Value::Object(serde_json::Map::new()),
+            exists: false,
+        })
+    }
+}
+
+pub fn write_config_file(paths: &Paths, config_type: &str, content: &Value) -> Result<(), String> {
+    let path = match config_type {
+        "user" => paths.user_settings(),
+        _ => return Err("Cannot write to enterprise configuration files".to_string()),
+    };
+
+    fsio::write_json(paths, &path, "file", content)
+}
+
+pub fn list_config_files(paths: &Paths) -> Vec<String> {
+    let mut configs = vec![];
+
+    // User settings
+    if paths.user_settings().exists() {
+        configs.push("user".to_string());
+    }
+
+    // Enterprise settings (read-only)
+    let candidates: &[&str] = if cfg!(target_os = "macos") {
+        &["enterprise_macos", "mcp_macos"]
+    } else if cfg!(target_os = "linux") {
+        &["enterprise_linux", "mcp_linux"]
+    } else if cfg!(target_os = "windows") {
+        &["enterprise_windows", "mcp_windows"]
+    } else {
+        &[]
+    };
+
+    for config_type in candidates {
+        if let Ok(path) = config_file_path(paths, config_type) {
+            if path.exists() {
+                configs.push(config_type.to_string());
+            }
+        }
+    }
+
+    configs
+}
+
+/// Read `~/.claude/settings.json`, or an empty object if it does not exist yet.
+pub fn read_user_settings(paths: &Paths) -> Result<Value, String> {
+    Ok(
+        fsio::read_json(&paths.user_settings(), "existing settings")?
+            .unwrap_or_else(|| Value::Object(serde_json::Map::new())),
+    )
+}
+
+/// Write `~/.claude/settings.json`, creating `~/.claude` if needed.
+pub fn write_user_settings(paths: &Paths, settings: &Value) -> Result<(), String> {
+    fsio::write_json(paths, &paths.user_settings(), "user settings", settings)
+}
+
+/// Locked read-modify-write of `~/.claude/settings.json`; `apply` returns whether it changed anything.
+pub fn update_user_settings(
+    paths: &Paths,
+    apply: impl FnMut(&mut Value) -> Result<bool, String>,
+) -> Result<bool, String> {
+    fsio::update_json_object(paths, &paths.user_settings(), "user settings", apply)
+}
+
+/// Revert the `previous` profile's changes in settings.json and merge `settings` in their place.
+///
+/// Returns the changes made, to be passed back as `previous` on the next switch.
+pub fn switch_user_settings(
+    paths: &Paths,
+    previous: &[MergeChange],
+    settings: Option<&Value>,
+    strategies: &MergeStrategies,
+) -> Result<Vec<MergeChange>, String> {
+    let mut changes = Vec::new();
+    update_user_settings(paths, |existing_settings| {
+        merge::revert(existing_settings, previous);
+        changes = match settings {
+            Some(settings) => merge::apply(existing_settings, settings, strategies)?,
+            None => vec![],
+        };
+        Ok(true)
+    })?;
+    Ok(changes)
+}
+
+/// Clear the env block in the user's settings.json.
+pub fn clear_user_env(paths: &Paths) -> Result<(), String> {
+    update_user_settings(paths, |existing_settings| {
+        // Set env to empty object
+        if let Some(existing_obj) = existing_settings.as_object_mut() {
+            existing_obj.insert("env".to_string(), serde_json::json!({}));
+        }
+        Ok(true)
+    })?;
+    Ok(())
+}
+
+pub fn unlock_cc_ext(paths: &Paths) -> Result<(), String> {
+    let claude_config_path = paths.claude_config();
+    let existed = claude_config_path.exists();
+
+    // Add primaryApiKey if it is missing, creating config.json if needed
+    let added =
+        fsio::update_json_object(paths, &claude_config_path, "config.json", |json_value| {
+            if json_value.get("primaryApiKey").is_some() {
+                return Ok(false);
+            }
+            if let Some(obj) = json_value.as_object_mut() {
+                obj.insert(
+                    "primaryApiKey".to_string(),
+                    Value::String("xxx".to_string()),
+                );
+            }
+            Ok(true)
+        })?;
+
+    if !added {
+        eprintln!("primaryApiKey already exists in config.json, no action needed");
+    } else if existed {
+        eprintln!("Added primaryApiKey to existing config.json");
+    } else {
+        eprintln!("Created new config.json with primaryApiKey");
+    }
+
+    Ok(())
+}
+
+pub fn read_claude_memory(paths: &Paths) -> Result<MemoryFile, String> {
+    let claude_md_path = paths.claude_memory();
+    let path_str = claude_md_path.to_string_lossy().to_string();
+
+    if claude_md_path.exists() {
+        let content = std::fs::read_to_string(&claude_md_path)
+            .map_err(|e| format!("Failed to read CLAUDE.md file: {}", e))?;
+
+        Ok(MemoryFile {
+            path: path_str,
+            content,
+            exists: true,
+        })
+    } else {
+        Ok(MemoryFile {
+            path: path_str,
+            content: String::new(),
+            exists: false,
+        })
+    }
+}
+
+pub fn write_claude_memory(paths: &Paths, content: &str) -> Result<(), String> {
+    fsio::write_text(paths, &paths.claude_memory(), "CLAUDE.md file", content)
+}
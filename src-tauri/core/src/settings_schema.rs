@@ -0,0 +1,726 @@
+//! Validation of profile settings against Claude Code's `settings.json` schema.
+//!
+//! Profiles keep their settings as a raw `serde_json::Value` so keys this app
+//! does not know about survive a round trip. Before a profile is saved or
+//! written into `~/.claude/settings.json` it is checked against the schema
+//! documented in `specs/claude-code-settings-doc.md`; every problem is
+//! reported with the JSON pointer of the offending value.
+
+use serde_json::{Map, Value};
+
+use crate::hooks::HOOK_EVENTS;
+use crate::pointer::child;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct SettingsIssue {
+    /// JSON pointer (RFC 6901) to the offending value, `""` for the whole document
+    pub pointer: String,
+    pub message: String,
+    pub severity: IssueSeverity,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct SettingsValidation {
+    pub valid: bool,
+    pub errors: Vec<SettingsIssue>,
+    pub warnings: Vec<SettingsIssue>,
+}
+
+const PERMISSION_MODES: [&str; 4] = ["default", "acceptEdits", "plan", "bypassPermissions"];
+
+const TOP_LEVEL_KEYS: [&str; 18] = [
+    "apiKeyHelper",
+    "cleanupPeriodDays",
+    "env",
+    "includeCoAuthoredBy",
+    "permissions",
+    "hooks",
+    "disableAllHooks",
+    "model",
+    "statusLine",
+    "outputStyle",
+    "forceLoginMethod",
+    "forceLoginOrgUUID",
+    "enableAllProjectMcpServers",
+    "enabledMcpjsonServers",
+    "disabledMcpjsonServers",
+    "useEnterpriseMcpConfigOnly",
+    "awsAuthRefresh",
+    "awsCredentialExport",
+];
+
+const PERMISSION_KEYS: [&str; 6] = [
+    "allow",
+    "ask",
+    "deny",
+    "additionalDirectories",
+    "defaultMode",
+    "disableBypassPermissionsMode",
+];
+
+// Environment variables Claude Code reads; unknown names close to one of these are flagged as likely typos
+const KNOWN_ENV_VARS: [&str; 51] = [
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_AUTH_TOKEN",
+    "ANTHROPIC_BASE_URL",
+    "ANTHROPIC_CUSTOM_HEADERS",
+    "ANTHROPIC_DEFAULT_HAIKU_MODEL",
+    "ANTHROPIC_DEFAULT_OPUS_MODEL",
+    "ANTHROPIC_DEFAULT_SONNET_MODEL",
+    "ANTHROPIC_MODEL",
+    "ANTHROPIC_SMALL_FAST_MODEL",
+    "ANTHROPIC_SMALL_FAST_MODEL_AWS_REGION",
+    "API_TIMEOUT_MS",
+    "AWS_BEARER_TOKEN_BEDROCK",
+    "BASH_DEFAULT_TIMEOUT_MS",
+    "BASH_MAX_OUTPUT_LENGTH",
+    "BASH_MAX_TIMEOUT_MS",
+    "CLAUDE_BASH_MAINTAIN_PROJECT_WORKING_DIR",
+    "CLAUDE_CODE_API_KEY_HELPER_TTL_MS",
+    "CLAUDE_CODE_CLIENT_CERT",
+    "CLAUDE_CODE_CLIENT_KEY_PASSPHRASE",
+    "CLAUDE_CODE_CLIENT_KEY",
+    "CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC",
+    "CLAUDE_CODE_DISABLE_TERMINAL_TITLE",
+    "CLAUDE_CODE_ENABLE_TELEMETRY",
+    "CLAUDE_CODE_IDE_SKIP_AUTO_INSTALL",
+    "CLAUDE_CODE_MAX_OUTPUT_TOKENS",
+    "CLAUDE_CODE_SKIP_BEDROCK_AUTH",
+    "CLAUDE_CODE_SKIP_VERTEX_AUTH",
+    "CLAUDE_CODE_SUBAGENT_MODEL",
+    "CLAUDE_CODE_USE_BEDROCK",
+    "CLAUDE_CODE_USE_VERTEX",
+    "DISABLE_AUTOUPDATER",
+    "DISABLE_BUG_COMMAND",
+    "DISABLE_COST_WARNINGS",
+    "DISABLE_ERROR_REPORTING",
+    "DISABLE_NON_ESSENTIAL_MODEL_CALLS",
+    "DISABLE_TELEMETRY",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "MAX_MCP_OUTPUT_TOKENS",
+    "MAX_THINKING_TOKENS",
+    "MCP_TIMEOUT",
+    "MCP_TOOL_TIMEOUT",
+    "NO_PROXY",
+    "SLASH_COMMAND_TOOL_CHAR_BUDGET",
+    "USE_BUILTIN_RIPGREP",
+    "VERTEX_REGION_CLAUDE_3_5_HAIKU",
+    "VERTEX_REGION_CLAUDE_3_5_SONNET",
+    "VERTEX_REGION_CLAUDE_3_7_SONNET",
+    "VERTEX_REGION_CLAUDE_4_0_OPUS",
+    "VERTEX_REGION_CLAUDE_4_0_SONNET",
+    "VERTEX_REGION_CLAUDE_4_1_OPUS",
+];
+
+const URL_ENV_VARS: [&str; 3] = ["ANTHROPIC_BASE_URL", "HTTP_PROXY", "HTTPS_PROXY"];
+
+const INTEGER_ENV_VARS: [&str; 11] = [
+    "API_TIMEOUT_MS",
+    "BASH_DEFAULT_TIMEOUT_MS",
+    "BASH_MAX_OUTPUT_LENGTH",
+    "BASH_MAX_TIMEOUT_MS",
+    "CLAUDE_CODE_API_KEY_HELPER_TTL_MS",
+    "CLAUDE_CODE_MAX_OUTPUT_TOKENS",
+    "MAX_MCP_OUTPUT_TOKENS",
+    "MAX_THINKING_TOKENS",
+    "MCP_TIMEOUT",
+    "MCP_TOOL_TIMEOUT",
+    "SLASH_COMMAND_TOOL_CHAR_BUDGET",
+];
+
+/// Check profile settings against the Claude Code settings schema.
+pub fn validate_settings(settings: &Value) -> SettingsValidation {
+    let mut validator = Validator::default();
+    validator.settings(settings);
+
+    let (errors, warnings): (Vec<_>, Vec<_>) = validator
+        .issues
+        .into_iter()
+        .partition(|issue| issue.severity == IssueSeverity::Error);
+
+    SettingsValidation {
+        valid: errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
+
+/// Fail with every schema error, formatted as `pointer: message`, if `settings` is invalid.
+pub fn ensure_valid_settings(settings: &Value) -> Result<(), String> {
+    let validation = validate_settings(settings);
+    if validation.valid {
+        return Ok(());
+    }
+
+    let details: Vec<String> = validation
+        .errors
+        .iter()
+        .map(|issue| {
+            let pointer = if issue.pointer.is_empty() {
+                "/"
+            } else {
+                &issue.pointer
+            };
+            format!("{}: {}", pointer, issue.message)
+        })
+        .collect();
+    Err(format!("Invalid settings: {}", details.join("; ")))
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    issues: Vec<SettingsIssue>,
+}
+
+impl Validator {
+    fn error(&mut self, pointer: &str, message: String) {
+        self.issues.push(SettingsIssue {
+            pointer: pointer.to_string(),
+            message,
+            severity: IssueSeverity::Error,
+        });
+    }
+
+    fn warning(&mut self, pointer: &str, message: String) {
+        self.issues.push(SettingsIssue {
+            pointer: pointer.to_string(),
+            message,
+            severity: IssueSeverity::Warning,
+        });
+    }
+
+    fn object<'a>(&mut self, pointer: &str, value: &'a Value) -> Option<&'a Map<String, Value>> {
+        let object = value.as_object();
+        if object.is_none() {
+            self.error(
+                pointer,
+                format!("expected an object, found {}", type_name(value)),
+            );
+        }
+        object
+    }
+
+    fn string<'a>(&mut self, pointer: &str, value: &'a Value) -> Option<&'a str> {
+        let string = value.as_str();
+        if string.is_none() {
+            self.error(
+                pointer,
+                format!("expected a string, found {}", type_name(value)),
+            );
+        }
+        string
+    }
+
+    fn boolean(&mut self, pointer: &str, value: &Value) {
+        if !value.is_boolean() {
+            self.error(
+                pointer,
+                format!("expected a boolean, found {}", type_name(value)),
+            );
+        }
+    }
+
+    /// Check for an array of strings, running `check` on each string item
+    fn string_array(
+        &mut self,
+        pointer: &str,
+        value: &Value,
+        check: impl Fn(&str) -> Result<(), String>,
+    ) {
+        let Some(items) = value.as_array() else {
+            self.error(
+                pointer,
+                format!("expected an array of strings, found {}", type_name(value)),
+            );
+            return;
+        };
+
+        for (index, item) in items.iter().enumerate() {
+            let item_pointer = child(pointer, &index.to_string());
+            if let Some(string) = self.string(&item_pointer, item) {
+                if let Err(message) = check(string) {
+                    self.error(&item_pointer, message);
+                }
+            }
+        }
+    }
+
+    fn one_of(&mut self, pointer: &str, value: &Value, allowed: &[&str]) {
+        if let Some(string) = self.string(pointer, value) {
+            if !allowed.contains(&string) {
+                self.error(
+                    pointer,
+                    format!(
+                        "must be one of {}, found \"{}\"",
+                        quote_all(allowed),
+                        string
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Unknown keys are allowed; one that is a near miss of a known key is probably a typo,
+    /// but may be a setting newer than this list, so it only gets a warning
+    fn typo(&mut self, pointer: &str, key: &str, known: &[&str]) {
+        if known.contains(&key) {
+            return;
+        }
+        if let Some(suggestion) = closest_match(key, known) {
+            self.warning(
+                pointer,
+                format!("unknown key \"{}\", did you mean \"{}\"?", key, suggestion),
+            );
+        }
+    }
+
+    fn settings(&mut self, settings: &Value) {
+        let Some(root) = self.object("", settings) else {
+            return;
+        };
+
+        for (key, value) in root {
+            let pointer = child("", key);
+            match key.as_str() {
+                "apiKeyHelper"
+                | "model"
+                | "outputStyle"
+                | "awsAuthRefresh"
+                | "awsCredentialExport"
+                | "forceLoginOrgUUID" => {
+                    self.string(&pointer, value);
+                }
+                "includeCoAuthoredBy"
+                | "disableAllHooks"
+                | "enableAllProjectMcpServers"
+                | "useEnterpriseMcpConfigOnly" => self.boolean(&pointer, value),
+                "enabledMcpjsonServers" | "disabledMcpjsonServers" => {
+                    self.string_array(&pointer, value, |_| Ok(()));
+                }
+                "cleanupPeriodDays" => {
+                    if value.as_u64().is_none() {
+                        self.error(
+                            &pointer,
+                            format!(
+                                "expected a non-negative integer, found {}",
+                                type_name(value)
+                            ),
+                        );
+                    }
+                }
+                "forceLoginMethod" => self.one_of(&pointer, value, &["claudeai", "console"]),
+                "env" => self.env(&pointer, value),
+                "permissions" => self.permissions(&pointer, value),
+                "hooks" => self.hooks(&pointer, value),
+                "statusLine" => self.status_line(&pointer, value),
+                _ => self.typo(&pointer, key, &TOP_LEVEL_KEYS),
+            }
+        }
+
+        if root.contains_key("forceLoginOrgUUID") && !root.contains_key("forceLoginMethod") {
+            self.warning(
+                "/forceLoginOrgUUID",
+                "only takes effect when forceLoginMethod is also set".to_string(),
+            );
+        }
+    }
+
+    fn env(&mut self, pointer: &str, value: &Value) {
+        let Some(env) = self.object(pointer, value) else {
+            return;
+        };
+
+        for (name, value) in env {
+            let pointer = child(pointer, name);
+            let Some(value) = value.as_str() else {
+                self.error(
+                    &pointer,
+                    format!(
+                        "environment values must be strings, found {}",
+                        type_name(value)
+                    ),
+                );
+                continue;
+            };
+
+            if URL_ENV_VARS.contains(&name.as_str()) && !is_http_url(value) {
+                self.error(
+                    &pointer,
+                    format!("expected an http(s) URL, found \"{}\"", value),
+                );
+            } else if INTEGER_ENV_VARS.contains(&name.as_str())
+                && value.trim().parse::<u64>().is_err()
+            {
+                self.error(
+                    &pointer,
+                    format!("expected an integer, found \"{}\"", value),
+                );
+            }
+
+            self.typo(&pointer, name, &KNOWN_ENV_VARS);
+        }
+    }
+
+    fn permissions(&mut self, pointer: &str, value: &Value) {
+        let Some(permissions) = self.object(pointer, value) else {
+            return;
+        };
+
+        for (key, value) in permissions {
+            let pointer = child(pointer, key);
+            match key.as_str() {
+                "allow" | "ask" | "deny" => {
+                    self.string_array(&pointer, value, check_permission_rule);
+                }
+                "additionalDirectories" => {
+                    self.string_array(&pointer, value, |_| Ok(()));
+                }
+                "defaultMode" => self.one_of(&pointer, value, &PERMISSION_MODES),
+                "disableBypassPermissionsMode" => self.one_of(&pointer, value, &["disable"]),
+                _ => self.typo(&pointer, key, &PERMISSION_KEYS),
+            }
+        }
+    }
+
+    fn hooks(&mut self, pointer: &str, value: &Value) {
+        let Some(hooks) = self.object(pointer, value) else {
+            return;
+        };
+
+        for (event, matchers) in hooks {
+            let pointer = child(pointer, event);
+            if !HOOK_EVENTS.contains(&event.as_str()) {
+                match closest_match(event, &HOOK_EVENTS) {
+                    Some(suggestion) => self.warning(
+                        &pointer,
+                        format!(
+                            "unknown hook event \"{}\", did you mean \"{}\"?",
+                            event, suggestion
+                        ),
+                    ),
+                    None => self.warning(&pointer, format!("unknown hook event \"{}\"", event)),
+                }
+            }
+
+            let Some(matchers) = matchers.as_array() else {
+                self.error(
+                    &pointer,
+                    format!(
+                        "expected an array of hook matchers, found {}",
+                        type_name(matchers)
+                    ),
+                );
+                continue;
+            };
+
+            for (index, matcher) in matchers.iter().enumerate() {
+                let pointer = child(&pointer, &index.to_string());
+                let Some(matcher) = self.object(&pointer, matcher) else {
+                    continue;
+                };
+
+                if let Some(value) = matcher.get("matcher") {
+                    self.string(&child(&pointer, "matcher"), value);
+                }
+
+                let hooks_pointer = child(&pointer, "hooks");
+                let Some(commands) = matcher.get("hooks") else {
+                    self.error(&hooks_pointer, "missing required key \"hooks\"".to_string());
+                    continue;
+                };
+                let Some(commands) = commands.as_array() else {
+                    self.error(
+                        &hooks_pointer,
+                        format!("expected an array of hooks, found {}", type_name(commands)),
+                    );
+                    continue;
+                };
+
+                for (index, command) in commands.iter().enumerate() {
+                    self.hook_command(&child(&hooks_pointer, &index.to_string()), command);
+                }
+            }
+        }
+    }
+
+    fn hook_command(&mut self, pointer: &str, value: &Value) {
+        let Some(hook) = self.object(pointer, value) else {
+            return;
+        };
+
+        match hook.get("type") {
+            Some(kind) => self.one_of(&child(pointer, "type"), kind, &["command"]),
+            None => self.error(
+                &child(pointer, "type"),
+                "missing required key \"type\"".to_string(),
+            ),
+        }
+
+        match hook.get("command") {
+            Some(command) => {
+                if let Some(command) = self.string(&child(pointer, "command"), command) {
+                    if command.trim().is_empty() {
+                        self.error(
+                            &child(pointer, "command"),
+                            "command must not be empty".to_string(),
+                        );
+                    }
+                }
+            }
+            None => self.error(
+                &child(pointer, "command"),
+                "missing required key \"command\"".to_string(),
+            ),
+        }
+
+        if let Some(timeout) = hook.get("timeout") {
+            if timeout.as_u64().is_none() {
+                self.error(
+                    &child(pointer, "timeout"),
+                    format!(
+                        "expected a non-negative integer, found {}",
+                        type_name(timeout)
+                    ),
+                );
+            }
+        }
+    }
+
+    fn status_line(&mut self, pointer: &str, value: &Value) {
+        let Some(status_line) = self.object(pointer, value) else {
+            return;
+        };
+
+        match status_line.get("type") {
+            Some(kind) => self.one_of(&child(pointer, "type"), kind, &["command"]),
+            None => self.error(
+                &child(pointer, "type"),
+                "missing required key \"type\"".to_string(),
+            ),
+        }
+
+        match status_line.get("command") {
+            Some(command) => {
+                self.string(&child(pointer, "command"), command);
+            }
+            None => self.error(
+                &child(pointer, "command"),
+                "missing required key \"command\"".to_string(),
+            ),
+        }
+
+        if let Some(padding) = status_line.get("padding") {
+            if padding.as_i64().is_none() {
+                self.error(
+                    &child(pointer, "padding"),
+                    format!("expected an integer, found {}", type_name(padding)),
+                );
+            }
+        }
+    }
+}
+
+/// Permission rules are `Tool` or `Tool(specifier)`, e.g. `Bash(npm run test:*)` or `mcp__github`
+fn check_permission_rule(rule: &str) -> Result<(), String> {
+    let rule = rule.trim();
+    let (tool, specifier) = match rule.find('(') {
+        Some(open) => {
+            if !rule.ends_with(')') {
+                return Err(format!(
+                    "permission rule \"{}\" is missing a closing parenthesis",
+                    rule
+                ));
+            }
+            (&rule[..open], Some(&rule[open + 1..rule.len() - 1]))
+        }
+        None => (rule, None),
+    };
+
+    let valid_tool = tool.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && tool
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid_tool {
+        return Err(format!("invalid tool name in permission rule \"{}\"", rule));
+    }
+    if specifier.is_some_and(|s| s.trim().is_empty()) {
+        return Err(format!(
+            "permission rule \"{}\" has an empty specifier",
+            rule
+        ));
+    }
+    Ok(())
+}
+
+fn is_http_url(value: &str) -> bool {
+    let rest = value
+        .strip_prefix("https://")
+        .or_else(|| value.strip_prefix("http://"));
+    match rest {
+        Some(rest) => {
+            let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+            !host.is_empty() && !value.chars().any(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+fn quote_all(values: &[&str]) -> String {
+    values
+        .iter()
+        .map(|v| format!("\"{}\"", v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Closest known name within a small edit distance
+fn closest_match<'a>(name: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .filter_map(|candidate| {
+            let max_distance = if candidate.len() >= 12 { 2 } else { 1 };
+            let distance = edit_distance(name, candidate);
+            (distance <= max_distance).then_some((distance, *candidate))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Optimal string alignment distance, so a swapped pair of letters counts as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pointers(issues: &[SettingsIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.pointer.as_str()).collect()
+    }
+
+    #[test]
+    fn unknown_keys_pass_and_near_misses_only_warn() {
+        let validation = validate_settings(&json!({
+            "someFutureSetting": true,
+            "modle": "opus",
+            "permissions": { "alow": ["Bash"] }
+        }));
+        assert!(validation.valid);
+        assert_eq!(
+            pointers(&validation.warnings),
+            ["/modle", "/permissions/alow"]
+        );
+        assert!(validation.warnings[0]
+            .message
+            .contains("did you mean \"model\""));
+    }
+
+    #[test]
+    fn env_names_close_to_known_ones_are_not_rejected() {
+        let settings = json!({
+            "env": {
+                "VERTEX_REGION_CLAUDE_4_5_SONNET": "us-east5",
+                "ANTHROPIC_AUTH_TOKNE": "sk-token"
+            }
+        });
+        assert!(ensure_valid_settings(&settings).is_ok());
+        let validation = validate_settings(&settings);
+        assert_eq!(
+            pointers(&validation.warnings),
+            [
+                "/env/ANTHROPIC_AUTH_TOKNE",
+                "/env/VERTEX_REGION_CLAUDE_4_5_SONNET"
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_types_are_errors() {
+        let validation = validate_settings(&json!({
+            "model": 4,
+            "includeCoAuthoredBy": "yes",
+            "cleanupPeriodDays": -1,
+            "env": { "API_TIMEOUT_MS": 600000, "MCP_TIMEOUT": "soon" },
+            "permissions": { "allow": "Bash" },
+            "statusLine": { "type": "command" }
+        }));
+        assert!(!validation.valid);
+        assert_eq!(
+            pointers(&validation.errors),
+            [
+                "/cleanupPeriodDays",
+                "/env/API_TIMEOUT_MS",
+                "/env/MCP_TIMEOUT",
+                "/includeCoAuthoredBy",
+                "/model",
+                "/permissions/allow",
+                "/statusLine/command"
+            ]
+        );
+        assert!(validate_settings(&json!([])).errors[0]
+            .message
+            .contains("expected an object"));
+    }
+
+    #[test]
+    fn permission_modes_and_rules_are_checked() {
+        for mode in PERMISSION_MODES {
+            assert!(validate_settings(&json!({ "permissions": { "defaultMode": mode } })).valid);
+        }
+
+        let error = ensure_valid_settings(&json!({
+            "permissions": {
+                "defaultMode": "yolo",
+                "deny": ["Bash(rm -rf:*", "Read()"]
+            }
+        }))
+        .unwrap_err();
+        assert!(error.contains("/permissions/defaultMode: must be one of"));
+        assert!(error.contains("/permissions/deny/0: permission rule \"Bash(rm -rf:*\" is missing"));
+        assert!(error.contains("/permissions/deny/1: permission rule \"Read()\" has an empty"));
+    }
+}
@@ -0,0 +1,729 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::bindings::{self, ProjectBinding};
+use crate::fsio::{self, FileLock};
+use crate::merge::{self, AppliedSettings, MergeChange, MergeStrategies};
+use crate::paths::Paths;
+use crate::relay;
+use crate::settings;
+use crate::settings_schema;
+use crate::vault;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ConfigStore {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: u64,
+    pub settings: Value,
+    pub using: bool,
+    /// Store whose settings this one is layered on top of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct StoresData {
+    pub configs: Vec<ConfigStore>,
+    pub distinct_id: Option<String>,
+    pub notification: Option<NotificationSettings>,
+    /// What the active store contributed to settings.json, reverted on the next switch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied: Option<AppliedSettings>,
+    /// Per-key overrides of [`merge::default_strategies`], keyed by JSON pointer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_strategies: Option<MergeStrategies>,
+    /// Projects whose settings.local.json a store is bound to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bindings: Vec<ProjectBinding>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct NotificationSettings {
+    pub enable: bool,
+    /// Hook events to install the hook for and notify about, see [`crate::hooks::HOOK_EVENTS`]
+    pub enabled_hooks: Vec<String>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            enabled_hooks: vec!["Notification".to_string()],
+        }
+    }
+}
+
+impl Default for StoresData {
+    fn default() -> Self {
+        Self {
+            configs: vec![],
+            distinct_id: None,
+            notification: Some(NotificationSettings::default()),
+            applied: None,
+            merge_strategies: None,
+            bindings: vec![],
+        }
+    }
+}
+
+/// Read stores.json, returning `None` if it does not exist yet.
+pub fn read_stores_data(paths: &Paths) -> Result<Option<StoresData>, String> {
+    fsio::read_json(&paths.stores_file(), "stores file")
+}
+
+/// Locked read-modify-write of stores.json, starting from defaults if it does not exist yet.
+pub fn update_stores_data<R>(
+    paths: &Paths,
+    mut apply: impl FnMut(&mut StoresData) -> Result<R, String>,
+) -> Result<R, String> {
+    fsio::update_json(paths, &paths.stores_file(), "stores file", |current| {
+        let mut stores_data = current.unwrap_or_default();
+        let result = apply(&mut stores_data)?;
+        Ok((Some(stores_data), result))
+    })
+}
+
+/// Like [`update_stores_data`], for a caller already holding the stores.json lock.
+fn update_locked_stores_data<R>(
+    lock: &FileLock,
+    paths: &Paths,
+    mut apply: impl FnMut(&mut StoresData) -> Result<R, String>,
+) -> Result<R, String> {
+    fsio::update_json_locked(
+        lock,
+        paths,
+        &paths.stores_file(),
+        "stores file",
+        |current| {
+            let mut stores_data = current.unwrap_or_default();
+            let result = apply(&mut stores_data)?;
+            Ok((Some(stores_data), result))
+        },
+    )
+}
+
+/// Locked read-modify-write of stores.json, failing if it does not exist.
+fn update_existing_stores_data<R>(
+    paths: &Paths,
+    mut apply: impl FnMut(&mut StoresData) -> Result<R, String>,
+) -> Result<R, String> {
+    fsio::update_json(paths, &paths.stores_file(), "stores file", |current| {
+        let mut stores_data: StoresData =
+            current.ok_or_else(|| "Stores file does not exist".to_string())?;
+        let result = apply(&mut stores_data)?;
+        Ok((Some(stores_data), result))
+    })
+}
+
+pub fn get_stores(paths: &Paths) -> Result<Vec<ConfigStore>, String> {
+    let Some(mut stores_data) = read_stores_data(paths)? else {
+        return Ok(vec![]);
+    };
+
+    // Add default notification settings if they don't exist
+    if stores_data.notification.is_none() {
+        // Write back to stores file with notification settings added
+        stores_data = update_stores_data(paths, |stores_data| {
+            stores_data
+                .notification
+                .get_or_insert_with(NotificationSettings::default);
+            Ok(stores_data.clone())
+        })?;
+
+        eprintln!("Added default notification settings to existing stores.json");
+    }
+
+    let mut stores_vec = stores_data.configs;
+    // Sort by createdAt in ascending order (oldest first)
+    stores_vec.sort_by_key(|store| store.created_at);
+
+    Ok(stores_vec)
+}
+
+pub fn get_store(paths: &Paths, store_id: &str) -> Result<ConfigStore, String> {
+    get_stores(paths)?
+        .into_iter()
+        .find(|store| store.id == store_id)
+        .ok_or_else(|| format!("Store with id '{}' not found", store_id))
+}
+
+pub fn get_current_store(paths: &Paths) -> Result<Option<ConfigStore>, String> {
+    Ok(get_stores(paths)?.into_iter().find(|store| store.using))
+}
+
+pub fn create_config(
+    paths: &Paths,
+    id: String,
+    title: String,
+    settings: Value,
+    parent: Option<String>,
+) -> Result<ConfigStore, String> {
+    add_config(paths, id, title, settings, parent, true)
+}
+
+/// Add a store without making it active, even when it is the first one
+pub fn import_config(
+    paths: &Paths,
+    id: String,
+    title: String,
+    settings: Value,
+    parent: Option<String>,
+) -> Result<ConfigStore, String> {
+    add_config(paths, id, title, settings, parent, false)
+}
+
+fn add_config(
+    paths: &Paths,
+    id: String,
+    title: String,
+    mut settings: Value,
+    parent: Option<String>,
+    activate_first: bool,
+) -> Result<ConfigStore, String> {
+    settings_schema::ensure_valid_settings(&settings)?;
+
+    // Tokens go into the vault, the store keeps a handle
+    let secret_pointers = vault::settings_secret_pointers(&settings);
+    vault::seal_secrets(paths, &mut settings, &secret_pointers, None)?;
+
+    let created_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| format!("Failed to get timestamp: {}", e))?
+        .as_secs();
+
+    // NOTE: We no longer auto-create "Original Config" here to avoid duplicate config creation.
+    // Users should manually create configs as needed. The backup functionality already preserves
+    // the original settings in ~/.ccconfig/claude_backup/
+
+    let new_store = update_stores_data(paths, |stores_data| {
+        ensure_valid_parent(&stores_data.configs, &id, parent.as_deref())?;
+
+        // The first store becomes the active one, unless it was imported
+        let new_store = ConfigStore {
+            id: id.clone(),
+            title: title.clone(),
+            created_at,
+            settings: settings.clone(),
+            using: activate_first && stores_data.configs.is_empty(),
+            parent: parent.clone(),
+        };
+
+        // Add store to collection
+        stores_data.configs.push(new_store.clone());
+        if new_store.using {
+            // Nothing of it is in settings.json yet
+            stores_data.applied = Some(AppliedSettings {
+                store_id: new_store.id.clone(),
+                changes: vec![],
+            });
+        }
+        Ok(new_store)
+    })?;
+
+    // If this is the first store (and therefore active), merge its settings into the user's actual settings.json
+    if new_store.using {
+        apply_store_settings(paths, Some(&new_store))?;
+    }
+
+    // Automatically unlock CC extension when creating new config
+    if let Err(e) = settings::unlock_cc_ext(paths) {
+        eprintln!("Warning: Failed to unlock CC extension: {}", e);
+    }
+
+    Ok(new_store)
+}
+
+pub fn update_config(
+    paths: &Paths,
+    store_id: &str,
+    title: String,
+    mut settings: Value,
+    parent: Option<String>,
+) -> Result<ConfigStore, String> {
+    settings_schema::ensure_valid_settings(&settings)?;
+
+    // Tokens go into the vault; masked ones sent back unchanged keep their handle
+    let previous_settings = get_store(paths, store_id).ok().map(|store| store.settings);
+    let secret_pointers = vault::settings_secret_pointers(&settings);
+    vault::seal_secrets(
+        paths,
+        &mut settings,
+        &secret_pointers,
+        previous_settings.as_ref(),
+    )?;
+
+    let (updated_store, stores_data) = update_existing_stores_data(paths, |stores_data| {
+        ensure_valid_parent(&stores_data.configs, store_id, parent.as_deref())?;
+
+        // Find the store by ID
+        let store = stores_data
+            .configs
+            .iter_mut()
+            .find(|store| store.id == store_id)
+            .ok_or_else(|| format!("Store with id '{}' not found", store_id))?;
+
+        // Update the store
+        store.title = title.clone();
+        store.settings = settings.clone();
+        store.parent = parent.clone();
+        let updated_store = store.clone();
+        Ok((updated_store, stores_data.clone()))
+    })?;
+
+    // If this store or one inheriting from it is in use, replace its previous contributions to the user's settings.json
+    if let Some(using_store) = stores_data.configs.iter().find(|store| {
+        store.using && inherits_from(&stores_data.configs, &store.id, &updated_store.id)
+    }) {
+        apply_store_settings(paths, Some(using_store))?;
+    }
+
+    // Projects bound to this store or its descendants get the new settings too
+    bindings::refresh_store_bindings(paths, &updated_store.id)?;
+
+    if let Err(e) = vault::prune_secrets(paths) {
+        eprintln!("Warning: Failed to remove unused secrets: {}", e);
+    }
+
+    // Automatically unlock CC extension when updating config
+    if let Err(e) = settings::unlock_cc_ext(paths) {
+        eprintln!("Warning: Failed to unlock CC extension: {}", e);
+    }
+
+    Ok(updated_store)
+}
+
+pub fn delete_config(paths: &Paths, store_id: &str) -> Result<(), String> {
+    // Children would be left with a missing parent
+    if let Some(stores_data) = read_stores_data(paths)? {
+        if let Some(child) = stores_data
+            .configs
+            .iter()
+            .find(|store| store.parent.as_deref() == Some(store_id))
+        {
+            return Err(format!(
+                "Cannot delete store '{}': \"{}\" inherits from it",
+                store_id, child.title
+            ));
+        }
+    }
+
+    // Take the store's settings back out of any projects it is bound to
+    bindings::unbind_store(paths, store_id)?;
+
+    update_existing_stores_data(paths, |stores_data| {
+        // Find and remove store by ID
+        let original_len = stores_data.configs.len();
+        stores_data.configs.retain(|store| store.id != store_id);
+
+        if stores_data.configs.len() == original_len {
+            return Err("Store not found".to_string());
+        }
+        Ok(())
+    })?;
+
+    if let Err(e) = vault::prune_secrets(paths) {
+        eprintln!("Warning: Failed to remove unused secrets: {}", e);
+    }
+    Ok(())
+}
+
+pub fn set_using_config(paths: &Paths, store_id: &str) -> Result<(), String> {
+    if !paths.stores_file().exists() {
+        return Err("Stores file does not exist".to_string());
+    }
+
+    // Find the store and check if it exists
+    let selected_store = get_stores(paths)?
+        .into_iter()
+        .find(|store| store.id == store_id)
+        .ok_or_else(|| "Store not found".to_string())?;
+
+    // Swap the previous store's contributions to settings.json for the selected store's
+    apply_store_settings(paths, Some(&selected_store))
+}
+
+pub fn reset_to_original_config(paths: &Paths) -> Result<(), String> {
+    let Some(stores_data) = read_stores_data(paths)? else {
+        // Clear env field in settings.json
+        return settings::clear_user_env(paths);
+    };
+
+    if stores_data.applied.is_none() && !stores_data.configs.iter().any(|store| store.using) {
+        // Nothing known to revert, fall back to clearing the env field
+        update_existing_stores_data(paths, |stores_data| {
+            for store in &mut stores_data.configs {
+                store.using = false;
+            }
+            Ok(())
+        })?;
+        return settings::clear_user_env(paths);
+    }
+
+    // Remove everything the active store contributed and deactivate all stores
+    apply_store_settings(paths, None)
+}
+
+/// Default merge strategies with the user's overrides from stores.json applied
+pub fn merge_strategies(stores_data: &StoresData) -> MergeStrategies {
+    let mut strategies = merge::default_strategies();
+    if let Some(overrides) = &stores_data.merge_strategies {
+        strategies.extend(overrides.clone());
+    }
+    strategies
+}
+
+/// Changes the active store made to settings.json. Stores activated before
+/// changes were recorded get them inferred from their current settings.
+fn previous_changes(
+    paths: &Paths,
+    stores_data: &StoresData,
+    strategies: &MergeStrategies,
+) -> Result<Vec<MergeChange>, String> {
+    if let Some(applied) = &stores_data.applied {
+        return Ok(applied.changes.clone());
+    }
+
+    match stores_data.configs.iter().find(|store| store.using) {
+        Some(store) => {
+            let store_settings = resolve_store_settings(stores_data, &store.id)
+                .unwrap_or_else(|_| store.settings.clone());
+            // Compare against what was actually written, tokens included
+            let store_settings = vault::reveal(paths, &store_settings).unwrap_or(store_settings);
+            Ok(merge::infer_changes(
+                &settings::read_user_settings(paths)?,
+                &store_settings,
+                strategies,
+            ))
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/// Make `store` the active one (or none), so that settings.json becomes the
+/// user's base settings plus exactly that store's settings.
+fn apply_store_settings(paths: &Paths, store: Option<&ConfigStore>) -> Result<(), String> {
+    // Held until the new changes are recorded, so a concurrent switch can't revert
+    // against an `applied` record this one is about to replace
+    let lock = fsio::lock(paths, &paths.stores_file())?;
+    let stores_data = read_stores_data(paths)?.unwrap_or_default();
+    let strategies = merge_strategies(&stores_data);
+
+    // Layer the store over its ancestors and refuse settings that would break Claude Code
+    let store_settings = store
+        .map(|store| resolve_store_settings(&stores_data, &store.id))
+        .transpose()?;
+    if let Some(store_settings) = &store_settings {
+        settings_schema::ensure_valid_settings(store_settings)?;
+    }
+
+    // Secrets are only resolved for the write; the recorded changes keep their handles
+    let (store_settings, secrets) = match store_settings {
+        Some(store_settings) => {
+            let (mut revealed, secrets) = vault::reveal_with_secrets(paths, &store_settings)?;
+            relay::route_env(paths, &mut revealed, None)?;
+            (Some(revealed), secrets)
+        }
+        None => (None, vec![]),
+    };
+    let previous =
+        vault::reveal_changes(paths, &previous_changes(paths, &stores_data, &strategies)?)?;
+    let mut changes =
+        settings::switch_user_settings(paths, &previous, store_settings.as_ref(), &strategies)?;
+    vault::conceal_changes(paths, &mut changes, &secrets)?;
+
+    // Set all stores to not using, then set the selected one to using
+    update_locked_stores_data(&lock, paths, |stores_data| {
+        for config in &mut stores_data.configs {
+            config.using = store.is_some_and(|store| store.id == config.id);
+        }
+        stores_data.applied = store.map(|store| AppliedSettings {
+            store_id: store.id.clone(),
+            changes: changes.clone(),
+        });
+        Ok(())
+    })?;
+    drop(lock);
+
+    // The user's own secrets were moved into the vault again, drop the copies the old record held
+    if let Err(e) = vault::prune_secrets(paths) {
+        eprintln!("Warning: Failed to remove unused secrets: {}", e);
+    }
+    Ok(())
+}
+
+/// The store followed by its ancestors, nearest first.
+fn inheritance_chain<'a>(
+    configs: &'a [ConfigStore],
+    store_id: &str,
+) -> Result<Vec<&'a ConfigStore>, String> {
+    let mut chain: Vec<&ConfigStore> = Vec::new();
+    let mut next = Some(store_id);
+
+    while let Some(id) = next {
+        let store =
+            configs
+                .iter()
+                .find(|store| store.id == id)
+                .ok_or_else(|| match chain.last() {
+                    Some(child) => {
+                        format!("Parent store '{}' of \"{}\" not found", id, child.title)
+                    }
+                    None => format!("Store with id '{}' not found", id),
+                })?;
+
+        if chain.iter().any(|seen| seen.id == store.id) {
+            let titles: Vec<&str> = chain
+                .iter()
+                .chain(std::iter::once(&store))
+                .map(|store| store.title.as_str())
+                .collect();
+            return Err(format!("Inheritance cycle: {}", titles.join(" → ")));
+        }
+
+        chain.push(store);
+        next = store.parent.as_deref();
+    }
+
+    Ok(chain)
+}
+
+/// Whether `store_id` is `ancestor_id` or inherits from it, directly or not
+pub fn inherits_from(configs: &[ConfigStore], store_id: &str, ancestor_id: &str) -> bool {
+    inheritance_chain(configs, store_id)
+        .is_ok_and(|chain| chain.iter().any(|store| store.id == ancestor_id))
+}
+
+/// Reject a parent that does not exist or would make `store_id` inherit from itself.
+fn ensure_valid_parent(
+    configs: &[ConfigStore],
+    store_id: &str,
+    parent: Option<&str>,
+) -> Result<(), String> {
+    let Some(parent) = parent else {
+        return Ok(());
+    };
+    if parent == store_id {
+        return Err("A store cannot inherit from itself".to_string());
+    }
+
+    if !configs.iter().any(|store| store.id == parent) {
+        return Err(format!("Parent store '{}' not found", parent));
+    }
+
+    let chain = inheritance_chain(configs, parent)?;
+    if let Some(index) = chain.iter().position(|store| store.id == store_id) {
+        let titles: Vec<&str> = chain[..=index]
+            .iter()
+            .map(|store| store.title.as_str())
+            .collect();
+        return Err(format!(
+            "Inheritance cycle: {} → {}",
+            chain[index].title,
+            titles.join(" → ")
+        ));
+    }
+    Ok(())
+}
+
+/// Settings of a store layered over its ancestors', root first, with the configured merge strategies
+pub fn resolve_store_settings(stores_data: &StoresData, store_id: &str) -> Result<Value, String> {
+    let strategies = merge_strategies(stores_data);
+    let mut resolved = Value::Object(serde_json::Map::new());
+    for store in inheritance_chain(&stores_data.configs, store_id)?
+        .iter()
+        .rev()
+    {
+        merge::apply(&mut resolved, &store.settings, &strategies)?;
+    }
+    Ok(resolved)
+}
+
+/// Effective settings of a store after inheritance
+pub fn get_resolved_store_settings(paths: &Paths, store_id: &str) -> Result<Value, String> {
+    let stores_data =
+        read_stores_data(paths)?.ok_or_else(|| "Stores file does not exist".to_string())?;
+    resolve_store_settings(&stores_data, store_id)
+}
+
+/// Effective merge strategies, defaults plus the user's overrides
+pub fn get_merge_strategies(paths: &Paths) -> Result<MergeStrategies, String> {
+    Ok(merge_strategies(
+        &read_stores_data(paths)?.unwrap_or_default(),
+    ))
+}
+
+/// Replace the per-key merge strategy overrides
+pub fn update_merge_strategies(paths: &Paths, overrides: MergeStrategies) -> Result<(), String> {
+    update_stores_data(paths, |stores_data| {
+        stores_data.merge_strategies = (!overrides.is_empty()).then(|| overrides.clone());
+        Ok(())
+    })
+}
+
+pub fn get_notification_settings(paths: &Paths) -> Result<Option<NotificationSettings>, String> {
+    Ok(read_stores_data(paths)?.and_then(|stores_data| stores_data.notification))
+}
+
+pub fn update_notification_settings(
+    paths: &Paths,
+    settings: NotificationSettings,
+) -> Result<(), String> {
+    // Creates stores.json with notification settings if it doesn't exist
+    update_stores_data(paths, |stores_data| {
+        stores_data.notification = Some(settings.clone());
+        Ok(())
+    })?;
+
+    // The hook is only installed for the events that are enabled
+    crate::hooks::sync_claude_code_hook(paths)?;
+
+    eprintln!("✅ Notification settings updated successfully");
+    Ok(())
+}
+
+/// Get or create the anonymous distinct_id stored in stores.json
+pub fn get_or_create_distinct_id(paths: &Paths) -> Result<String, String> {
+    // Return existing distinct_id without taking the write lock
+    if let Some(id) = read_stores_data(paths)?.and_then(|stores_data| stores_data.distinct_id) {
+        return Ok(id);
+    }
+
+    update_stores_data(paths, |stores_data| {
+        let id = stores_data
+            .distinct_id
+            .get_or_insert_with(|| {
+                // Generate new UUID
+                let new_id = Uuid::new_v4().to_string();
+                eprintln!("Created new distinct_id: {}", new_id);
+                new_id
+            })
+            .clone();
+        Ok(id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn store(id: &str, parent: Option<&str>, settings: Value) -> ConfigStore {
+        ConfigStore {
+            id: id.to_string(),
+            title: id.to_uppercase(),
+            created_at: 0,
+            settings,
+            using: false,
+            parent: parent.map(str::to_string),
+        }
+    }
+
+    fn family() -> Vec<ConfigStore> {
+        vec![
+            store(
+                "base",
+                None,
+                json!({ "model": "sonnet", "env": { "A": "base", "B": "base" }, "permissions": { "allow": ["Read"] } }),
+            ),
+            store(
+                "team",
+                Some("base"),
+                json!({ "env": { "B": "team", "C": "team" }, "permissions": { "allow": ["Edit"] } }),
+            ),
+            store(
+                "mine",
+                Some("team"),
+                json!({ "model": "opus", "env": { "C": "mine" } }),
+            ),
+        ]
+    }
+
+    fn ids(chain: Vec<&ConfigStore>) -> Vec<&str> {
+        chain.iter().map(|store| store.id.as_str()).collect()
+    }
+
+    #[test]
+    fn chain_lists_the_store_then_its_ancestors() {
+        let configs = family();
+        assert_eq!(
+            ids(inheritance_chain(&configs, "mine").unwrap()),
+            ["mine", "team", "base"]
+        );
+        assert_eq!(ids(inheritance_chain(&configs, "base").unwrap()), ["base"]);
+        assert!(inherits_from(&configs, "mine", "base"));
+        assert!(!inherits_from(&configs, "base", "mine"));
+    }
+
+    #[test]
+    fn chain_reports_missing_parents_and_cycles() {
+        let error =
+            inheritance_chain(&[store("orphan", Some("gone"), json!({}))], "orphan").unwrap_err();
+        assert_eq!(error, "Parent store 'gone' of \"ORPHAN\" not found");
+        assert_eq!(
+            inheritance_chain(&family(), "nope").unwrap_err(),
+            "Store with id 'nope' not found"
+        );
+
+        // Written by hand or by an older version, a cycle must not loop forever
+        let configs = [
+            store("a", Some("b"), json!({})),
+            store("b", Some("a"), json!({})),
+        ];
+        assert_eq!(
+            inheritance_chain(&configs, "a").unwrap_err(),
+            "Inheritance cycle: A → B → A"
+        );
+        let configs = [store("a", Some("a"), json!({}))];
+        assert_eq!(
+            inheritance_chain(&configs, "a").unwrap_err(),
+            "Inheritance cycle: A → A"
+        );
+    }
+
+    #[test]
+    fn parent_must_exist_and_not_lead_back() {
+        let configs = family();
+        assert!(ensure_valid_parent(&configs, "mine", None).is_ok());
+        assert!(ensure_valid_parent(&configs, "mine", Some("base")).is_ok());
+        assert!(ensure_valid_parent(&configs, "new", Some("mine")).is_ok());
+
+        assert_eq!(
+            ensure_valid_parent(&configs, "mine", Some("mine")).unwrap_err(),
+            "A store cannot inherit from itself"
+        );
+        assert_eq!(
+            ensure_valid_parent(&configs, "mine", Some("gone")).unwrap_err(),
+            "Parent store 'gone' not found"
+        );
+        assert_eq!(
+            ensure_valid_parent(&configs, "base", Some("team")).unwrap_err(),
+            "Inheritance cycle: BASE → TEAM → BASE"
+        );
+        assert_eq!(
+            ensure_valid_parent(&configs, "base", Some("mine")).unwrap_err(),
+            "Inheritance cycle: BASE → MINE → TEAM → BASE"
+        );
+    }
+
+    #[test]
+    fn resolved_settings_layer_children_over_ancestors() {
+        let stores_data = StoresData {
+            configs: family(),
+            ..StoresData::default()
+        };
+        assert_eq!(
+            resolve_store_settings(&stores_data, "mine").unwrap(),
+            json!({
+                "model": "opus",
+                "env": { "A": "base", "B": "team", "C": "mine" },
+                "permissions": { "allow": ["Read", "Edit"] }
+            })
+        );
+        assert_eq!(
+            resolve_store_settings(&stores_data, "team").unwrap()["model"],
+            "sonnet"
+        );
+    }
+}
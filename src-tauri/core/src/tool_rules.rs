@@ -0,0 +1,499 @@
+//! Rules that decide Claude Code's tool calls without asking the user.
+//!
+//! Rules are kept in `~/.ccconfig/tool_rules.json` and evaluated by the hook
+//! server against every PreToolUse event. The first enabled rule matching the
+//! call decides it, and the hook response tells Claude Code the decision and
+//! why. In dry-run mode matches are only recorded, see
+//! [`crate::hook_log::record_rule_decision`], and the call goes on as if no
+//! rule matched.
+
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::approval::PermissionDecision;
+use crate::fsio;
+use crate::hooks;
+use crate::paths::Paths;
+
+// Tool input fields holding the file a tool works on
+const PATH_FIELDS: [&str; 3] = ["file_path", "notebook_path", "path"];
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct ToolRuleSettings {
+    pub enabled: bool,
+    /// Log what the rules would decide instead of deciding
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Evaluated in order, the first matching rule wins
+    #[serde(default)]
+    pub rules: Vec<ToolRule>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ToolRule {
+    /// Generated when empty
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Tool names, a trailing `*` matches a prefix such as `mcp__*`. Every tool if empty.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    pub condition: ToolCondition,
+    pub action: RuleAction,
+    /// Told to Claude Code with the decision, generated from the rule name when empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolCondition {
+    /// Every call of the rule's tools
+    Always,
+    /// A text field of the tool input, e.g. `command`, matches the regular expression
+    InputMatches { field: String, pattern: String },
+    /// The file the tool works on is outside the session's working directory.
+    /// Symlinks in the part of either path that exists are resolved first.
+    OutsideCwd,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Deny,
+    /// Leave it to Claude Code's own permission prompt
+    Ask,
+    /// Hold the call until it is answered in the app, see [`crate::approval`]
+    Approve,
+}
+
+impl RuleAction {
+    /// Decision to answer with right away, `None` when the user has to approve first
+    pub fn decision(self) -> Option<PermissionDecision> {
+        match self {
+            RuleAction::Allow => Some(PermissionDecision::Allow),
+            RuleAction::Deny => Some(PermissionDecision::Deny),
+            RuleAction::Ask => Some(PermissionDecision::Ask),
+            RuleAction::Approve => None,
+        }
+    }
+}
+
+/// The rule deciding a tool call
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RuleMatch {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub action: RuleAction,
+    pub reason: String,
+}
+
+pub fn get_tool_rule_settings(paths: &Paths) -> Result<ToolRuleSettings, String> {
+    Ok(fsio::read_json(&paths.tool_rules_file(), "tool rules")?.unwrap_or_default())
+}
+
+/// Validate and save the rules, generating ids for new ones.
+pub fn update_tool_rule_settings(
+    paths: &Paths,
+    mut settings: ToolRuleSettings,
+) -> Result<ToolRuleSettings, String> {
+    for rule in &mut settings.rules {
+        if rule.id.is_empty() {
+            rule.id = nanoid::nanoid!(8);
+        }
+        rule.name = rule.name.trim().to_string();
+        rule.tools = rule
+            .tools
+            .iter()
+            .map(|tool| tool.trim().to_string())
+            .filter(|tool| !tool.is_empty())
+            .collect();
+    }
+    ensure_valid_settings(&settings)?;
+
+    fsio::write_json(paths, &paths.tool_rules_file(), "tool rules", &settings)?;
+
+    // Rules are evaluated on the PreToolUse hook, installed while they are enabled
+    hooks::sync_claude_code_hook(paths)?;
+    Ok(settings)
+}
+
+fn ensure_valid_settings(settings: &ToolRuleSettings) -> Result<(), String> {
+    let mut ids: HashSet<&str> = HashSet::new();
+    for rule in &settings.rules {
+        if !ids.insert(&rule.id) {
+            return Err(format!("Duplicate rule id '{}'", rule.id));
+        }
+        if rule.name.is_empty() {
+            return Err("Every rule needs a name".to_string());
+        }
+
+        let invalid = |message: String| format!("Rule \"{}\": {}", rule.name, message);
+        if let ToolCondition::InputMatches { field, pattern } = &rule.condition {
+            if field.trim().is_empty() {
+                return Err(invalid("no tool input field given".to_string()));
+            }
+            Regex::new(pattern)
+                .map_err(|e| invalid(format!("invalid regular expression: {}", e)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Rules with their regular expressions compiled, ready to evaluate
+#[derive(Debug)]
+pub struct CompiledToolRules {
+    pub settings: ToolRuleSettings,
+    // Pattern of each rule with an `InputMatches` condition, by rule index
+    patterns: Vec<Option<Regex>>,
+}
+
+impl CompiledToolRules {
+    /// Compile the rules' patterns. A rule with an invalid pattern never matches.
+    pub fn new(settings: ToolRuleSettings) -> Self {
+        let patterns = settings
+            .rules
+            .iter()
+            .map(|rule| match &rule.condition {
+                ToolCondition::InputMatches { pattern, .. } => Regex::new(pattern).ok(),
+                _ => None,
+            })
+            .collect();
+        Self { settings, patterns }
+    }
+
+    /// The first enabled rule matching a call of `tool_name` made in `cwd`
+    pub fn evaluate(&self, tool_name: &str, tool_input: &Value, cwd: &str) -> Option<RuleMatch> {
+        let rule = self
+            .settings
+            .rules
+            .iter()
+            .zip(&self.patterns)
+            .find(|(rule, pattern)| {
+                rule.enabled
+                    && matches_tool(&rule.tools, tool_name)
+                    && matches_condition(&rule.condition, pattern.as_ref(), tool_input, cwd)
+            })
+            .map(|(rule, _)| rule)?;
+
+        let reason = if rule.reason.is_empty() {
+            let verb = match rule.action {
+                RuleAction::Allow => "Allowed",
+                RuleAction::Deny => "Denied",
+                RuleAction::Ask | RuleAction::Approve => "Needs approval",
+            };
+            format!("{} by TVC Buddy rule \"{}\"", verb, rule.name)
+        } else {
+            rule.reason.clone()
+        };
+        Some(RuleMatch {
+            rule_id: rule.id.clone(),
+            rule_name: rule.name.clone(),
+            action: rule.action,
+            reason,
+        })
+    }
+}
+
+/// Compiled rules of `tool_rules.json`, loaded again only when the file changes
+#[derive(Debug, Default)]
+pub struct ToolRuleCache {
+    loaded: Option<(fsio::Fingerprint, Arc<CompiledToolRules>)>,
+}
+
+impl ToolRuleCache {
+    pub const fn new() -> Self {
+        Self { loaded: None }
+    }
+
+    pub fn load(&mut self, paths: &Paths) -> Result<Arc<CompiledToolRules>, String> {
+        let fingerprint = fsio::Fingerprint::of(&paths.tool_rules_file())?;
+        if let Some((loaded_from, rules)) = &self.loaded {
+            if *loaded_from == fingerprint {
+                return Ok(rules.clone());
+            }
+        }
+
+        let rules = Arc::new(CompiledToolRules::new(get_tool_rule_settings(paths)?));
+        self.loaded = Some((fingerprint, rules.clone()));
+        Ok(rules)
+    }
+}
+
+/// The first enabled rule of `rules` matching a call of `tool_name` made in `cwd`
+pub fn evaluate(
+    rules: &[ToolRule],
+    tool_name: &str,
+    tool_input: &Value,
+    cwd: &str,
+) -> Option<RuleMatch> {
+    let settings = ToolRuleSettings {
+        rules: rules.to_vec(),
+        ..Default::default()
+    };
+    CompiledToolRules::new(settings).evaluate(tool_name, tool_input, cwd)
+}
+
+fn matches_tool(tools: &[String], tool_name: &str) -> bool {
+    tools.is_empty()
+        || tools.iter().any(|tool| match tool.strip_suffix('*') {
+            Some(prefix) => tool_name.starts_with(prefix),
+            None => tool == tool_name,
+        })
+}
+
+fn matches_condition(
+    condition: &ToolCondition,
+    pattern: Option<&Regex>,
+    tool_input: &Value,
+    cwd: &str,
+) -> bool {
+    match condition {
+        ToolCondition::Always => true,
+        ToolCondition::InputMatches { field, .. } => {
+            let text = tool_input.get(field.trim()).and_then(|v| v.as_str());
+            match (text, pattern) {
+                (Some(text), Some(pattern)) => pattern.is_match(text),
+                _ => false,
+            }
+        }
+        ToolCondition::OutsideCwd => {
+            let file = PATH_FIELDS
+                .iter()
+                .find_map(|field| tool_input.get(*field).and_then(|v| v.as_str()));
+            match file {
+                Some(file) if !cwd.is_empty() => {
+                    let cwd = resolve(Path::new(cwd));
+                    !resolve(&cwd.join(file)).starts_with(&cwd)
+                }
+                _ => false,
+            }
+        }
+    }
+}
+
+// Resolve symlinks, `.` and `..` in the longest part of `path` that exists, and
+// `.` and `..` in the rest without touching the file system, as the file may not exist yet
+fn resolve(path: &Path) -> PathBuf {
+    let existing = path.ancestors().find_map(|ancestor| {
+        let canonical = std::fs::canonicalize(ancestor).ok()?;
+        Some((ancestor, canonical))
+    });
+    match existing {
+        Some((ancestor, canonical)) => match path.strip_prefix(ancestor) {
+            Ok(rest) => normalize(&canonical.join(rest)),
+            Err(_) => normalize(path),
+        },
+        None => normalize(path),
+    }
+}
+
+// Resolve `.` and `..` without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(name: &str, tools: &[&str], condition: ToolCondition, action: RuleAction) -> ToolRule {
+        ToolRule {
+            id: name.to_string(),
+            name: name.to_string(),
+            enabled: true,
+            tools: tools.iter().map(|tool| tool.to_string()).collect(),
+            condition,
+            action,
+            reason: String::new(),
+        }
+    }
+
+    fn input_matches(field: &str, pattern: &str) -> ToolCondition {
+        ToolCondition::InputMatches {
+            field: field.to_string(),
+            pattern: pattern.to_string(),
+        }
+    }
+
+    #[test]
+    fn matches_tools_by_name_or_prefix() {
+        assert!(matches_tool(&[], "Bash"));
+        assert!(matches_tool(&["Bash".to_string()], "Bash"));
+        assert!(!matches_tool(&["Bash".to_string()], "BashOutput"));
+
+        let mcp = ["mcp__*".to_string()];
+        assert!(matches_tool(&mcp, "mcp__github__create_issue"));
+        assert!(!matches_tool(&mcp, "Bash"));
+        assert!(matches_tool(&["*".to_string()], "Write"));
+        assert!(matches_tool(
+            &["Read".to_string(), "mcp__fs__*".to_string()],
+            "mcp__fs__read"
+        ));
+    }
+
+    #[test]
+    fn normalizes_dots_lexically() {
+        assert_eq!(normalize(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+        assert_eq!(
+            normalize(Path::new("/a/b/../../../etc")),
+            PathBuf::from("/etc")
+        );
+        assert_eq!(normalize(Path::new("a/b/./")), PathBuf::from("a/b"));
+    }
+
+    #[test]
+    fn first_enabled_matching_rule_decides() {
+        let mut disabled = rule(
+            "disabled",
+            &["Bash"],
+            ToolCondition::Always,
+            RuleAction::Allow,
+        );
+        disabled.enabled = false;
+        let rules = vec![
+            disabled,
+            rule(
+                "no rm",
+                &["Bash"],
+                input_matches("command", r"^rm\s+-rf"),
+                RuleAction::Deny,
+            ),
+            rule(
+                "mcp",
+                &["mcp__*"],
+                ToolCondition::Always,
+                RuleAction::Approve,
+            ),
+            rule("bash", &["Bash"], ToolCondition::Always, RuleAction::Ask),
+        ];
+
+        let matched =
+            evaluate(&rules, "Bash", &json!({ "command": "rm -rf build" }), "/p").unwrap();
+        assert_eq!(matched.rule_id, "no rm");
+        assert_eq!(matched.action, RuleAction::Deny);
+        assert_eq!(matched.reason, "Denied by TVC Buddy rule \"no rm\"");
+
+        let matched = evaluate(&rules, "Bash", &json!({ "command": "ls" }), "/p").unwrap();
+        assert_eq!(matched.rule_id, "bash");
+
+        let matched = evaluate(&rules, "mcp__github__merge", &json!({}), "/p").unwrap();
+        assert_eq!(matched.action.decision(), None);
+
+        assert!(evaluate(&rules, "Read", &json!({ "file_path": "/p/a" }), "/p").is_none());
+    }
+
+    #[test]
+    fn input_conditions_need_the_field_and_a_valid_pattern() {
+        let mut custom = rule(
+            "custom",
+            &[],
+            input_matches("url", "internal"),
+            RuleAction::Deny,
+        );
+        custom.reason = "No internal URLs".to_string();
+        let rules = vec![
+            rule(
+                "invalid",
+                &[],
+                input_matches("command", "("),
+                RuleAction::Allow,
+            ),
+            custom,
+        ];
+
+        assert!(evaluate(&rules, "Bash", &json!({ "command": "(" }), "/p").is_none());
+        assert!(evaluate(&rules, "WebFetch", &json!({ "url": 42 }), "/p").is_none());
+        let matched = evaluate(
+            &rules,
+            "WebFetch",
+            &json!({ "url": "https://internal.example" }),
+            "/p",
+        )
+        .unwrap();
+        assert_eq!(matched.reason, "No internal URLs");
+    }
+
+    #[test]
+    fn outside_cwd_compares_resolved_paths() {
+        let rules = vec![rule(
+            "outside",
+            &[],
+            ToolCondition::OutsideCwd,
+            RuleAction::Deny,
+        )];
+        let outside = |file: &str, cwd: &str| {
+            evaluate(&rules, "Write", &json!({ "file_path": file }), cwd).is_some()
+        };
+
+        assert!(!outside("src/new/file.rs", "/nonexistent/project"));
+        assert!(!outside(
+            "/nonexistent/project/a/../b.rs",
+            "/nonexistent/project"
+        ));
+        assert!(outside("../other/file.rs", "/nonexistent/project"));
+        assert!(outside("/etc/passwd", "/nonexistent/project"));
+        assert!(outside("new/../../../etc/passwd", "/nonexistent/project"));
+        // Without a working directory there is nothing to compare with
+        assert!(!outside("/etc/passwd", ""));
+        assert!(evaluate(&rules, "Bash", &json!({ "command": "ls /" }), "/p").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn outside_cwd_follows_symlinks() {
+        let root = std::env::temp_dir().join(format!("tvcbuddy-rules-{}", std::process::id()));
+        let project = root.join("project");
+        let elsewhere = root.join("elsewhere");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(&elsewhere).unwrap();
+        std::os::unix::fs::symlink(&elsewhere, project.join("link")).unwrap();
+        std::os::unix::fs::symlink(&project, root.join("project-link")).unwrap();
+
+        let rules = vec![rule(
+            "outside",
+            &[],
+            ToolCondition::OutsideCwd,
+            RuleAction::Deny,
+        )];
+        let outside = |file: &str, cwd: &Path| {
+            evaluate(
+                &rules,
+                "Write",
+                &json!({ "file_path": file }),
+                &cwd.to_string_lossy(),
+            )
+            .is_some()
+        };
+        assert!(outside("link/new.txt", &project));
+        assert!(!outside("src/new.txt", &project));
+        // A working directory reached through a symlink is the same directory
+        assert!(!outside(
+            &project.join("a.txt").to_string_lossy(),
+            &root.join("project-link")
+        ));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,975 @@
+//! Translation between the Anthropic Messages API and OpenAI's Chat Completions
+//! and Responses APIs, for profiles whose endpoint only speaks OpenAI.
+//!
+//! The relay turns Claude Code's Messages requests into the profile's wire API,
+//! and the answers back into Messages responses or stream events, covering
+//! system prompts, images, tool calls and their results, stop reasons and
+//! token usage.
+
+use serde_json::{json, Value};
+
+/// API a Claude Code profile's endpoint speaks
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WireApi {
+    /// Anthropic Messages, forwarded as is
+    #[default]
+    Anthropic,
+    /// OpenAI Chat Completions
+    Chat,
+    /// OpenAI Responses
+    Responses,
+}
+
+impl WireApi {
+    /// Path requests are sent to, relative to the profile's base URL
+    pub fn endpoint(self) -> &'static str {
+        match self {
+            WireApi::Anthropic => "v1/messages",
+            WireApi::Chat => "chat/completions",
+            WireApi::Responses => "responses",
+        }
+    }
+}
+
+/// Turn a Messages request into a request for `api`
+pub fn to_openai_request(api: WireApi, body: &[u8]) -> Result<Vec<u8>, String> {
+    let request: Value =
+        serde_json::from_slice(body).map_err(|e| format!("Invalid request body: {}", e))?;
+    let translated = match api {
+        WireApi::Anthropic => request,
+        WireApi::Chat => to_chat_request(&request)?,
+        WireApi::Responses => to_responses_request(&request)?,
+    };
+    serde_json::to_vec(&translated).map_err(|e| format!("Failed to serialize request: {}", e))
+}
+
+fn to_chat_request(request: &Value) -> Result<Value, String> {
+    let mut messages = Vec::new();
+    if let Some(system) = system_text(request) {
+        messages.push(json!({ "role": "system", "content": system }));
+    }
+
+    for message in request_messages(request)? {
+        let blocks = content_blocks(&message["content"]);
+        if message["role"] == "assistant" {
+            let text = joined_text(&blocks);
+            let tool_calls: Vec<Value> = blocks
+                .iter()
+                .filter(|block| block["type"] == "tool_use")
+                .map(|block| {
+                    json!({
+                        "id": block["id"],
+                        "type": "function",
+                        "function": { "name": block["name"], "arguments": block["input"].to_string() },
+                    })
+                })
+                .collect();
+            let mut translated = json!({
+                "role": "assistant",
+                "content": if text.is_empty() { Value::Null } else { Value::String(text) },
+            });
+            if !tool_calls.is_empty() {
+                translated["tool_calls"] = Value::Array(tool_calls);
+            }
+            messages.push(translated);
+            continue;
+        }
+
+        // Tool results become tool messages, which have to follow the assistant's calls
+        let mut parts = Vec::new();
+        for block in &blocks {
+            match block["type"].as_str() {
+                Some("tool_result") => messages.push(json!({
+                    "role": "tool",
+                    "tool_call_id": block["tool_use_id"],
+                    "content": tool_result_text(block),
+                })),
+                Some("text") => parts.push(json!({ "type": "text", "text": block["text"] })),
+                Some("image") => {
+                    if let Some(url) = image_url(block) {
+                        parts.push(json!({ "type": "image_url", "image_url": { "url": url } }));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if parts.iter().all(|part| part["type"] == "text") {
+            let text = joined_text(&parts);
+            if !text.is_empty() {
+                messages.push(json!({ "role": "user", "content": text }));
+            }
+        } else {
+            messages.push(json!({ "role": "user", "content": parts }));
+        }
+    }
+
+    let mut translated = json!({ "model": request["model"], "messages": messages });
+    copy_fields(
+        request,
+        &mut translated,
+        &[
+            (
+                "max_tokens",
+                chat_max_tokens_field(request["model"].as_str().unwrap_or("")),
+            ),
+            ("temperature", "temperature"),
+            ("top_p", "top_p"),
+            ("stop_sequences", "stop"),
+        ],
+    );
+    if request["stream"] == true {
+        translated["stream"] = Value::Bool(true);
+        translated["stream_options"] = json!({ "include_usage": true });
+    }
+    if let Some(tools) = request["tools"].as_array() {
+        translated["tools"] = tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool["name"],
+                        "description": tool["description"],
+                        "parameters": tool["input_schema"],
+                    },
+                })
+            })
+            .collect();
+    }
+    if let Some(choice) = tool_choice(request) {
+        translated["tool_choice"] = match choice {
+            ToolChoice::Named(name) => json!({ "type": "function", "function": { "name": name } }),
+            other => Value::String(other.mode().to_string()),
+        };
+    }
+    Ok(translated)
+}
+
+/// Chat field limiting the output: reasoning models (o-series, gpt-5) reject `max_tokens`
+pub fn chat_max_tokens_field(model: &str) -> &'static str {
+    let model = model.to_ascii_lowercase();
+    // Routers name models like `openai/o3-mini`
+    let name = model.rsplit('/').next().unwrap_or("");
+    let mut chars = name.chars();
+    let o_series = chars.next() == Some('o') && chars.next().is_some_and(|c| c.is_ascii_digit());
+    if o_series || name.starts_with("gpt-5") {
+        "max_completion_tokens"
+    } else {
+        "max_tokens"
+    }
+}
+
+fn to_responses_request(request: &Value) -> Result<Value, String> {
+    let mut input = Vec::new();
+    for message in request_messages(request)? {
+        let blocks = content_blocks(&message["content"]);
+        if message["role"] == "assistant" {
+            let text = joined_text(&blocks);
+            if !text.is_empty() {
+                input.push(json!({
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [{ "type": "output_text", "text": text }],
+                }));
+            }
+            for block in blocks.iter().filter(|block| block["type"] == "tool_use") {
+                input.push(json!({
+                    "type": "function_call",
+                    "call_id": block["id"],
+                    "name": block["name"],
+                    "arguments": block["input"].to_string(),
+                }));
+            }
+            continue;
+        }
+
+        let mut parts = Vec::new();
+        for block in &blocks {
+            match block["type"].as_str() {
+                Some("tool_result") => input.push(json!({
+                    "type": "function_call_output",
+                    "call_id": block["tool_use_id"],
+                    "output": tool_result_text(block),
+                })),
+                Some("text") => parts.push(json!({ "type": "input_text", "text": block["text"] })),
+                Some("image") => {
+                    if let Some(url) = image_url(block) {
+                        parts.push(json!({ "type": "input_image", "image_url": url }));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !parts.is_empty() {
+            input.push(json!({ "type": "message", "role": "user", "content": parts }));
+        }
+    }
+
+    let mut translated = json!({ "model": request["model"], "input": input, "store": false });
+    if let Some(system) = system_text(request) {
+        translated["instructions"] = Value::String(system);
+    }
+    copy_fields(
+        request,
+        &mut translated,
+        &[("temperature", "temperature"), ("top_p", "top_p")],
+    );
+    // The Responses API rejects fewer than 16 output tokens
+    if let Some(max_tokens) = request["max_tokens"].as_u64() {
+        translated["max_output_tokens"] = json!(max_tokens.max(16));
+    }
+    if request["stream"] == true {
+        translated["stream"] = Value::Bool(true);
+    }
+    if let Some(tools) = request["tools"].as_array() {
+        translated["tools"] = tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "name": tool["name"],
+                    "description": tool["description"],
+                    "parameters": tool["input_schema"],
+                })
+            })
+            .collect();
+    }
+    if let Some(choice) = tool_choice(request) {
+        translated["tool_choice"] = match choice {
+            ToolChoice::Named(name) => json!({ "type": "function", "name": name }),
+            other => Value::String(other.mode().to_string()),
+        };
+    }
+    Ok(translated)
+}
+
+fn request_messages(request: &Value) -> Result<&Vec<Value>, String> {
+    request["messages"]
+        .as_array()
+        .ok_or_else(|| "Request has no messages".to_string())
+}
+
+/// The system prompt, given as a string or as text blocks
+fn system_text(request: &Value) -> Option<String> {
+    let text = joined_text(&content_blocks(&request["system"]));
+    (!text.is_empty()).then_some(text)
+}
+
+/// Content given as a plain string or as blocks, always as blocks
+fn content_blocks(content: &Value) -> Vec<Value> {
+    match content {
+        Value::String(text) => vec![json!({ "type": "text", "text": text })],
+        Value::Array(blocks) => blocks.clone(),
+        _ => vec![],
+    }
+}
+
+fn joined_text(blocks: &[Value]) -> String {
+    blocks
+        .iter()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn tool_result_text(block: &Value) -> String {
+    let text = joined_text(&content_blocks(&block["content"]));
+    if block["is_error"] == true {
+        format!("Error: {}", text)
+    } else {
+        text
+    }
+}
+
+fn image_url(block: &Value) -> Option<String> {
+    let source = &block["source"];
+    match source["type"].as_str()? {
+        "base64" => Some(format!(
+            "data:{};base64,{}",
+            source["media_type"].as_str()?,
+            source["data"].as_str()?
+        )),
+        "url" => source["url"].as_str().map(str::to_string),
+        _ => None,
+    }
+}
+
+fn copy_fields(from: &Value, to: &mut Value, fields: &[(&str, &str)]) {
+    for (source, target) in fields {
+        if let Some(value) = from.get(*source).filter(|value| !value.is_null()) {
+            to[*target] = value.clone();
+        }
+    }
+}
+
+enum ToolChoice {
+    Auto,
+    Any,
+    None,
+    Named(String),
+}
+
+impl ToolChoice {
+    fn mode(&self) -> &'static str {
+        match self {
+            ToolChoice::Auto | ToolChoice::Named(_) => "auto",
+            ToolChoice::Any => "required",
+            ToolChoice::None => "none",
+        }
+    }
+}
+
+fn tool_choice(request: &Value) -> Option<ToolChoice> {
+    let choice = request.get("tool_choice")?;
+    match choice["type"].as_str()? {
+        "auto" => Some(ToolChoice::Auto),
+        "any" => Some(ToolChoice::Any),
+        "none" => Some(ToolChoice::None),
+        "tool" => Some(ToolChoice::Named(choice["name"].as_str()?.to_string())),
+        _ => None,
+    }
+}
+
+fn chat_stop_reason(finish_reason: &str) -> &'static str {
+    match finish_reason {
+        "length" => "max_tokens",
+        "tool_calls" | "function_call" => "tool_use",
+        _ => "end_turn",
+    }
+}
+
+/// Token usage in Anthropic's terms, where input tokens leave out cached ones
+fn anthropic_usage(api: WireApi, usage: &Value) -> Value {
+    let (input, output, cached) = match api {
+        WireApi::Chat => (
+            &usage["prompt_tokens"],
+            &usage["completion_tokens"],
+            &usage["prompt_tokens_details"]["cached_tokens"],
+        ),
+        _ => (
+            &usage["input_tokens"],
+            &usage["output_tokens"],
+            &usage["input_tokens_details"]["cached_tokens"],
+        ),
+    };
+    let cached = cached.as_u64().unwrap_or(0);
+    json!({
+        "input_tokens": input.as_u64().unwrap_or(0).saturating_sub(cached),
+        "output_tokens": output.as_u64().unwrap_or(0),
+        "cache_read_input_tokens": cached,
+    })
+}
+
+fn tool_use_block(id: &Value, name: &Value, arguments: Option<&str>) -> Value {
+    let input = arguments
+        .and_then(|arguments| serde_json::from_str::<Value>(arguments).ok())
+        .filter(Value::is_object)
+        .unwrap_or_else(|| json!({}));
+    json!({ "type": "tool_use", "id": id, "name": name, "input": input })
+}
+
+fn message_id(id: &Value) -> String {
+    match id.as_str() {
+        Some(id) if !id.is_empty() => id.to_string(),
+        _ => format!("msg_{}", nanoid::nanoid!(24)),
+    }
+}
+
+fn from_chat_response(response: &Value) -> Value {
+    let choice = &response["choices"][0];
+    let message = &choice["message"];
+    let mut content = Vec::new();
+    if let Some(text) = message["content"].as_str().filter(|text| !text.is_empty()) {
+        content.push(json!({ "type": "text", "text": text }));
+    }
+    for call in message["tool_calls"].as_array().into_iter().flatten() {
+        content.push(tool_use_block(
+            &call["id"],
+            &call["function"]["name"],
+            call["function"]["arguments"].as_str(),
+        ));
+    }
+    json!({
+        "id": message_id(&response["id"]),
+        "type": "message",
+        "role": "assistant",
+        "model": response["model"],
+        "content": content,
+        "stop_reason": chat_stop_reason(choice["finish_reason"].as_str().unwrap_or("stop")),
+        "stop_sequence": null,
+        "usage": anthropic_usage(WireApi::Chat, &response["usage"]),
+    })
+}
+
+fn from_responses_response(response: &Value) -> Value {
+    let mut content = Vec::new();
+    for item in response["output"].as_array().into_iter().flatten() {
+        match item["type"].as_str() {
+            Some("message") => {
+                for part in item["content"].as_array().into_iter().flatten() {
+                    let text = part["text"].as_str().or_else(|| part["refusal"].as_str());
+                    if let Some(text) = text.filter(|text| !text.is_empty()) {
+                        content.push(json!({ "type": "text", "text": text }));
+                    }
+                }
+            }
+            Some("function_call") => content.push(tool_use_block(
+                &item["call_id"],
+                &item["name"],
+                item["arguments"].as_str(),
+            )),
+            _ => {}
+        }
+    }
+    let stop_reason = if response["status"] == "incomplete" {
+        "max_tokens"
+    } else if content.iter().any(|block| block["type"] == "tool_use") {
+        "tool_use"
+    } else {
+        "end_turn"
+    };
+    json!({
+        "id": message_id(&response["id"]),
+        "type": "message",
+        "role": "assistant",
+        "model": response["model"],
+        "content": content,
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": anthropic_usage(WireApi::Responses, &response["usage"]),
+    })
+}
+
+/// An error answer in the Anthropic format, so Claude Code shows the endpoint's message
+pub fn anthropic_error(status: u16, body: &[u8]) -> Value {
+    let parsed: Option<Value> = serde_json::from_slice(body).ok();
+    let message = parsed
+        .as_ref()
+        .and_then(|body| {
+            body.pointer("/error/message")
+                .or_else(|| body.get("message"))
+                .or_else(|| body.get("error"))
+        })
+        .and_then(|message| message.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| String::from_utf8_lossy(body).trim().to_string());
+    let error_type = match status {
+        400 | 422 => "invalid_request_error",
+        401 => "authentication_error",
+        403 => "permission_error",
+        404 => "not_found_error",
+        413 => "request_too_large",
+        429 => "rate_limit_error",
+        529 | 503 => "overloaded_error",
+        _ => "api_error",
+    };
+    json!({ "type": "error", "error": { "type": error_type, "message": message } })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Text,
+    /// Tool call, keyed by the Chat tool call index or the Responses item id
+    Tool(String),
+}
+
+/// Turns an OpenAI response into a Messages response as it streams through.
+///
+/// Streams are translated event by event. Whole responses and errors are held
+/// until they are complete and converted at once.
+#[derive(Debug)]
+pub struct ResponseTranslator {
+    api: WireApi,
+    status: u16,
+    streaming: bool,
+    // An incomplete event line, or the whole body so far
+    pending: Vec<u8>,
+    started: bool,
+    finished: bool,
+    block: Option<(usize, Block)>,
+    next_index: usize,
+    // Whether the open tool block got any argument deltas
+    streamed_arguments: bool,
+    saw_tool_call: bool,
+    stop_reason: Option<&'static str>,
+    usage: Value,
+}
+
+impl ResponseTranslator {
+    pub fn new(api: WireApi, status: u16, content_type: Option<&str>) -> Self {
+        Self {
+            api,
+            status,
+            streaming: (200..300).contains(&status)
+                && content_type
+                    .map(|value| value.starts_with("text/event-stream"))
+                    .unwrap_or(false),
+            pending: vec![],
+            started: false,
+            finished: false,
+            block: None,
+            next_index: 0,
+            streamed_arguments: false,
+            saw_tool_call: false,
+            stop_reason: None,
+            usage: json!({ "input_tokens": 0, "output_tokens": 0 }),
+        }
+    }
+
+    /// Content type of the translated response
+    pub fn content_type(&self) -> &'static str {
+        if self.streaming {
+            "text/event-stream"
+        } else {
+            "application/json"
+        }
+    }
+
+    /// The translated bytes to pass on for `chunk`
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+        if !self.streaming {
+            return vec![];
+        }
+
+        let mut output = Vec::new();
+        while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            self.read_line(&line, &mut output);
+        }
+        output
+    }
+
+    /// The rest of the translated response once the upstream is done
+    pub fn finish(mut self) -> Vec<u8> {
+        let rest = std::mem::take(&mut self.pending);
+        if !self.streaming {
+            let translated = match serde_json::from_slice::<Value>(&rest) {
+                Ok(response) if (200..300).contains(&self.status) => match self.api {
+                    WireApi::Chat => from_chat_response(&response),
+                    WireApi::Responses => from_responses_response(&response),
+                    WireApi::Anthropic => response,
+                },
+                _ => anthropic_error(self.status, &rest),
+            };
+            return translated.to_string().into_bytes();
+        }
+
+        let mut output = Vec::new();
+        self.read_line(&rest, &mut output);
+        self.end(&mut output);
+        output
+    }
+
+    fn read_line(&mut self, line: &[u8], output: &mut Vec<u8>) {
+        if self.finished {
+            return;
+        }
+        let Ok(line) = std::str::from_utf8(line) else {
+            return;
+        };
+        let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+            return;
+        };
+        if data == "[DONE]" {
+            self.end(output);
+            return;
+        }
+        let Ok(event) = serde_json::from_str::<Value>(data) else {
+            return;
+        };
+        match self.api {
+            WireApi::Chat => self.read_chat_chunk(&event, output),
+            WireApi::Responses => self.read_responses_event(&event, output),
+            WireApi::Anthropic => {}
+        }
+    }
+
+    fn read_chat_chunk(&mut self, chunk: &Value, output: &mut Vec<u8>) {
+        if let Some(error) = chunk.get("error") {
+            self.fail(error["message"].as_str().unwrap_or("Stream failed"), output);
+            return;
+        }
+        self.start(&chunk["id"], &chunk["model"], output);
+        if chunk["usage"].is_object() {
+            self.usage = anthropic_usage(WireApi::Chat, &chunk["usage"]);
+        }
+
+        let choice = &chunk["choices"][0];
+        let delta = &choice["delta"];
+        if let Some(text) = delta["content"].as_str().filter(|text| !text.is_empty()) {
+            self.text_delta(text, output);
+        }
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let key = call["index"].as_u64().unwrap_or(0).to_string();
+            if self.block.as_ref().map(|(_, block)| block) != Some(&Block::Tool(key.clone())) {
+                self.start_tool(key, &call["id"], &call["function"]["name"], output);
+            }
+            if let Some(arguments) = call["function"]["arguments"].as_str() {
+                self.arguments_delta(arguments, output);
+            }
+        }
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            self.stop_reason = Some(chat_stop_reason(reason));
+        }
+    }
+
+    fn read_responses_event(&mut self, event: &Value, output: &mut Vec<u8>) {
+        match event["type"].as_str().unwrap_or("") {
+            "response.created" | "response.in_progress" => {
+                self.start(
+                    &event["response"]["id"],
+                    &event["response"]["model"],
+                    output,
+                );
+            }
+            "response.output_item.added" if event["item"]["type"] == "function_call" => {
+                let item = &event["item"];
+                let key = item["id"].as_str().unwrap_or("").to_string();
+                self.start_tool(key, &item["call_id"], &item["name"], output);
+            }
+            "response.output_text.delta" => {
+                if let Some(text) = event["delta"].as_str() {
+                    self.text_delta(text, output);
+                }
+            }
+            "response.function_call_arguments.delta" => {
+                if let Some(arguments) = event["delta"].as_str() {
+                    self.arguments_delta(arguments, output);
+                }
+            }
+            "response.output_item.done" => {
+                // Some endpoints only send the arguments with the finished item
+                let item = &event["item"];
+                if item["type"] == "function_call" && !self.streamed_arguments {
+                    if let Some(arguments) = item["arguments"].as_str() {
+                        self.arguments_delta(arguments, output);
+                    }
+                }
+                self.close_block(output);
+            }
+            "response.completed" | "response.incomplete" => {
+                let response = &event["response"];
+                self.start(&response["id"], &response["model"], output);
+                if response["usage"].is_object() {
+                    self.usage = anthropic_usage(WireApi::Responses, &response["usage"]);
+                }
+                self.stop_reason = Some(if response["status"] == "incomplete" {
+                    "max_tokens"
+                } else if self.saw_tool_call {
+                    "tool_use"
+                } else {
+                    "end_turn"
+                });
+                self.end(output);
+            }
+            "response.failed" => {
+                let message = event
+                    .pointer("/response/error/message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Response failed");
+                self.fail(message, output);
+            }
+            "error" => {
+                let message = event["message"].as_str().unwrap_or("Stream failed");
+                self.fail(message, output);
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, id: &Value, model: &Value, output: &mut Vec<u8>) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        write_event(
+            output,
+            "message_start",
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": message_id(id),
+                    "type": "message",
+                    "role": "assistant",
+                    "model": model,
+                    "content": [],
+                    "stop_reason": null,
+                    "stop_sequence": null,
+                    "usage": { "input_tokens": 0, "output_tokens": 0 },
+                },
+            }),
+        );
+    }
+
+    fn text_delta(&mut self, text: &str, output: &mut Vec<u8>) {
+        if !matches!(self.block, Some((_, Block::Text))) {
+            self.open_block(Block::Text, json!({ "type": "text", "text": "" }), output);
+        }
+        self.block_delta(json!({ "type": "text_delta", "text": text }), output);
+    }
+
+    fn start_tool(&mut self, key: String, id: &Value, name: &Value, output: &mut Vec<u8>) {
+        self.saw_tool_call = true;
+        self.streamed_arguments = false;
+        let id = match id.as_str() {
+            Some(id) if !id.is_empty() => id.to_string(),
+            _ => format!("toolu_{}", nanoid::nanoid!(24)),
+        };
+        self.open_block(
+            Block::Tool(key),
+            json!({ "type": "tool_use", "id": id, "name": name, "input": {} }),
+            output,
+        );
+    }
+
+    fn arguments_delta(&mut self, arguments: &str, output: &mut Vec<u8>) {
+        if arguments.is_empty() || !matches!(self.block, Some((_, Block::Tool(_)))) {
+            return;
+        }
+        self.streamed_arguments = true;
+        self.block_delta(
+            json!({ "type": "input_json_delta", "partial_json": arguments }),
+            output,
+        );
+    }
+
+    fn open_block(&mut self, block: Block, content_block: Value, output: &mut Vec<u8>) {
+        self.start(&Value::Null, &Value::Null, output);
+        self.close_block(output);
+        let index = self.next_index;
+        self.next_index += 1;
+        self.block = Some((index, block));
+        write_event(
+            output,
+            "content_block_start",
+            json!({ "type": "content_block_start", "index": index, "content_block": content_block }),
+        );
+    }
+
+    fn block_delta(&mut self, delta: Value, output: &mut Vec<u8>) {
+        if let Some((index, _)) = &self.block {
+            write_event(
+                output,
+                "content_block_delta",
+                json!({ "type": "content_block_delta", "index": index, "delta": delta }),
+            );
+        }
+    }
+
+    fn close_block(&mut self, output: &mut Vec<u8>) {
+        if let Some((index, _)) = self.block.take() {
+            write_event(
+                output,
+                "content_block_stop",
+                json!({ "type": "content_block_stop", "index": index }),
+            );
+        }
+    }
+
+    fn end(&mut self, output: &mut Vec<u8>) {
+        if self.finished {
+            return;
+        }
+        self.start(&Value::Null, &Value::Null, output);
+        self.close_block(output);
+        self.finished = true;
+        write_event(
+            output,
+            "message_delta",
+            json!({
+                "type": "message_delta",
+                "delta": {
+                    "stop_reason": self.stop_reason.unwrap_or("end_turn"),
+                    "stop_sequence": null,
+                },
+                "usage": self.usage,
+            }),
+        );
+        write_event(output, "message_stop", json!({ "type": "message_stop" }));
+    }
+
+    fn fail(&mut self, message: &str, output: &mut Vec<u8>) {
+        self.finished = true;
+        write_event(
+            output,
+            "error",
+            json!({ "type": "error", "error": { "type": "api_error", "message": message } }),
+        );
+    }
+}
+
+fn write_event(output: &mut Vec<u8>, name: &str, data: Value) {
+    output.extend_from_slice(format!("event: {}\ndata: {}\n\n", name, data).as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Value {
+        let text = match name {
+            "messages_request" => include_str!("../tests/fixtures/translate/messages_request.json"),
+            "chat_request" => include_str!("../tests/fixtures/translate/chat_request.json"),
+            "responses_request" => {
+                include_str!("../tests/fixtures/translate/responses_request.json")
+            }
+            _ => unreachable!(),
+        };
+        serde_json::from_str(text).unwrap()
+    }
+
+    /// Stream `body` through a translator in `chunk_size` pieces and parse the events it wrote
+    fn translate_stream(api: WireApi, body: &str, chunk_size: usize) -> Vec<(String, Value)> {
+        let mut translator = ResponseTranslator::new(api, 200, Some("text/event-stream"));
+        let mut output = Vec::new();
+        for chunk in body.as_bytes().chunks(chunk_size) {
+            output.extend(translator.feed(chunk));
+        }
+        output.extend(translator.finish());
+
+        String::from_utf8(output)
+            .unwrap()
+            .split("\n\n")
+            .filter(|event| !event.is_empty())
+            .map(|event| {
+                let (name, data) = event.split_once('\n').unwrap();
+                (
+                    name.strip_prefix("event: ").unwrap().to_string(),
+                    serde_json::from_str(data.strip_prefix("data: ").unwrap()).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn assert_tool_stream(events: &[(String, Value)]) {
+        let names: Vec<&str> = events.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names.first(), Some(&"message_start"));
+        assert_eq!(names.last(), Some(&"message_stop"));
+        assert_eq!(
+            names
+                .iter()
+                .filter(|name| **name == "content_block_start")
+                .count(),
+            2
+        );
+
+        let deltas = |kind: &str, field: &str| -> String {
+            events
+                .iter()
+                .filter(|(_, data)| data["delta"]["type"] == kind)
+                .map(|(_, data)| data["delta"][field].as_str().unwrap())
+                .collect()
+        };
+        assert_eq!(deltas("text_delta", "text"), "Checking the file.");
+        let input: Value =
+            serde_json::from_str(&deltas("input_json_delta", "partial_json")).unwrap();
+        assert_eq!(input, json!({ "path": "a.txt" }));
+
+        let tool = events
+            .iter()
+            .find(|(_, data)| data["content_block"]["type"] == "tool_use")
+            .map(|(_, data)| &data["content_block"])
+            .unwrap();
+        assert_eq!(tool["id"], "call_9");
+        assert_eq!(tool["name"], "read_file");
+
+        let (_, message_delta) = events
+            .iter()
+            .find(|(name, _)| name == "message_delta")
+            .unwrap();
+        assert_eq!(message_delta["delta"]["stop_reason"], "tool_use");
+        assert_eq!(
+            message_delta["usage"],
+            json!({ "input_tokens": 20, "output_tokens": 30, "cache_read_input_tokens": 100 })
+        );
+    }
+
+    #[test]
+    fn translates_messages_to_chat_request() {
+        let translated = to_chat_request(&fixture("messages_request")).unwrap();
+        assert_eq!(translated, fixture("chat_request"));
+    }
+
+    #[test]
+    fn translates_messages_to_responses_request() {
+        let translated = to_responses_request(&fixture("messages_request")).unwrap();
+        assert_eq!(translated, fixture("responses_request"));
+    }
+
+    #[test]
+    fn chat_output_limit_depends_on_the_model() {
+        for (model, field) in [
+            ("gpt-5-mini", "max_completion_tokens"),
+            ("o3-mini", "max_completion_tokens"),
+            ("openai/o4-mini", "max_completion_tokens"),
+            ("gpt-4o", "max_tokens"),
+            ("deepseek-chat", "max_tokens"),
+            ("omni-model", "max_tokens"),
+        ] {
+            let request = json!({ "model": model, "max_tokens": 64, "messages": [] });
+            let translated = to_chat_request(&request).unwrap();
+            assert_eq!(translated[field], 64, "{}", model);
+            assert_eq!(translated.as_object().unwrap().len(), 3, "{}", model);
+        }
+    }
+
+    #[test]
+    fn streams_chat_chunks_as_message_events() {
+        let body = include_str!("../tests/fixtures/translate/chat_stream.sse");
+        // Every split must give the same result, down to single bytes
+        for chunk_size in [body.len(), 64, 7, 1] {
+            assert_tool_stream(&translate_stream(WireApi::Chat, body, chunk_size));
+        }
+    }
+
+    #[test]
+    fn streams_responses_events_as_message_events() {
+        let body = include_str!("../tests/fixtures/translate/responses_stream.sse");
+        for chunk_size in [body.len(), 64, 7, 1] {
+            let events = translate_stream(WireApi::Responses, body, chunk_size);
+            assert_tool_stream(&events);
+            // Arguments that were streamed aren't repeated from the finished item
+            assert_eq!(
+                events
+                    .iter()
+                    .filter(|(_, data)| data["delta"]["type"] == "input_json_delta")
+                    .count(),
+                2
+            );
+        }
+    }
+
+    #[test]
+    fn translates_whole_chat_response() {
+        let mut translator = ResponseTranslator::new(WireApi::Chat, 200, Some("application/json"));
+        let body = json!({
+            "id": "chatcmpl-2",
+            "model": "gpt-4o",
+            "choices": [{
+                "message": {
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "function": { "name": "read_file", "arguments": "{\"path\":\"a.txt\"}" }
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": { "prompt_tokens": 10, "completion_tokens": 5 }
+        })
+        .to_string();
+        assert!(translator.feed(body.as_bytes()).is_empty());
+        let response: Value = serde_json::from_slice(&translator.finish()).unwrap();
+
+        assert_eq!(response["stop_reason"], "tool_use");
+        assert_eq!(
+            response["content"],
+            json!([{ "type": "tool_use", "id": "call_1", "name": "read_file", "input": { "path": "a.txt" } }])
+        );
+        assert_eq!(response["usage"]["input_tokens"], 10);
+        assert_eq!(response["usage"]["output_tokens"], 5);
+    }
+}
@@ -0,0 +1,157 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::paths::Paths;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UsageData {
+    pub input_tokens: Option<u64>,
+    pub cache_read_input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ProjectUsageRecord {
+    pub uuid: String,
+    pub timestamp: String,
+    pub model: Option<String>,
+    pub usage: Option<UsageData>,
+}
+
+// Recursively find all .jsonl files in the projects directory and subdirectories
+fn find_jsonl_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+
+        if path.is_file() && path.extension().map(|ext| ext == "jsonl").unwrap_or(false) {
+            files.push(path);
+        } else if path.is_dir() {
+            // Recursively search subdirectories
+            if let Err(e) = find_jsonl_files(&path, files) {
+                eprintln!("Warning: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_usage(usage_obj: &Value) -> UsageData {
+    UsageData {
+        input_tokens: usage_obj.get("input_tokens").and_then(|v| v.as_u64()),
+        cache_read_input_tokens: usage_obj
+            .get("cache_read_input_tokens")
+            .and_then(|v| v.as_u64()),
+        output_tokens: usage_obj.get("output_tokens").and_then(|v| v.as_u64()),
+    }
+}
+
+/// Parse one transcript line into a usage record, skipping lines without token usage
+pub fn parse_usage_line(json_value: &Value) -> Option<ProjectUsageRecord> {
+    // Extract the required fields
+    let uuid = json_value
+        .get("uuid")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let timestamp = json_value
+        .get("timestamp")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+
+    // Only include records with valid uuid and timestamp
+    if uuid.is_empty() || timestamp.is_empty() {
+        return None;
+    }
+
+    let message_obj = json_value.get("message");
+
+    // Extract model field (optional) - check both top-level and nested in message field
+    let model = json_value
+        .get("model")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            message_obj
+                .and_then(|m| m.get("model"))
+                .and_then(|v| v.as_str())
+        })
+        .map(|s| s.to_string());
+
+    // Extract usage data (optional) - check both top-level and nested in message field
+    let usage = match json_value.get("usage") {
+        Some(usage_obj) => parse_usage(usage_obj),
+        None => parse_usage(message_obj.and_then(|m| m.get("usage"))?),
+    };
+
+    // Only include if input_tokens + output_tokens > 0
+    let input_tokens = usage.input_tokens.unwrap_or(0);
+    let output_tokens = usage.output_tokens.unwrap_or(0);
+    if input_tokens + output_tokens == 0 {
+        return None;
+    }
+
+    Some(ProjectUsageRecord {
+        uuid: uuid.to_string(),
+        timestamp: timestamp.to_string(),
+        model,
+        usage: Some(usage),
+    })
+}
+
+pub fn read_project_usage_files(paths: &Paths) -> Result<Vec<ProjectUsageRecord>, String> {
+    let projects_dir = paths.claude_projects_dir();
+
+    eprintln!(
+        "🔍 Looking for projects directory: {}",
+        projects_dir.display()
+    );
+
+    if !projects_dir.exists() {
+        eprintln!("❌ Projects directory does not exist");
+        return Ok(vec![]);
+    }
+
+    eprintln!("✅ Projects directory exists");
+
+    let mut all_records = Vec::new();
+    let mut files_processed = 0;
+    let mut lines_processed = 0;
+
+    let mut jsonl_files = Vec::new();
+    find_jsonl_files(&projects_dir, &mut jsonl_files)?;
+
+    for path in jsonl_files {
+        files_processed += 1;
+
+        // Read the JSONL file
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+
+        // Process each line in the JSONL file
+        for line in content.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            lines_processed += 1;
+
+            // Parse the JSON line
+            let json_value: Value = serde_json::from_str(line)
+                .map_err(|e| format!("Failed to parse JSON line: {}", e))?;
+
+            if let Some(record) = parse_usage_line(&json_value) {
+                all_records.push(record);
+            }
+        }
+    }
+
+    eprintln!(
+        "📊 Summary: Processed {} files, {} lines, found {} records",
+        files_processed,
+        lines_processed,
+        all_records.len()
+    );
+    Ok(all_records)
+}
@@ -0,0 +1,964 @@
+//! Encrypted storage for API tokens.
+//!
+//! Secret values such as `ANTHROPIC_AUTH_TOKEN` or a Codex `api_key` live in
+//! `~/.ccconfig/vault.json`, each sealed with XChaCha20-Poly1305, while the
+//! stores only hold a `vault://<id>` handle. Handles are resolved when a store
+//! is written to the files Claude Code and Codex read, and anything returned
+//! to the UI is masked.
+//!
+//! The vault key is derived from a passphrase (Argon2id), or kept in the OS
+//! keyring, or, where no keyring is available, in `~/.ccconfig/vault.key`
+//! readable only by the user.
+
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::codex;
+use crate::fsio;
+use crate::history;
+use crate::merge::MergeChange;
+use crate::paths::Paths;
+use crate::pointer;
+use crate::stores;
+
+/// Prefix of the values that reference a vault secret
+pub const HANDLE_PREFIX: &str = "vault://";
+
+/// Pointer of the API key in a Codex store config
+pub const CODEX_API_KEY_POINTER: &str = "/api_key";
+
+/// Passphrase for headless use, e.g. the CLI against a passphrase-protected vault
+const PASSPHRASE_ENV: &str = "TVCBUDDY_VAULT_PASSPHRASE";
+
+const VAULT_VERSION: u32 = 1;
+const MASK: &str = "••••••••";
+// Shorter secrets are masked entirely
+const MIN_HINT_CHARS: usize = 12;
+const HINT_CHARS: usize = 4;
+const CHECK_AAD: &[u8] = b"tvcbuddy-vault-check";
+const CHECK_VALUE: &[u8] = b"tvcbuddy-vault";
+
+type VaultKey = [u8; 32];
+
+// Keys of unlocked vaults, by vault file
+static UNLOCKED: Mutex<BTreeMap<PathBuf, VaultKey>> = Mutex::new(BTreeMap::new());
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySource {
+    Passphrase,
+    Keyring,
+    File,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct Sealed {
+    nonce: String,
+    data: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct VaultSecret {
+    #[serde(flatten)]
+    sealed: Sealed,
+    /// Masked value shown in place of the secret
+    hint: String,
+    created_at: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct VaultFile {
+    version: u32,
+    key_source: KeySource,
+    /// Argon2id salt when the key comes from a passphrase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    /// Known value sealed with the key, to recognise a wrong key
+    check: Sealed,
+    #[serde(default)]
+    secrets: BTreeMap<String, VaultSecret>,
+}
+
+/// Data sealed with a passphrase of its own instead of the vault key, e.g. in an exported bundle
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct PassphraseSealed {
+    salt: String,
+    #[serde(flatten)]
+    sealed: Sealed,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct VaultStatus {
+    pub initialized: bool,
+    pub key_source: Option<KeySource>,
+    pub locked: bool,
+    pub secrets: usize,
+    /// Whether an OS keyring can hold the key on this platform
+    pub keyring_available: bool,
+}
+
+pub fn is_handle(value: &str) -> bool {
+    value.starts_with(HANDLE_PREFIX)
+}
+
+/// Env vars that hold credentials
+pub fn is_secret_env_key(key: &str) -> bool {
+    const SUFFIXES: &[&str] = &["API_KEY", "TOKEN", "SECRET", "SECRET_KEY", "PASSWORD"];
+    let key = key.to_ascii_uppercase();
+    SUFFIXES.iter().any(|suffix| key.ends_with(suffix))
+}
+
+/// Pointers of the secret values in Claude Code settings
+pub fn settings_secret_pointers(settings: &Value) -> Vec<String> {
+    settings
+        .get("env")
+        .and_then(Value::as_object)
+        .map(|env| {
+            env.keys()
+                .filter(|key| is_secret_env_key(key))
+                .map(|key| pointer::child("/env", key))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether an MCP server header carries credentials, e.g. `Authorization` or `X-Api-Key`
+pub fn is_secret_header(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "authorization" | "proxy-authorization" | "cookie"
+    ) || is_secret_env_key(&name.replace('-', "_"))
+}
+
+/// Pointers of the secret `env` values and headers in an MCP server config
+pub fn mcp_server_secret_pointers(config: &Value) -> Vec<String> {
+    let mut pointers = Vec::new();
+    for (field, is_secret) in [
+        ("env", is_secret_env_key as fn(&str) -> bool),
+        ("headers", is_secret_header),
+    ] {
+        if let Some(object) = config.get(field).and_then(Value::as_object) {
+            pointers.extend(
+                object
+                    .keys()
+                    .filter(|key| is_secret(key))
+                    .map(|key| pointer::child(&format!("/{}", field), key)),
+            );
+        }
+    }
+    pointers
+}
+
+/// Pointers of the secret values in a Codex store config
+pub fn codex_secret_pointers() -> Vec<String> {
+    vec![CODEX_API_KEY_POINTER.to_string()]
+}
+
+/// Masked form of a secret: bullets followed by its last four characters when it is long enough
+pub fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() < MIN_HINT_CHARS {
+        return MASK.to_string();
+    }
+    format!(
+        "{}{}",
+        MASK,
+        chars[chars.len() - HINT_CHARS..].iter().collect::<String>()
+    )
+}
+
+pub fn is_masked(value: &str) -> bool {
+    value.contains(MASK)
+}
+
+pub fn get_vault_status(paths: &Paths) -> Result<VaultStatus, String> {
+    let vault = read_vault(paths)?;
+    Ok(VaultStatus {
+        initialized: vault.is_some(),
+        key_source: vault.as_ref().map(|vault| vault.key_source),
+        locked: vault
+            .as_ref()
+            .is_some_and(|vault| vault_key(paths, vault).is_err()),
+        secrets: vault.as_ref().map_or(0, |vault| vault.secrets.len()),
+        keyring_available: os_keyring::AVAILABLE,
+    })
+}
+
+/// Unlock a passphrase-protected vault for the rest of the session.
+pub fn unlock_vault(paths: &Paths, passphrase: &str) -> Result<(), String> {
+    let vault = read_vault(paths)?.ok_or_else(|| "Vault does not exist yet".to_string())?;
+    if vault.key_source != KeySource::Passphrase {
+        return Ok(());
+    }
+
+    let key = derive_key(passphrase, vault.salt.as_deref())?;
+    open(&key, &vault.check, CHECK_AAD).map_err(|_| "Wrong vault passphrase".to_string())?;
+    cache_key(paths, Some(key));
+
+    eprintln!("🔓 Vault unlocked");
+    Ok(())
+}
+
+/// Forget the session key of a passphrase-protected vault.
+pub fn lock_vault(paths: &Paths) {
+    cache_key(paths, None);
+    eprintln!("🔒 Vault locked");
+}
+
+/// Protect the vault with `passphrase`, or with the OS keyring or key file when `None`.
+///
+/// Every secret is re-encrypted with the new key. The vault must be unlocked.
+pub fn set_vault_passphrase(paths: &Paths, passphrase: Option<&str>) -> Result<(), String> {
+    if passphrase.is_some_and(|passphrase| passphrase.chars().count() < 8) {
+        return Err("Vault passphrase must be at least 8 characters".to_string());
+    }
+
+    let old_source = ensure_vault(paths)?;
+    fsio::update_json(
+        paths,
+        &paths.vault_file(),
+        "vault",
+        |current: Option<VaultFile>| {
+            let vault = current.ok_or_else(|| "Vault does not exist".to_string())?;
+            let old_key = vault_key(paths, &vault)?;
+            let (key_source, salt, new_key) = new_vault_key(paths, passphrase)?;
+
+            let mut secrets = BTreeMap::new();
+            for (id, secret) in &vault.secrets {
+                let plain = open(&old_key, &secret.sealed, id.as_bytes())?;
+                secrets.insert(
+                    id.clone(),
+                    VaultSecret {
+                        sealed: seal(&new_key, &plain, id.as_bytes())?,
+                        ..secret.clone()
+                    },
+                );
+            }
+
+            let updated = VaultFile {
+                version: VAULT_VERSION,
+                key_source,
+                salt,
+                check: seal(&new_key, CHECK_VALUE, CHECK_AAD)?,
+                secrets,
+            };
+            cache_key(paths, Some(new_key));
+            Ok((Some(updated), ()))
+        },
+    )?;
+
+    // The previous key is useless now, don't leave it behind
+    let new_source = read_vault(paths)?.map(|vault| vault.key_source);
+    if Some(old_source) != new_source {
+        if let Err(e) = forget_stored_key(paths, old_source) {
+            eprintln!("Warning: Failed to remove old vault key: {}", e);
+        }
+    }
+
+    eprintln!("✅ Vault key updated");
+    Ok(())
+}
+
+/// Encrypt `value` into the vault and return its handle.
+pub fn store_secret(paths: &Paths, value: &str) -> Result<String, String> {
+    ensure_vault(paths)?;
+    let id = nanoid::nanoid!(12);
+    update_vault(paths, |vault, key| {
+        vault.secrets.insert(
+            id.clone(),
+            VaultSecret {
+                sealed: seal(key, value.as_bytes(), id.as_bytes())?,
+                hint: mask(value),
+                created_at: chrono::Utc::now().timestamp_millis() as u64,
+            },
+        );
+        Ok(())
+    })?;
+    Ok(format!("{}{}", HANDLE_PREFIX, id))
+}
+
+/// Decrypt the secret behind `handle`.
+pub fn resolve_secret(paths: &Paths, handle: &str) -> Result<String, String> {
+    Resolver::new(paths).resolve(handle)
+}
+
+/// Copy of `value` with every handle replaced by its secret, for writing the target files
+pub fn reveal(paths: &Paths, value: &Value) -> Result<Value, String> {
+    Ok(reveal_with_secrets(paths, value)?.0)
+}
+
+/// Like [`reveal`], also returning the `(secret, handle)` pairs it resolved for [`conceal`]
+pub fn reveal_with_secrets(
+    paths: &Paths,
+    value: &Value,
+) -> Result<(Value, Vec<(String, String)>), String> {
+    let mut resolver = Resolver::new(paths);
+    let mut secrets = Vec::new();
+    let mut revealed = value.clone();
+    map_strings(&mut revealed, &mut |text| {
+        if !is_handle(text) {
+            return Ok(None);
+        }
+        let secret = resolver.resolve(text)?;
+        secrets.push((secret.clone(), text.to_string()));
+        Ok(Some(secret))
+    })?;
+    Ok((revealed, secrets))
+}
+
+/// Replace secrets in `value` with the handles they were revealed from
+pub fn conceal(value: &mut Value, secrets: &[(String, String)]) {
+    let _ = map_strings(value, &mut |text| {
+        Ok(secrets
+            .iter()
+            .find(|(secret, _)| secret == text)
+            .map(|(_, handle)| handle.clone()))
+    });
+}
+
+/// Recorded merge changes with their handles revealed, so they can be reverted in a target file
+pub fn reveal_changes(paths: &Paths, changes: &[MergeChange]) -> Result<Vec<MergeChange>, String> {
+    let value =
+        serde_json::to_value(changes).map_err(|e| format!("Failed to serialize changes: {}", e))?;
+    serde_json::from_value(reveal(paths, &value)?)
+        .map_err(|e| format!("Failed to parse changes: {}", e))
+}
+
+/// Replace secrets in recorded merge changes with handles before they are saved.
+///
+/// Profile secrets get the handles they were revealed from. Secrets of the
+/// user's own that a profile overwrote are moved into the vault, so reverting
+/// the changes still restores them; those are returned with their new handles.
+pub fn conceal_changes(
+    paths: &Paths,
+    changes: &mut [MergeChange],
+    secrets: &[(String, String)],
+) -> Result<Vec<(String, String)>, String> {
+    let mut moved = Vec::new();
+    for change in changes {
+        match change {
+            MergeChange::Set {
+                pointer: at,
+                previous,
+                value,
+            } => {
+                conceal(value, secrets);
+                if let Some(previous) = previous {
+                    conceal(previous, secrets);
+                    let (mut settings, pointers) = previous_settings(at, previous)?;
+                    let sealed = move_plain_secrets(paths, &mut settings, &pointers)?;
+                    if !sealed.is_empty() {
+                        if let Some(value) = settings.pointer(at) {
+                            *previous = value.clone();
+                        }
+                        moved.extend(sealed);
+                    }
+                }
+            }
+            MergeChange::Append { value, .. } => conceal(value, secrets),
+            MergeChange::Create { .. } => {}
+        }
+    }
+    Ok(moved)
+}
+
+// Settings holding only the value that was at `at` before a change replaced it, and its secret pointers
+fn previous_settings(at: &str, previous: &Value) -> Result<(Value, Vec<String>), String> {
+    let mut settings = Value::Object(serde_json::Map::new());
+    pointer::set(&mut settings, at, previous.clone())?;
+    let pointers = settings_secret_pointers(&settings)
+        .into_iter()
+        .filter(|secret| secret == at || secret.starts_with(&format!("{}/", at)))
+        .collect();
+    Ok((settings, pointers))
+}
+
+fn has_plain_previous_secrets(changes: &[MergeChange]) -> bool {
+    changes.iter().any(|change| match change {
+        MergeChange::Set {
+            pointer: at,
+            previous: Some(previous),
+            ..
+        } => previous_settings(at, previous)
+            .is_ok_and(|(settings, pointers)| has_plain_secrets(&settings, &pointers)),
+        _ => false,
+    })
+}
+
+/// Move plain secrets at `pointers` into the vault, replacing them with handles.
+///
+/// A masked value sent back unchanged from the UI gets the handle it had in
+/// `previous`; any other masked value is rejected.
+pub fn seal_secrets(
+    paths: &Paths,
+    value: &mut Value,
+    pointers: &[String],
+    previous: Option<&Value>,
+) -> Result<(), String> {
+    for at in pointers {
+        let Some(Value::String(text)) = value.pointer(at).cloned() else {
+            continue;
+        };
+        if text.is_empty() {
+            continue;
+        }
+
+        let handle = if is_handle(&text) {
+            // Make sure it points somewhere
+            resolve_hint(paths, &text)?;
+            text
+        } else if is_masked(&text) {
+            previous
+                .and_then(|previous| previous.pointer(at))
+                .and_then(Value::as_str)
+                .filter(|handle| {
+                    is_handle(handle) && resolve_hint(paths, handle).is_ok_and(|hint| hint == text)
+                })
+                .map(str::to_string)
+                .ok_or_else(|| format!("{} is masked, enter the full value", at))?
+        } else {
+            store_secret(paths, &text)?
+        };
+        pointer::set(value, at, Value::String(handle))?;
+    }
+    Ok(())
+}
+
+/// Mask every handle in `value` and any plain secret at `pointers`, for returning to the UI.
+pub fn mask_secrets(paths: &Paths, value: &mut Value, pointers: &[String]) {
+    let hints: BTreeMap<String, String> = read_vault(paths)
+        .ok()
+        .flatten()
+        .map(|vault| {
+            vault
+                .secrets
+                .into_iter()
+                .map(|(id, secret)| (format!("{}{}", HANDLE_PREFIX, id), secret.hint))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let _ = map_strings(value, &mut |text| {
+        Ok(is_handle(text).then(|| hints.get(text).cloned().unwrap_or_else(|| MASK.to_string())))
+    });
+
+    for at in pointers {
+        if let Some(Value::String(text)) = value.pointer_mut(at) {
+            if !text.is_empty() && !is_masked(text) {
+                *text = mask(text);
+            }
+        }
+    }
+}
+
+/// Put back the secrets of `current` where `value` still holds their masked form.
+///
+/// Used when a file the UI only saw masked is written back.
+pub fn unmask_secrets(
+    value: &mut Value,
+    current: &Value,
+    pointers: &[String],
+) -> Result<(), String> {
+    for at in pointers {
+        let Some(Value::String(text)) = value.pointer(at) else {
+            continue;
+        };
+        if !is_masked(text) {
+            continue;
+        }
+        let secret = current
+            .pointer(at)
+            .and_then(Value::as_str)
+            .filter(|secret| mask(secret) == *text)
+            .ok_or_else(|| format!("{} is masked, enter the full value", at))?
+            .to_string();
+        pointer::set(value, at, Value::String(secret))?;
+    }
+    Ok(())
+}
+
+pub fn seal_with_passphrase(
+    passphrase: &str,
+    plain: &[u8],
+    aad: &[u8],
+) -> Result<PassphraseSealed, String> {
+    let salt = new_salt();
+    let key = derive_key(passphrase, Some(&salt))?;
+    Ok(PassphraseSealed {
+        sealed: seal(&key, plain, aad)?,
+        salt,
+    })
+}
+
+pub fn open_with_passphrase(
+    passphrase: &str,
+    sealed: &PassphraseSealed,
+    aad: &[u8],
+) -> Result<Vec<u8>, String> {
+    let key = derive_key(passphrase, Some(&sealed.salt))?;
+    open(&key, &sealed.sealed, aad).map_err(|_| "Wrong passphrase".to_string())
+}
+
+/// Move plain tokens in stores.json and codexcc.json into the vault.
+///
+/// Snapshots of both files in the history are scrubbed as well. Returns the
+/// number of secrets moved.
+pub fn migrate_plaintext_secrets(paths: &Paths) -> Result<usize, String> {
+    let mut moved: Vec<(String, String)> = Vec::new();
+
+    let has_plain_store_secrets = stores::read_stores_data(paths)?.is_some_and(|stores_data| {
+        stores_data.configs.iter().any(|store| {
+            has_plain_secrets(&store.settings, &settings_secret_pointers(&store.settings))
+        }) || stores_data
+            .applied
+            .iter()
+            .map(|applied| &applied.changes)
+            .chain(stores_data.bindings.iter().map(|binding| &binding.changes))
+            .any(|changes| has_plain_previous_secrets(changes))
+    });
+    if has_plain_store_secrets {
+        let store_moved = stores::update_stores_data(paths, |stores_data| {
+            let mut store_moved = Vec::new();
+            for store in &mut stores_data.configs {
+                let pointers = settings_secret_pointers(&store.settings);
+                store_moved.extend(move_plain_secrets(paths, &mut store.settings, &pointers)?);
+            }
+            // Recorded changes hold the same values, and the user's own ones profiles replaced
+            let mut changes_moved = Vec::new();
+            if let Some(applied) = &mut stores_data.applied {
+                changes_moved.extend(conceal_changes(paths, &mut applied.changes, &store_moved)?);
+            }
+            for binding in &mut stores_data.bindings {
+                changes_moved.extend(conceal_changes(paths, &mut binding.changes, &store_moved)?);
+            }
+            store_moved.extend(changes_moved);
+            Ok(store_moved)
+        })?;
+        scrub_history(paths, &paths.stores_file(), &store_moved)?;
+        moved.extend(store_moved);
+    }
+
+    let pointers = codex_secret_pointers();
+    if codex::read_codex_stores(paths)?
+        .configs
+        .iter()
+        .any(|store| has_plain_secrets(&store.config, &pointers))
+    {
+        let codex_moved = codex::update_codex_stores(paths, |data| {
+            let mut codex_moved = Vec::new();
+            for store in &mut data.configs {
+                codex_moved.extend(move_plain_secrets(paths, &mut store.config, &pointers)?);
+            }
+            Ok(codex_moved)
+        })?;
+        scrub_history(paths, &paths.codex_stores_file(), &codex_moved)?;
+        moved.extend(codex_moved);
+    }
+
+    if !moved.is_empty() {
+        eprintln!("🔐 Moved {} secret(s) into the vault", moved.len());
+    }
+    Ok(moved.len())
+}
+
+/// Delete secrets no store references any more.
+pub fn prune_secrets(paths: &Paths) -> Result<(), String> {
+    let Some(vault) = read_vault(paths)? else {
+        return Ok(());
+    };
+
+    let mut referenced = BTreeSet::new();
+    for file in [paths.stores_file(), paths.codex_stores_file()] {
+        if let Some(mut value) = fsio::read_json::<Value>(&file, "stores file")? {
+            let _ = map_strings(&mut value, &mut |text| {
+                if let Some(id) = text.strip_prefix(HANDLE_PREFIX) {
+                    referenced.insert(id.to_string());
+                }
+                Ok(None)
+            });
+        }
+    }
+
+    if vault.secrets.keys().all(|id| referenced.contains(id)) {
+        return Ok(());
+    }
+    fsio::update_json(
+        paths,
+        &paths.vault_file(),
+        "vault",
+        |current: Option<VaultFile>| {
+            let Some(mut vault) = current else {
+                return Ok((None, ()));
+            };
+            vault.secrets.retain(|id, _| referenced.contains(id));
+            Ok((Some(vault), ()))
+        },
+    )
+}
+
+fn has_plain_secrets(value: &Value, pointers: &[String]) -> bool {
+    pointers.iter().any(|at| {
+        value
+            .pointer(at)
+            .and_then(Value::as_str)
+            .is_some_and(|text| !text.is_empty() && !is_handle(text) && !is_masked(text))
+    })
+}
+
+fn move_plain_secrets(
+    paths: &Paths,
+    value: &mut Value,
+    pointers: &[String],
+) -> Result<Vec<(String, String)>, String> {
+    let mut moved = Vec::new();
+    for at in pointers {
+        let Some(text) = value.pointer(at).and_then(Value::as_str) else {
+            continue;
+        };
+        if text.is_empty() || is_handle(text) || is_masked(text) {
+            continue;
+        }
+        let text = text.to_string();
+        let handle = store_secret(paths, &text)?;
+        pointer::set(value, at, Value::String(handle.clone()))?;
+        moved.push((text, handle));
+    }
+    Ok(moved)
+}
+
+fn scrub_history(
+    paths: &Paths,
+    path: &std::path::Path,
+    moved: &[(String, String)],
+) -> Result<(), String> {
+    let replacements: Vec<(String, String)> = moved
+        .iter()
+        .filter_map(|(secret, handle)| {
+            Some((
+                serde_json::to_string(secret).ok()?,
+                serde_json::to_string(handle).ok()?,
+            ))
+        })
+        .collect();
+    history::redact(paths, path, &replacements)
+}
+
+/// Resolves handles with a single vault read and key lookup
+struct Resolver<'a> {
+    paths: &'a Paths,
+    vault: Option<(VaultFile, VaultKey)>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(paths: &'a Paths) -> Self {
+        Self { paths, vault: None }
+    }
+
+    fn resolve(&mut self, handle: &str) -> Result<String, String> {
+        let id = handle
+            .strip_prefix(HANDLE_PREFIX)
+            .ok_or_else(|| format!("Invalid secret handle '{}'", handle))?;
+
+        if self.vault.is_none() {
+            let vault = read_vault(self.paths)?
+                .ok_or_else(|| format!("Secret '{}' not found: the vault is empty", handle))?;
+            let key = vault_key(self.paths, &vault)?;
+            self.vault = Some((vault, key));
+        }
+        let Some((vault, key)) = &self.vault else {
+            unreachable!()
+        };
+
+        let secret = vault
+            .secrets
+            .get(id)
+            .ok_or_else(|| format!("Secret '{}' not found in the vault", handle))?;
+        let plain = open(key, &secret.sealed, id.as_bytes())?;
+        String::from_utf8(plain).map_err(|e| format!("Invalid secret '{}': {}", handle, e))
+    }
+}
+
+/// Hint of the secret behind `handle`, without needing the key
+fn resolve_hint(paths: &Paths, handle: &str) -> Result<String, String> {
+    let id = handle.strip_prefix(HANDLE_PREFIX).unwrap_or(handle);
+    read_vault(paths)?
+        .and_then(|vault| vault.secrets.get(id).map(|secret| secret.hint.clone()))
+        .ok_or_else(|| format!("Secret '{}' not found in the vault", handle))
+}
+
+fn read_vault(paths: &Paths) -> Result<Option<VaultFile>, String> {
+    fsio::read_json(&paths.vault_file(), "vault")
+}
+
+/// Create the vault with a keyring or file key if it does not exist, returning its key source
+fn ensure_vault(paths: &Paths) -> Result<KeySource, String> {
+    if let Some(vault) = read_vault(paths)? {
+        return Ok(vault.key_source);
+    }
+
+    fsio::update_json(
+        paths,
+        &paths.vault_file(),
+        "vault",
+        |current: Option<VaultFile>| {
+            if let Some(vault) = current {
+                return Ok((None, vault.key_source));
+            }
+            let (key_source, salt, key) = new_vault_key(paths, None)?;
+            let vault = VaultFile {
+                version: VAULT_VERSION,
+                key_source,
+                salt,
+                check: seal(&key, CHECK_VALUE, CHECK_AAD)?,
+                secrets: BTreeMap::new(),
+            };
+            cache_key(paths, Some(key));
+            eprintln!("🔐 Created secret vault ({:?} key)", key_source);
+            Ok((Some(vault), key_source))
+        },
+    )
+}
+
+/// Locked read-modify-write of an existing vault with its key
+fn update_vault<R>(
+    paths: &Paths,
+    mut apply: impl FnMut(&mut VaultFile, &VaultKey) -> Result<R, String>,
+) -> Result<R, String> {
+    fsio::update_json(
+        paths,
+        &paths.vault_file(),
+        "vault",
+        |current: Option<VaultFile>| {
+            let mut vault = current.ok_or_else(|| "Vault does not exist".to_string())?;
+            let key = vault_key(paths, &vault)?;
+            let result = apply(&mut vault, &key)?;
+            Ok((Some(vault), result))
+        },
+    )
+}
+
+/// Generate a key for `passphrase`, or a random one kept in the OS keyring or the key file
+fn new_vault_key(
+    paths: &Paths,
+    passphrase: Option<&str>,
+) -> Result<(KeySource, Option<String>, VaultKey), String> {
+    if let Some(passphrase) = passphrase {
+        let salt = new_salt();
+        let key = derive_key(passphrase, Some(&salt))?;
+        return Ok((KeySource::Passphrase, Some(salt), key));
+    }
+
+    let key: VaultKey = XChaCha20Poly1305::generate_key(&mut OsRng).into();
+    match os_keyring::set(paths, &BASE64.encode(key)) {
+        Ok(()) => Ok((KeySource::Keyring, None, key)),
+        Err(e) => {
+            if os_keyring::AVAILABLE {
+                eprintln!(
+                    "Warning: Failed to store vault key in OS keyring, using key file: {}",
+                    e
+                );
+            }
+            write_key_file(paths, &key)?;
+            Ok((KeySource::File, None, key))
+        }
+    }
+}
+
+/// Key of `vault`, from the session cache or its key source, checked against the vault
+fn vault_key(paths: &Paths, vault: &VaultFile) -> Result<VaultKey, String> {
+    let cached = UNLOCKED
+        .lock()
+        .ok()
+        .and_then(|unlocked| unlocked.get(&paths.vault_file()).copied());
+    if let Some(key) = cached {
+        if open(&key, &vault.check, CHECK_AAD).is_ok() {
+            return Ok(key);
+        }
+        // The vault was replaced, e.g. restored from history
+        cache_key(paths, None);
+    }
+
+    let key = match vault.key_source {
+        KeySource::Passphrase => {
+            let passphrase = std::env::var(PASSPHRASE_ENV)
+                .map_err(|_| "Vault is locked, unlock it with its passphrase".to_string())?;
+            derive_key(&passphrase, vault.salt.as_deref())?
+        }
+        KeySource::Keyring => decode_key(&os_keyring::get(paths)?)?,
+        KeySource::File => {
+            let encoded = std::fs::read_to_string(paths.vault_key_file())
+                .map_err(|e| format!("Failed to read vault key file: {}", e))?;
+            decode_key(encoded.trim())?
+        }
+    };
+
+    open(&key, &vault.check, CHECK_AAD).map_err(|_| match vault.key_source {
+        KeySource::Passphrase => "Wrong vault passphrase".to_string(),
+        _ => "Vault key does not match the vault".to_string(),
+    })?;
+    cache_key(paths, Some(key));
+    Ok(key)
+}
+
+fn cache_key(paths: &Paths, key: Option<VaultKey>) {
+    if let Ok(mut unlocked) = UNLOCKED.lock() {
+        match key {
+            Some(key) => unlocked.insert(paths.vault_file(), key),
+            None => unlocked.remove(&paths.vault_file()),
+        };
+    }
+}
+
+/// Remove the key a vault used before its key source changed
+fn forget_stored_key(paths: &Paths, key_source: KeySource) -> Result<(), String> {
+    match key_source {
+        KeySource::Passphrase => Ok(()),
+        KeySource::Keyring => os_keyring::delete(paths),
+        KeySource::File => std::fs::remove_file(paths.vault_key_file())
+            .map_err(|e| format!("Failed to remove vault key file: {}", e)),
+    }
+}
+
+fn new_salt() -> String {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    BASE64.encode(salt)
+}
+
+fn derive_key(passphrase: &str, salt: Option<&str>) -> Result<VaultKey, String> {
+    let salt = BASE64
+        .decode(salt.ok_or_else(|| "Vault has no passphrase salt".to_string())?)
+        .map_err(|e| format!("Invalid vault salt: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| format!("Failed to derive vault key: {}", e))?;
+    Ok(key)
+}
+
+fn decode_key(encoded: &str) -> Result<VaultKey, String> {
+    BASE64
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| VaultKey::try_from(bytes).ok())
+        .ok_or_else(|| "Invalid vault key".to_string())
+}
+
+fn write_key_file(paths: &Paths, key: &VaultKey) -> Result<(), String> {
+    // Written directly so the key never ends up in the history
+    fsio::write_private(&paths.vault_key_file(), BASE64.encode(key).as_bytes())
+}
+
+fn seal(key: &VaultKey, plain: &[u8], aad: &[u8]) -> Result<Sealed, String> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let data = cipher
+        .encrypt(&nonce, Payload { msg: plain, aad })
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+    Ok(Sealed {
+        nonce: BASE64.encode(nonce),
+        data: BASE64.encode(data),
+    })
+}
+
+fn open(key: &VaultKey, sealed: &Sealed, aad: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = BASE64
+        .decode(&sealed.nonce)
+        .ok()
+        .filter(|nonce| nonce.len() == 24)
+        .ok_or_else(|| "Invalid secret nonce".to_string())?;
+    let data = BASE64
+        .decode(&sealed.data)
+        .map_err(|e| format!("Invalid secret data: {}", e))?;
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(&nonce), Payload { msg: &data, aad })
+        .map_err(|_| "Failed to decrypt secret".to_string())
+}
+
+/// Apply `f` to every string in `value`, replacing it when `f` returns a new one
+fn map_strings(
+    value: &mut Value,
+    f: &mut impl FnMut(&str) -> Result<Option<String>, String>,
+) -> Result<(), String> {
+    match value {
+        Value::String(text) => {
+            if let Some(replacement) = f(text)? {
+                *text = replacement;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                map_strings(item, f)?;
+            }
+        }
+        Value::Object(object) => {
+            for item in object.values_mut() {
+                map_strings(item, f)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+mod os_keyring {
+    use crate::fsio;
+    use crate::paths::Paths;
+
+    pub const AVAILABLE: bool = true;
+    const SERVICE: &str = "tvcbuddy";
+
+    // One entry per vault file, so separate homes don't share a key
+    fn entry(paths: &Paths) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(SERVICE, &fsio::file_key(&paths.vault_file()))
+            .map_err(|e| format!("Failed to open OS keyring: {}", e))
+    }
+
+    pub fn get(paths: &Paths) -> Result<String, String> {
+        entry(paths)?
+            .get_password()
+            .map_err(|e| format!("Failed to read vault key from OS keyring: {}", e))
+    }
+
+    pub fn set(paths: &Paths, key: &str) -> Result<(), String> {
+        entry(paths)?
+            .set_password(key)
+            .map_err(|e| format!("Failed to store vault key in OS keyring: {}", e))
+    }
+
+    pub fn delete(paths: &Paths) -> Result<(), String> {
+        entry(paths)?
+            .delete_credential()
+            .map_err(|e| format!("Failed to remove vault key from OS keyring: {}", e))
+    }
+}
+
+// Linux keyrings either need D-Bus or don't survive a reboot, so the key file is used there
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod os_keyring {
+    use crate::paths::Paths;
+
+    pub const AVAILABLE: bool = false;
+    const UNAVAILABLE: &str = "No OS keyring available on this platform";
+
+    pub fn get(_paths: &Paths) -> Result<String, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn set(_paths: &Paths, _key: &str) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn delete(_paths: &Paths) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use chrono::{DateTime, Local, TimeZone};
+use common::TempHome;
+use serde_json::json;
+use tvcbuddy_core::auto_switch::{
+    self, AutoSwitchSettings, Observations, RuleCondition, RuleEvaluator, SwitchRule, SwitchTarget,
+};
+use tvcbuddy_core::stores;
+
+fn home_with_profiles() -> TempHome {
+    let home = TempHome::new();
+    for id in ["home", "office", "lab"] {
+        stores::create_config(
+            &home.paths,
+            id.to_string(),
+            id.to_string(),
+            json!({ "env": { "ANTHROPIC_BASE_URL": format!("https://{}.example.com", id) } }),
+            None,
+        )
+        .unwrap();
+    }
+    home
+}
+
+fn rule(id: &str, store_id: &str, condition: RuleCondition) -> SwitchRule {
+    SwitchRule {
+        id: id.to_string(),
+        name: id.to_string(),
+        enabled: true,
+        target: SwitchTarget::Claude,
+        store_id: store_id.to_string(),
+        condition,
+    }
+}
+
+fn public_ip(addresses: &[&str]) -> RuleCondition {
+    RuleCondition::PublicIp {
+        addresses: addresses
+            .iter()
+            .map(|address| address.to_string())
+            .collect(),
+    }
+}
+
+fn settings(rules: Vec<SwitchRule>) -> AutoSwitchSettings {
+    AutoSwitchSettings {
+        enabled: true,
+        rules,
+        ..AutoSwitchSettings::default()
+    }
+}
+
+fn from(ip: &str) -> Observations {
+    Observations {
+        public_ip: Some(ip.parse().unwrap()),
+        ..Observations::default()
+    }
+}
+
+fn at(hour: u32, minute: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2025, 1, 6, hour, minute, 0).unwrap()
+}
+
+fn active(home: &TempHome) -> String {
+    stores::get_current_store(&home.paths).unwrap().unwrap().id
+}
+
+#[test]
+fn the_first_rule_holding_for_a_target_wins() {
+    let home = home_with_profiles();
+    let settings = settings(vec![
+        rule("office-network", "office", public_ip(&["10.0.0.0/8"])),
+        rule("lab-network", "lab", public_ip(&["10.1.0.0/16"])),
+    ]);
+
+    let entries =
+        RuleEvaluator::default().evaluate(&home.paths, &settings, at(9, 0), &from("10.1.2.3"));
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].rule_id, "office-network");
+    assert_eq!(entries[0].from_store.as_deref(), Some("home"));
+    assert!(entries[0].success);
+    assert_eq!(active(&home), "office");
+
+    // A disabled rule is skipped, so the next one in line fires
+    let mut settings = settings;
+    settings.rules[0].enabled = false;
+    let entries =
+        RuleEvaluator::default().evaluate(&home.paths, &settings, at(9, 0), &from("10.1.2.3"));
+    assert_eq!(entries[0].rule_id, "lab-network");
+    assert_eq!(active(&home), "lab");
+
+    let log = auto_switch::get_auto_switch_log(&home.paths, 10).unwrap();
+    let logged: Vec<&str> = log.iter().map(|entry| entry.rule_id.as_str()).collect();
+    assert_eq!(logged, ["lab-network", "office-network"]);
+}
+
+#[test]
+fn a_rule_fires_once_each_time_its_condition_starts_to_hold() {
+    let home = home_with_profiles();
+    let settings = settings(vec![rule(
+        "office-network",
+        "office",
+        public_ip(&["10.0.0.0/8"]),
+    )]);
+    let mut evaluator = RuleEvaluator::default();
+
+    assert_eq!(
+        evaluator
+            .evaluate(&home.paths, &settings, at(9, 0), &from("10.0.0.1"))
+            .len(),
+        1
+    );
+
+    // Switching back by hand while still in the office sticks
+    stores::set_using_config(&home.paths, "home").unwrap();
+    assert!(evaluator
+        .evaluate(&home.paths, &settings, at(9, 1), &from("10.0.0.1"))
+        .is_empty());
+    assert_eq!(active(&home), "home");
+
+    // Leaving and coming back is a new onset
+    assert!(evaluator
+        .evaluate(&home.paths, &settings, at(9, 2), &from("192.0.2.1"))
+        .is_empty());
+    assert_eq!(
+        evaluator
+            .evaluate(&home.paths, &settings, at(9, 3), &from("10.0.0.1"))
+            .len(),
+        1
+    );
+    assert_eq!(active(&home), "office");
+
+    // Nothing to do when the profile is already the active one
+    let mut evaluator = RuleEvaluator::default();
+    assert!(evaluator
+        .evaluate(&home.paths, &settings, at(9, 4), &from("10.0.0.1"))
+        .is_empty());
+}
+
+#[test]
+fn schedules_fire_for_minutes_between_two_evaluations() {
+    let home = home_with_profiles();
+    let settings = settings(vec![rule(
+        "workday",
+        "office",
+        RuleCondition::Schedule {
+            cron: "30 8 * * 1-5".to_string(),
+        },
+    )]);
+    let mut evaluator = RuleEvaluator::default();
+    let observations = Observations::default();
+
+    assert!(evaluator
+        .evaluate(&home.paths, &settings, at(8, 29), &observations)
+        .is_empty());
+    // 8:30 passed between two runs a few minutes apart
+    let entries = evaluator.evaluate(&home.paths, &settings, at(8, 33), &observations);
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].reason.contains("30 8 * * 1-5"));
+    assert_eq!(active(&home), "office");
+}
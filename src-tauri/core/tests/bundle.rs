@@ -0,0 +1,141 @@
+mod common;
+
+use common::TempHome;
+use serde_json::json;
+use tvcbuddy_core::bundle::{self, ConflictStrategy, ImportAction};
+use tvcbuddy_core::{mcp, settings, stores};
+
+const STORE_TOKEN: &str = "sk-store-token-0123456789";
+const STORE_HEADER: &str = "Bearer store-header-0123456789";
+const SERVER_HEADER: &str = "Bearer server-header-0123456789";
+const SERVER_KEY: &str = "server-api-key-0123456789";
+
+fn home_with_profiles() -> TempHome {
+    let home = TempHome::new();
+    stores::create_config(
+        &home.paths,
+        "work".to_string(),
+        "Work".to_string(),
+        json!({
+            "env": { "ANTHROPIC_AUTH_TOKEN": STORE_TOKEN },
+            "mcpServers": {
+                "docs": {
+                    "type": "http",
+                    "url": "https://docs.example.com/mcp",
+                    "headers": { "Authorization": STORE_HEADER, "Accept": "text/event-stream" }
+                }
+            }
+        }),
+        None,
+    )
+    .unwrap();
+    mcp::update_global_mcp_server(
+        &home.paths,
+        "search".to_string(),
+        json!({
+            "type": "http",
+            "url": "https://search.example.com/mcp",
+            "headers": { "Authorization": SERVER_HEADER },
+            "env": { "SEARCH_API_KEY": SERVER_KEY, "SEARCH_REGION": "eu" }
+        }),
+    )
+    .unwrap();
+    home
+}
+
+#[test]
+fn sealed_bundle_hides_mcp_credentials_and_restores_them() {
+    let source = home_with_profiles();
+    let file = source.dir.join("bundle.json");
+    bundle::export_profiles(&source.paths, &file, Some("correct horse")).unwrap();
+
+    let content = std::fs::read_to_string(&file).unwrap();
+    for secret in [STORE_TOKEN, STORE_HEADER, SERVER_HEADER, SERVER_KEY] {
+        assert!(!content.contains(secret), "{} left in the bundle", secret);
+    }
+    assert!(content.contains("text/event-stream"));
+    assert!(content.contains("\"eu\""));
+
+    let target = TempHome::new();
+    bundle::import_profiles(
+        &target.paths,
+        &file,
+        Some("correct horse"),
+        ConflictStrategy::Skip,
+        false,
+    )
+    .unwrap();
+
+    let store = stores::get_store(&target.paths, "work").unwrap();
+    assert_eq!(
+        store.settings["mcpServers"]["docs"]["headers"]["Authorization"],
+        STORE_HEADER
+    );
+    let servers = mcp::get_global_mcp_servers(&target.paths).unwrap();
+    assert_eq!(
+        servers["search"].config["headers"]["Authorization"],
+        SERVER_HEADER
+    );
+    assert_eq!(
+        servers["search"].config["env"]["SEARCH_API_KEY"],
+        SERVER_KEY
+    );
+}
+
+#[test]
+fn stripped_overwrite_keeps_existing_mcp_credentials() {
+    let home = home_with_profiles();
+    let file = home.dir.join("bundle.json");
+    let exported = bundle::export_profiles(&home.paths, &file, None).unwrap();
+    assert!(exported.mcp_servers["search"]["headers"]
+        .get("Authorization")
+        .is_none());
+    assert_eq!(exported.mcp_servers["search"]["env"]["SEARCH_REGION"], "eu");
+
+    let report =
+        bundle::import_profiles(&home.paths, &file, None, ConflictStrategy::Overwrite, false)
+            .unwrap();
+    assert!(report
+        .items
+        .iter()
+        .all(|item| item.action == ImportAction::Overwrite));
+
+    let servers = mcp::get_global_mcp_servers(&home.paths).unwrap();
+    assert_eq!(
+        servers["search"].config["headers"]["Authorization"],
+        SERVER_HEADER
+    );
+    assert_eq!(
+        servers["search"].config["env"]["SEARCH_API_KEY"],
+        SERVER_KEY
+    );
+    let store = stores::get_store(&home.paths, "work").unwrap();
+    assert_eq!(
+        store.settings["mcpServers"]["docs"]["headers"]["Authorization"],
+        STORE_HEADER
+    );
+}
+
+#[test]
+fn import_leaves_the_active_profile_alone() {
+    let source = home_with_profiles();
+    let file = source.dir.join("bundle.json");
+    bundle::export_profiles(&source.paths, &file, None).unwrap();
+
+    let target = TempHome::new();
+    settings::write_user_settings(&target.paths, &json!({ "theme": "dark" })).unwrap();
+    let report =
+        bundle::import_profiles(&target.paths, &file, None, ConflictStrategy::Skip, false).unwrap();
+    assert!(report
+        .items
+        .iter()
+        .all(|item| item.action == ImportAction::Create));
+
+    // Imported into an empty list, the profile is still not switched to
+    assert!(stores::get_current_store(&target.paths).unwrap().is_none());
+    assert!(!stores::get_store(&target.paths, "work").unwrap().using);
+    assert_eq!(
+        settings::read_user_settings(&target.paths).unwrap(),
+        json!({ "theme": "dark" })
+    );
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::TempHome;
+use serde_json::Value;
+use std::process::{Command, Output};
+use tvcbuddy_core::settings;
+
+fn cli(home: &TempHome, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tvcbuddy-cli"))
+        .args(args)
+        .env("HOME", &home.dir)
+        .env_remove("TVCBUDDY_VAULT_PASSPHRASE")
+        .output()
+        .expect("run tvcbuddy-cli")
+}
+
+fn json_stdout(output: &Output) -> Value {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!(
+            "stdout is not JSON ({}): {}",
+            e,
+            String::from_utf8_lossy(&output.stdout)
+        )
+    })
+}
+
+#[test]
+fn json_output_stays_parseable_from_a_fresh_home() {
+    let home = TempHome::new();
+    let settings = home.dir.join("s.json");
+    std::fs::write(
+        &settings,
+        r#"{ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-work-token-0123456789" } }"#,
+    )
+    .unwrap();
+
+    // Creating the vault, config.json and the first activation all report on stderr
+    let created = json_stdout(&cli(
+        &home,
+        &[
+            "--json",
+            "create",
+            "work",
+            "--settings",
+            settings.to_str().unwrap(),
+        ],
+    ));
+    assert_eq!(created["title"], "work");
+    let id = created["id"].as_str().unwrap();
+
+    let listed = json_stdout(&cli(&home, &["list", "--json"]));
+    assert_eq!(listed[0]["id"], id);
+    json_stdout(&cli(&home, &["--json", "use", id]));
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap()["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-work-token-0123456789"
+    );
+    json_stdout(&cli(&home, &["--json", "vault"]));
+    json_stdout(&cli(&home, &["--json", "reset"]));
+}
+
+#[test]
+fn extra_arguments_are_rejected() {
+    let home = TempHome::new();
+    for args in [
+        &["list", "extra"][..],
+        &["use", "work", "extra"],
+        &["delete", "work", "extra"],
+        &["unbind", "project", "extra"],
+        &["export", "bundle.json", "extra"],
+        &["reset", "extra"],
+    ] {
+        let output = cli(&home, args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("unexpected argument 'extra'"));
+    }
+}
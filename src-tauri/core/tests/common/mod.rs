@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tvcbuddy_core::Paths;
+
+static NEXT_HOME: AtomicUsize = AtomicUsize::new(0);
+
+/// A fresh home directory under the system temp dir, removed when dropped
+pub struct TempHome {
+    pub dir: PathBuf,
+    pub paths: Paths,
+}
+
+impl TempHome {
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "tvcbuddy-test-{}-{}",
+            std::process::id(),
+            NEXT_HOME.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create temp home");
+        let paths = Paths::from_home(&dir);
+        Self { dir, paths }
+    }
+}
+
+impl Drop for TempHome {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
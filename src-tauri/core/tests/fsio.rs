@@ -0,0 +1,123 @@
+mod common;
+
+use common::TempHome;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tvcbuddy_core::fsio;
+
+fn leftover_temp_files(dir: &std::path::Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.contains(".tmp-"))
+        .collect()
+}
+
+#[test]
+fn updates_wait_for_the_lock() {
+    let home = TempHome::new();
+    let file = home.dir.join("data.json");
+    let lock = fsio::lock(&home.paths, &file).unwrap();
+
+    let writer = std::thread::spawn({
+        let paths = home.paths.clone();
+        let file = file.clone();
+        move || {
+            fsio::update_json(&paths, &file, "data", |_: Option<Value>| {
+                Ok((Some(json!({ "writer": true })), ()))
+            })
+        }
+    });
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(!file.exists(), "wrote while the lock was held");
+
+    drop(lock);
+    writer.join().unwrap().unwrap();
+    assert_eq!(
+        fsio::read_json::<Value>(&file, "data").unwrap(),
+        Some(json!({ "writer": true }))
+    );
+}
+
+#[test]
+fn update_is_retried_when_the_file_changes_underneath() {
+    let home = TempHome::new();
+    let file = home.dir.join("data.json");
+    std::fs::write(&file, r#"{ "count": 1 }"#).unwrap();
+
+    // The first attempt races with a writer that does not take the lock
+    let mut attempts = 0;
+    fsio::update_json(&home.paths, &file, "data", |current: Option<Value>| {
+        attempts += 1;
+        if attempts == 1 {
+            std::fs::write(&file, r#"{ "count": 10, "outside": true }"#).unwrap();
+        }
+        let mut value = current.unwrap();
+        value["count"] = json!(value["count"].as_u64().unwrap() + 1);
+        Ok((Some(value), ()))
+    })
+    .unwrap();
+
+    assert_eq!(attempts, 2);
+    assert_eq!(
+        fsio::read_json::<Value>(&file, "data").unwrap(),
+        Some(json!({ "count": 11, "outside": true }))
+    );
+}
+
+#[test]
+fn update_gives_up_on_a_file_that_keeps_changing() {
+    let home = TempHome::new();
+    let file = home.dir.join("data.json");
+
+    let mut attempts = 0;
+    let error = fsio::update_json(&home.paths, &file, "data", |_: Option<Value>| {
+        attempts += 1;
+        std::fs::write(&file, format!(r#"{{ "outside": {} }}"#, attempts)).unwrap();
+        Ok((Some(json!({ "ours": true })), ()))
+    })
+    .unwrap_err();
+
+    assert!(error.contains("kept changing"), "{}", error);
+    assert_eq!(attempts, 5);
+    assert_eq!(
+        fsio::read_json::<Value>(&file, "data").unwrap(),
+        Some(json!({ "outside": 5 }))
+    );
+}
+
+#[test]
+fn atomic_writes_replace_the_file_without_leaving_temp_files() {
+    let home = TempHome::new();
+    let file = home.dir.join("nested/data.json");
+    fsio::write_atomic(&file, b"first").unwrap();
+    fsio::write_atomic(&file, b"second").unwrap();
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "second");
+    assert!(leftover_temp_files(file.parent().unwrap()).is_empty());
+
+    // A rename that fails cleans up after itself and leaves the target alone
+    let dir = home.dir.join("taken");
+    std::fs::create_dir_all(dir.join("child")).unwrap();
+    assert!(fsio::write_atomic(&dir, b"content").is_err());
+    assert!(dir.join("child").is_dir());
+    assert!(leftover_temp_files(&home.dir).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn atomic_writes_keep_the_mode_of_the_replaced_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = TempHome::new();
+    let mode =
+        |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    let file = home.dir.join("credentials.json");
+    fsio::write_atomic(&file, b"{}").unwrap();
+    std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o640)).unwrap();
+    fsio::write_atomic(&file, b"{ \"token\": 1 }").unwrap();
+    assert_eq!(mode(&file), 0o640);
+
+    let private = home.dir.join("auth.json");
+    fsio::write_private(&private, b"{}").unwrap();
+    assert_eq!(mode(&private), 0o600);
+}
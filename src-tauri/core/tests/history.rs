@@ -0,0 +1,63 @@
+mod common;
+
+use common::TempHome;
+use serde_json::json;
+use tvcbuddy_core::{history, settings};
+
+#[test]
+fn restores_a_snapshot_of_settings() {
+    let home = TempHome::new();
+    settings::write_user_settings(&home.paths, &json!({ "model": "sonnet" })).unwrap();
+    settings::write_user_settings(&home.paths, &json!({ "model": "opus" })).unwrap();
+
+    let files = history::list_history_files(&home.paths).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].name, "settings.json");
+    let versions = history::list_history_versions(&home.paths, &files[0].key).unwrap();
+    assert_eq!(versions.len(), 1);
+
+    history::restore_history_version(&home.paths, &files[0].key, &versions[0].id).unwrap();
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap(),
+        json!({ "model": "sonnet" })
+    );
+    // The replaced content was snapshotted too, so the restore can be undone
+    let versions = history::list_history_versions(&home.paths, &files[0].key).unwrap();
+    assert_eq!(versions.len(), 2);
+    assert!(
+        history::read_history_version(&home.paths, &files[0].key, &versions[0].id)
+            .unwrap()
+            .contains("opus")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn snapshots_are_private_and_keep_the_source_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = TempHome::new();
+    let mode =
+        |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    let source = home.paths.user_settings();
+    settings::write_user_settings(&home.paths, &json!({ "env": { "TOKEN": "sk-one" } })).unwrap();
+    std::fs::set_permissions(&source, std::fs::Permissions::from_mode(0o600)).unwrap();
+    settings::write_user_settings(&home.paths, &json!({ "env": { "TOKEN": "sk-two" } })).unwrap();
+
+    let key = history::list_history_files(&home.paths).unwrap()[0]
+        .key
+        .clone();
+    let dir = home.paths.history_dir().join(&key);
+    let version = history::list_history_versions(&home.paths, &key).unwrap()[0]
+        .id
+        .clone();
+    assert_eq!(mode(&home.paths.history_dir()), 0o700);
+    assert_eq!(mode(&dir), 0o700);
+    assert_eq!(mode(&dir.join(&version)), 0o600);
+
+    history::restore_history_version(&home.paths, &key, &version).unwrap();
+    assert_eq!(mode(&source), 0o600);
+    for version in history::list_history_versions(&home.paths, &key).unwrap() {
+        assert_eq!(mode(&dir.join(&version.id)), 0o600);
+    }
+}
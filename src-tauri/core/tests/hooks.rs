@@ -0,0 +1,178 @@
+mod common;
+
+use common::TempHome;
+use serde_json::{json, Value};
+use tvcbuddy_core::stores::{self, NotificationSettings};
+use tvcbuddy_core::{hooks, settings};
+
+fn ccmate_events(settings: &Value) -> Vec<String> {
+    let mut events: Vec<String> = settings["hooks"]
+        .as_object()
+        .map(|hooks| {
+            hooks
+                .iter()
+                .filter(|(_, entries)| entries.to_string().contains("__ccmate__"))
+                .map(|(event, _)| event.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    events.sort();
+    events
+}
+
+#[test]
+fn installs_the_hook_for_enabled_events_next_to_user_hooks() {
+    let home = TempHome::new();
+    let user_hook =
+        json!({ "matcher": "Bash", "hooks": [{ "type": "command", "command": "echo hi" }] });
+    settings::write_user_settings(
+        &home.paths,
+        &json!({ "hooks": { "Notification": [user_hook.clone()] } }),
+    )
+    .unwrap();
+
+    hooks::add_claude_code_hook(&home.paths).unwrap();
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    // Notification is the only event enabled by default
+    assert_eq!(ccmate_events(&user_settings), vec!["Notification"]);
+    let entries = user_settings["hooks"]["Notification"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0], user_hook);
+    let token = hooks::hook_token(&home.paths).unwrap();
+    assert_eq!(
+        entries[1]["hooks"][0],
+        hooks::get_latest_hook_command("Notification", &token)
+    );
+
+    // Installing again doesn't add a second hook
+    hooks::add_claude_code_hook(&home.paths).unwrap();
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert_eq!(
+        user_settings["hooks"]["Notification"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn notification_settings_sync_an_installed_hook() {
+    let home = TempHome::new();
+    hooks::add_claude_code_hook(&home.paths).unwrap();
+
+    stores::update_notification_settings(
+        &home.paths,
+        NotificationSettings {
+            enable: true,
+            enabled_hooks: vec!["Stop".to_string(), "PreToolUse".to_string()],
+        },
+    )
+    .unwrap();
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert_eq!(ccmate_events(&user_settings), vec!["PreToolUse", "Stop"]);
+}
+
+#[test]
+fn only_the_pre_tool_use_hook_waits_for_approvals() {
+    let home = TempHome::new();
+    stores::update_notification_settings(
+        &home.paths,
+        NotificationSettings {
+            enable: true,
+            enabled_hooks: vec!["Stop".to_string(), "PreToolUse".to_string()],
+        },
+    )
+    .unwrap();
+    hooks::add_claude_code_hook(&home.paths).unwrap();
+
+    let timeout =
+        |settings: &Value, event: &str| settings["hooks"][event][0]["hooks"][0]["timeout"].clone();
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert_eq!(
+        timeout(&user_settings, "PreToolUse"),
+        json!(hooks::APPROVAL_HOOK_TIMEOUT_SECS)
+    );
+    assert_eq!(
+        timeout(&user_settings, "Stop"),
+        json!(hooks::HOOK_TIMEOUT_SECS)
+    );
+
+    // Hooks that were installed with the approval timeout on every event get the short one back
+    let mut user_settings = user_settings;
+    user_settings["hooks"]["Stop"][0]["hooks"][0]["timeout"] =
+        json!(hooks::APPROVAL_HOOK_TIMEOUT_SECS);
+    settings::write_user_settings(&home.paths, &user_settings).unwrap();
+    hooks::update_claude_code_hook(&home.paths).unwrap();
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert_eq!(
+        timeout(&user_settings, "Stop"),
+        json!(hooks::HOOK_TIMEOUT_SECS)
+    );
+    assert_eq!(
+        timeout(&user_settings, "PreToolUse"),
+        json!(hooks::APPROVAL_HOOK_TIMEOUT_SECS)
+    );
+}
+
+#[test]
+fn removes_only_the_app_hooks() {
+    let home = TempHome::new();
+    let user_hook = json!({ "hooks": [{ "type": "command", "command": "echo hi" }] });
+    settings::write_user_settings(
+        &home.paths,
+        &json!({ "hooks": { "Stop": [user_hook.clone()] } }),
+    )
+    .unwrap();
+    hooks::add_claude_code_hook(&home.paths).unwrap();
+
+    hooks::remove_claude_code_hook(&home.paths).unwrap();
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert!(ccmate_events(&user_settings).is_empty());
+    assert_eq!(user_settings["hooks"]["Stop"], json!([user_hook]));
+
+    // Without an installed hook, syncing leaves settings.json alone
+    hooks::sync_claude_code_hook(&home.paths).unwrap();
+    assert!(ccmate_events(&settings::read_user_settings(&home.paths).unwrap()).is_empty());
+}
+
+#[test]
+fn the_hook_command_carries_a_private_per_install_token() {
+    let home = TempHome::new();
+    hooks::add_claude_code_hook(&home.paths).unwrap();
+
+    let token = hooks::hook_token(&home.paths).unwrap();
+    assert!(token.len() > 32);
+    assert_eq!(hooks::hook_token(&home.paths).unwrap(), token);
+    assert_ne!(hooks::hook_token(&TempHome::new().paths).unwrap(), token);
+    let command = settings::read_user_settings(&home.paths).unwrap()["hooks"]["Notification"][0]
+        ["hooks"][0]["command"]
+        .clone();
+    assert!(command.as_str().unwrap().contains(&format!(
+        "{}: {}",
+        hooks::HOOK_TOKEN_HEADER,
+        token
+    )));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(home.paths.hook_token_file())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Hooks installed before the token existed are brought up to date
+    let mut settings = settings::read_user_settings(&home.paths).unwrap();
+    settings["hooks"]["Notification"][0]["hooks"][0]["command"] =
+        json!("curl -s -X POST http://localhost:59948/claude_code/hooks");
+    settings::write_user_settings(&home.paths, &settings).unwrap();
+    hooks::update_claude_code_hook(&home.paths).unwrap();
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap()["hooks"]["Notification"][0]["hooks"][0]
+            ["command"],
+        command
+    );
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::TempHome;
+use serde_json::json;
+use tvcbuddy_core::mcp;
+
+#[test]
+fn adds_and_removes_global_mcp_servers() {
+    let home = TempHome::new();
+    std::fs::write(
+        &home.paths.claude_json,
+        r#"{ "numStartups": 3, "mcpServers": {} }"#,
+    )
+    .unwrap();
+
+    let server = json!({ "type": "stdio", "command": "npx", "args": ["-y", "server"] });
+    mcp::update_global_mcp_server(&home.paths, "files".to_string(), server.clone()).unwrap();
+    mcp::update_global_mcp_server(
+        &home.paths,
+        "web".to_string(),
+        json!({ "type": "http", "url": "https://example.com/mcp" }),
+    )
+    .unwrap();
+
+    assert!(mcp::check_mcp_server_exists(&home.paths, "files").unwrap());
+    let servers = mcp::get_global_mcp_servers(&home.paths).unwrap();
+    assert_eq!(servers.len(), 2);
+    assert_eq!(servers["files"].config, server);
+
+    mcp::delete_global_mcp_server(&home.paths, "files").unwrap();
+    assert!(!mcp::check_mcp_server_exists(&home.paths, "files").unwrap());
+    assert!(mcp::delete_global_mcp_server(&home.paths, "files").is_err());
+
+    // The rest of .claude.json is left alone, and the last server takes mcpServers with it
+    mcp::delete_global_mcp_server(&home.paths, "web").unwrap();
+    let claude_json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&home.paths.claude_json).unwrap()).unwrap();
+    assert_eq!(claude_json, json!({ "numStartups": 3 }));
+}
+
+#[test]
+fn adding_a_server_creates_claude_json() {
+    let home = TempHome::new();
+    assert!(mcp::get_global_mcp_servers(&home.paths).unwrap().is_empty());
+    assert!(mcp::delete_global_mcp_server(&home.paths, "files").is_err());
+
+    mcp::update_global_mcp_server(&home.paths, "files".to_string(), json!({ "command": "x" }))
+        .unwrap();
+    assert!(mcp::check_mcp_server_exists(&home.paths, "files").unwrap());
+}
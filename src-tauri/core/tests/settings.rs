@@ -0,0 +1,67 @@
+mod common;
+
+use common::TempHome;
+use serde_json::json;
+use tvcbuddy_core::merge;
+use tvcbuddy_core::settings;
+
+#[test]
+fn switch_user_settings_reverts_the_previous_changes() {
+    let home = TempHome::new();
+    let strategies = merge::default_strategies();
+    settings::write_user_settings(
+        &home.paths,
+        &json!({ "env": { "USER_VAR": "1" }, "permissions": { "allow": ["Read"] } }),
+    )
+    .unwrap();
+
+    let first = json!({
+        "env": { "ANTHROPIC_BASE_URL": "https://a" },
+        "permissions": { "allow": ["Read", "Bash(ls)"] }
+    });
+    let changes =
+        settings::switch_user_settings(&home.paths, &[], Some(&first), &strategies).unwrap();
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap(),
+        json!({
+            "env": { "USER_VAR": "1", "ANTHROPIC_BASE_URL": "https://a" },
+            "permissions": { "allow": ["Read", "Bash(ls)"] }
+        })
+    );
+
+    let second = json!({ "model": "opus" });
+    let changes =
+        settings::switch_user_settings(&home.paths, &changes, Some(&second), &strategies).unwrap();
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap(),
+        json!({
+            "env": { "USER_VAR": "1" },
+            "permissions": { "allow": ["Read"] },
+            "model": "opus"
+        })
+    );
+
+    // Switching to nothing leaves only the user's own settings
+    let changes = settings::switch_user_settings(&home.paths, &changes, None, &strategies).unwrap();
+    assert!(changes.is_empty());
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap(),
+        json!({ "env": { "USER_VAR": "1" }, "permissions": { "allow": ["Read"] } })
+    );
+}
+
+#[test]
+fn switch_user_settings_creates_settings_json() {
+    let home = TempHome::new();
+    let settings = json!({ "env": { "ANTHROPIC_MODEL": "sonnet" } });
+    settings::switch_user_settings(
+        &home.paths,
+        &[],
+        Some(&settings),
+        &merge::default_strategies(),
+    )
+    .unwrap();
+
+    assert!(home.paths.user_settings().exists());
+    assert_eq!(settings::read_user_settings(&home.paths).unwrap(), settings);
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use common::TempHome;
+use serde_json::json;
+use tvcbuddy_core::{settings, stores, vault};
+
+fn create(home: &TempHome, id: &str, settings: serde_json::Value) -> stores::ConfigStore {
+    stores::create_config(
+        &home.paths,
+        id.to_string(),
+        format!("Profile {}", id),
+        settings,
+        None,
+    )
+    .expect("create profile")
+}
+
+#[test]
+fn first_profile_is_activated_and_merged_into_settings() {
+    let home = TempHome::new();
+    settings::write_user_settings(&home.paths, &json!({ "theme": "dark" })).unwrap();
+
+    let store = create(
+        &home,
+        "a",
+        json!({ "env": { "ANTHROPIC_BASE_URL": "https://a" } }),
+    );
+    assert!(store.using);
+
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert_eq!(user_settings["theme"], "dark");
+    assert_eq!(user_settings["env"]["ANTHROPIC_BASE_URL"], "https://a");
+
+    // Later profiles are created inactive
+    let second = create(&home, "b", json!({}));
+    assert!(!second.using);
+    assert_eq!(
+        stores::get_current_store(&home.paths).unwrap().unwrap().id,
+        "a"
+    );
+}
+
+#[test]
+fn tokens_are_sealed_in_the_store_and_revealed_in_settings() {
+    let home = TempHome::new();
+    let store = create(
+        &home,
+        "a",
+        json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-secret-token-value" } }),
+    );
+
+    let stored = store.settings["env"]["ANTHROPIC_AUTH_TOKEN"]
+        .as_str()
+        .unwrap();
+    assert!(vault::is_handle(stored));
+    let stores_json = std::fs::read_to_string(home.paths.stores_file()).unwrap();
+    assert!(!stores_json.contains("sk-secret-token-value"));
+
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert_eq!(
+        user_settings["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-secret-token-value"
+    );
+}
+
+#[test]
+fn switching_replaces_the_previous_profile_and_keeps_user_settings() {
+    let home = TempHome::new();
+    settings::write_user_settings(
+        &home.paths,
+        &json!({ "env": { "USER_VAR": "1" }, "permissions": { "allow": ["Read"] } }),
+    )
+    .unwrap();
+
+    create(
+        &home,
+        "a",
+        json!({
+            "env": { "ANTHROPIC_BASE_URL": "https://a", "ONLY_A": "1" },
+            "permissions": { "allow": ["Bash(ls)"] }
+        }),
+    );
+    create(
+        &home,
+        "b",
+        json!({ "env": { "ANTHROPIC_BASE_URL": "https://b" }, "model": "opus" }),
+    );
+
+    stores::set_using_config(&home.paths, "b").unwrap();
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert_eq!(
+        user_settings,
+        json!({
+            "env": { "USER_VAR": "1", "ANTHROPIC_BASE_URL": "https://b" },
+            "permissions": { "allow": ["Read"] },
+            "model": "opus"
+        })
+    );
+    assert_eq!(
+        stores::get_current_store(&home.paths).unwrap().unwrap().id,
+        "b"
+    );
+
+    // Resetting takes the active profile back out
+    stores::reset_to_original_config(&home.paths).unwrap();
+    let user_settings = settings::read_user_settings(&home.paths).unwrap();
+    assert_eq!(
+        user_settings,
+        json!({ "env": { "USER_VAR": "1" }, "permissions": { "allow": ["Read"] } })
+    );
+    assert!(stores::get_current_store(&home.paths).unwrap().is_none());
+}
+
+#[test]
+fn deleting_a_profile_removes_it_unless_another_inherits_from_it() {
+    let home = TempHome::new();
+    create(&home, "a", json!({}));
+    stores::create_config(
+        &home.paths,
+        "child".to_string(),
+        "Child".to_string(),
+        json!({}),
+        Some("a".to_string()),
+    )
+    .unwrap();
+
+    assert!(stores::delete_config(&home.paths, "a").is_err());
+
+    stores::delete_config(&home.paths, "child").unwrap();
+    stores::delete_config(&home.paths, "a").unwrap();
+    assert!(stores::get_stores(&home.paths).unwrap().is_empty());
+    assert!(stores::delete_config(&home.paths, "a").is_err());
+}
+
+#[test]
+fn a_parent_that_would_form_a_cycle_is_rejected_and_nothing_changes() {
+    let home = TempHome::new();
+    create(&home, "a", json!({ "model": "sonnet" }));
+    stores::create_config(
+        &home.paths,
+        "b".to_string(),
+        "Profile b".to_string(),
+        json!({ "env": { "B": "1" } }),
+        Some("a".to_string()),
+    )
+    .unwrap();
+
+    let error = stores::update_config(
+        &home.paths,
+        "a",
+        "Profile a".to_string(),
+        json!({ "model": "opus" }),
+        Some("b".to_string()),
+    )
+    .unwrap_err();
+    assert!(error.starts_with("Inheritance cycle"), "{}", error);
+    assert!(stores::create_config(
+        &home.paths,
+        "c".to_string(),
+        "Profile c".to_string(),
+        json!({}),
+        Some("missing".to_string()),
+    )
+    .is_err());
+
+    assert_eq!(stores::get_store(&home.paths, "a").unwrap().parent, None);
+    assert_eq!(
+        stores::get_resolved_store_settings(&home.paths, "b").unwrap(),
+        json!({ "model": "sonnet", "env": { "B": "1" } })
+    );
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap()["model"],
+        "sonnet"
+    );
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::TempHome;
+use serde_json::json;
+use std::sync::Arc;
+use tvcbuddy_core::hook_log;
+use tvcbuddy_core::tool_rules::{
+    self, RuleAction, ToolCondition, ToolRule, ToolRuleCache, ToolRuleSettings,
+};
+
+fn deny_rule(pattern: &str) -> ToolRule {
+    ToolRule {
+        id: String::new(),
+        name: "No rm".to_string(),
+        enabled: true,
+        tools: vec!["Bash".to_string()],
+        condition: ToolCondition::InputMatches {
+            field: "command".to_string(),
+            pattern: pattern.to_string(),
+        },
+        action: RuleAction::Deny,
+        reason: String::new(),
+    }
+}
+
+#[test]
+fn cache_reloads_rules_only_when_the_file_changes() {
+    let home = TempHome::new();
+    let mut cache = ToolRuleCache::new();
+    let empty = cache.load(&home.paths).unwrap();
+    assert!(empty.settings.rules.is_empty());
+
+    tool_rules::update_tool_rule_settings(
+        &home.paths,
+        ToolRuleSettings {
+            enabled: true,
+            dry_run: false,
+            rules: vec![deny_rule("^rm ")],
+        },
+    )
+    .unwrap();
+    let loaded = cache.load(&home.paths).unwrap();
+    assert!(!Arc::ptr_eq(&empty, &loaded));
+    assert!(Arc::ptr_eq(&loaded, &cache.load(&home.paths).unwrap()));
+
+    let command = json!({ "command": "rm -rf build" });
+    let matched = loaded.evaluate("Bash", &command, "/p").unwrap();
+    assert_eq!(matched.action, RuleAction::Deny);
+    assert!(loaded
+        .evaluate("Bash", &json!({ "command": "ls" }), "/p")
+        .is_none());
+}
+
+#[test]
+fn records_rule_decisions_in_the_hook_log() {
+    let home = TempHome::new();
+    let rules = vec![deny_rule("^rm ")];
+    let matched =
+        tool_rules::evaluate(&rules, "Bash", &json!({ "command": "rm -rf /" }), "/p").unwrap();
+
+    hook_log::record_rule_decision(
+        &home.paths,
+        "session",
+        "/p",
+        "Bash",
+        "Bash: rm -rf /",
+        &matched,
+        true,
+    )
+    .unwrap();
+    let decisions = hook_log::get_rule_decisions(&home.paths, None).unwrap();
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].rule_name, "No rm");
+    assert_eq!(decisions[0].action, RuleAction::Deny);
+    assert_eq!(decisions[0].reason, matched.reason);
+    assert!(decisions[0].dry_run);
+
+    hook_log::clear_hook_events(&home.paths, None).unwrap();
+    assert!(hook_log::get_rule_decisions(&home.paths, None)
+        .unwrap()
+        .is_empty());
+}
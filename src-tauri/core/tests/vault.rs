@@ -0,0 +1,111 @@
+mod common;
+
+use common::TempHome;
+use serde_json::json;
+use tvcbuddy_core::{bindings, settings, stores, vault};
+
+#[test]
+fn masks_show_at_most_four_characters() {
+    assert_eq!(vault::mask("sk-short"), "••••••••");
+    assert_eq!(vault::mask("sk-elevenxx"), "••••••••");
+    assert_eq!(vault::mask("sk-twelve-ab"), "••••••••e-ab");
+    assert_eq!(
+        vault::mask("sk-ant-REDACTED"),
+        "••••••••wxyz"
+    );
+    assert!(vault::is_masked(&vault::mask(
+        "sk-ant-REDACTED"
+    )));
+}
+
+#[test]
+fn overwritten_user_tokens_are_sealed_in_the_recorded_changes() {
+    let home = TempHome::new();
+    settings::write_user_settings(
+        &home.paths,
+        &json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-users-own-token" } }),
+    )
+    .unwrap();
+
+    stores::create_config(
+        &home.paths,
+        "a".to_string(),
+        "A".to_string(),
+        json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-profile-token" } }),
+        None,
+    )
+    .unwrap();
+    let stores_json = std::fs::read_to_string(home.paths.stores_file()).unwrap();
+    assert!(!stores_json.contains("sk-users-own-token"));
+    assert!(!stores_json.contains("sk-profile-token"));
+
+    // Reverting still brings the user's token back
+    stores::reset_to_original_config(&home.paths).unwrap();
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap(),
+        json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-users-own-token" } })
+    );
+}
+
+#[test]
+fn switching_back_and_forth_keeps_the_secret_count_stable() {
+    let home = TempHome::new();
+    settings::write_user_settings(
+        &home.paths,
+        &json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-users-own-token" } }),
+    )
+    .unwrap();
+    for (id, token) in [("a", "sk-profile-a-token"), ("b", "sk-profile-b-token")] {
+        stores::create_config(
+            &home.paths,
+            id.to_string(),
+            id.to_uppercase(),
+            json!({ "env": { "ANTHROPIC_AUTH_TOKEN": token } }),
+            None,
+        )
+        .unwrap();
+    }
+    let project = home.dir.join("project");
+    std::fs::create_dir_all(project.join(".claude")).unwrap();
+    std::fs::write(
+        project.join(".claude/settings.local.json"),
+        r#"{ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-project-token" } }"#,
+    )
+    .unwrap();
+    let project = project.to_string_lossy().to_string();
+
+    let secrets = || vault::get_vault_status(&home.paths).unwrap().secrets;
+    stores::set_using_config(&home.paths, "b").unwrap();
+    bindings::bind_project(&home.paths, &project, "a").unwrap();
+    let expected = secrets();
+    for _ in 0..5 {
+        stores::set_using_config(&home.paths, "a").unwrap();
+        stores::set_using_config(&home.paths, "b").unwrap();
+        bindings::bind_project(&home.paths, &project, "b").unwrap();
+        bindings::bind_project(&home.paths, &project, "a").unwrap();
+    }
+    assert_eq!(secrets(), expected);
+
+    // Nothing holds the moved copies once everything is reverted
+    bindings::unbind_project(&home.paths, &project).unwrap();
+    stores::reset_to_original_config(&home.paths).unwrap();
+    assert_eq!(secrets(), 2);
+    assert_eq!(
+        settings::read_user_settings(&home.paths).unwrap(),
+        json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-users-own-token" } })
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn key_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = TempHome::new();
+    vault::store_secret(&home.paths, "sk-secret").unwrap();
+    let mode = std::fs::metadata(home.paths.vault_key_file())
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
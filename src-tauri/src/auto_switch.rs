@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Runtime};
+use tauri_plugin_notification::NotificationExt;
+use tvcbuddy_core::auto_switch::{
+    self, AuditEntry, AutoSwitchSettings, EndpointProbe, Observations, RuleEvaluator, SwitchTarget,
+};
+use tvcbuddy_core::Paths;
+
+use crate::{health, tray};
+
+/// Event sent to the frontend after a rule switched profiles
+pub const AUTO_SWITCHED_EVENT: &str = "auto-switched";
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Evaluate the automatic switching rules in the background for as long as the app runs.
+pub fn start_auto_switch<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut evaluator = RuleEvaluator::default();
+        loop {
+            let interval = match run_rules(&app, &mut evaluator).await {
+                Ok(interval) => interval,
+                Err(e) => {
+                    eprintln!("Failed to evaluate auto switch rules: {}", e);
+                    AutoSwitchSettings::default().interval_secs
+                }
+            };
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+/// One evaluation, returning the seconds to wait before the next
+async fn run_rules<R: Runtime>(
+    app: &AppHandle<R>,
+    evaluator: &mut RuleEvaluator,
+) -> Result<u64, String> {
+    let paths = Paths::detect()?;
+    let settings = auto_switch::get_auto_switch_settings(&paths)?;
+    if !settings.enabled || settings.rules.is_empty() {
+        return Ok(settings.interval_secs);
+    }
+
+    let observations = observe(&paths, &settings).await?;
+    let entries = evaluator.evaluate(&paths, &settings, chrono::Local::now(), &observations);
+    announce(app, &entries).await;
+    Ok(settings.interval_secs)
+}
+
+/// Tell the user and the frontend about automatic switches, and refresh the tray
+pub async fn announce<R: Runtime>(app: &AppHandle<R>, entries: &[AuditEntry]) {
+    if entries.is_empty() {
+        return;
+    }
+
+    for entry in entries {
+        notify(app, entry);
+        if let Err(e) = app.emit(AUTO_SWITCHED_EVENT, entry) {
+            eprintln!("Failed to emit {} event: {}", AUTO_SWITCHED_EVENT, e);
+        }
+    }
+    if let Err(e) = tray::rebuild_tray_menu(app.clone()).await {
+        eprintln!("Failed to rebuild tray menu: {}", e);
+    }
+}
+
+// Measure only what the enabled rules look at
+async fn observe(paths: &Paths, settings: &AutoSwitchSettings) -> Result<Observations, String> {
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut observations = Observations::default();
+    if auto_switch::needs_public_ip(settings) {
+        match fetch_public_ip(&client, &settings.public_ip_url).await {
+            Ok(ip) => observations.public_ip = Some(ip),
+            Err(e) => eprintln!("Warning: Failed to get public IP: {}", e),
+        }
+    }
+    for endpoint in auto_switch::endpoints_to_probe(paths, settings) {
+        let probe = probe_endpoint(&client, &endpoint).await;
+        observations.probes.insert(endpoint, probe);
+    }
+    if !auto_switch::profiles_to_check(settings).is_empty() {
+        let health_client = health::probe_client()?;
+        for target in auto_switch::profiles_to_check(settings) {
+            match auto_switch::active_profile_probe(paths, target) {
+                Ok(Some(probe)) => {
+                    let health = health::send_probe(&health_client, &probe).await;
+                    observations.profiles.insert(target, health);
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Warning: Failed to prepare health check: {}", e),
+            }
+        }
+    }
+    Ok(observations)
+}
+
+async fn fetch_public_ip(client: &reqwest::Client, url: &str) -> Result<std::net::IpAddr, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to request public IP: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Request failed with status: {}", response.status()));
+    }
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    text.trim()
+        .parse()
+        .map_err(|_| format!("Invalid public IP response: {}", text.trim()))
+}
+
+// Any answer below 500 means the server is up, 401/403 only ask for credentials
+async fn probe_endpoint(client: &reqwest::Client, endpoint: &str) -> EndpointProbe {
+    let start = Instant::now();
+    match client.head(endpoint).send().await {
+        Ok(response) => EndpointProbe {
+            healthy: response.status().as_u16() < 500,
+            latency_ms: start.elapsed().as_millis() as u64,
+        },
+        Err(_) => EndpointProbe {
+            healthy: false,
+            latency_ms: start.elapsed().as_millis() as u64,
+        },
+    }
+}
+
+fn notify<R: Runtime>(app: &AppHandle<R>, entry: &AuditEntry) {
+    let tool = match entry.target {
+        SwitchTarget::Claude => "Claude Code",
+        SwitchTarget::Codex => "Codex",
+    };
+    let body = match &entry.error {
+        None => format!(
+            "{} config switched to \"{}\": {}",
+            tool,
+            entry.to_title.as_deref().unwrap_or(&entry.to_store),
+            entry.reason
+        ),
+        Some(e) => format!(
+            "Rule \"{}\" failed to switch {}: {}",
+            entry.rule_name, tool, e
+        ),
+    };
+
+    let _ = app
+        .notification()
+        .builder()
+        .title("TVC BUDDY")
+        .body(&body)
+        .show();
+}
@@ -1,620 +1,188 @@
-use nanoid;
-use reqwest;
 use serde_json::Value;
-use std::path::PathBuf;
+use std::collections::HashMap;
 use tauri_plugin_updater::UpdaterExt;
-use uuid::Uuid;
-
-// Application configuration directory
-const APP_CONFIG_DIR: &str = ".ccconfig";
-
-pub async fn initialize_app_config() -> Result<(), String> {
-    println!("initialize_app_config called");
-
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-
-    println!(
-        "Checking if app config directory exists: {}",
-        app_config_path.display()
-    );
-
-    // Create config directory if it doesn't exist
-    if !app_config_path.exists() {
-        println!("App config directory does not exist, creating...");
-        std::fs::create_dir_all(&app_config_path)
-            .map_err(|e| format!("Failed to create app config directory: {}", e))?;
-        println!(
-            "App config directory created: {}",
-            app_config_path.display()
-        );
-    } else {
-        println!("App config directory already exists");
-    }
-
-    // Check if we need to backup Claude configs
-    let claude_dir = home_dir.join(".claude");
-    println!(
-        "Checking if Claude directory exists: {}",
-        claude_dir.display()
-    );
-
-    if claude_dir.exists() {
-        // Check if we already have a backup
-        let backup_dir = app_config_path.join("claude_backup");
-        if backup_dir.exists() {
-            println!("Claude backup already exists, skipping backup");
-        } else {
-            println!("Claude directory exists but no backup found, backing up...");
-            if let Err(e) = backup_claude_configs_internal(&app_config_path, &claude_dir) {
-                return Err(format!("Failed to backup Claude configs: {}", e));
-            }
-            println!("Claude configs backed up successfully");
-        }
-    } else {
-        println!("Claude directory does not exist, skipping backup");
-    }
-
-    Ok(())
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct ConfigFile {
-    pub path: String,
-    pub content: Value,
-    pub exists: bool,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct ConfigStore {
-    pub id: String,
-    pub title: String,
-    #[serde(rename = "createdAt")]
-    pub created_at: u64,
-    pub settings: Value,
-    pub using: bool,
+use tvcbuddy_core::{
+    approval, auto_switch, backup, bindings, bundle, claude_files, codex, drift, failover, health,
+    history, hook_log, hooks, mcp, model_aliases, relay, relay_log, relay_policy, settings,
+    settings_schema, stores, tool_rules, usage, vault, Paths,
+};
+
+pub use tvcbuddy_core::approval::{ApprovalSettings, PermissionDecision};
+pub use tvcbuddy_core::auto_switch::{AuditEntry, AutoSwitchSettings, SwitchTarget};
+pub use tvcbuddy_core::bindings::ProjectBinding;
+pub use tvcbuddy_core::bundle::{ConflictStrategy, ImportReport};
+pub use tvcbuddy_core::claude_files::{AgentFile, CommandFile};
+pub use tvcbuddy_core::codex::{CodexGlobalSettings, CodexStore};
+pub use tvcbuddy_core::drift::{ActiveDrift, ProfileDiff};
+pub use tvcbuddy_core::failover::FailoverSettings;
+pub use tvcbuddy_core::health::ProfileHealth;
+pub use tvcbuddy_core::history::{HistoryDiff, HistoryFile, HistorySettings, HistoryVersion};
+pub use tvcbuddy_core::hook_log::{HookEventFilter, HookEventRecord, HookSession, RuleDecisionRecord};
+pub use tvcbuddy_core::live_sessions::LiveSession;
+pub use tvcbuddy_core::mcp::{ClaudeConfigFile, McpServer, ProjectConfig};
+pub use tvcbuddy_core::merge::MergeStrategies;
+pub use tvcbuddy_core::model_aliases::ModelAlias;
+pub use tvcbuddy_core::relay::RelaySettings;
+pub use tvcbuddy_core::relay_log::{RelayLogEntry, RelayUsage};
+pub use tvcbuddy_core::relay_policy::{RelayMetrics, RelayPolicy};
+pub use tvcbuddy_core::settings::{ConfigFile, MemoryFile};
+pub use tvcbuddy_core::settings_schema::SettingsValidation;
+pub use tvcbuddy_core::stores::{ConfigStore, NotificationSettings};
+pub use tvcbuddy_core::tool_rules::{RuleMatch, ToolRuleSettings};
+pub use tvcbuddy_core::translate::WireApi;
+pub use tvcbuddy_core::usage::ProjectUsageRecord;
+pub use tvcbuddy_core::vault::VaultStatus;
+
+// Resolve the standard file layout under the current user's home directory
+fn paths() -> Result<Paths, String> {
+    Paths::detect()
+}
+
+// Secrets only ever reach the UI masked
+fn masked_store(paths: &Paths, mut store: ConfigStore) -> ConfigStore {
+    let pointers = vault::settings_secret_pointers(&store.settings);
+    vault::mask_secrets(paths, &mut store.settings, &pointers);
+    store
+}
+
+fn masked_codex_store(paths: &Paths, mut store: CodexStore) -> CodexStore {
+    vault::mask_secrets(paths, &mut store.config, &vault::codex_secret_pointers());
+    store
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct McpServer {
-    #[serde(flatten)]
-    pub config: serde_json::Value,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct StoresData {
-    pub configs: Vec<ConfigStore>,
-    pub distinct_id: Option<String>,
-    pub notification: Option<NotificationSettings>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct NotificationSettings {
-    pub enable: bool,
-    pub enabled_hooks: Vec<String>,
+pub async fn initialize_app_config() -> Result<(), String> {
+    backup::initialize_app_config(&paths()?)
 }
 
 #[tauri::command]
 pub async fn read_config_file(config_type: String) -> Result<ConfigFile, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-
-    let path = match config_type.as_str() {
-        "user" => home_dir.join(".claude/settings.json"),
-        "enterprise_macos" => {
-            PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json")
-        }
-        "enterprise_linux" => PathBuf::from("/etc/claude-code/managed-settings.json"),
-        "enterprise_windows" => PathBuf::from("C:\\ProgramData\\ClaudeCode\\managed-settings.json"),
-        "mcp_macos" => PathBuf::from("/Library/Application Support/ClaudeCode/managed-mcp.json"),
-        "mcp_linux" => PathBuf::from("/etc/claude-code/managed-mcp.json"),
-        "mcp_windows" => PathBuf::from("C:\\ProgramData\\ClaudeCode\\managed-mcp.json"),
-        _ => return Err("Invalid configuration type".to_string()),
-    };
-
-    let path_str = path.to_string_lossy().to_string();
-
-    if path.exists() {
-        let content =
-            std::fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
-
-        let json_content: Value =
-            serde_json::from_str(&content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-        Ok(ConfigFile {
-            path: path_str,
-            content: json_content,
-            exists: true,
-        })
-    } else {
-        Ok(ConfigFile {
-            path: path_str,
-            content: Value::Object(serde_json::Map::new()),
-            exists: false,
-        })
-    }
+    let paths = paths()?;
+    let mut config_file = settings::read_config_file(&paths, &config_type)?;
+    let pointers = vault::settings_secret_pointers(&config_file.content);
+    vault::mask_secrets(&paths, &mut config_file.content, &pointers);
+    Ok(config_file)
 }
 
 #[tauri::command]
-pub async fn write_config_file(config_type: String, content: Value) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-
-    let path = match config_type.as_str() {
-        "user" => home_dir.join(".claude/settings.json"),
-        _ => return Err("Cannot write to enterprise configuration files".to_string()),
-    };
-
-    let json_content = serde_json::to_string_pretty(&content)
-        .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
-
-    std::fs::write(&path, json_content).map_err(|e| format!("Failed to write file: {}", e))?;
-
-    Ok(())
+pub async fn write_config_file(config_type: String, mut content: Value) -> Result<(), String> {
+    let paths = paths()?;
+    // Tokens the editor only saw masked are kept as they are on disk
+    let current = settings::read_config_file(&paths, &config_type)?.content;
+    let pointers = vault::settings_secret_pointers(&content);
+    vault::unmask_secrets(&mut content, &current, &pointers)?;
+    settings::write_config_file(&paths, &config_type, &content)
 }
 
 #[tauri::command]
 pub async fn list_config_files() -> Result<Vec<String>, String> {
-    let mut configs = vec![];
-
-    // User settings
-    if let Some(home) = dirs::home_dir() {
-        let user_settings = home.join(".claude/settings.json");
-        if user_settings.exists() {
-            configs.push("user".to_string());
-        }
-    }
-
-    // Enterprise settings (read-only)
-    if cfg!(target_os = "macos") {
-        let enterprise_path =
-            PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json");
-        if enterprise_path.exists() {
-            configs.push("enterprise_macos".to_string());
-        }
-
-        let mcp_path = PathBuf::from("/Library/Application Support/ClaudeCode/managed-mcp.json");
-        if mcp_path.exists() {
-            configs.push("mcp_macos".to_string());
-        }
-    } else if cfg!(target_os = "linux") {
-        let enterprise_path = PathBuf::from("/etc/claude-code/managed-settings.json");
-        if enterprise_path.exists() {
-            configs.push("enterprise_linux".to_string());
-        }
-
-        let mcp_path = PathBuf::from("/etc/claude-code/managed-mcp.json");
-        if mcp_path.exists() {
-            configs.push("mcp_linux".to_string());
-        }
-    } else if cfg!(target_os = "windows") {
-        let enterprise_path = PathBuf::from("C:\\ProgramData\\ClaudeCode\\managed-settings.json");
-        if enterprise_path.exists() {
-            configs.push("enterprise_windows".to_string());
-        }
-
-        let mcp_path = PathBuf::from("C:\\ProgramData\\ClaudeCode\\managed-mcp.json");
-        if mcp_path.exists() {
-            configs.push("mcp_windows".to_string());
-        }
-    }
-
-    Ok(configs)
+    Ok(settings::list_config_files(&paths()?))
 }
 
 #[tauri::command]
 pub async fn check_app_config_exists() -> Result<bool, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    Ok(app_config_path.exists())
+    Ok(paths()?.app_config_dir.exists())
 }
 
 #[tauri::command]
 pub async fn create_app_config_dir() -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-
-    std::fs::create_dir_all(&app_config_path)
-        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
-
-    Ok(())
-}
-
-fn backup_claude_configs_internal(
-    app_config_path: &std::path::Path,
-    claude_dir: &std::path::Path,
-) -> Result<(), String> {
-    // Create backup directory
-    let backup_dir = app_config_path.join("claude_backup");
-
-    std::fs::create_dir_all(&backup_dir)
-        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
-
-    // Copy all files from .claude directory to backup
-    for entry in std::fs::read_dir(claude_dir)
-        .map_err(|e| format!("Failed to read Claude directory: {}", e))?
-    {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let source_path = entry.path();
-        let file_name = source_path.file_name().ok_or("Invalid file name")?;
-        let dest_path = backup_dir.join(file_name);
-
-        if source_path.is_file() {
-            std::fs::copy(&source_path, &dest_path)
-                .map_err(|e| format!("Failed to copy file {}: {}", source_path.display(), e))?;
-        }
-    }
-
-    Ok(())
+    std::fs::create_dir_all(paths()?.app_config_dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))
 }
 
 #[tauri::command]
 pub async fn backup_claude_configs() -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_dir = home_dir.join(".claude");
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-
-    if !claude_dir.exists() {
-        return Err("Claude configuration directory does not exist".to_string());
-    }
-
-    // Ensure app config directory exists
-    std::fs::create_dir_all(&app_config_path)
-        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
-
-    backup_claude_configs_internal(&app_config_path, &claude_dir)
+    backup::backup_claude_configs(&paths()?)
 }
 
 // Store management functions
 
 #[tauri::command]
 pub async fn get_stores() -> Result<Vec<ConfigStore>, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    if !stores_file.exists() {
-        return Ok(vec![]);
-    }
-
-    let content = std::fs::read_to_string(&stores_file)
-        .map_err(|e| format!("Failed to read stores file: {}", e))?;
-
-    let mut stores_data: StoresData = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse stores file: {}", e))?;
-
-    // Add default notification settings if they don't exist
-    if stores_data.notification.is_none() {
-        stores_data.notification = Some(NotificationSettings {
-            enable: true,
-            enabled_hooks: vec!["Notification".to_string()],
-        });
-
-        // Write back to stores file with notification settings added
-        let json_content = serde_json::to_string_pretty(&stores_data)
-            .map_err(|e| format!("Failed to serialize stores: {}", e))?;
-
-        std::fs::write(&stores_file, json_content)
-            .map_err(|e| format!("Failed to write stores file: {}", e))?;
-
-        println!("Added default notification settings to existing stores.json");
-    }
-
-    let mut stores_vec = stores_data.configs;
-    // Sort by createdAt in ascending order (oldest first)
-    stores_vec.sort_by(|a, b| a.created_at.cmp(&b.created_at));
-
-    Ok(stores_vec)
+    let paths = paths()?;
+    Ok(stores::get_stores(&paths)?
+        .into_iter()
+        .map(|store| masked_store(&paths, store))
+        .collect())
 }
 
 #[tauri::command]
 pub async fn create_config(
     id: String,
     title: String,
-    settings: Value,
+    mut settings: Value,
+    parent: Option<String>,
 ) -> Result<ConfigStore, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    // Ensure app config directory exists
-    std::fs::create_dir_all(&app_config_path)
-        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
-
-    // Read existing stores
-    let mut stores_data = if stores_file.exists() {
-        let content = std::fs::read_to_string(&stores_file)
-            .map_err(|e| format!("Failed to read stores file: {}", e))?;
-
-        serde_json::from_str::<StoresData>(&content)
-            .map_err(|e| format!("Failed to parse stores file: {}", e))?
-    } else {
-        StoresData {
-            configs: vec![],
-            distinct_id: None,
-            notification: Some(NotificationSettings {
-                enable: true,
-                enabled_hooks: vec!["Notification".to_string()],
-            }),
-        }
-    };
-
-    // Determine if this should be the active store (true if no other stores exist)
-    let should_be_active = stores_data.configs.is_empty();
-
-    // NOTE: We no longer auto-create "Original Config" here to avoid duplicate config creation.
-    // Users should manually create configs as needed. The backup functionality already preserves
-    // the original settings in ~/.ccconfig/claude_backup/
-
-    // If this is the first store (and therefore active), write its settings to the user's actual settings.json with partial update
-    if should_be_active {
-        let user_settings_path = home_dir.join(".claude/settings.json");
-
-        // Create .claude directory if it doesn't exist
-        if let Some(parent) = user_settings_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-        }
-
-        // Read existing settings if file exists, otherwise start with empty object
-        let mut existing_settings = if user_settings_path.exists() {
-            let content = std::fs::read_to_string(&user_settings_path)
-                .map_err(|e| format!("Failed to read existing settings: {}", e))?;
-            serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse existing settings: {}", e))?
-        } else {
-            serde_json::Value::Object(serde_json::Map::new())
-        };
-
-        // Merge the new settings into existing settings (partial update)
-        if let Some(settings_obj) = settings.as_object() {
-            if let Some(existing_obj) = existing_settings.as_object_mut() {
-                // Update only the keys present in the stored settings
-                for (key, value) in settings_obj {
-                    existing_obj.insert(key.clone(), value.clone());
-                }
-            } else {
-                // If existing settings is not an object, replace it entirely
-                existing_settings = settings.clone();
-            }
-        } else {
-            // If stored settings is not an object, replace existing entirely
-            existing_settings = settings.clone();
-        }
-
-        // Write the merged settings back to file
-        let json_content = serde_json::to_string_pretty(&existing_settings)
-            .map_err(|e| format!("Failed to serialize merged settings: {}", e))?;
-
-        std::fs::write(&user_settings_path, json_content)
-            .map_err(|e| format!("Failed to write user settings: {}", e))?;
-    }
-
-    // Create new store
-    let new_store = ConfigStore {
-        id: id.clone(),
-        title: title.clone(),
-        created_at: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| format!("Failed to get timestamp: {}", e))?
-            .as_secs(),
-        settings,
-        using: should_be_active,
-    };
-
-    // Add store to collection
-    stores_data.configs.push(new_store.clone());
-
-    // Write back to stores file
-    let json_content = serde_json::to_string_pretty(&stores_data)
-        .map_err(|e| format!("Failed to serialize stores: {}", e))?;
+    let paths = paths()?;
+    // A config saved from the system default only has the masked token
+    let system_env = serde_json::json!({
+        "env": { "ANTHROPIC_AUTH_TOKEN": std::env::var("ANTHROPIC_AUTH_TOKEN").ok() }
+    });
+    let pointers = vault::settings_secret_pointers(&settings);
+    vault::unmask_secrets(&mut settings, &system_env, &pointers)?;
+    let store = stores::create_config(&paths, id, title, settings, parent)?;
+    Ok(masked_store(&paths, store))
+}
 
-    std::fs::write(&stores_file, json_content)
-        .map_err(|e| format!("Failed to write stores file: {}", e))?;
+#[tauri::command]
+pub async fn validate_store_settings(settings: Value) -> Result<SettingsValidation, String> {
+    Ok(settings_schema::validate_settings(&settings))
+}
 
-    // Automatically unlock CC extension when creating new config
-    if let Err(e) = unlock_cc_ext().await {
-        eprintln!("Warning: Failed to unlock CC extension: {}", e);
-    }
+#[tauri::command]
+pub async fn get_merge_strategies() -> Result<MergeStrategies, String> {
+    stores::get_merge_strategies(&paths()?)
+}
 
-    Ok(new_store)
+#[tauri::command]
+pub async fn update_merge_strategies(strategies: MergeStrategies) -> Result<(), String> {
+    stores::update_merge_strategies(&paths()?, strategies)
 }
 
 #[tauri::command]
 pub async fn delete_config(store_id: String) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    if !stores_file.exists() {
-        return Err("Stores file does not exist".to_string());
-    }
-
-    // Read existing stores
-    let content = std::fs::read_to_string(&stores_file)
-        .map_err(|e| format!("Failed to read stores file: {}", e))?;
-
-    let mut stores_data: StoresData = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse stores file: {}", e))?;
-
-    // Find and remove store by ID
-    let original_len = stores_data.configs.len();
-    stores_data.configs.retain(|store| store.id != store_id);
-
-    if stores_data.configs.len() == original_len {
-        return Err("Store not found".to_string());
-    }
-
-    // Write back to file
-    let json_content = serde_json::to_string_pretty(&stores_data)
-        .map_err(|e| format!("Failed to serialize stores: {}", e))?;
-
-    std::fs::write(&stores_file, json_content)
-        .map_err(|e| format!("Failed to write stores file: {}", e))?;
-
-    Ok(())
+    stores::delete_config(&paths()?, &store_id)
 }
 
 #[tauri::command]
 pub async fn set_using_config(store_id: String) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    if !stores_file.exists() {
-        return Err("Stores file does not exist".to_string());
-    }
-
-    // Read existing stores
-    let content = std::fs::read_to_string(&stores_file)
-        .map_err(|e| format!("Failed to read stores file: {}", e))?;
-
-    let mut stores_data: StoresData = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse stores file: {}", e))?;
-
-    // Find the store and check if it exists
-    let store_found = stores_data.configs.iter().any(|store| store.id == store_id);
-    if !store_found {
-        return Err("Store not found".to_string());
-    }
-
-    // Set all stores to not using, then set the selected one to using
-    let mut selected_store_settings: Option<Value> = None;
-    for store in &mut stores_data.configs {
-        if store.id == store_id {
-            store.using = true;
-            selected_store_settings = Some(store.settings.clone());
-        } else {
-            store.using = false;
-        }
-    }
-
-    // Write the selected store's settings to the user's actual settings.json with partial update
-    if let Some(settings) = selected_store_settings {
-        let user_settings_path = home_dir.join(".claude/settings.json");
-
-        // Create .claude directory if it doesn't exist
-        if let Some(parent) = user_settings_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-        }
-
-        // Read existing settings if file exists, otherwise start with empty object
-        let mut existing_settings = if user_settings_path.exists() {
-            let content = std::fs::read_to_string(&user_settings_path)
-                .map_err(|e| format!("Failed to read existing settings: {}", e))?;
-            serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse existing settings: {}", e))?
-        } else {
-            serde_json::Value::Object(serde_json::Map::new())
-        };
-
-        // Merge the new settings into existing settings (partial update)
-        if let Some(settings_obj) = settings.as_object() {
-            if let Some(existing_obj) = existing_settings.as_object_mut() {
-                // Update only the keys present in the stored settings
-                for (key, value) in settings_obj {
-                    existing_obj.insert(key.clone(), value.clone());
-                }
-            } else {
-                // If existing settings is not an object, replace it entirely
-                existing_settings = settings.clone();
-            }
-        } else {
-            // If stored settings is not an object, replace existing entirely
-            existing_settings = settings.clone();
-        }
-
-        // Write the merged settings back to file
-        let json_content = serde_json::to_string_pretty(&existing_settings)
-            .map_err(|e| format!("Failed to serialize merged settings: {}", e))?;
-
-        std::fs::write(&user_settings_path, json_content)
-            .map_err(|e| format!("Failed to write user settings: {}", e))?;
-    }
-
-    // Write back to stores file
-    let json_content = serde_json::to_string_pretty(&stores_data)
-        .map_err(|e| format!("Failed to serialize stores: {}", e))?;
-
-    std::fs::write(&stores_file, json_content)
-        .map_err(|e| format!("Failed to write stores file: {}", e))?;
-
-    Ok(())
+    stores::set_using_config(&paths()?, &store_id)
 }
 
 #[tauri::command]
 pub async fn reset_to_original_config() -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    // Set all stores to not using
-    if stores_file.exists() {
-        let content = std::fs::read_to_string(&stores_file)
-            .map_err(|e| format!("Failed to read stores file: {}", e))?;
-
-        let mut stores_data: StoresData = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse stores file: {}", e))?;
-
-        // Set all stores to not using
-        for store in &mut stores_data.configs {
-            store.using = false;
-        }
-
-        // Write back to stores file
-        let json_content = serde_json::to_string_pretty(&stores_data)
-            .map_err(|e| format!("Failed to serialize stores: {}", e))?;
-
-        std::fs::write(&stores_file, json_content)
-            .map_err(|e| format!("Failed to write stores file: {}", e))?;
-    }
-
-    // Clear env field in settings.json
-    let user_settings_path = home_dir.join(".claude/settings.json");
-
-    // Create .claude directory if it doesn't exist
-    if let Some(parent) = user_settings_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-    }
-
-    // Read existing settings if file exists, otherwise start with empty object
-    let mut existing_settings = if user_settings_path.exists() {
-        let content = std::fs::read_to_string(&user_settings_path)
-            .map_err(|e| format!("Failed to read existing settings: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse existing settings: {}", e))?
-    } else {
-        serde_json::Value::Object(serde_json::Map::new())
-    };
+    stores::reset_to_original_config(&paths()?)
+}
 
-    // Set env to empty object
-    if let Some(existing_obj) = existing_settings.as_object_mut() {
-        existing_obj.insert("env".to_string(), serde_json::json!({}));
-    }
+#[tauri::command]
+pub async fn get_current_store() -> Result<Option<ConfigStore>, String> {
+    let paths = paths()?;
+    Ok(stores::get_current_store(&paths)?.map(|store| masked_store(&paths, store)))
+}
 
-    // Write the merged settings back to file
-    let json_content = serde_json::to_string_pretty(&existing_settings)
-        .map_err(|e| format!("Failed to serialize merged settings: {}", e))?;
+#[tauri::command]
+pub async fn get_active_drift() -> Result<Option<ActiveDrift>, String> {
+    drift::check_active_drift(&paths()?)
+}
 
-    std::fs::write(&user_settings_path, json_content)
-        .map_err(|e| format!("Failed to write user settings: {}", e))?;
+#[tauri::command]
+pub async fn diff_active_profile() -> Result<Option<ProfileDiff>, String> {
+    drift::diff_active_profile(&paths()?)
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn reapply_active_profile() -> Result<(), String> {
+    drift::reapply_active_profile(&paths()?)
 }
 
 #[tauri::command]
-pub async fn get_current_store() -> Result<Option<ConfigStore>, String> {
-    let stores = get_stores().await?;
-    Ok(stores.into_iter().find(|store| store.using))
+pub async fn absorb_active_profile() -> Result<Option<ProfileDiff>, String> {
+    drift::absorb_active_profile(&paths()?)
 }
 
 #[tauri::command]
 pub async fn get_store(store_id: String) -> Result<ConfigStore, String> {
-    let stores = get_stores().await?;
-    stores
-        .into_iter()
-        .find(|store| store.id == store_id)
-        .ok_or_else(|| format!("Store with id '{}' not found", store_id))
+    let paths = paths()?;
+    Ok(masked_store(&paths, stores::get_store(&paths, &store_id)?))
 }
 
 #[tauri::command]
@@ -622,104 +190,76 @@ pub async fn update_config(
     store_id: String,
     title: String,
     settings: Value,
+    parent: Option<String>,
 ) -> Result<ConfigStore, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    if !stores_file.exists() {
-        return Err("Stores file does not exist".to_string());
-    }
+    let paths = paths()?;
+    let store = stores::update_config(&paths, &store_id, title, settings, parent)?;
+    Ok(masked_store(&paths, store))
+}
 
-    // Read existing stores
-    let content = std::fs::read_to_string(&stores_file)
-        .map_err(|e| format!("Failed to read stores file: {}", e))?;
-
-    let mut stores_data: StoresData = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse stores file: {}", e))?;
-
-    // Find the store by ID
-    let store_index = stores_data
-        .configs
-        .iter()
-        .position(|store| store.id == store_id)
-        .ok_or_else(|| format!("Store with id '{}' not found", store_id))?;
-
-    // // Check if new title conflicts with existing stores (excluding current one)
-    // for existing_store in &stores_data.configs {
-    //     if existing_store.id != store_id && existing_store.title == title {
-    //         return Err("Store with this title already exists".to_string());
-    //     }
-    // }
-
-    // Update the store
-    let store = &mut stores_data.configs[store_index];
-    store.title = title.clone();
-    store.settings = settings.clone();
-
-    // If this store is currently in use, also update the user's settings.json with partial update
-    if store.using {
-        let user_settings_path = home_dir.join(".claude/settings.json");
-
-        // Create .claude directory if it doesn't exist
-        if let Some(parent) = user_settings_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-        }
+#[tauri::command]
+pub async fn get_resolved_store_settings(store_id: String) -> Result<Value, String> {
+    let paths = paths()?;
+    let mut settings = stores::get_resolved_store_settings(&paths, &store_id)?;
+    let pointers = vault::settings_secret_pointers(&settings);
+    vault::mask_secrets(&paths, &mut settings, &pointers);
+    Ok(settings)
+}
 
-        // Read existing settings if file exists, otherwise start with empty object
-        let mut existing_settings = if user_settings_path.exists() {
-            let content = std::fs::read_to_string(&user_settings_path)
-                .map_err(|e| format!("Failed to read existing settings: {}", e))?;
-            serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse existing settings: {}", e))?
-        } else {
-            serde_json::Value::Object(serde_json::Map::new())
-        };
+// Secret vault functions
 
-        // Merge the new settings into existing settings (partial update)
-        if let Some(settings_obj) = settings.as_object() {
-            if let Some(existing_obj) = existing_settings.as_object_mut() {
-                // Update only the keys present in the stored settings
-                for (key, value) in settings_obj {
-                    existing_obj.insert(key.clone(), value.clone());
-                }
-            } else {
-                // If existing settings is not an object, replace it entirely
-                existing_settings = settings.clone();
-            }
-        } else {
-            // If stored settings is not an object, replace existing entirely
-            existing_settings = settings.clone();
-        }
+#[tauri::command]
+pub async fn get_vault_status() -> Result<VaultStatus, String> {
+    vault::get_vault_status(&paths()?)
+}
 
-        // Write the merged settings back to file
-        let json_content = serde_json::to_string_pretty(&existing_settings)
-            .map_err(|e| format!("Failed to serialize merged settings: {}", e))?;
+#[tauri::command]
+pub async fn unlock_vault(passphrase: String) -> Result<(), String> {
+    vault::unlock_vault(&paths()?, &passphrase)
+}
 
-        std::fs::write(&user_settings_path, json_content)
-            .map_err(|e| format!("Failed to write user settings: {}", e))?;
-    }
+#[tauri::command]
+pub async fn lock_vault() -> Result<(), String> {
+    vault::lock_vault(&paths()?);
+    Ok(())
+}
 
-    // Write back to stores file
-    let json_content = serde_json::to_string_pretty(&stores_data)
-        .map_err(|e| format!("Failed to serialize stores: {}", e))?;
+#[tauri::command]
+pub async fn set_vault_passphrase(passphrase: Option<String>) -> Result<(), String> {
+    vault::set_vault_passphrase(&paths()?, passphrase.as_deref())
+}
 
-    std::fs::write(&stores_file, json_content)
-        .map_err(|e| format!("Failed to write stores file: {}", e))?;
+// Profile bundle functions
 
-    // Automatically unlock CC extension when updating config
-    if let Err(e) = unlock_cc_ext().await {
-        eprintln!("Warning: Failed to unlock CC extension: {}", e);
-    }
+#[tauri::command]
+pub async fn export_profiles(file_path: String, passphrase: Option<String>) -> Result<(), String> {
+    bundle::export_profiles(
+        &paths()?,
+        std::path::Path::new(&file_path),
+        passphrase.as_deref(),
+    )?;
+    Ok(())
+}
 
-    Ok(stores_data.configs[store_index].clone())
+#[tauri::command]
+pub async fn import_profiles(
+    file_path: String,
+    passphrase: Option<String>,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+) -> Result<ImportReport, String> {
+    bundle::import_profiles(
+        &paths()?,
+        std::path::Path::new(&file_path),
+        passphrase.as_deref(),
+        strategy,
+        dry_run,
+    )
 }
 
 #[tauri::command]
 pub async fn open_config_path() -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
+    let app_config_path = paths()?.app_config_dir;
 
     // Ensure the directory exists
     if !app_config_path.exists() {
@@ -758,42 +298,13 @@ pub async fn open_config_path() -> Result<(), String> {
 // MCP Server management functions
 
 #[tauri::command]
-pub async fn get_global_mcp_servers() -> Result<std::collections::HashMap<String, McpServer>, String>
-{
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_json_path = home_dir.join(".claude.json");
-
-    if !claude_json_path.exists() {
-        return Ok(std::collections::HashMap::new());
-    }
-
-    let content = std::fs::read_to_string(&claude_json_path)
-        .map_err(|e| format!("Failed to read .claude.json: {}", e))?;
-
-    let json_value: Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse .claude.json: {}", e))?;
-
-    let mcp_servers_obj = json_value
-        .get("mcpServers")
-        .and_then(|servers| servers.as_object())
-        .cloned()
-        .unwrap_or_else(serde_json::Map::new);
-
-    let mut result = std::collections::HashMap::new();
-    for (name, config) in mcp_servers_obj {
-        let mcp_server = McpServer {
-            config: config.clone(),
-        };
-        result.insert(name.clone(), mcp_server);
-    }
-
-    Ok(result)
+pub async fn get_global_mcp_servers() -> Result<HashMap<String, McpServer>, String> {
+    mcp::get_global_mcp_servers(&paths()?)
 }
 
 #[tauri::command]
 pub async fn check_mcp_server_exists(server_name: String) -> Result<bool, String> {
-    let mcp_servers = get_global_mcp_servers().await?;
-    Ok(mcp_servers.contains_key(&server_name))
+    mcp::check_mcp_server_exists(&paths()?, &server_name)
 }
 
 #[tauri::command]
@@ -801,90 +312,12 @@ pub async fn update_global_mcp_server(
     server_name: String,
     server_config: Value,
 ) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_json_path = home_dir.join(".claude.json");
-
-    // Read existing .claude.json or create new structure
-    let mut json_value = if claude_json_path.exists() {
-        let content = std::fs::read_to_string(&claude_json_path)
-            .map_err(|e| format!("Failed to read .claude.json: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse .claude.json: {}", e))?
-    } else {
-        Value::Object(serde_json::Map::new())
-    };
-
-    // Update mcpServers object
-    let mcp_servers = json_value
-        .as_object_mut()
-        .unwrap()
-        .entry("mcpServers".to_string())
-        .or_insert_with(|| Value::Object(serde_json::Map::new()))
-        .as_object_mut()
-        .unwrap();
-
-    // Update the specific server
-    mcp_servers.insert(server_name, server_config);
-
-    // Write back to file
-    let json_content = serde_json::to_string_pretty(&json_value)
-        .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
-
-    std::fs::write(&claude_json_path, json_content)
-        .map_err(|e| format!("Failed to write .claude.json: {}", e))?;
-
-    Ok(())
+    mcp::update_global_mcp_server(&paths()?, server_name, server_config)
 }
 
 #[tauri::command]
 pub async fn delete_global_mcp_server(server_name: String) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_json_path = home_dir.join(".claude.json");
-
-    if !claude_json_path.exists() {
-        return Err("Claude configuration file does not exist".to_string());
-    }
-
-    // Read existing .claude.json
-    let content = std::fs::read_to_string(&claude_json_path)
-        .map_err(|e| format!("Failed to read .claude.json: {}", e))?;
-
-    let mut json_value: Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse .claude.json: {}", e))?;
-
-    // Check if mcpServers exists
-    let mcp_servers = json_value
-        .as_object_mut()
-        .unwrap()
-        .get_mut("mcpServers")
-        .and_then(|servers| servers.as_object_mut());
-
-    let mcp_servers = match mcp_servers {
-        Some(servers) => servers,
-        None => return Err("No mcpServers found in .claude.json".to_string()),
-    };
-
-    // Check if the server exists
-    if !mcp_servers.contains_key(&server_name) {
-        return Err(format!("MCP server '{}' not found", server_name));
-    }
-
-    // Remove the server
-    mcp_servers.remove(&server_name);
-
-    // If mcpServers is now empty, we can optionally remove the entire mcpServers object
-    if mcp_servers.is_empty() {
-        json_value.as_object_mut().unwrap().remove("mcpServers");
-    }
-
-    // Write back to file
-    let json_content = serde_json::to_string_pretty(&json_value)
-        .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
-
-    std::fs::write(&claude_json_path, json_content)
-        .map_err(|e| format!("Failed to write .claude.json: {}", e))?;
-
-    Ok(())
+    mcp::delete_global_mcp_server(&paths()?, &server_name)
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -952,272 +385,22 @@ pub async fn rebuild_tray_menu_command(app: tauri::AppHandle) -> Result<(), Stri
 
 #[tauri::command]
 pub async fn unlock_cc_ext() -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_config_path = home_dir.join(".claude/config.json");
-
-    // Ensure .claude directory exists
-    if let Some(parent) = claude_config_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-    }
-
-    if claude_config_path.exists() {
-        // File exists, check if primaryApiKey key exists
-        let content = std::fs::read_to_string(&claude_config_path)
-            .map_err(|e| format!("Failed to read config.json: {}", e))?;
-
-        let mut json_value: Value = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse config.json: {}", e))?;
-
-        // Check if primaryApiKey exists
-        if json_value.get("primaryApiKey").is_none() {
-            // Add primaryApiKey to existing config
-            if let Some(obj) = json_value.as_object_mut() {
-                obj.insert(
-                    "primaryApiKey".to_string(),
-                    Value::String("xxx".to_string()),
-                );
-            }
-
-            // Write back to file
-            let json_content = serde_json::to_string_pretty(&json_value)
-                .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
-
-            std::fs::write(&claude_config_path, json_content)
-                .map_err(|e| format!("Failed to write config.json: {}", e))?;
-
-            println!("Added primaryApiKey to existing config.json");
-        } else {
-            println!("primaryApiKey already exists in config.json, no action needed");
-        }
-    } else {
-        // File doesn't exist, create it with primaryApiKey
-        let config = serde_json::json!({
-            "primaryApiKey": "xxx"
-        });
-
-        let json_content = serde_json::to_string_pretty(&config)
-            .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
-
-        std::fs::write(&claude_config_path, json_content)
-            .map_err(|e| format!("Failed to write config.json: {}", e))?;
-
-        println!("Created new config.json with primaryApiKey");
-    }
-
-    Ok(())
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct UsageData {
-    pub input_tokens: Option<u64>,
-    pub cache_read_input_tokens: Option<u64>,
-    pub output_tokens: Option<u64>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct ProjectUsageRecord {
-    pub uuid: String,
-    pub timestamp: String,
-    pub model: Option<String>,
-    pub usage: Option<UsageData>,
+    settings::unlock_cc_ext(&paths()?)
 }
 
 #[tauri::command]
 pub async fn read_project_usage_files() -> Result<Vec<ProjectUsageRecord>, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let projects_dir = home_dir.join(".claude/projects");
-
-    println!(
-        "🔍 Looking for projects directory: {}",
-        projects_dir.display()
-    );
-
-    if !projects_dir.exists() {
-        println!("❌ Projects directory does not exist");
-        return Ok(vec![]);
-    }
-
-    println!("✅ Projects directory exists");
-
-    let mut all_records = Vec::new();
-    let mut files_processed = 0;
-    let mut lines_processed = 0;
-
-    // Recursively find all .jsonl files in the projects directory and subdirectories
-    fn find_jsonl_files(
-        dir: &std::path::Path,
-        files: &mut Vec<std::path::PathBuf>,
-    ) -> Result<(), String> {
-        let entries = std::fs::read_dir(dir)
-            .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
-
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-            let path = entry.path();
-
-            if path.is_file() && path.extension().map(|ext| ext == "jsonl").unwrap_or(false) {
-                files.push(path);
-            } else if path.is_dir() {
-                // Recursively search subdirectories
-                if let Err(e) = find_jsonl_files(&path, files) {
-                    println!("Warning: {}", e);
-                }
-            }
-        }
-        Ok(())
-    }
-
-    let mut jsonl_files = Vec::new();
-    find_jsonl_files(&projects_dir, &mut jsonl_files)?;
-
-    for path in jsonl_files {
-        files_processed += 1;
-        // println!("📄 Processing file: {}", path.display());
-
-        // Read the JSONL file
-        let content = std::fs::read_to_string(&path)
-            .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
-
-        // Process each line in the JSONL file
-        for line in content.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
-
-            lines_processed += 1;
-
-            // Parse the JSON line
-            let json_value: Value = serde_json::from_str(line)
-                .map_err(|e| format!("Failed to parse JSON line: {}", e))?;
-
-            // Extract the required fields
-            let uuid = json_value
-                .get("uuid")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            let timestamp = json_value
-                .get("timestamp")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            // Extract model field (optional) - check both top-level and nested in message field
-            let model = if let Some(model_str) = json_value.get("model").and_then(|v| v.as_str()) {
-                Some(model_str.to_string())
-            } else if let Some(message_obj) = json_value.get("message") {
-                message_obj
-                    .get("model")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-            } else {
-                None
-            };
-
-            // Extract usage data (optional) - check both top-level and nested in message field
-            let usage = if let Some(usage_obj) = json_value.get("usage") {
-                Some(UsageData {
-                    input_tokens: usage_obj.get("input_tokens").and_then(|v| v.as_u64()),
-                    cache_read_input_tokens: usage_obj
-                        .get("cache_read_input_tokens")
-                        .and_then(|v| v.as_u64()),
-                    output_tokens: usage_obj.get("output_tokens").and_then(|v| v.as_u64()),
-                })
-            } else if let Some(message_obj) = json_value.get("message") {
-                if let Some(usage_obj) = message_obj.get("usage") {
-                    Some(UsageData {
-                        input_tokens: usage_obj.get("input_tokens").and_then(|v| v.as_u64()),
-                        cache_read_input_tokens: usage_obj
-                            .get("cache_read_input_tokens")
-                            .and_then(|v| v.as_u64()),
-                        output_tokens: usage_obj.get("output_tokens").and_then(|v| v.as_u64()),
-                    })
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            // Only include records with valid uuid, timestamp, and valid usage data
-            if !uuid.is_empty() && !timestamp.is_empty() {
-                // Check if usage data exists and has meaningful token values
-                if let Some(ref usage_data) = usage {
-                    let input_tokens = usage_data.input_tokens.unwrap_or(0);
-                    let output_tokens = usage_data.output_tokens.unwrap_or(0);
-
-                    // Only include if input_tokens + output_tokens > 0
-                    if input_tokens + output_tokens > 0 {
-                        all_records.push(ProjectUsageRecord {
-                            uuid,
-                            timestamp,
-                            model,
-                            usage,
-                        });
-                    }
-                }
-            }
-        }
-    }
-
-    println!(
-        "📊 Summary: Processed {} files, {} lines, found {} records",
-        files_processed,
-        lines_processed,
-        all_records.len()
-    );
-    Ok(all_records)
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct MemoryFile {
-    pub path: String,
-    pub content: String,
-    pub exists: bool,
+    usage::read_project_usage_files(&paths()?)
 }
 
 #[tauri::command]
 pub async fn read_claude_memory() -> Result<MemoryFile, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_md_path = home_dir.join(".claude/CLAUDE.md");
-
-    let path_str = claude_md_path.to_string_lossy().to_string();
-
-    if claude_md_path.exists() {
-        let content = std::fs::read_to_string(&claude_md_path)
-            .map_err(|e| format!("Failed to read CLAUDE.md file: {}", e))?;
-
-        Ok(MemoryFile {
-            path: path_str,
-            content,
-            exists: true,
-        })
-    } else {
-        Ok(MemoryFile {
-            path: path_str,
-            content: String::new(),
-            exists: false,
-        })
-    }
+    settings::read_claude_memory(&paths()?)
 }
 
 #[tauri::command]
 pub async fn write_claude_memory(content: String) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_md_path = home_dir.join(".claude/CLAUDE.md");
-
-    // Ensure .claude directory exists
-    if let Some(parent) = claude_md_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-    }
-
-    std::fs::write(&claude_md_path, content)
-        .map_err(|e| format!("Failed to write CLAUDE.md file: {}", e))?;
-
-    Ok(())
+    settings::write_claude_memory(&paths()?, &content)
 }
 
 #[tauri::command]
@@ -1290,54 +473,6 @@ pub async fn install_and_restart(app: tauri::AppHandle) -> Result<(), String> {
     }
 }
 
-// Get or create distinct_id from stores.json
-async fn get_or_create_distinct_id() -> Result<String, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    // Ensure app config directory exists
-    std::fs::create_dir_all(&app_config_path)
-        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
-
-    // Read existing stores.json or create new one
-    let mut stores_data = if stores_file.exists() {
-        let content = std::fs::read_to_string(&stores_file)
-            .map_err(|e| format!("Failed to read stores file: {}", e))?;
-
-        serde_json::from_str::<StoresData>(&content)
-            .map_err(|e| format!("Failed to parse stores file: {}", e))?
-    } else {
-        StoresData {
-            configs: vec![],
-            distinct_id: None,
-            notification: Some(NotificationSettings {
-                enable: true,
-                enabled_hooks: vec!["Notification".to_string()],
-            }),
-        }
-    };
-
-    // Return existing distinct_id or create new one
-    if let Some(ref id) = stores_data.distinct_id {
-        Ok(id.clone())
-    } else {
-        // Generate new UUID
-        let new_id = Uuid::new_v4().to_string();
-        stores_data.distinct_id = Some(new_id.clone());
-
-        // Write back to stores.json
-        let json_content = serde_json::to_string_pretty(&stores_data)
-            .map_err(|e| format!("Failed to serialize stores data: {}", e))?;
-
-        std::fs::write(&stores_file, json_content)
-            .map_err(|e| format!("Failed to write stores file: {}", e))?;
-
-        println!("Created new distinct_id: {}", new_id);
-        Ok(new_id)
-    }
-}
-
 // Get operating system name in PostHog format
 fn get_os_name() -> &'static str {
     #[cfg(target_os = "macos")]
@@ -1421,92 +556,37 @@ fn get_os_version() -> Result<String, String> {
     Ok("Unknown".to_string())
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct ProjectConfig {
-    pub path: String,
-    pub config: serde_json::Value,
-}
-
 #[tauri::command]
 pub async fn read_claude_projects() -> Result<Vec<ProjectConfig>, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_json_path = home_dir.join(".claude.json");
-
-    if !claude_json_path.exists() {
-        return Ok(vec![]);
-    }
-
-    let content = std::fs::read_to_string(&claude_json_path)
-        .map_err(|e| format!("Failed to read .claude.json: {}", e))?;
-
-    let json_value: Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse .claude.json: {}", e))?;
-
-    let projects_obj = json_value
-        .get("projects")
-        .and_then(|projects| projects.as_object())
-        .cloned()
-        .unwrap_or_else(serde_json::Map::new);
+    mcp::read_claude_projects(&paths()?)
+}
 
-    let mut result = Vec::new();
-    for (path, config) in projects_obj {
-        let project_config = ProjectConfig {
-            path: path.clone(),
-            config: config.clone(),
-        };
-        result.push(project_config);
-    }
+#[tauri::command]
+pub async fn get_project_bindings() -> Result<Vec<ProjectBinding>, String> {
+    bindings::get_project_bindings(&paths()?)
+}
 
-    Ok(result)
+#[tauri::command]
+pub async fn bind_project_config(
+    project_path: String,
+    store_id: String,
+) -> Result<ProjectBinding, String> {
+    bindings::bind_project(&paths()?, &project_path, &store_id)
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct ClaudeConfigFile {
-    pub path: String,
-    pub content: Value,
-    pub exists: bool,
+#[tauri::command]
+pub async fn unbind_project_config(project_path: String) -> Result<(), String> {
+    bindings::unbind_project(&paths()?, &project_path)
 }
 
 #[tauri::command]
 pub async fn read_claude_config_file() -> Result<ClaudeConfigFile, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_json_path = home_dir.join(".claude.json");
-
-    let path_str = claude_json_path.to_string_lossy().to_string();
-
-    if claude_json_path.exists() {
-        let content = std::fs::read_to_string(&claude_json_path)
-            .map_err(|e| format!("Failed to read .claude.json: {}", e))?;
-
-        let json_content: Value =
-            serde_json::from_str(&content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-        Ok(ClaudeConfigFile {
-            path: path_str,
-            content: json_content,
-            exists: true,
-        })
-    } else {
-        Ok(ClaudeConfigFile {
-            path: path_str,
-            content: Value::Object(serde_json::Map::new()),
-            exists: false,
-        })
-    }
+    mcp::read_claude_config_file(&paths()?)
 }
 
 #[tauri::command]
 pub async fn write_claude_config_file(content: Value) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let claude_json_path = home_dir.join(".claude.json");
-
-    let json_content = serde_json::to_string_pretty(&content)
-        .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
-
-    std::fs::write(&claude_json_path, json_content)
-        .map_err(|e| format!("Failed to write file: {}", e))?;
-
-    Ok(())
+    mcp::write_claude_config_file(&paths()?, &content)
 }
 
 #[tauri::command]
@@ -1518,7 +598,7 @@ pub async fn track(
     println!("📊 Tracking event: {}", event);
 
     // Get distinct_id
-    let distinct_id = get_or_create_distinct_id().await?;
+    let distinct_id = stores::get_or_create_distinct_id(&paths()?)?;
 
     // Get app version
     let app_version = app.package_info().version.to_string();
@@ -1573,612 +653,173 @@ pub async fn track(
         .await
         .map_err(|e| format!("Failed to send request to PostHog: {}", e))?;
 
-    if response.status().is_success() {
-        println!("✅ Event tracked successfully");
-        Ok(())
-    } else {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        println!("❌ Failed to track event: {} - {}", status, error_text);
-        Err(format!("PostHog API error: {} - {}", status, error_text))
-    }
-}
-
-// Hook management functions
-
-/// Get the latest hook command based on the current operating system
-fn get_latest_hook_command() -> serde_json::Value {
-    if cfg!(target_os = "windows") {
-        serde_json::json!({
-            "__ccmate__": true,
-            "type": "command",
-            "command": "powershell -Command \"try { Invoke-RestMethod -Uri http://localhost:59948/claude_code/hooks -Method POST -ContentType 'application/json' -Body $input -ErrorAction Stop } catch { '' }\""
-        })
-    } else {
-        serde_json::json!({
-            "__ccmate__": true,
-            "type": "command",
-            "command": "curl -s -X POST http://localhost:59948/claude_code/hooks -H 'Content-Type: application/json' --data-binary @- 2>/dev/null || echo"
-        })
-    }
-}
-
-/// Update existing ccmate hooks for specified events (doesn't add new ones)
-fn update_existing_hooks(
-    hooks_obj: &mut serde_json::Map<String, serde_json::Value>,
-    events: &[&str],
-) -> Result<bool, String> {
-    let latest_hook_command = get_latest_hook_command();
-    let latest_command_str = latest_hook_command
-        .get("command")
-        .and_then(|cmd| cmd.as_str())
-        .unwrap_or("");
-
-    let mut hook_updated = false;
-
-    for event in events {
-        if let Some(event_hooks) = hooks_obj.get_mut(*event).and_then(|h| h.as_array_mut()) {
-            // Find and update existing ccmate hooks only
-            for entry in event_hooks.iter_mut() {
-                if let Some(hooks_array) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) {
-                    for hook in hooks_array.iter_mut() {
-                        if hook.get("__ccmate__").is_some() {
-                            // Compare only the command string, not the entire JSON object
-                            if let Some(existing_command) =
-                                hook.get("command").and_then(|cmd| cmd.as_str())
-                            {
-                                if existing_command != latest_command_str {
-                                    // Update only the command field, preserve other properties
-                                    hook["command"] =
-                                        serde_json::Value::String(latest_command_str.to_string());
-                                    hook_updated = true;
-                                    println!(
-                                        "🔄 Updated {} hook command: {}",
-                                        event, latest_command_str
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(hook_updated)
-}
-
-/// Update or add ccmate hooks for specified events
-fn update_or_add_hooks(
-    hooks_obj: &mut serde_json::Map<String, serde_json::Value>,
-    events: &[&str],
-) -> Result<bool, String> {
-    let latest_hook_command = get_latest_hook_command();
-    let mut hook_updated = false;
-
-    for event in events {
-        if let Some(event_hooks) = hooks_obj.get_mut(*event).and_then(|h| h.as_array_mut()) {
-            // Find and update existing ccmate hooks
-            for entry in event_hooks.iter_mut() {
-                if let Some(hooks_array) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) {
-                    for hook in hooks_array.iter_mut() {
-                        if hook.get("__ccmate__").is_some() {
-                            // Update the command to the latest version
-                            if hook.get("command") != latest_hook_command.get("command") {
-                                *hook = latest_hook_command.clone();
-                                hook_updated = true;
-                            }
-                        }
-                    }
-                }
-            }
-
-            // If no ccmate hooks found, add one
-            let ccmate_hook_exists = event_hooks.iter().any(|entry| {
-                if let Some(hooks_array) = entry.get("hooks").and_then(|h| h.as_array()) {
-                    hooks_array
-                        .iter()
-                        .any(|hook| hook.get("__ccmate__").is_some())
-                } else {
-                    false
-                }
-            });
-
-            if !ccmate_hook_exists {
-                let ccmate_hook_entry = serde_json::json!({
-                    "hooks": [latest_hook_command.clone()]
-                });
-                event_hooks.push(ccmate_hook_entry);
-                hook_updated = true;
-            }
-        } else {
-            // Create event hooks array with ccmate hook
-            let ccmate_hook_entry = serde_json::json!({
-                "hooks": [latest_hook_command.clone()]
-            });
-            hooks_obj.insert(
-                event.to_string(),
-                serde_json::Value::Array(vec![ccmate_hook_entry]),
-            );
-            hook_updated = true;
-        }
+    if response.status().is_success() {
+        println!("✅ Event tracked successfully");
+        Ok(())
+    } else {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        println!("❌ Failed to track event: {} - {}", status, error_text);
+        Err(format!("PostHog API error: {} - {}", status, error_text))
     }
-
-    Ok(hook_updated)
 }
 
+// Hook management functions
+
 #[tauri::command]
 pub async fn get_notification_settings() -> Result<Option<NotificationSettings>, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    if !stores_file.exists() {
-        return Ok(None);
-    }
-
-    let content = std::fs::read_to_string(&stores_file)
-        .map_err(|e| format!("Failed to read stores file: {}", e))?;
-
-    let stores_data: StoresData = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse stores file: {}", e))?;
-
-    Ok(stores_data.notification)
+    stores::get_notification_settings(&paths()?)
 }
 
 #[tauri::command]
 pub async fn update_claude_code_hook() -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let settings_path = home_dir.join(".claude/settings.json");
-
-    if !settings_path.exists() {
-        // If settings file doesn't exist, just add the hooks
-        return add_claude_code_hook().await;
-    }
-
-    // Read existing settings
-    let content = std::fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read settings.json: {}", e))?;
-
-    let mut settings: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse settings.json: {}", e))?;
-
-    // Ensure hooks object exists
-    let hooks_obj = settings
-        .as_object_mut()
-        .unwrap()
-        .entry("hooks".to_string())
-        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
-        .as_object_mut()
-        .unwrap();
-
-    // Update existing hooks for Notification, Stop, and PreToolUse events (only update, don't add new ones)
-    let events = ["Notification", "Stop", "PreToolUse"];
-    let hook_updated = update_existing_hooks(hooks_obj, &events)?;
-
-    if hook_updated {
-        // Write back to settings file
-        let json_content = serde_json::to_string_pretty(&settings)
-            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-        // Create .claude directory if it doesn't exist
-        if let Some(parent) = settings_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-        }
-
-        std::fs::write(&settings_path, json_content)
-            .map_err(|e| format!("Failed to write settings.json: {}", e))?;
-
-        println!("✅ Claude Code hooks updated successfully");
-    } else {
-        println!("ℹ️  Claude Code hooks are already up to date - no updates needed");
-    }
-
-    Ok(())
+    hooks::update_claude_code_hook(&paths()?)
 }
 
 #[tauri::command]
 pub async fn add_claude_code_hook() -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let settings_path = home_dir.join(".claude/settings.json");
-
-    // Read existing settings or create new structure
-    let mut settings = if settings_path.exists() {
-        let content = std::fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings.json: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse settings.json: {}", e))?
-    } else {
-        serde_json::Value::Object(serde_json::Map::new())
-    };
-
-    // Ensure hooks object exists
-    let hooks_obj = settings
-        .as_object_mut()
-        .unwrap()
-        .entry("hooks".to_string())
-        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
-        .as_object_mut()
-        .unwrap();
-
-    // Add hooks for Notification, Stop, and PreToolUse events
-    let events = ["Notification", "Stop", "PreToolUse"];
-    update_or_add_hooks(hooks_obj, &events)?;
-
-    // Write back to settings file
-    let json_content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-    // Create .claude directory if it doesn't exist
-    if let Some(parent) = settings_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-    }
-
-    std::fs::write(&settings_path, json_content)
-        .map_err(|e| format!("Failed to write settings.json: {}", e))?;
-
-    println!("✅ Claude Code hooks added successfully");
-    Ok(())
+    hooks::add_claude_code_hook(&paths()?)
 }
 
 #[tauri::command]
 pub async fn remove_claude_code_hook() -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let settings_path = home_dir.join(".claude/settings.json");
-
-    if !settings_path.exists() {
-        return Ok(()); // Settings file doesn't exist, nothing to remove
-    }
-
-    // Read existing settings
-    let content = std::fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read settings.json: {}", e))?;
-
-    let mut settings: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse settings.json: {}", e))?;
-
-    // Check if hooks object exists
-    if let Some(hooks_obj) = settings.get_mut("hooks").and_then(|h| h.as_object_mut()) {
-        let events = ["Notification", "Stop", "PreToolUse"];
-
-        for event in events {
-            if let Some(event_hooks) = hooks_obj.get_mut(event).and_then(|h| h.as_array_mut()) {
-                // Remove hooks that have __ccmate__ key from nested hooks arrays
-                let mut new_event_hooks = Vec::new();
-                for entry in event_hooks.iter() {
-                    if let Some(hooks_array) = entry.get("hooks").and_then(|h| h.as_array()) {
-                        // Filter out hooks that have __ccmate__ key
-                        let filtered_hooks: Vec<serde_json::Value> = hooks_array
-                            .iter()
-                            .filter(|hook| hook.get("__ccmate__").is_none())
-                            .cloned()
-                            .collect();
-
-                        // Keep the entry only if it still has hooks
-                        if !filtered_hooks.is_empty() {
-                            let mut new_entry = entry.clone();
-                            new_entry["hooks"] = serde_json::Value::Array(filtered_hooks);
-                            new_event_hooks.push(new_entry);
-                        }
-                    } else {
-                        // Keep entries that don't have a hooks array
-                        new_event_hooks.push(entry.clone());
-                    }
-                }
-                *event_hooks = new_event_hooks;
-
-                // If the event hooks array is empty, remove the entire event entry
-                if event_hooks.is_empty() {
-                    hooks_obj.remove(event);
-                }
-            }
-        }
-
-        // If hooks object is empty, remove it entirely
-        if hooks_obj.is_empty() {
-            settings.as_object_mut().unwrap().remove("hooks");
-        }
-    }
+    hooks::remove_claude_code_hook(&paths()?)
+}
 
-    // Write back to settings file
-    let json_content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+#[tauri::command]
+pub async fn list_hook_sessions(
+    filter: Option<HookEventFilter>,
+) -> Result<Vec<HookSession>, String> {
+    hook_log::get_hook_sessions(&paths()?, &filter.unwrap_or_default())
+}
 
-    std::fs::write(&settings_path, json_content)
-        .map_err(|e| format!("Failed to write settings.json: {}", e))?;
+#[tauri::command]
+pub async fn get_hook_session_timeline(session_id: String) -> Result<Vec<HookEventRecord>, String> {
+    hook_log::get_session_timeline(&paths()?, &session_id)
+}
 
-    println!("✅ Claude Code hooks removed successfully");
-    Ok(())
+#[tauri::command]
+pub async fn get_hook_events(
+    filter: Option<HookEventFilter>,
+) -> Result<Vec<HookEventRecord>, String> {
+    hook_log::get_hook_events(&paths()?, &filter.unwrap_or_default())
 }
 
 #[tauri::command]
-pub async fn update_notification_settings(settings: NotificationSettings) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let app_config_path = home_dir.join(APP_CONFIG_DIR);
-    let stores_file = app_config_path.join("stores.json");
-
-    if !stores_file.exists() {
-        // Create stores.json with notification settings if it doesn't exist
-        let stores_data = StoresData {
-            configs: vec![],
-            distinct_id: None,
-            notification: Some(settings.clone()),
-        };
+pub async fn get_hook_tool_names() -> Result<Vec<String>, String> {
+    hook_log::get_hook_tool_names(&paths()?)
+}
 
-        // Ensure app config directory exists
-        std::fs::create_dir_all(&app_config_path)
-            .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+#[tauri::command]
+pub async fn clear_hook_events(before: Option<u64>) -> Result<usize, String> {
+    hook_log::clear_hook_events(&paths()?, before)
+}
 
-        let json_content = serde_json::to_string_pretty(&stores_data)
-            .map_err(|e| format!("Failed to serialize stores: {}", e))?;
+#[tauri::command]
+pub async fn get_active_sessions() -> Result<Vec<LiveSession>, String> {
+    Ok(crate::hook_server::active_sessions())
+}
 
-        std::fs::write(&stores_file, json_content)
-            .map_err(|e| format!("Failed to write stores file: {}", e))?;
+#[tauri::command]
+pub async fn get_approval_settings() -> Result<ApprovalSettings, String> {
+    approval::get_approval_settings(&paths()?)
+}
 
-        println!("Created stores.json with notification settings");
-        return Ok(());
-    }
+#[tauri::command]
+pub async fn update_approval_settings(settings: ApprovalSettings) -> Result<ApprovalSettings, String> {
+    approval::update_approval_settings(&paths()?, settings)
+}
 
-    // Read existing stores
-    let content = std::fs::read_to_string(&stores_file)
-        .map_err(|e| format!("Failed to read stores file: {}", e))?;
+#[tauri::command]
+pub async fn get_pending_approvals() -> Result<Vec<crate::hook_server::PendingApproval>, String> {
+    Ok(crate::hook_server::pending_approvals())
+}
 
-    let mut stores_data: StoresData = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse stores file: {}", e))?;
+#[tauri::command]
+pub async fn resolve_approval(id: String, decision: PermissionDecision) -> Result<(), String> {
+    crate::hook_server::resolve_approval(&id, decision)
+}
 
-    // Update notification settings
-    stores_data.notification = Some(settings);
+#[tauri::command]
+pub async fn get_tool_rules() -> Result<ToolRuleSettings, String> {
+    tool_rules::get_tool_rule_settings(&paths()?)
+}
 
-    // Write back to stores file
-    let json_content = serde_json::to_string_pretty(&stores_data)
-        .map_err(|e| format!("Failed to serialize stores: {}", e))?;
+#[tauri::command]
+pub async fn update_tool_rules(settings: ToolRuleSettings) -> Result<ToolRuleSettings, String> {
+    tool_rules::update_tool_rule_settings(&paths()?, settings)
+}
 
-    std::fs::write(&stores_file, json_content)
-        .map_err(|e| format!("Failed to write stores file: {}", e))?;
+// Which saved rule would decide a tool call, whether or not rules are on
+#[tauri::command]
+pub async fn evaluate_tool_rules(
+    tool_name: String,
+    tool_input: Value,
+    cwd: String,
+) -> Result<Option<RuleMatch>, String> {
+    let settings = tool_rules::get_tool_rule_settings(&paths()?)?;
+    Ok(tool_rules::evaluate(&settings.rules, &tool_name, &tool_input, &cwd))
+}
 
-    println!("✅ Notification settings updated successfully");
-    Ok(())
+// Latest tool calls the rules decided, including the dry-run matches
+#[tauri::command]
+pub async fn get_tool_rule_decisions(limit: Option<usize>) -> Result<Vec<RuleDecisionRecord>, String> {
+    hook_log::get_rule_decisions(&paths()?, limit)
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct CommandFile {
-    pub name: String,
-    pub content: String,
-    pub exists: bool,
+#[tauri::command]
+pub async fn update_notification_settings(settings: NotificationSettings) -> Result<(), String> {
+    stores::update_notification_settings(&paths()?, settings)
 }
 
 #[tauri::command]
 pub async fn read_claude_commands() -> Result<Vec<CommandFile>, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let commands_dir = home_dir.join(".claude/commands");
-
-    if !commands_dir.exists() {
-        return Ok(vec![]);
-    }
-
-    let mut command_files = Vec::new();
-
-    // Read all .md files in the commands directory
-    let entries = std::fs::read_dir(&commands_dir)
-        .map_err(|e| format!("Failed to read commands directory: {}", e))?;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
-
-        if path.is_file() && path.extension().map(|ext| ext == "md").unwrap_or(false) {
-            let file_name = path
-                .file_stem()
-                .and_then(|name| name.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-
-            let content = std::fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read command file {}: {}", path.display(), e))?;
-
-            command_files.push(CommandFile {
-                name: file_name,
-                content,
-                exists: true,
-            });
-        }
-    }
-
-    // Sort commands alphabetically by name
-    command_files.sort_by(|a, b| a.name.cmp(&b.name));
-
-    Ok(command_files)
+    claude_files::read_claude_commands(&paths()?)
 }
 
 #[tauri::command]
 pub async fn write_claude_command(command_name: String, content: String) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let commands_dir = home_dir.join(".claude/commands");
-    let command_file_path = commands_dir.join(format!("{}.md", command_name));
-
-    // Ensure .claude/commands directory exists
-    std::fs::create_dir_all(&commands_dir)
-        .map_err(|e| format!("Failed to create .claude/commands directory: {}", e))?;
-
-    std::fs::write(&command_file_path, content)
-        .map_err(|e| format!("Failed to write command file: {}", e))?;
-
-    Ok(())
+    claude_files::write_claude_command(&paths()?, &command_name, &content)
 }
 
 #[tauri::command]
 pub async fn delete_claude_command(command_name: String) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let commands_dir = home_dir.join(".claude/commands");
-    let command_file_path = commands_dir.join(format!("{}.md", command_name));
-
-    if command_file_path.exists() {
-        std::fs::remove_file(&command_file_path)
-            .map_err(|e| format!("Failed to delete command file: {}", e))?;
-    }
-
-    Ok(())
+    claude_files::delete_claude_command(&paths()?, &command_name)
 }
 
 // Agent management functions
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct AgentFile {
-    pub name: String,
-    pub content: String,
-    pub exists: bool,
-}
-
 #[tauri::command]
 pub async fn read_claude_agents() -> Result<Vec<AgentFile>, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let agents_dir = home_dir.join(".claude/agents");
-
-    if !agents_dir.exists() {
-        return Ok(vec![]);
-    }
-
-    let mut agent_files = Vec::new();
-
-    // Read all .md files in the agents directory
-    let entries = std::fs::read_dir(&agents_dir)
-        .map_err(|e| format!("Failed to read agents directory: {}", e))?;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
-
-        if path.is_file() && path.extension().map(|ext| ext == "md").unwrap_or(false) {
-            let file_name = path
-                .file_stem()
-                .and_then(|name| name.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-
-            let content = std::fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read agent file {}: {}", path.display(), e))?;
-
-            agent_files.push(AgentFile {
-                name: file_name,
-                content,
-                exists: true,
-            });
-        }
-    }
-
-    // Sort agents alphabetically by name
-    agent_files.sort_by(|a, b| a.name.cmp(&b.name));
-
-    Ok(agent_files)
+    claude_files::read_claude_agents(&paths()?)
 }
 
 #[tauri::command]
 pub async fn write_claude_agent(agent_name: String, content: String) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let agents_dir = home_dir.join(".claude/agents");
-    let agent_file_path = agents_dir.join(format!("{}.md", agent_name));
-
-    // Ensure .claude/agents directory exists
-    std::fs::create_dir_all(&agents_dir)
-        .map_err(|e| format!("Failed to create .claude/agents directory: {}", e))?;
-
-    std::fs::write(&agent_file_path, content)
-        .map_err(|e| format!("Failed to write agent file: {}", e))?;
-
-    Ok(())
+    claude_files::write_claude_agent(&paths()?, &agent_name, &content)
 }
 
 #[tauri::command]
 pub async fn delete_claude_agent(agent_name: String) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let agents_dir = home_dir.join(".claude/agents");
-    let agent_file_path = agents_dir.join(format!("{}.md", agent_name));
-
-    if agent_file_path.exists() {
-        std::fs::remove_file(&agent_file_path)
-            .map_err(|e| format!("Failed to delete agent file: {}", e))?;
-    }
-
-    Ok(())
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct CodexStore {
-    pub id: String,
-    pub title: String,
-    #[serde(rename = "createdAt")]
-    pub created_at: u64,
-    pub config: Value,
-    pub using: bool,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct CodexStoresData {
-    pub configs: Vec<CodexStore>,
-}
-
-const CODEX_CONFIG_FILE: &str = "codexcc.json";
-
-async fn read_codex_stores_internal() -> Result<CodexStoresData, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let config_path = home_dir.join(APP_CONFIG_DIR).join(CODEX_CONFIG_FILE);
-
-    if !config_path.exists() {
-        return Ok(CodexStoresData { configs: vec![] });
-    }
-
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read codex config file: {}", e))?;
-
-    let data: CodexStoresData = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse codex config file: {}", e))?;
-
-    Ok(data)
+    claude_files::delete_claude_agent(&paths()?, &agent_name)
 }
 
-async fn write_codex_stores_internal(data: &CodexStoresData) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let config_dir = home_dir.join(APP_CONFIG_DIR);
-    let config_path = config_dir.join(CODEX_CONFIG_FILE);
-
-    if !config_dir.exists() {
-        std::fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    }
-
-    let content = serde_json::to_string_pretty(data)
-        .map_err(|e| format!("Failed to serialize codex config data: {}", e))?;
-
-    std::fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write codex config file: {}", e))?;
-
-    Ok(())
-}
+// Codex store management functions
 
 #[tauri::command]
 pub async fn get_codex_stores() -> Result<Vec<CodexStore>, String> {
-    let data = read_codex_stores_internal().await?;
-    Ok(data.configs)
+    let paths = paths()?;
+    Ok(codex::get_codex_stores(&paths)?
+        .into_iter()
+        .map(|store| masked_codex_store(&paths, store))
+        .collect())
 }
 
 #[tauri::command]
 pub async fn create_codex_store(title: String, config: Value) -> Result<CodexStore, String> {
-    let mut data = read_codex_stores_internal().await?;
-
-    let new_store = CodexStore {
-        id: nanoid::nanoid!(),
-        title,
-        created_at: chrono::Utc::now().timestamp_millis() as u64,
-        config,
-        using: false,
-    };
-
-    data.configs.push(new_store.clone());
-    write_codex_stores_internal(&data).await?;
-
-    Ok(new_store)
+    let paths = paths()?;
+    let store = codex::create_codex_store(&paths, title, config)?;
+    Ok(masked_codex_store(&paths, store))
 }
 
 #[tauri::command]
@@ -2187,175 +828,191 @@ pub async fn update_codex_store(
     title: String,
     config: Value,
 ) -> Result<CodexStore, String> {
-    let mut data = read_codex_stores_internal().await?;
+    let paths = paths()?;
+    let store = codex::update_codex_store(&paths, &id, title, config)?;
+    Ok(masked_codex_store(&paths, store))
+}
 
-    if let Some(store) = data.configs.iter_mut().find(|s| s.id == id) {
-        store.title = title;
-        store.config = config;
+#[tauri::command]
+pub async fn delete_codex_store(id: String) -> Result<(), String> {
+    codex::delete_codex_store(&paths()?, &id)
+}
 
-        let updated_store = store.clone();
-        write_codex_stores_internal(&data).await?;
-        Ok(updated_store)
-    } else {
-        Err("Store not found".to_string())
-    }
+#[tauri::command]
+pub async fn set_using_codex_store(id: String) -> Result<(), String> {
+    codex::set_using_codex_store(&paths()?, &id)
 }
 
 #[tauri::command]
-pub async fn delete_codex_store(id: String) -> Result<(), String> {
-    let mut data = read_codex_stores_internal().await?;
+pub async fn get_current_codex_store() -> Result<Option<CodexStore>, String> {
+    let paths = paths()?;
+    Ok(codex::get_current_codex_store(&paths)?.map(|store| masked_codex_store(&paths, store)))
+}
 
-    data.configs.retain(|s| s.id != id);
-    write_codex_stores_internal(&data).await?;
+#[tauri::command]
+pub async fn get_codex_global_settings() -> Result<CodexGlobalSettings, String> {
+    codex::get_codex_global_settings(&paths()?)
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn update_codex_global_settings(settings: CodexGlobalSettings) -> Result<(), String> {
+    codex::update_codex_global_settings(&paths()?, &settings)
 }
 
+// Config history functions
+
 #[tauri::command]
-pub async fn set_using_codex_store(id: String) -> Result<(), String> {
-    let mut data = read_codex_stores_internal().await?;
-    let mut selected_store: Option<CodexStore> = None;
+pub async fn list_history_files() -> Result<Vec<HistoryFile>, String> {
+    history::list_history_files(&paths()?)
+}
 
-    for store in &mut data.configs {
-        if store.id == id {
-            store.using = true;
-            selected_store = Some(store.clone());
-        } else {
-            store.using = false;
-        }
-    }
+#[tauri::command]
+pub async fn list_history_versions(key: String) -> Result<Vec<HistoryVersion>, String> {
+    history::list_history_versions(&paths()?, &key)
+}
 
-    write_codex_stores_internal(&data).await?;
+#[tauri::command]
+pub async fn read_history_version(key: String, version_id: String) -> Result<String, String> {
+    history::read_history_version(&paths()?, &key, &version_id)
+}
 
-    // If a store was selected, update the .codex directory
-    if let Some(store) = selected_store {
-        let settings = get_codex_global_settings().await?;
+#[tauri::command]
+pub async fn diff_history_version(
+    key: String,
+    from: String,
+    to: Option<String>,
+) -> Result<HistoryDiff, String> {
+    history::diff_history_version(&paths()?, &key, &from, to.as_deref())
+}
 
-        // Only proceed if Codex is enabled
-        if settings.enabled {
-            let root_path = std::path::PathBuf::from(&settings.root_path);
+#[tauri::command]
+pub async fn restore_history_version(key: String, version_id: String) -> Result<(), String> {
+    history::restore_history_version(&paths()?, &key, &version_id)
+}
 
-            // Ensure .codex directory exists
-            if !root_path.exists() {
-                std::fs::create_dir_all(&root_path)
-                    .map_err(|e| format!("Failed to create codex root directory: {}", e))?;
-            }
+#[tauri::command]
+pub async fn get_history_settings() -> Result<HistorySettings, String> {
+    history::get_history_settings(&paths()?)
+}
 
-            let config_obj = store.config.as_object().ok_or("Invalid config format")?;
-
-            // 1. Write auth.json
-            if let Some(api_key) = config_obj.get("api_key").and_then(|v| v.as_str()) {
-                let auth_content = serde_json::json!({
-                    "OPENAI_API_KEY": api_key
-                });
-                let auth_path = root_path.join("auth.json");
-                let auth_json = serde_json::to_string_pretty(&auth_content)
-                    .map_err(|e| format!("Failed to serialize auth.json: {}", e))?;
-                std::fs::write(auth_path, auth_json)
-                    .map_err(|e| format!("Failed to write auth.json: {}", e))?;
-            }
+#[tauri::command]
+pub async fn update_history_settings(settings: HistorySettings) -> Result<(), String> {
+    history::update_history_settings(&paths()?, &settings)
+}
 
-            // 2. Write config.toml
-            let _platform = config_obj
-                .get("platform")
-                .and_then(|v| v.as_str())
-                .unwrap_or("custom");
-            let model = config_obj
-                .get("model")
-                .and_then(|v| v.as_str())
-                .unwrap_or("gpt-5-codex");
-            let url = config_obj
-                .get("url")
-                .and_then(|v| v.as_str())
-                .unwrap_or("https://api.lightai.io/v1");
-            let name = store.title.clone(); // Use configuration name as provider name
-
-            let config_toml_content = format!(
-                r#"model_provider = "{name}"
-model = "{model}"
-model_reasoning_effort = "high"
-disable_response_storage = true
-windows_wsl_setup_acknowledged = true
-
-[model_providers.{name}]
-name = "{name}"
-base_url = "{url}"
-wire_api = "responses"
-requires_openai_auth = true
-"#
-            );
-
-            let config_toml_path = root_path.join("config.toml");
-            std::fs::write(config_toml_path, config_toml_content)
-                .map_err(|e| format!("Failed to write config.toml: {}", e))?;
-        }
-    }
+#[tauri::command]
+pub async fn get_auto_switch_settings() -> Result<AutoSwitchSettings, String> {
+    auto_switch::get_auto_switch_settings(&paths()?)
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn update_auto_switch_settings(
+    settings: AutoSwitchSettings,
+) -> Result<AutoSwitchSettings, String> {
+    auto_switch::update_auto_switch_settings(&paths()?, settings)
 }
 
 #[tauri::command]
-pub async fn get_current_codex_store() -> Result<Option<CodexStore>, String> {
-    let data = read_codex_stores_internal().await?;
-    Ok(data.configs.into_iter().find(|s| s.using))
+pub async fn get_auto_switch_log(limit: Option<usize>) -> Result<Vec<AuditEntry>, String> {
+    auto_switch::get_auto_switch_log(&paths()?, limit.unwrap_or(100))
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct CodexGlobalSettings {
-    pub enabled: bool,
-    pub root_path: String,
+#[tauri::command]
+pub async fn get_failover_settings() -> Result<FailoverSettings, String> {
+    failover::get_failover_settings(&paths()?)
 }
 
-impl Default for CodexGlobalSettings {
-    fn default() -> Self {
-        let home = dirs::home_dir().unwrap_or_default();
-        let root_path = home.join(".codex").to_string_lossy().to_string();
+#[tauri::command]
+pub async fn update_failover_settings(
+    settings: FailoverSettings,
+) -> Result<FailoverSettings, String> {
+    failover::update_failover_settings(&paths()?, settings)
+}
 
-        Self {
-            enabled: true,
-            root_path,
-        }
-    }
+#[tauri::command]
+pub async fn get_relay_settings() -> Result<RelaySettings, String> {
+    relay::get_relay_settings(&paths()?)
 }
 
-const CODEX_SETTINGS_FILE: &str = "codex_settings.json";
+// Re-applies the active profile, then starts or stops the listener
+#[tauri::command]
+pub async fn update_relay_settings(
+    app: tauri::AppHandle,
+    enabled: bool,
+    port: u16,
+    log_bodies: bool,
+) -> Result<RelaySettings, String> {
+    let settings = relay::update_relay_settings(&paths()?, enabled, port, log_bodies)?;
+    crate::relay::apply_relay_settings(&settings).await?;
+    let _ = crate::tray::rebuild_tray_menu(app).await;
+    Ok(settings)
+}
 
 #[tauri::command]
-pub async fn get_codex_global_settings() -> Result<CodexGlobalSettings, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let config_path = home_dir.join(APP_CONFIG_DIR).join(CODEX_SETTINGS_FILE);
+pub async fn set_relay_wire_api(
+    store_id: String,
+    wire_api: WireApi,
+) -> Result<RelaySettings, String> {
+    relay::set_wire_api(&paths()?, &store_id, wire_api)
+}
 
-    if !config_path.exists() {
-        return Ok(CodexGlobalSettings::default());
-    }
+#[tauri::command]
+pub async fn get_model_aliases(store_id: String) -> Result<Vec<ModelAlias>, String> {
+    model_aliases::get_store_model_aliases(&paths()?, &store_id)
+}
+
+#[tauri::command]
+pub async fn update_model_aliases(
+    store_id: String,
+    aliases: Vec<ModelAlias>,
+) -> Result<Vec<ModelAlias>, String> {
+    model_aliases::update_store_model_aliases(&paths()?, &store_id, aliases)
+}
 
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read codex settings: {}", e))?;
+#[tauri::command]
+pub async fn get_relay_policy(store_id: String) -> Result<RelayPolicy, String> {
+    relay_policy::get_store_relay_policy(&paths()?, &store_id)
+}
 
-    let settings: CodexGlobalSettings = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse codex settings: {}", e))?;
+#[tauri::command]
+pub async fn update_relay_policy(
+    store_id: String,
+    policy: RelayPolicy,
+) -> Result<RelayPolicy, String> {
+    relay_policy::update_store_relay_policy(&paths()?, &store_id, policy)
+}
 
-    Ok(settings)
+#[tauri::command]
+pub async fn get_relay_metrics() -> Result<Vec<RelayMetrics>, String> {
+    Ok(crate::relay::relay_metrics())
 }
 
 #[tauri::command]
-pub async fn update_codex_global_settings(settings: CodexGlobalSettings) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let config_dir = home_dir.join(APP_CONFIG_DIR);
-    let config_path = config_dir.join(CODEX_SETTINGS_FILE);
+pub async fn get_relay_log(
+    limit: usize,
+    store_id: Option<String>,
+) -> Result<Vec<RelayLogEntry>, String> {
+    relay_log::get_relay_log(&paths()?, limit, store_id.as_deref())
+}
 
-    if !config_dir.exists() {
-        std::fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    }
+#[tauri::command]
+pub async fn get_relay_log_entry(id: i64) -> Result<RelayLogEntry, String> {
+    relay_log::get_relay_log_entry(&paths()?, id)
+}
 
-    let content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize codex settings: {}", e))?;
+#[tauri::command]
+pub async fn get_relay_usage(since: Option<u64>) -> Result<Vec<RelayUsage>, String> {
+    relay_log::get_relay_usage(&paths()?, since)
+}
 
-    std::fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write codex settings: {}", e))?;
+#[tauri::command]
+pub async fn clear_relay_log_bodies() -> Result<usize, String> {
+    relay_log::clear_relay_log_bodies(&paths()?)
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn clear_relay_log() -> Result<(), String> {
+    relay_log::clear_relay_log(&paths()?)
 }
 
 #[derive(serde::Serialize)]
@@ -2365,51 +1022,32 @@ pub struct ConnectionStatus {
     pub message: Option<String>,
 }
 
+// Send a minimal authenticated request with a Codex store's key and model
 #[tauri::command]
-pub async fn check_codex_connection(
-    url: String,
-    _api_key: Option<String>,
-    _model: Option<String>,
-) -> Result<ConnectionStatus, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let start = std::time::Instant::now();
-
-    // Extract the base domain from the URL for a simple connectivity check
-    // Parse the URL to get just the scheme + host (e.g., https://api.openai.com)
-    let parsed_url = reqwest::Url::parse(&url)
-        .or_else(|_| reqwest::Url::parse(&format!("https://{}", url)))
-        .map_err(|e| format!("Invalid URL: {}", e))?;
-
-    let base_url = format!("{}://{}", parsed_url.scheme(), parsed_url.host_str().unwrap_or(""));
-
-    // Use HEAD request for a lightweight connectivity check
-    match client.head(&base_url).send().await {
-        Ok(response) => {
-            let latency = start.elapsed().as_millis() as u64;
-            // Consider 2xx, 3xx, 4xx as "online" - we just want to know if the domain is reachable
-            // 401/403 still means the server is online, just requires auth
-            let is_reachable = response.status().as_u16() < 500;
-
-            Ok(ConnectionStatus {
-                success: is_reachable,
-                latency_ms: latency,
-                message: if is_reachable {
-                    None
-                } else {
-                    Some(format!("HTTP {}", response.status()))
-                },
-            })
-        }
-        Err(e) => Ok(ConnectionStatus {
-            success: false,
-            latency_ms: 0,
-            message: Some(e.to_string()),
-        }),
-    }
+pub async fn check_codex_connection(store_id: String) -> Result<ConnectionStatus, String> {
+    let probe = health::codex_probe(&paths()?, &store_id)?;
+    let client = crate::health::probe_client()?;
+    let result = crate::health::send_probe(&client, &probe).await;
+    Ok(ConnectionStatus {
+        success: result.healthy(),
+        latency_ms: result.latency_ms,
+        message: result.error,
+    })
+}
+
+// Send a minimal authenticated request with a Claude Code or Codex store's credentials
+#[tauri::command]
+pub async fn check_profile_health(
+    store_id: String,
+    target: Option<SwitchTarget>,
+) -> Result<ProfileHealth, String> {
+    let paths = paths()?;
+    let probe = match target.unwrap_or(SwitchTarget::Claude) {
+        SwitchTarget::Claude => health::claude_probe(&paths, &store_id)?,
+        SwitchTarget::Codex => health::codex_probe(&paths, &store_id)?,
+    };
+    let client = crate::health::probe_client()?;
+    Ok(crate::health::send_probe(&client, &probe).await)
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -2772,7 +1410,8 @@ pub fn get_system_env_config() -> SystemEnvConfig {
     SystemEnvConfig {
         has_config,
         base_url,
-        auth_token,
+        // Only the masked form leaves the backend
+        auth_token: auth_token.as_deref().map(vault::mask),
         main_model: std::env::var("ANTHROPIC_MODEL").ok(),
         haiku_model: std::env::var("ANTHROPIC_DEFAULT_HAIKU_MODEL").ok(),
         sonnet_model: std::env::var("ANTHROPIC_DEFAULT_SONNET_MODEL").ok(),
@@ -2880,3 +1519,4 @@ pub async fn install_claude_cli() -> Result<(), String> {
         }
     }
 }
+
@@ -0,0 +1,98 @@
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use tvcbuddy_core::failover::{self, FailoverAction, FailoverMonitor, FailoverSettings};
+use tvcbuddy_core::{health, Paths};
+
+use crate::auto_switch;
+
+/// Health-check the failover group in the background for as long as the app runs.
+pub fn start_failover<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut monitor = FailoverMonitor::default();
+        loop {
+            let interval = match run_check(&app, &mut monitor).await {
+                Ok(interval) => interval,
+                Err(e) => {
+                    eprintln!("Failed to check failover group: {}", e);
+                    FailoverSettings::default().interval_secs
+                }
+            };
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+/// One round of checks, returning the seconds to wait before the next
+async fn run_check<R: Runtime>(
+    app: &AppHandle<R>,
+    monitor: &mut FailoverMonitor,
+) -> Result<u64, String> {
+    let paths = Paths::detect()?;
+    let settings = failover::get_failover_settings(&paths)?;
+    let Some(check) = monitor.next_check(&paths, &settings)? else {
+        return Ok(settings.interval_secs);
+    };
+
+    let client = crate::health::probe_client()?;
+    let (active_healthy, active_error) = check_store(&client, &paths, &check.active).await;
+    let primary_healthy = match &check.primary {
+        Some(primary) => Some(check_store(&client, &paths, primary).await.0),
+        None => None,
+    };
+
+    let entry = match monitor.record(&settings, active_healthy, primary_healthy) {
+        FailoverAction::Stay => None,
+        FailoverAction::FailOver => {
+            let reason = format!(
+                "active profile failed {} health checks in a row ({})",
+                settings.failure_threshold,
+                active_error.unwrap_or_else(|| "no answer".to_string())
+            );
+            let mut healthy_candidate = None;
+            for candidate in failover::candidates(&settings, &check.active) {
+                if check_store(&client, &paths, &candidate).await.0 {
+                    healthy_candidate = Some(candidate);
+                    break;
+                }
+            }
+            match healthy_candidate {
+                Some(to) => Some(failover::switch_to(&paths, &check.active, &to, reason)),
+                None => monitor.no_candidate(&paths, &check.active, reason),
+            }
+        }
+        FailoverAction::SwitchBack => check.primary.as_deref().map(|primary| {
+            failover::switch_to(
+                &paths,
+                &check.active,
+                primary,
+                "primary profile recovered".to_string(),
+            )
+        }),
+    };
+
+    if let Some(entry) = entry {
+        auto_switch::announce(app, &[entry]).await;
+    }
+    Ok(settings.interval_secs)
+}
+
+// Whether the store passes a health check, and the error if it does not
+async fn check_store(
+    client: &reqwest::Client,
+    paths: &Paths,
+    store_id: &str,
+) -> (bool, Option<String>) {
+    match health::claude_probe(paths, store_id) {
+        Ok(probe) => {
+            let result = crate::health::send_probe(client, &probe).await;
+            (result.healthy(), result.error)
+        }
+        Err(e) => {
+            eprintln!(
+                "Warning: Failed to prepare health check for {}: {}",
+                store_id, e
+            );
+            (false, Some(e))
+        }
+    }
+}
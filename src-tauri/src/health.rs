@@ -0,0 +1,226 @@
+use std::time::{Duration, Instant};
+use tvcbuddy_core::health::{self, ProbeRequest, ProfileHealth};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub fn probe_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Send a health check request and interpret the answer
+pub async fn send_probe(client: &reqwest::Client, probe: &ProbeRequest) -> ProfileHealth {
+    let mut request = client.post(&probe.url).json(&probe.body);
+    for (name, value) in &probe.headers {
+        request = request.header(name, value);
+    }
+
+    let start = Instant::now();
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return health::unreachable(probe, e.to_string(), start.elapsed().as_millis() as u64)
+        }
+    };
+    let status = response.status().as_u16();
+    let body = response.text().await.unwrap_or_default();
+    health::read_response(probe, status, &body, start.elapsed().as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use tvcbuddy_core::codex::CodexStore;
+
+    const VALID_KEY: &str = "sk-valid";
+
+    // Answers like a provider: bad keys get 401, unknown models a not_found_error naming
+    // the model, unknown paths a plain not_found_error, everything else a completion
+    fn start_mock_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                answer(stream);
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    fn answer(mut stream: TcpStream) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let path = request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or("")
+            .to_string();
+
+        let mut authorized = false;
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line.split_once(':').unwrap();
+            let value = value.trim();
+            match name.to_lowercase().as_str() {
+                "content-length" => content_length = value.parse().unwrap(),
+                "authorization" => authorized = value == format!("Bearer {}", VALID_KEY),
+                "x-api-key" => authorized = value == VALID_KEY,
+                _ => {}
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+
+        let (status, response) = if !path.ends_with("/messages") && !path.ends_with("/completions")
+        {
+            (404, error_body("not_found_error", "Not Found"))
+        } else if !authorized {
+            (401, error_body("authentication_error", "invalid x-api-key"))
+        } else if body["model"] != "known-model" {
+            let message = format!("model: {}", body["model"].as_str().unwrap_or_default());
+            (404, error_body("not_found_error", &message))
+        } else {
+            (200, json!({ "id": "msg_1", "content": [] }))
+        };
+
+        let response = response.to_string();
+        write!(
+            stream,
+            "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            response.len(),
+            response
+        )
+        .unwrap();
+    }
+
+    fn error_body(error_type: &str, message: &str) -> Value {
+        json!({ "type": "error", "error": { "type": error_type, "message": message } })
+    }
+
+    fn send(probe: &ProbeRequest) -> ProfileHealth {
+        let client = probe_client().unwrap();
+        tauri::async_runtime::block_on(send_probe(&client, probe))
+    }
+
+    fn claude_settings(base_url: &str, key: &str, model: &str) -> Value {
+        json!({ "env": {
+            "ANTHROPIC_BASE_URL": base_url,
+            "ANTHROPIC_API_KEY": key,
+            "ANTHROPIC_MODEL": model,
+        } })
+    }
+
+    #[test]
+    fn maps_mock_server_answers() {
+        let base_url = start_mock_server();
+        let probe = |key: &str, model: &str| {
+            health::claude_probe_for_settings(&claude_settings(&base_url, key, model)).unwrap()
+        };
+
+        let ok = send(&probe(VALID_KEY, "known-model"));
+        assert!(ok.healthy());
+        assert_eq!(
+            (ok.auth_valid, ok.model_available),
+            (Some(true), Some(true))
+        );
+        assert_eq!(ok.error, None);
+
+        let bad_key = send(&probe("sk-wrong", "known-model"));
+        assert_eq!(bad_key.status, Some(401));
+        assert_eq!(
+            (bad_key.auth_valid, bad_key.model_available),
+            (Some(false), None)
+        );
+        assert_eq!(
+            bad_key.error.as_deref(),
+            Some("HTTP 401: invalid x-api-key")
+        );
+
+        let bad_model = send(&probe(VALID_KEY, "gone-model"));
+        assert_eq!(bad_model.status, Some(404));
+        assert_eq!(
+            (bad_model.auth_valid, bad_model.model_available),
+            (Some(true), Some(false))
+        );
+    }
+
+    #[test]
+    fn wrong_url_is_not_a_missing_model() {
+        let base_url = start_mock_server();
+        let settings = claude_settings(&base_url, VALID_KEY, "known-model");
+        let probe = ProbeRequest {
+            url: format!("{}/v1/nothing", base_url),
+            ..health::claude_probe_for_settings(&settings).unwrap()
+        };
+
+        let health = send(&probe);
+        assert_eq!(health.status, Some(404));
+        assert_eq!((health.auth_valid, health.model_available), (None, None));
+    }
+
+    #[test]
+    fn checks_codex_stores_with_their_key() {
+        let base_url = start_mock_server();
+        let store = |api_key: &str, model: &str| CodexStore {
+            id: "codex".to_string(),
+            title: "Codex".to_string(),
+            created_at: 0,
+            config: json!({
+                "url": base_url,
+                "api_key": api_key,
+                "model": model,
+                "wire_api": "chat",
+            }),
+            using: false,
+        };
+        let probe = |api_key: &str, model: &str| {
+            health::codex_probe_for_store(&store(api_key, model)).unwrap()
+        };
+
+        assert!(send(&probe(VALID_KEY, "known-model")).healthy());
+        assert_eq!(
+            send(&probe("sk-wrong", "known-model")).auth_valid,
+            Some(false)
+        );
+        assert_eq!(
+            send(&probe(VALID_KEY, "gone-model")).model_available,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn a_server_that_is_not_there_is_unreachable() {
+        // Bind and drop a listener to get a port nothing listens on
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let base_url = format!("http://127.0.0.1:{}", port);
+        let probe = health::claude_probe_for_settings(&claude_settings(
+            &base_url,
+            VALID_KEY,
+            "known-model",
+        ))
+        .unwrap();
+
+        let health = send(&probe);
+        assert!(!health.reachable);
+        assert_eq!(health.status, None);
+        assert!(health.error.is_some());
+    }
+}
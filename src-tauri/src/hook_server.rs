@@ -1,31 +1,251 @@
 use serde_json::Value;
 use axum::{
     extract::Json,
-    http::StatusCode,
-    response::IntoResponse,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::post,
     Router,
 };
-use tower_http::cors::{Any, CorsLayer};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager};
 use tauri_plugin_notification::NotificationExt;
+use tokio::sync::oneshot;
+use tvcbuddy_core::approval::{self, ApprovalSettings, PermissionDecision};
+use tvcbuddy_core::live_sessions::{LiveSession, LiveSessions, SessionChange};
+use tvcbuddy_core::tool_rules::{RuleMatch, ToolRuleCache};
+use tvcbuddy_core::{hook_log, hooks, stores, Paths};
 
-// Hook event data structure
+/// Emitted with the session id once an event was added to the hook event log
+pub const HOOK_EVENT_RECORDED: &str = "hook-event-recorded";
+/// Emitted with all active sessions whenever one of them changes
+pub const ACTIVE_SESSIONS_CHANGED: &str = "active-sessions-changed";
+
+/// Emitted with a [`PendingApproval`] when a tool call waits for the user
+pub const APPROVAL_REQUESTED: &str = "approval-requested";
+/// Emitted with the approval id once it was answered, timed out or abandoned by Claude Code
+pub const APPROVAL_RESOLVED: &str = "approval-resolved";
+/// Emitted once a tool rule decision, or dry-run match, was added to the hook event log
+pub const TOOL_RULE_DECIDED: &str = "tool-rule-decided";
+
+// What each running Claude Code session is doing, from the hook events seen so far
+static LIVE_SESSIONS: Mutex<LiveSessions> = Mutex::new(LiveSessions::new());
+
+// Tool rules with their patterns compiled, reloaded when tool_rules.json changes
+static TOOL_RULES: Mutex<ToolRuleCache> = Mutex::new(ToolRuleCache::new());
+
+// Tool calls held until the user answers, with the channel their answer goes through
+static APPROVALS: Mutex<Vec<(PendingApproval, oneshot::Sender<PermissionDecision>)>> =
+    Mutex::new(Vec::new());
+
+// What every request handler needs, read once when the server starts
+struct HookServer {
+    app_handle: tauri::AppHandle,
+    paths: Paths,
+    /// Sent by the installed hook, see [`hooks::hook_token`]
+    token: String,
+}
+
+// A tool call Claude Code waits to hear back about
+#[derive(serde::Serialize, Debug, Clone)]
+pub struct PendingApproval {
+    pub id: String,
+    pub session_id: String,
+    pub cwd: String,
+    pub tool_name: String,
+    pub tool_input: Value,
+    /// One line describing the call, e.g. `Bash: rm -rf build`
+    pub summary: String,
+    pub requested_at: u64,
+    pub expires_at: u64,
+    /// Answer given when the user doesn't answer in time
+    pub timeout_decision: PermissionDecision,
+}
+
+// Hook event data structure, common fields plus the payload of the event type
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct HookEvent {
     pub session_id: String,
+    #[serde(default)]
     pub transcript_path: String,
+    #[serde(default)]
     pub cwd: String,
-    pub hook_event_name: String,
     #[serde(flatten)]
-    pub extra: serde_json::Map<String, Value>,
+    pub payload: HookPayload,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(tag = "hook_event_name")]
+pub enum HookPayload {
+    PreToolUse(ToolUse),
+    PostToolUse(ToolResult),
+    Notification(Notification),
+    UserPromptSubmit(UserPrompt),
+    Stop(Stop),
+    SubagentStop(Stop),
+    PreCompact(Compact),
+    SessionStart(SessionStart),
+    SessionEnd(SessionEnd),
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ToolUse {
+    pub tool_name: String,
+    #[serde(default)]
+    pub tool_input: Value,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ToolResult {
+    pub tool_name: String,
+    #[serde(default)]
+    pub tool_input: Value,
+    #[serde(default)]
+    pub tool_response: Value,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Notification {
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UserPrompt {
+    #[serde(default)]
+    pub prompt: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Stop {
+    /// Whether Claude Code is already continuing because of a stop hook
+    #[serde(default)]
+    pub stop_hook_active: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Compact {
+    /// "manual" for /compact, "auto" when the context window is full
+    #[serde(default)]
+    pub trigger: String,
+    #[serde(default)]
+    pub custom_instructions: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct SessionStart {
+    /// "startup", "resume", "clear" or "compact"
+    #[serde(default)]
+    pub source: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct SessionEnd {
+    /// "clear", "logout", "prompt_input_exit" or "other"
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl HookPayload {
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            HookPayload::PreToolUse(_) => "PreToolUse",
+            HookPayload::PostToolUse(_) => "PostToolUse",
+            HookPayload::Notification(_) => "Notification",
+            HookPayload::UserPromptSubmit(_) => "UserPromptSubmit",
+            HookPayload::Stop(_) => "Stop",
+            HookPayload::SubagentStop(_) => "SubagentStop",
+            HookPayload::PreCompact(_) => "PreCompact",
+            HookPayload::SessionStart(_) => "SessionStart",
+            HookPayload::SessionEnd(_) => "SessionEnd",
+        }
+    }
+}
+
+impl HookEvent {
+    /// What a notification about this event says
+    pub fn default_message(&self) -> String {
+        match &self.payload {
+            HookPayload::PreToolUse(tool) => format!("🔨 Using {} tool", tool.tool_name),
+            HookPayload::PostToolUse(tool) => format!("🧰 {} tool finished", tool.tool_name),
+            HookPayload::Notification(notification) if !notification.message.is_empty() => {
+                format!("✅ {}", notification.message)
+            }
+            HookPayload::Notification(_) => "Received notification".to_string(),
+            HookPayload::UserPromptSubmit(prompt) => {
+                format!("💬 {}", shorten(&prompt.prompt, 100))
+            }
+            HookPayload::Stop(_) => "Task completed successfully".to_string(),
+            HookPayload::SubagentStop(_) => "Subagent completed its task".to_string(),
+            HookPayload::PreCompact(compact) if compact.trigger == "auto" => {
+                "🗜️ Compacting the full conversation".to_string()
+            }
+            HookPayload::PreCompact(_) => "🗜️ Compacting the conversation".to_string(),
+            HookPayload::SessionStart(start) if start.source == "resume" => {
+                format!("▶️ Session resumed in {}", self.project_name())
+            }
+            HookPayload::SessionStart(_) => format!("▶️ Session started in {}", self.project_name()),
+            HookPayload::SessionEnd(_) => format!("⏹️ Session ended in {}", self.project_name()),
+        }
+    }
+
+    fn project_name(&self) -> String {
+        std::path::Path::new(&self.cwd)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.cwd.clone())
+    }
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
 }
 
 // Hook server functions
 
+/// Claude Code sessions that sent hook events and haven't ended, most recently active first
+pub fn active_sessions() -> Vec<LiveSession> {
+    let mut sessions = LIVE_SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    sessions.active(now_ms())
+}
+
+/// Tool calls waiting for the user, oldest first
+pub fn pending_approvals() -> Vec<PendingApproval> {
+    let approvals = APPROVALS.lock().unwrap_or_else(|e| e.into_inner());
+    approvals.iter().map(|(approval, _)| approval.clone()).collect()
+}
+
+/// Answer a held tool call
+pub fn resolve_approval(id: &str, decision: PermissionDecision) -> Result<(), String> {
+    let answer = {
+        let mut approvals = APPROVALS.lock().unwrap_or_else(|e| e.into_inner());
+        let index = approvals
+            .iter()
+            .position(|(approval, _)| approval.id == id)
+            .ok_or_else(|| format!("Approval '{}' was already answered or has expired", id))?;
+        approvals.remove(index).1
+    };
+    answer
+        .send(decision)
+        .map_err(|_| format!("Approval '{}' is no longer waited for", id))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
 pub async fn start_hook_server(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let app = create_hook_app(app_handle);
+    let paths = Paths::detect()?;
+    // Only the installed hook knows it, so other local processes can't post events or answer tool calls
+    let token = hooks::hook_token(&paths)?;
+    let app = create_hook_app(HookServer { app_handle, paths, token });
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 59948));
     println!("🚀 Starting hook server on {}", addr);
@@ -43,66 +263,239 @@ pub async fn start_hook_server(app_handle: tauri::AppHandle) -> Result<(), Strin
     Ok(())
 }
 
-fn create_hook_app(app_handle: tauri::AppHandle) -> Router {
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-
-    let shared_handle = Arc::new(app_handle);
+fn create_hook_app(server: HookServer) -> Router {
+    let server = Arc::new(server);
 
     Router::new()
-        .route("/claude_code/hooks", post(move |payload| handle_hook_event(payload, shared_handle.clone())))
-        .layer(cors)
+        .route("/claude_code/hooks", post(move |headers, payload| handle_hook_event(headers, payload, server.clone())))
 }
 
-async fn handle_hook_event(Json(payload): Json<HookEvent>, app_handle: Arc<tauri::AppHandle>) -> impl IntoResponse {
-    println!("📥 Received hook event: {}", payload.hook_event_name);
-    println!("📄 Hook data: {}", serde_json::to_string_pretty(&payload).unwrap_or_else(|_| "Failed to serialize".to_string()));
+async fn handle_hook_event(headers: HeaderMap, Json(event): Json<Value>, server: Arc<HookServer>) -> Response {
+    if headers.get(hooks::HOOK_TOKEN_HEADER).and_then(|value| value.to_str().ok()) != Some(server.token.as_str()) {
+        eprintln!("Warning: Rejected a hook event without the hook token");
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let event_name = event.get("hook_event_name").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    println!("📥 Received hook event: {}", event_name);
+    println!("📄 Hook data: {}", serde_json::to_string_pretty(&event).unwrap_or_else(|_| "Failed to serialize".to_string()));
+
+    // Keep every event as sent, so what a session did can be looked up later
+    update_live_sessions(&event, &server.app_handle);
+    let recorder = server.clone();
+    let recorded = event.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        match hook_log::record_hook_event(&recorder.paths, &recorded) {
+            Ok(_) => {
+                let session_id = recorded.get("session_id").and_then(|v| v.as_str()).unwrap_or_default();
+                let _ = recorder.app_handle.emit(HOOK_EVENT_RECORDED, session_id);
+            }
+            Err(e) => eprintln!("Warning: Failed to record hook event: {}", e),
+        }
+    });
+
+    let payload = match serde_json::from_value::<HookEvent>(event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            eprintln!("Warning: Failed to parse '{}' hook event: {}", event_name, e);
+            return StatusCode::OK.into_response();
+        }
+    };
+
+    // Decide tool calls by rule, or hold them for approval, and answer with the decision
+    if let HookPayload::PreToolUse(tool) = &payload.payload {
+        // Reading the settings and rules touches the disk, keep it off the async runtime
+        let (decider, session_id, cwd, decided_tool) = (server.clone(), payload.session_id.clone(), payload.cwd.clone(), tool.clone());
+        let decided = tauri::async_runtime::spawn_blocking(move || {
+            let settings = approval::get_approval_settings(&decider.paths).unwrap_or_else(|e| {
+                eprintln!("Warning: Failed to read approval settings: {}", e);
+                ApprovalSettings::default()
+            });
+            (settings, match_tool_rule(&decider, &session_id, &cwd, &decided_tool))
+        })
+        .await;
+        let (settings, matched) = decided.unwrap_or_else(|e| {
+            eprintln!("Warning: Failed to decide tool use: {}", e);
+            (ApprovalSettings::default(), None)
+        });
+        match matched {
+            Some(matched) => {
+                let response = match matched.action.decision() {
+                    Some(decision) => approval::hook_response(decision, &matched.reason),
+                    None => request_approval(&payload, tool, &settings, &server.app_handle).await,
+                };
+                return Json(response).into_response();
+            }
+            None if settings.requires_approval(&tool.tool_name) => {
+                let response = request_approval(&payload, tool, &settings, &server.app_handle).await;
+                return Json(response).into_response();
+            }
+            None => {}
+        }
+    }
 
     // Check notification settings before sending notification
-    if let Ok(Some(settings)) = crate::commands::get_notification_settings().await {
-        if settings.enable && settings.enabled_hooks.contains(&payload.hook_event_name) {
+    let notifier = server.clone();
+    let notification_settings = tauri::async_runtime::spawn_blocking(move || stores::get_notification_settings(&notifier.paths)).await;
+    if let Ok(Ok(Some(settings))) = notification_settings {
+        if settings.enable && settings.enabled_hooks.iter().any(|hook| hook == payload.payload.event_name()) {
             // Send notification based on the hook event
-            send_hook_notification(&payload, &app_handle).await;
+            send_hook_notification(&payload, &server.app_handle).await;
         } else {
-            println!("🔕 Hook '{}' is not enabled in notification settings, skipping notification", payload.hook_event_name);
+            println!("🔕 Hook '{}' is not enabled in notification settings, skipping notification", event_name);
         }
     } else {
         println!("⚠️ Could not get notification settings, defaulting to sending notification");
         // Send notification based on the hook event (fallback behavior)
-        send_hook_notification(&payload, &app_handle).await;
+        send_hook_notification(&payload, &server.app_handle).await;
     }
 
-    (StatusCode::OK, "Hook received")
+    // Claude Code adds the output of some hooks to the conversation, so answer with nothing
+    StatusCode::OK.into_response()
 }
 
-// Send notification based on hook event type
-async fn send_hook_notification(event: &HookEvent, app_handle: &tauri::AppHandle) {
-    let title = "Claude Code";
-    let description = match event.hook_event_name.as_str() {
-        "Stop" => {
-            "Task completed successfully".to_string()
-        }
-        "PreToolUse" => {
-            if let Some(tool_name) = event.extra.get("tool_name").and_then(|v| v.as_str()) {
-                format!("🔨 Using {} tool", tool_name)
-            } else {
-                "A tool is going to be used".to_string()
-            }
-        }
-        "Notification" => {
-            if let Some(message) = event.extra.get("message").and_then(|v| v.as_str()) {
-                format!("✅ {}", message)
-            } else {
-                "Received notification".to_string()
+// The tool rule deciding a call, if rules are on and not in dry-run mode.
+// Matches are recorded in the hook event log either way. Reads tool_rules.json, so call it off the async runtime.
+fn match_tool_rule(server: &Arc<HookServer>, session_id: &str, cwd: &str, tool: &ToolUse) -> Option<RuleMatch> {
+    let rules = TOOL_RULES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .load(&server.paths)
+        .map_err(|e| eprintln!("Warning: Failed to read tool rules: {}", e))
+        .ok()?;
+    if !rules.settings.enabled {
+        return None;
+    }
+
+    let matched = rules.evaluate(&tool.tool_name, &tool.tool_input, cwd)?;
+    let dry_run = rules.settings.dry_run;
+    let summary = approval::describe_tool_use(&tool.tool_name, &tool.tool_input);
+    if dry_run {
+        println!("🧪 Dry run: rule \"{}\" would {:?} {} ({})", matched.rule_name, matched.action, summary, matched.reason);
+    } else {
+        println!("📏 Rule \"{}\" decided {:?} for {} ({})", matched.rule_name, matched.action, summary, matched.reason);
+    }
+
+    let recorder = server.clone();
+    let (session_id, cwd, tool_name, recorded) = (session_id.to_string(), cwd.to_string(), tool.tool_name.clone(), matched.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        match hook_log::record_rule_decision(&recorder.paths, &session_id, &cwd, &tool_name, &summary, &recorded, dry_run) {
+            Ok(_) => {
+                let _ = recorder.app_handle.emit(TOOL_RULE_DECIDED, &recorded.rule_id);
             }
+            Err(e) => eprintln!("Warning: Failed to record rule decision: {}", e),
         }
-        _ => {
-            "Hook event received".to_string()
+    });
+
+    (!dry_run).then_some(matched)
+}
+
+// Removes a held tool call when its request ends, including when Claude Code gives up on it
+struct HeldApproval<'a> {
+    id: String,
+    app_handle: &'a tauri::AppHandle,
+}
+
+impl Drop for HeldApproval<'_> {
+    fn drop(&mut self) {
+        let mut approvals = APPROVALS.lock().unwrap_or_else(|e| e.into_inner());
+        approvals.retain(|(approval, _)| approval.id != self.id);
+        let _ = self.app_handle.emit(APPROVAL_RESOLVED, &self.id);
+    }
+}
+
+// Ask the user about a tool call and wait for the answer, or the default once the timeout passes
+async fn request_approval(event: &HookEvent, tool: &ToolUse, settings: &ApprovalSettings, app_handle: &tauri::AppHandle) -> Value {
+    let now = now_ms();
+    let approval = PendingApproval {
+        id: nanoid::nanoid!(10),
+        session_id: event.session_id.clone(),
+        cwd: event.cwd.clone(),
+        tool_name: tool.tool_name.clone(),
+        tool_input: tool.tool_input.clone(),
+        summary: approval::describe_tool_use(&tool.tool_name, &tool.tool_input),
+        requested_at: now,
+        expires_at: now + settings.timeout_secs * 1000,
+        timeout_decision: settings.timeout_decision,
+    };
+    println!("✋ Waiting for approval: {}", approval.summary);
+
+    let (answer, decision) = oneshot::channel();
+    APPROVALS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push((approval.clone(), answer));
+    let _held = HeldApproval {
+        id: approval.id.clone(),
+        app_handle,
+    };
+
+    if let Err(e) = app_handle.emit(APPROVAL_REQUESTED, &approval) {
+        eprintln!("Warning: Failed to emit approval request: {}", e);
+    }
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(format!("Claude Code · {}", event.project_name()))
+        .body(format!("Allow {}?", approval.summary))
+        .show()
+    {
+        eprintln!("Failed to send Tauri notification: {}", e);
+    }
+
+    let (decision, reason) = match tokio::time::timeout(Duration::from_secs(settings.timeout_secs), decision).await {
+        Ok(Ok(decision)) => {
+            let reason = match decision {
+                PermissionDecision::Allow => "Allowed in TVC Buddy",
+                PermissionDecision::Deny => "Denied in TVC Buddy",
+                PermissionDecision::Ask => "Left to Claude Code by TVC Buddy",
+            };
+            (decision, reason.to_string())
         }
+        _ => (
+            settings.timeout_decision,
+            format!("No answer in TVC Buddy within {} seconds", settings.timeout_secs),
+        ),
+    };
+    println!("✅ Approval for {}: {:?} ({})", approval.summary, decision, reason);
+
+    approval::hook_response(decision, &reason)
+}
+
+// Track the session the event belongs to, and tell the UI and tray about changes
+fn update_live_sessions(event: &Value, app_handle: &tauri::AppHandle) {
+    let (change, sessions) = {
+        let mut live = LIVE_SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        let now = now_ms();
+        let change = live.apply(event, now);
+        (change, live.active(now))
     };
 
+    if let Err(e) = app_handle.emit(ACTIVE_SESSIONS_CHANGED, &sessions) {
+        eprintln!("Warning: Failed to emit active sessions: {}", e);
+    }
+
+    // The tray only lists sessions and their status, so tool changes don't need a rebuild
+    if change != SessionChange::Details {
+        let app = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::tray::rebuild_tray_menu(app).await {
+                eprintln!("Warning: Failed to rebuild tray menu: {}", e);
+            }
+        });
+    }
+}
+
+// Send notification based on hook event type
+async fn send_hook_notification(event: &HookEvent, app_handle: &tauri::AppHandle) {
+    let title = "Claude Code";
+    let description = event.default_message();
+
     // Send notification using Tauri notification plugin
     match app_handle.notification()
         .builder()
@@ -1,6 +1,11 @@
+mod auto_switch;
 mod commands;
+mod failover;
+mod health;
 mod hook_server;
+mod relay;
 mod tray;
+mod watcher;
 
 use commands::*;
 use hook_server::start_hook_server;
@@ -162,6 +167,20 @@ pub fn run() {
                     Err(e) => eprintln!("Failed to start hook server: {}", e),
                 }
             });
+
+            // Watch managed files for edits made outside the app
+            if let Err(e) = watcher::start_file_watcher(app.handle().clone()) {
+                eprintln!("Failed to start file watcher: {}", e);
+            }
+
+            // Evaluate the automatic switching rules in the background
+            auto_switch::start_auto_switch(app.handle().clone());
+
+            // Fail over to another profile when the active endpoint goes down
+            failover::start_failover(app.handle().clone());
+
+            // Forward Claude Code's API requests to the active profile when the relay is on
+            relay::start_relay();
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -175,16 +194,33 @@ pub fn run() {
             get_store,
             create_config,
             update_config,
+            get_resolved_store_settings,
+            get_vault_status,
+            unlock_vault,
+            lock_vault,
+            set_vault_passphrase,
+            export_profiles,
+            import_profiles,
+            validate_store_settings,
+            get_merge_strategies,
+            update_merge_strategies,
             delete_config,
             set_using_config,
             reset_to_original_config,
             get_current_store,
+            get_active_drift,
+            diff_active_profile,
+            reapply_active_profile,
+            absorb_active_profile,
             open_config_path,
             get_global_mcp_servers,
             update_global_mcp_server,
             delete_global_mcp_server,
             check_mcp_server_exists,
             read_claude_projects,
+            get_project_bindings,
+            bind_project_config,
+            unbind_project_config,
             read_claude_config_file,
             write_claude_config_file,
             check_for_updates,
@@ -200,6 +236,20 @@ pub fn run() {
             add_claude_code_hook,
             update_claude_code_hook,
             remove_claude_code_hook,
+            list_hook_sessions,
+            get_hook_session_timeline,
+            get_hook_events,
+            get_hook_tool_names,
+            clear_hook_events,
+            get_active_sessions,
+            get_approval_settings,
+            update_approval_settings,
+            get_pending_approvals,
+            resolve_approval,
+            get_tool_rules,
+            update_tool_rules,
+            evaluate_tool_rules,
+            get_tool_rule_decisions,
             read_claude_commands,
             write_claude_command,
             delete_claude_command,
@@ -214,7 +264,33 @@ pub fn run() {
             get_current_codex_store,
             get_codex_global_settings,
             update_codex_global_settings,
+            list_history_files,
+            list_history_versions,
+            read_history_version,
+            diff_history_version,
+            restore_history_version,
+            get_history_settings,
+            update_history_settings,
+            get_auto_switch_settings,
+            update_auto_switch_settings,
+            get_auto_switch_log,
+            get_failover_settings,
+            update_failover_settings,
+            get_relay_settings,
+            update_relay_settings,
+            set_relay_wire_api,
+            get_model_aliases,
+            update_model_aliases,
+            get_relay_policy,
+            update_relay_policy,
+            get_relay_metrics,
+            get_relay_log,
+            get_relay_log_entry,
+            get_relay_usage,
+            clear_relay_log_bodies,
+            clear_relay_log,
             check_codex_connection,
+            check_profile_health,
             get_system_network_info,
             set_system_network_node,
             check_site_latency,
@@ -0,0 +1,735 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, State},
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::any,
+    Router,
+};
+use futures_util::Stream;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
+use tvcbuddy_core::model_aliases::{self, ResponseModelRewriter};
+use tvcbuddy_core::relay::{self, RelaySettings, Upstream};
+use tvcbuddy_core::relay_log::{self, RelayLogEntry, ResponseRecorder};
+use tvcbuddy_core::relay_policy::{self, CircuitState, PolicyGate, RelayMetrics};
+use tvcbuddy_core::translate::{self, ResponseTranslator, WireApi};
+use tvcbuddy_core::Paths;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Request headers that describe the connection to the relay, or carry the relay token
+const SKIPPED_REQUEST_HEADERS: &[&str] = &[
+    "host",
+    "authorization",
+    "x-api-key",
+    "content-length",
+    "connection",
+    "accept-encoding",
+];
+const SKIPPED_RESPONSE_HEADERS: &[&str] = &["content-length", "transfer-encoding", "connection"];
+
+struct RunningRelay {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+// The listener currently serving, replaced whenever the settings change
+static RELAY: Mutex<Option<RunningRelay>> = Mutex::new(None);
+
+// Traffic of each profile relayed since the app started, by store id
+static TRAFFIC: Mutex<BTreeMap<String, Traffic>> = Mutex::new(BTreeMap::new());
+
+/// Start the relay on launch when it is enabled.
+pub fn start_relay() {
+    tauri::async_runtime::spawn(async move {
+        let result = match Paths::detect().and_then(|paths| relay::get_relay_settings(&paths)) {
+            Ok(settings) => apply_relay_settings(&settings).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to start relay: {}", e);
+        }
+    });
+}
+
+/// Start, restart or stop the relay listener to match `settings`.
+pub async fn apply_relay_settings(settings: &RelaySettings) -> Result<(), String> {
+    let previous = RELAY.lock().map_err(|e| e.to_string())?.take();
+    if let Some(previous) = previous {
+        if settings.enabled && previous.port == settings.port {
+            *RELAY.lock().map_err(|e| e.to_string())? = Some(previous);
+            return Ok(());
+        }
+        let _ = previous.shutdown.send(());
+        println!("🛑 Relay on port {} stopped", previous.port);
+    }
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], settings.port));
+    let listener = bind(addr).await?;
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let app = Router::new()
+        .route("/v1/*rest", any(forward_active))
+        .route("/profiles/:store_id/v1/*rest", any(forward_profile))
+        .with_state(client);
+
+    let (shutdown, stopped) = oneshot::channel::<()>();
+    *RELAY.lock().map_err(|e| e.to_string())? = Some(RunningRelay {
+        port: settings.port,
+        shutdown,
+    });
+
+    println!("✅ Relay listening on {}", settings.url());
+    tauri::async_runtime::spawn(async move {
+        let result = axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = stopped.await;
+            })
+            .await;
+        if let Err(e) = result {
+            eprintln!("Relay server failed: {}", e);
+        }
+    });
+    Ok(())
+}
+
+// A listener that was just shut down may hold the port for a moment
+async fn bind(addr: SocketAddr) -> Result<tokio::net::TcpListener, String> {
+    let mut attempts = 0;
+    loop {
+        match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => return Ok(listener),
+            Err(_) if attempts < 10 => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Err(e) => return Err(format!("Failed to bind to address {}: {}", addr, e)),
+        }
+    }
+}
+
+async fn forward_active(
+    State(client): State<reqwest::Client>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/");
+    forward(&client, None, path, method, &headers, body).await
+}
+
+async fn forward_profile(
+    State(client): State<reqwest::Client>,
+    Path((store_id, rest)): Path<(String, String)>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = match uri.query() {
+        Some(query) => format!("/v1/{}?{}", rest, query),
+        None => format!("/v1/{}", rest),
+    };
+    forward(&client, Some(&store_id), &path, method, &headers, body).await
+}
+
+/// Send the request on to the profile's upstream and stream the answer back
+async fn forward(
+    client: &reqwest::Client,
+    store_id: Option<&str>,
+    path: &str,
+    method: Method,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Response {
+    let started = Instant::now();
+    let paths = match Paths::detect() {
+        Ok(paths) => paths,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, "api_error", &e),
+    };
+    let settings = match relay::get_relay_settings(&paths) {
+        Ok(settings) => settings,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, "api_error", &e),
+    };
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    if !settings.accepts(header("authorization"), header("x-api-key")) {
+        return error_response(
+            StatusCode::UNAUTHORIZED,
+            "authentication_error",
+            "Invalid relay token",
+        );
+    }
+
+    let mut log = RelayLogEntry {
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        method: method.to_string(),
+        path: path.to_string(),
+        model: relay_log::request_model(&body),
+        store_id: store_id.map(str::to_string),
+        ..RelayLogEntry::default()
+    };
+    let upstream = match relay::upstream(&paths, store_id) {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            log.error = Some(e.clone());
+            save_log(paths, log, started);
+            return error_response(StatusCode::SERVICE_UNAVAILABLE, "api_error", &e);
+        }
+    };
+    log.store_id = Some(upstream.store_id.clone());
+    log.store_title = Some(upstream.store_title.clone());
+    // Claude Code's model names are replaced with the ones the endpoint knows
+    let (body, requested_model) =
+        match model_aliases::rewrite_request(&upstream.model_aliases, &body) {
+            Some((rewritten, requested)) => (Bytes::from(rewritten), Some(requested)),
+            None => (body, None),
+        };
+    if requested_model.is_some() {
+        log.model = relay_log::request_model(&body);
+    }
+    // OpenAI endpoints only get Messages requests, translated to their API
+    let translating = upstream.wire_api != WireApi::Anthropic;
+    if translating && (method != Method::POST || path.split('?').next() != Some("/v1/messages")) {
+        let message = format!(
+            "{} is not available for a profile using the {:?} API",
+            path, upstream.wire_api
+        );
+        log.error = Some(message.clone());
+        save_log(paths, log, started);
+        return error_response(StatusCode::NOT_FOUND, "not_found_error", &message);
+    }
+    // Credentials never end up in the logged bodies
+    let mut secrets = vec![settings.token.clone()];
+    if let Some((_, value)) = &upstream.auth_header {
+        secrets.push(value.trim_start_matches("Bearer ").to_string());
+    }
+    if settings.log_bodies {
+        log.request_body = Some(relay_log::body_text(&body, &secrets));
+    }
+    let body = if translating {
+        match translate::to_openai_request(upstream.wire_api, &body) {
+            Ok(translated) => Bytes::from(translated),
+            Err(e) => {
+                log.error = Some(e.clone());
+                save_log(paths, log, started);
+                return error_response(StatusCode::BAD_REQUEST, "invalid_request_error", &e);
+            }
+        }
+    } else {
+        body
+    };
+    let url = upstream.url(path);
+    println!(
+        "🔁 Relaying {} {} to \"{}\"",
+        method, path, upstream.store_title
+    );
+
+    let method = match reqwest::Method::from_bytes(method.as_str().as_bytes()) {
+        Ok(method) => method,
+        Err(e) => {
+            log.error = Some(e.to_string());
+            save_log(paths, log, started);
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "invalid_request_error",
+                &e.to_string(),
+            );
+        }
+    };
+    let build_request = || {
+        let mut request = client.request(method.clone(), &url).body(body.clone());
+        if translating {
+            request = request.header(reqwest::header::CONTENT_TYPE, "application/json");
+        } else {
+            for (name, value) in headers {
+                if !SKIPPED_REQUEST_HEADERS.contains(&name.as_str()) {
+                    request = request.header(name.as_str(), value.as_bytes());
+                }
+            }
+        }
+        if let Some((name, value)) = &upstream.auth_header {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request
+    };
+
+    // The profile's policy decides when the request goes out, and whether it is retried
+    let traffic = match Traffic::of(&paths, &upstream) {
+        Ok(traffic) => traffic,
+        Err(e) => {
+            log.error = Some(e.clone());
+            save_log(paths, log, started);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "api_error", &e);
+        }
+    };
+    let mut in_flight = match traffic.enter().await {
+        Ok(in_flight) => in_flight,
+        Err(refusal) => {
+            let (status, error_type, message, wait) = match refusal {
+                Refusal::CircuitOpen(wait) => (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "overloaded_error",
+                    format!(
+                        "\"{}\" keeps failing, the relay sends it no requests for now",
+                        upstream.store_title
+                    ),
+                    wait,
+                ),
+                Refusal::RateLimited(wait) => (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "rate_limit_error",
+                    format!("Rate limit of \"{}\" reached", upstream.store_title),
+                    wait,
+                ),
+            };
+            log.error = Some(message.clone());
+            save_log(paths, log, started);
+            let mut response = error_response(status, error_type, &message);
+            response
+                .headers_mut()
+                .insert("retry-after", (wait.as_secs() + 1).into());
+            return response;
+        }
+    };
+
+    let response = match traffic.send(&mut in_flight, &upstream, build_request).await {
+        Ok(response) => response,
+        Err(e) => {
+            let message = format!("Failed to reach {}: {}", upstream.base_url, e);
+            log.error = Some(message.clone());
+            save_log(paths, log, started);
+            return error_response(StatusCode::BAD_GATEWAY, "api_error", &message);
+        }
+    };
+    log.status = Some(response.status().as_u16());
+    log.latency_ms = started.elapsed().as_millis() as u64;
+
+    let upstream_content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    let translator = translating.then(|| {
+        ResponseTranslator::new(
+            upstream.wire_api,
+            response.status().as_u16(),
+            upstream_content_type,
+        )
+    });
+    // Recorder and rewriter see the response as it goes out to Claude Code
+    let content_type = match &translator {
+        Some(translator) => Some(translator.content_type()),
+        None => upstream_content_type,
+    };
+    let recorder = ResponseRecorder::new(content_type, settings.log_bodies);
+    log.streamed = recorder.streaming();
+    let rewriter = requested_model.map(|model| ResponseModelRewriter::new(model, content_type));
+    let exchange = Exchange {
+        paths,
+        log: Some(log),
+        recorder,
+        secrets,
+        started,
+    };
+
+    let mut builder = Response::builder().status(response.status().as_u16());
+    for (name, value) in response.headers() {
+        if !SKIPPED_RESPONSE_HEADERS.contains(&name.as_str())
+            && (!translating || name != reqwest::header::CONTENT_TYPE)
+        {
+            builder = builder.header(name.as_str(), value.as_bytes());
+        }
+    }
+    if let Some(translator) = &translator {
+        builder = builder.header("content-type", translator.content_type());
+    }
+    // Server-sent events are passed on chunk by chunk as they arrive, and read
+    // for token usage on the way
+    let stream = Relayed {
+        inner: Box::pin(response.bytes_stream()),
+        translator,
+        rewriter,
+        exchange,
+        _in_flight: in_flight,
+        ended: false,
+    };
+    builder
+        .body(Body::from_stream(stream))
+        .unwrap_or_else(|e| error_response(StatusCode::BAD_GATEWAY, "api_error", &e.to_string()))
+}
+
+type ByteStream = Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>;
+
+/// The upstream's response on its way to Claude Code: translated from the
+/// profile's API, read for the log, and with the model Claude Code asked for
+/// put back when an alias applied
+struct Relayed {
+    inner: ByteStream,
+    translator: Option<ResponseTranslator>,
+    rewriter: Option<ResponseModelRewriter>,
+    exchange: Exchange,
+    // Holds the request's slot until the response has ended
+    _in_flight: InFlight,
+    ended: bool,
+}
+
+impl Relayed {
+    fn pass_on(&mut self, chunk: Bytes) -> Bytes {
+        let chunk = match &mut self.translator {
+            Some(translator) => Bytes::from(translator.feed(&chunk)),
+            None => chunk,
+        };
+        self.exchange.recorder.feed(&chunk);
+        match &mut self.rewriter {
+            Some(rewriter) => Bytes::from(rewriter.feed(&chunk)),
+            None => chunk,
+        }
+    }
+
+    // Whatever translator and rewriter still hold goes out once the upstream is done
+    fn flush(&mut self) -> Bytes {
+        let rest = match self.translator.take() {
+            Some(translator) => translator.finish(),
+            None => vec![],
+        };
+        self.exchange.recorder.feed(&rest);
+        match self.rewriter.take() {
+            Some(mut rewriter) => {
+                let mut rest = rewriter.feed(&rest);
+                rest.extend(rewriter.finish());
+                Bytes::from(rest)
+            }
+            None => Bytes::from(rest),
+        }
+    }
+}
+
+impl Stream for Relayed {
+    type Item = reqwest::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.ended {
+            return Poll::Ready(None);
+        }
+        match self.inner.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => Poll::Ready(Some(Ok(self.pass_on(chunk)))),
+            Poll::Ready(Some(Err(e))) => {
+                self.exchange.fail(e.to_string());
+                Poll::Ready(Some(Err(e)))
+            }
+            Poll::Ready(None) => {
+                self.ended = true;
+                let rest = self.flush();
+                Poll::Ready((!rest.is_empty()).then_some(Ok(rest)))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Metrics of every profile relayed since the app started
+pub fn relay_metrics() -> Vec<RelayMetrics> {
+    lock(&TRAFFIC)
+        .values()
+        .map(|traffic| lock(&traffic.gate).metrics())
+        .collect()
+}
+
+/// Limits and counters of one profile's traffic, see [`relay_policy`]
+#[derive(Clone)]
+struct Traffic {
+    gate: Arc<Mutex<PolicyGate>>,
+    // Concurrency limit the slots were made for, and the slots
+    slots: Option<(u32, Arc<Semaphore>)>,
+}
+
+enum Refusal {
+    CircuitOpen(Duration),
+    RateLimited(Duration),
+}
+
+impl Traffic {
+    /// The profile's traffic, following edits to its policy
+    fn of(paths: &Paths, upstream: &Upstream) -> Result<Traffic, String> {
+        let policy = relay_policy::get_store_relay_policy(paths, &upstream.store_id)?;
+        let mut all = lock(&TRAFFIC);
+        let traffic = all
+            .entry(upstream.store_id.clone())
+            .or_insert_with(|| Traffic {
+                gate: Arc::new(Mutex::new(PolicyGate::new(
+                    &upstream.store_id,
+                    policy.clone(),
+                    Instant::now(),
+                ))),
+                slots: None,
+            });
+
+        let mut gate = lock(&traffic.gate);
+        gate.set_title(&upstream.store_title);
+        if *gate.policy() != policy {
+            gate.set_policy(policy.clone());
+        }
+        drop(gate);
+        // Requests holding a slot of a previous limit keep it until they are answered
+        if traffic.slots.as_ref().map(|(limit, _)| *limit) != policy.max_concurrent {
+            traffic.slots = policy
+                .max_concurrent
+                .map(|limit| (limit, Arc::new(Semaphore::new(limit as usize))));
+        }
+        Ok(traffic.clone())
+    }
+
+    /// Wait for a free slot and for the rate limit, unless the request is turned away
+    async fn enter(&self) -> Result<InFlight, Refusal> {
+        lock(&self.gate)
+            .admit(Instant::now())
+            .map_err(Refusal::CircuitOpen)?;
+
+        let queued = Queued::new(&self.gate);
+        let slot = match &self.slots {
+            Some((_, slots)) => slots.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        self.wait_for_rate_limit().await?;
+        drop(queued);
+
+        lock(&self.gate).started();
+        Ok(InFlight {
+            gate: self.gate.clone(),
+            failed: false,
+            _slot: slot,
+        })
+    }
+
+    async fn wait_for_rate_limit(&self) -> Result<(), Refusal> {
+        let wait = lock(&self.gate)
+            .reserve(Instant::now())
+            .map_err(Refusal::RateLimited)?;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    /// Send the request, and send it again while the endpoint fails and the policy allows
+    async fn send(
+        &self,
+        in_flight: &mut InFlight,
+        upstream: &Upstream,
+        build_request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> reqwest::Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let result = build_request().send().await;
+            let (failed, retry_after) = match &result {
+                Ok(response) => (
+                    relay_policy::is_retryable(response.status().as_u16()),
+                    response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok()),
+                ),
+                Err(_) => (true, None),
+            };
+
+            let delay = {
+                let mut gate = lock(&self.gate);
+                let outcome = match &result {
+                    Ok(response) => Ok(response.status().as_u16()),
+                    Err(e) => Err(e.to_string()),
+                };
+                gate.record(outcome, Instant::now());
+                let delay = relay_policy::retry_delay(gate.policy(), attempt, retry_after);
+                // An open circuit ends the retries too
+                delay.filter(|_| failed && gate.circuit() != CircuitState::Open)
+            };
+            // Retries count against the rate limit like any other request
+            let delay = delay.map(|delay| (delay, lock(&self.gate).reserve(Instant::now())));
+            let delay = match delay {
+                Some((delay, Ok(wait))) => delay.max(wait),
+                _ => {
+                    in_flight.failed = failed;
+                    return result;
+                }
+            };
+
+            attempt += 1;
+            lock(&self.gate).retried();
+            println!(
+                "🔁 Retrying request to \"{}\" in {} ms, attempt {}",
+                upstream.store_title,
+                delay.as_millis(),
+                attempt
+            );
+            drop(result);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// A request waiting for its turn, counted until it gets it or the client gives up
+struct Queued {
+    gate: Arc<Mutex<PolicyGate>>,
+}
+
+impl Queued {
+    fn new(gate: &Arc<Mutex<PolicyGate>>) -> Self {
+        lock(gate).queued();
+        Self { gate: gate.clone() }
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        lock(&self.gate).dequeued();
+    }
+}
+
+/// A request sent to the endpoint, counted until its response has ended
+struct InFlight {
+    gate: Arc<Mutex<PolicyGate>>,
+    failed: bool,
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        lock(&self.gate).finished(self.failed);
+    }
+}
+
+// Counters stay usable even if a thread panicked while holding them
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A response on its way back, logged once it has ended or the client went away
+struct Exchange {
+    paths: Paths,
+    log: Option<RelayLogEntry>,
+    recorder: ResponseRecorder,
+    secrets: Vec<String>,
+    started: Instant,
+}
+
+impl Exchange {
+    fn fail(&mut self, error: String) {
+        if let Some(log) = &mut self.log {
+            log.error = Some(error);
+        }
+    }
+}
+
+impl Drop for Exchange {
+    fn drop(&mut self) {
+        let Some(mut log) = self.log.take() else {
+            return;
+        };
+        let (model, usage, body) = std::mem::take(&mut self.recorder).finish();
+        log.model = model.or(log.model);
+        log.usage = usage;
+        log.response_body = body.map(|body| relay_log::body_text(&body, &self.secrets));
+        save_log(self.paths.clone(), log, self.started);
+    }
+}
+
+fn save_log(paths: Paths, mut log: RelayLogEntry, started: Instant) {
+    log.duration_ms = started.elapsed().as_millis() as u64;
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = relay_log::record(&paths, &log) {
+            eprintln!("Warning: Failed to write relay log: {}", e);
+        }
+    });
+}
+
+// Errors in the Anthropic format, so Claude Code shows the message
+fn error_response(status: StatusCode, error_type: &str, message: &str) -> Response {
+    let body = serde_json::json!({
+        "type": "error",
+        "error": { "type": error_type, "message": message },
+    });
+    (status, axum::Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use tvcbuddy_core::relay_policy::RelayPolicy;
+
+    fn traffic(max_concurrent: u32) -> Traffic {
+        let policy = RelayPolicy {
+            max_concurrent: Some(max_concurrent),
+            ..RelayPolicy::default()
+        };
+        Traffic {
+            gate: Arc::new(Mutex::new(PolicyGate::new("work", policy, Instant::now()))),
+            slots: Some((max_concurrent, Arc::new(Semaphore::new(max_concurrent as usize)))),
+        }
+    }
+
+    // Entering never sleeps without a rate limit, so polling is enough to drive it
+    fn poll_enter<F: Future<Output = Result<InFlight, Refusal>>>(
+        entering: Pin<&mut F>,
+    ) -> Option<InFlight> {
+        match entering.poll(&mut Context::from_waker(std::task::Waker::noop())) {
+            Poll::Ready(Ok(in_flight)) => Some(in_flight),
+            Poll::Ready(Err(_)) => panic!("request was turned away"),
+            Poll::Pending => None,
+        }
+    }
+
+    fn counts(traffic: &Traffic) -> (u32, u32) {
+        let metrics = lock(&traffic.gate).metrics();
+        (metrics.in_flight, metrics.queued)
+    }
+
+    #[test]
+    fn requests_wait_for_a_free_slot() {
+        let traffic = traffic(2);
+        let first = poll_enter(std::pin::pin!(traffic.enter())).unwrap();
+        let second = poll_enter(std::pin::pin!(traffic.enter())).unwrap();
+        assert_eq!(counts(&traffic), (2, 0));
+
+        let mut third = std::pin::pin!(traffic.enter());
+        assert!(poll_enter(third.as_mut()).is_none());
+        assert!(poll_enter(third.as_mut()).is_none());
+        assert_eq!(counts(&traffic), (2, 1));
+
+        // An answered request hands its slot on
+        drop(first);
+        let third = poll_enter(third.as_mut()).unwrap();
+        assert_eq!(counts(&traffic), (2, 0));
+
+        drop((second, third));
+        assert_eq!(counts(&traffic), (0, 0));
+        assert_eq!(lock(&traffic.gate).metrics().requests, 3);
+    }
+
+    #[test]
+    fn a_request_that_gave_up_waiting_leaves_the_queue() {
+        let traffic = traffic(1);
+        let _first = poll_enter(std::pin::pin!(traffic.enter())).unwrap();
+
+        let mut second = Box::pin(traffic.enter());
+        assert!(poll_enter(second.as_mut()).is_none());
+        assert_eq!(counts(&traffic), (1, 1));
+        drop(second);
+        assert_eq!(counts(&traffic), (1, 0));
+    }
+}
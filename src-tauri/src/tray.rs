@@ -1,16 +1,24 @@
+use std::sync::Mutex;
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder},
+    menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Manager, Runtime,
 };
 use tauri_plugin_notification::NotificationExt;
+use tvcbuddy_core::live_sessions::SessionStatus;
 
-use crate::commands::{get_store, get_stores, set_using_config};
+use crate::commands::{
+    bind_project_config, get_active_drift, get_project_bindings, get_store, get_stores,
+    set_using_config, unbind_project_config,
+};
 
 // Store the tray icon ID globally
 const TRAY_ID: &str = "main-tray";
 
+// Project paths of the current tray menu, whose item IDs refer to them by index
+static PROJECT_PATHS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
 pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), Box<dyn std::error::Error>> {
     println!("🔧 Creating system tray icon...");
 
@@ -119,10 +127,21 @@ pub async fn build_tray_menu<R: Runtime>(
                 let configs_label = tauri::menu::MenuItem::with_id(app, "configs_label", "Configs", false, None::<&str>)?;
                 builder = builder.item(&configs_label);
 
+                // Flag the active config when settings.json was edited outside the app
+                let drift = get_active_drift().await.unwrap_or_else(|e| {
+                    eprintln!("Failed to check active config for tray menu: {}", e);
+                    None
+                });
+
                 // Add config items
-                for store in stores {
+                for store in &stores {
                     let prefix = if store.using { "✓ " } else { "  " };
-                    let label = format!("{}{}", prefix, store.title);
+                    let suffix = if drift.as_ref().is_some_and(|drift| drift.store_id == store.id) {
+                        "  ⚠ edited"
+                    } else {
+                        ""
+                    };
+                    let label = format!("{}{}{}", prefix, store.title, suffix);
 
                     println!(
                         "  {} Config: {}",
@@ -136,6 +155,85 @@ pub async fn build_tray_menu<R: Runtime>(
                     builder = builder.item(&item);
                 }
 
+                // Add a submenu per project bound to a config
+                let bindings = get_project_bindings().await.unwrap_or_else(|e| {
+                    eprintln!("Failed to get project bindings for tray menu: {}", e);
+                    vec![]
+                });
+                *PROJECT_PATHS.lock().unwrap_or_else(|e| e.into_inner()) = bindings
+                    .iter()
+                    .map(|binding| binding.project_path.clone())
+                    .collect();
+                if !bindings.is_empty() {
+                    let separator = tauri::menu::PredefinedMenuItem::separator(app)?;
+                    builder = builder.item(&separator);
+
+                    let projects_label = tauri::menu::MenuItem::with_id(app, "projects_label", "Projects", false, None::<&str>)?;
+                    builder = builder.item(&projects_label);
+
+                    for (index, binding) in bindings.into_iter().enumerate() {
+                        let project_name = project_display_name(&binding.project_path);
+                        let store_title = stores
+                            .iter()
+                            .find(|store| store.id == binding.store_id)
+                            .map(|store| store.title.clone())
+                            .unwrap_or_else(|| "Unknown config".to_string());
+
+                        println!("  Project: {} → {}", project_name, store_title);
+
+                        let mut submenu = SubmenuBuilder::new(
+                            app,
+                            format!("{} — {}", project_name, store_title),
+                        );
+                        for store in &stores {
+                            let prefix = if store.id == binding.store_id { "✓ " } else { "  " };
+                            let item = MenuItemBuilder::with_id(
+                                format!("project_bind:{}:{}", index, store.id),
+                                format!("{}{}", prefix, store.title),
+                            )
+                            .build(app)?;
+                            submenu = submenu.item(&item);
+                        }
+                        let unbind_item = MenuItemBuilder::with_id(
+                            format!("project_unbind:{}", index),
+                            "Unbind",
+                        )
+                        .build(app)?;
+                        submenu = submenu.separator().item(&unbind_item);
+
+                        builder = builder.item(&submenu.build()?);
+                    }
+                }
+
+                // List the Claude Code sessions that are still going
+                let sessions = crate::hook_server::active_sessions();
+                if !sessions.is_empty() {
+                    let separator = tauri::menu::PredefinedMenuItem::separator(app)?;
+                    builder = builder.item(&separator);
+
+                    let sessions_label = tauri::menu::MenuItem::with_id(app, "sessions_label", "Sessions", false, None::<&str>)?;
+                    builder = builder.item(&sessions_label);
+
+                    for session in sessions {
+                        let (icon, status) = match session.status {
+                            SessionStatus::Running => ("●", "Running"),
+                            SessionStatus::WaitingForInput => ("◐", "Waiting for input"),
+                            SessionStatus::Stopped => ("○", "Stopped"),
+                        };
+                        let name = if session.cwd.is_empty() {
+                            session.session_id.clone()
+                        } else {
+                            project_display_name(&session.cwd)
+                        };
+                        let item = MenuItemBuilder::with_id(
+                            format!("session_{}", session.session_id),
+                            format!("{} {} — {}", icon, name, status),
+                        )
+                        .build(app)?;
+                        builder = builder.item(&item);
+                    }
+                }
+
                 // Add separator
                 let separator = tauri::menu::PredefinedMenuItem::separator(app)?;
                 builder = builder.item(&separator);
@@ -199,14 +297,23 @@ pub fn handle_tray_menu_event<R: Runtime>(app_handle: &AppHandle<R>, event_id: &
             }
             true
         }
-        "configs_label" => {
-            // Ignore clicks on the configs label
+        "configs_label" | "projects_label" | "sessions_label" => {
+            // Ignore clicks on the section labels
             true
         }
         "quit_app" => {
             app_handle.exit(0);
             true
         }
+        id if id.starts_with("session_") => {
+            // Sessions are followed in the main window
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            true
+        }
         id if id.starts_with("config_") => {
             // Extract store ID from the menu item ID and convert to owned String
             let store_id = id.trim_start_matches("config_").to_string();
@@ -262,6 +369,96 @@ pub fn handle_tray_menu_event<R: Runtime>(app_handle: &AppHandle<R>, event_id: &
             });
             true
         }
+        id if id.starts_with("project_bind:") => {
+            // Menu item IDs are "project_bind:{project index}:{store_id}"
+            let Some((index, store_id)) = id.trim_start_matches("project_bind:").split_once(':')
+            else {
+                return false;
+            };
+            let Some(project_path) = project_path(index) else {
+                return false;
+            };
+            let store_id = store_id.to_string();
+            let app_clone = app_handle.clone();
+
+            tauri::async_runtime::spawn(async move {
+                println!("🔄 Binding project {} to config: {}", project_path, store_id);
+
+                let result = bind_project_config(project_path.clone(), store_id.clone()).await;
+                let notification_body = match result {
+                    Ok(_) => match get_store(store_id).await {
+                        Ok(store) => format!(
+                            "{} now uses config \"{}\"",
+                            project_display_name(&project_path),
+                            store.title
+                        ),
+                        Err(_) => "Project config has been switched successfully".to_string(),
+                    },
+                    Err(e) => {
+                        eprintln!("❌ Failed to bind project: {}", e);
+                        format!("Error: {}", e)
+                    }
+                };
+
+                finish_project_action(app_clone, &notification_body).await;
+            });
+            true
+        }
+        id if id.starts_with("project_unbind:") => {
+            let Some(project_path) = project_path(id.trim_start_matches("project_unbind:")) else {
+                return false;
+            };
+            let app_clone = app_handle.clone();
+
+            tauri::async_runtime::spawn(async move {
+                println!("🔄 Unbinding project: {}", project_path);
+
+                let notification_body = match unbind_project_config(project_path.clone()).await {
+                    Ok(_) => format!(
+                        "{} no longer has a project config",
+                        project_display_name(&project_path)
+                    ),
+                    Err(e) => {
+                        eprintln!("❌ Failed to unbind project: {}", e);
+                        format!("Error: {}", e)
+                    }
+                };
+
+                finish_project_action(app_clone, &notification_body).await;
+            });
+            true
+        }
         _ => false,
     }
 }
+
+fn project_display_name(project_path: &str) -> String {
+    std::path::Path::new(project_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_path.to_string())
+}
+
+/// Project path a tray menu item ID refers to by its index
+fn project_path(index: &str) -> Option<String> {
+    let index: usize = index.parse().ok()?;
+    PROJECT_PATHS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(index)
+        .cloned()
+}
+
+// Refresh the checkmarks and tell the user what happened to the project binding
+async fn finish_project_action<R: Runtime>(app: AppHandle<R>, notification_body: &str) {
+    if let Err(e) = rebuild_tray_menu(app.clone()).await {
+        eprintln!("❌ Failed to rebuild tray menu: {}", e);
+    }
+
+    let _ = app
+        .notification()
+        .builder()
+        .title("TVC BUDDY")
+        .body(notification_body)
+        .show();
+}
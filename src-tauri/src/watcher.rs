@@ -0,0 +1,209 @@
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Runtime};
+use tvcbuddy_core::drift::{self, ActiveDrift};
+use tvcbuddy_core::{codex, Paths};
+
+use crate::tray;
+
+/// Event sent to the frontend when managed files change on disk
+pub const FILES_CHANGED_EVENT: &str = "config-files-changed";
+
+// Editors and Claude Code save in bursts, wait for them to settle
+const DEBOUNCE: Duration = Duration::from_millis(500);
+// How often files that can't be watched are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(serde::Serialize, Clone)]
+pub struct FilesChanged {
+    /// Names of the files that changed
+    pub files: Vec<String>,
+    /// Set when settings.json no longer matches the active profile
+    pub drift: Option<ActiveDrift>,
+}
+
+/// Watch settings.json, .claude.json, the Codex config.toml and stores.json for
+/// external edits, notifying the frontend and refreshing the tray.
+///
+/// Files are seen through their directory, except for those directly in the
+/// home directory, which is too busy to watch, and those whose directory does
+/// not exist yet. Those are polled, until their directory shows up.
+pub fn start_file_watcher<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let paths = Paths::detect()?;
+    let codex_root = codex::get_codex_global_settings(&paths)
+        .map(|settings| PathBuf::from(settings.root_path))
+        .unwrap_or_else(|_| paths.codex_root.clone());
+    let files = [
+        paths.user_settings(),
+        paths.claude_json.clone(),
+        codex_root.join("config.toml"),
+        paths.stores_file(),
+    ];
+    let home = paths.claude_json.parent().map(Path::to_path_buf);
+
+    // Startup may still be creating ~/.ccconfig
+    std::fs::create_dir_all(&paths.app_config_dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+
+    let (tx, rx) = mpsc::channel::<DebounceEventResult>();
+    let debouncer =
+        new_debouncer(DEBOUNCE, tx).map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    let mut watch = Watch {
+        debouncer,
+        watched: Vec::new(),
+        dirs: BTreeSet::new(),
+        polled: Vec::new(),
+    };
+    for file in files {
+        if file.parent().map(Path::to_path_buf) == home {
+            watch.poll(file, false);
+        } else if !watch.arm(&file)? {
+            watch.poll(file, true);
+        }
+    }
+    println!(
+        "👀 Watching {} config files for changes, polling {}",
+        watch.watched.len(),
+        watch.polled.len()
+    );
+
+    std::thread::spawn(move || {
+        let mut polled_at = Instant::now();
+        loop {
+            let mut changed = BTreeSet::new();
+            match rx.recv_timeout(POLL_INTERVAL.saturating_sub(polled_at.elapsed())) {
+                Ok(Ok(events)) => changed.extend(
+                    events
+                        .iter()
+                        .filter(|event| watch.watched.iter().any(|file| file == &event.path))
+                        .filter_map(|event| file_name(&event.path)),
+                ),
+                Ok(Err(e)) => eprintln!("File watcher error: {}", e),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            if polled_at.elapsed() >= POLL_INTERVAL {
+                polled_at = Instant::now();
+                changed.extend(watch.check_polled());
+            }
+            if changed.is_empty() {
+                continue;
+            }
+            println!("📂 Config files changed: {:?}", changed);
+
+            let drift = drift::check_active_drift(&paths).unwrap_or_else(|e| {
+                eprintln!("Failed to compare settings with the active config: {}", e);
+                None
+            });
+            let payload = FilesChanged {
+                files: changed.into_iter().collect(),
+                drift,
+            };
+            if let Err(e) = app.emit(FILES_CHANGED_EVENT, payload) {
+                eprintln!("Failed to emit {} event: {}", FILES_CHANGED_EVENT, e);
+            }
+
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = tray::rebuild_tray_menu(app).await {
+                    eprintln!("Failed to rebuild tray menu: {}", e);
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// A polled file and its size and modification time when last seen
+struct Polled {
+    file: PathBuf,
+    stamp: Option<(u64, Option<SystemTime>)>,
+    // Polled only until its directory can be watched
+    waiting_for_dir: bool,
+}
+
+/// What is watched or polled, on the thread handling the changes
+struct Watch {
+    // Dropping the debouncer stops the watcher
+    debouncer: Debouncer<RecommendedWatcher>,
+    watched: Vec<PathBuf>,
+    dirs: BTreeSet<PathBuf>,
+    polled: Vec<Polled>,
+}
+
+impl Watch {
+    /// Watch `file` through its directory, `false` when that does not exist
+    fn arm(&mut self, file: &Path) -> Result<bool, String> {
+        let (Some(dir), Some(name)) = (file.parent(), file.file_name()) else {
+            return Ok(false);
+        };
+        // Files are replaced by renaming over them, which only their directory sees
+        let Ok(dir) = dir.canonicalize() else {
+            return Ok(false);
+        };
+        if !self.dirs.contains(&dir) {
+            self.debouncer
+                .watcher()
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+            self.dirs.insert(dir.clone());
+        }
+        self.watched.push(dir.join(name));
+        Ok(true)
+    }
+
+    fn poll(&mut self, file: PathBuf, waiting_for_dir: bool) {
+        let stamp = stamp(&file);
+        self.polled.push(Polled {
+            file,
+            stamp,
+            waiting_for_dir,
+        });
+    }
+
+    /// Names of the polled files that changed, watching those whose directory appeared
+    fn check_polled(&mut self) -> Vec<String> {
+        let mut changed = Vec::new();
+        let mut armed = Vec::new();
+        for (index, polled) in self.polled.iter_mut().enumerate() {
+            let stamp = stamp(&polled.file);
+            if stamp != polled.stamp {
+                polled.stamp = stamp;
+                changed.extend(file_name(&polled.file));
+            }
+            if polled.waiting_for_dir && polled.file.parent().is_some_and(Path::is_dir) {
+                armed.push(index);
+            }
+        }
+        for index in armed.into_iter().rev() {
+            let file = self.polled[index].file.clone();
+            match self.arm(&file) {
+                Ok(true) => {
+                    println!(
+                        "👀 Watching {} now that its directory exists",
+                        file.display()
+                    );
+                    self.polled.remove(index);
+                }
+                Ok(false) => {}
+                Err(e) => eprintln!("Warning: {}", e),
+            }
+        }
+        changed
+    }
+}
+
+fn stamp(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()))
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
@@ -0,0 +1,94 @@
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import {
+	type PermissionDecision,
+	usePendingApprovals,
+	useResolveApproval,
+} from "@/lib/query";
+import { Button } from "./ui/button";
+import {
+	Dialog,
+	DialogContent,
+	DialogDescription,
+	DialogFooter,
+	DialogHeader,
+	DialogTitle,
+} from "./ui/dialog";
+
+// Asks about the oldest tool call Claude Code is waiting on, wherever the app is
+export function ApprovalDialog() {
+	const { t } = useTranslation();
+	const { data: approvals } = usePendingApprovals();
+	const resolveApproval = useResolveApproval();
+	const [now, setNow] = useState(Date.now());
+	const approval = approvals?.[0];
+
+	useEffect(() => {
+		if (!approval) return;
+		const timer = setInterval(() => setNow(Date.now()), 1000);
+		return () => clearInterval(timer);
+	}, [approval]);
+
+	if (!approval) return null;
+
+	const answer = (decision: PermissionDecision) =>
+		resolveApproval.mutate({ id: approval.id, decision });
+	const secondsLeft = Math.max(
+		0,
+		Math.ceil((approval.expires_at - now) / 1000),
+	);
+
+	// Dismissing the dialog leaves the call to Claude Code's own prompt
+	return (
+		<Dialog open onOpenChange={(open) => !open && answer("ask")}>
+			<DialogContent className="max-w-xl">
+				<DialogHeader>
+					<DialogTitle>
+						{t("approvals.title", { tool: approval.tool_name })}
+					</DialogTitle>
+					<DialogDescription className="font-mono truncate">
+						{approval.cwd}
+					</DialogDescription>
+				</DialogHeader>
+
+				<pre className="max-h-[40vh] overflow-auto rounded-lg bg-secondary p-3 text-xs whitespace-pre-wrap break-all">
+					{approval.tool_input
+						? JSON.stringify(approval.tool_input, null, 2)
+						: approval.summary}
+				</pre>
+
+				<p className="text-xs text-muted-foreground">
+					{t("approvals.expires", {
+						decision: t(`approvals.${approval.timeout_decision}`),
+						seconds: secondsLeft,
+					})}
+					{approvals.length > 1 &&
+						` · ${t("approvals.more", { count: approvals.length - 1 })}`}
+				</p>
+
+				<DialogFooter>
+					<Button
+						variant="outline"
+						onClick={() => answer("ask")}
+						disabled={resolveApproval.isPending}
+					>
+						{t("approvals.ask")}
+					</Button>
+					<Button
+						variant="destructive"
+						onClick={() => answer("deny")}
+						disabled={resolveApproval.isPending}
+					>
+						{t("approvals.deny")}
+					</Button>
+					<Button
+						onClick={() => answer("allow")}
+						disabled={resolveApproval.isPending}
+					>
+						{t("approvals.allow")}
+					</Button>
+				</DialogFooter>
+			</DialogContent>
+		</Dialog>
+	);
+}
@@ -6,15 +6,20 @@ import {
 	CpuIcon,
 	FileJsonIcon,
 	FolderIcon,
+	HistoryIcon,
 	LayoutGridIcon,
+	RepeatIcon,
 	SettingsIcon,
+	ShieldCheckIcon,
 	TerminalIcon,
 } from "lucide-react";
 import type React from "react";
 import { useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { NavLink, Outlet, useLocation, useNavigate } from "react-router-dom";
+import { useConfigFileEvents } from "../lib/query";
 import { cn } from "../lib/utils";
+import { ApprovalDialog } from "./ApprovalDialog";
 import { UpdateButton } from "./UpdateButton";
 import { WindowControls } from "./WindowControls";
 import { ScrollArea } from "./ui/scroll-area";
@@ -35,6 +40,9 @@ export function Layout() {
 	const navigate = useNavigate();
 	const isProjectsRoute = location.pathname.startsWith("/projects");
 
+	// Keep every page in sync with edits made outside the app
+	useConfigFileEvents();
+
 	// State to track the active module context
 	const [activeModule, setActiveModule] = useState<ModuleType>(() => {
 		return (localStorage.getItem("activeModule") as ModuleType) || "claude";
@@ -90,11 +98,26 @@ export function Layout() {
 			icon: TerminalIcon,
 			label: t("navigation.commands"),
 		},
+		{
+			to: "/sessions",
+			icon: HistoryIcon,
+			label: t("navigation.sessions"),
+		},
 		{
 			to: "/notification",
 			icon: BellIcon,
 			label: t("navigation.notifications"),
 		},
+		{
+			to: "/tool-rules",
+			icon: ShieldCheckIcon,
+			label: t("navigation.toolRules"),
+		},
+		{
+			to: "/auto-switch",
+			icon: RepeatIcon,
+			label: t("navigation.autoSwitch"),
+		},
 		{
 			to: "/usage",
 			icon: ActivityIcon,
@@ -185,6 +208,7 @@ export function Layout() {
 					</ScrollArea>
 				)}
 			</div>
+			<ApprovalDialog />
 		</div >
 	);
 }
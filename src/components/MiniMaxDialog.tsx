@@ -47,7 +47,7 @@ export function MiniMaxDialog(props: {
 						ANTHROPIC_AUTH_TOKEN: apiKey.trim(),
 						ANTHROPIC_BASE_URL: baseUrl,
 						API_TIMEOUT_MS: "3000000",
-						CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC: 1,
+						CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC: "1",
 						ANTHROPIC_MODEL: "MiniMax-M2",
 						ANTHROPIC_SMALL_FAST_MODEL: "MiniMax-M2",
 						ANTHROPIC_DEFAULT_SONNET_MODEL: "MiniMax-M2",
@@ -0,0 +1,107 @@
+import { useState } from "react";
+import { useTranslation } from "react-i18next";
+import {
+	type DriftEntry,
+	useAbsorbActiveProfile,
+	useProfileDiff,
+	useReapplyActiveProfile,
+} from "@/lib/query";
+import { cn } from "@/lib/utils";
+import { Button } from "./ui/button";
+import {
+	Dialog,
+	DialogContent,
+	DialogDescription,
+	DialogHeader,
+	DialogTitle,
+	DialogTrigger,
+} from "./ui/dialog";
+
+const formatValue = (value: unknown) =>
+	value === undefined ? "" : JSON.stringify(value);
+
+const markers: Record<DriftEntry["kind"], string> = {
+	added: "+",
+	removed: "-",
+	changed: "~",
+};
+
+export function ProfileDiffDialog(props: { trigger: React.ReactNode }) {
+	const { t } = useTranslation();
+	const [isOpen, setIsOpen] = useState(false);
+	const { data: diff } = useProfileDiff(isOpen);
+	const reapplyMutation = useReapplyActiveProfile();
+	const absorbMutation = useAbsorbActiveProfile();
+	const busy = reapplyMutation.isPending || absorbMutation.isPending;
+
+	const handleReapply = async () => {
+		await reapplyMutation.mutateAsync();
+		setIsOpen(false);
+	};
+
+	const handleAbsorb = async () => {
+		await absorbMutation.mutateAsync();
+		setIsOpen(false);
+	};
+
+	return (
+		<Dialog open={isOpen} onOpenChange={setIsOpen}>
+			<DialogTrigger asChild>{props.trigger}</DialogTrigger>
+			<DialogContent className="max-w-2xl">
+				<DialogHeader>
+					<DialogTitle>
+						{t("configSwitcher.diff.title", { title: diff?.store_title ?? "" })}
+					</DialogTitle>
+					<DialogDescription>
+						{t("configSwitcher.diff.description")}
+					</DialogDescription>
+				</DialogHeader>
+
+				<div className="max-h-[50vh] overflow-y-auto rounded-lg bg-secondary p-3 font-mono text-xs space-y-1">
+					{!diff?.entries.length ? (
+						<p className="text-muted-foreground">
+							{t("configSwitcher.diff.inSync")}
+						</p>
+					) : (
+						diff.entries.map((entry, index) => (
+							<div
+								key={`${entry.pointer}-${index}`}
+								className={cn("flex gap-2 break-all", {
+									"text-green-600": entry.kind === "added",
+									"text-red-600": entry.kind === "removed",
+									"text-amber-600": entry.kind === "changed",
+								})}
+							>
+								<span className="shrink-0">{markers[entry.kind]}</span>
+								<span className="shrink-0 font-medium">
+									{entry.item ? `${entry.pointer}[]` : entry.pointer}
+								</span>
+								<span>
+									{entry.kind === "changed"
+										? `${formatValue(entry.expected)} → ${formatValue(entry.actual)}`
+										: formatValue(entry.actual ?? entry.expected)}
+								</span>
+							</div>
+						))
+					)}
+				</div>
+
+				<div className="flex justify-end gap-2">
+					<Button
+						variant="outline"
+						onClick={handleAbsorb}
+						disabled={!diff?.entries.length || busy}
+					>
+						{t("configSwitcher.diff.absorb")}
+					</Button>
+					<Button
+						onClick={handleReapply}
+						disabled={!diff?.entries.length || busy}
+					>
+						{t("configSwitcher.diff.reapply")}
+					</Button>
+				</div>
+			</DialogContent>
+		</Dialog>
+	);
+}
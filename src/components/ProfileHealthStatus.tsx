@@ -0,0 +1,37 @@
+import { useTranslation } from "react-i18next";
+import { isHealthy, type ProfileHealth } from "@/lib/query";
+import { cn } from "@/lib/utils";
+
+export function ProfileHealthStatus(props: { health: ProfileHealth }) {
+	const { t } = useTranslation();
+	const { health } = props;
+	const healthy = isHealthy(health);
+
+	const summary = healthy
+		? t("profileHealth.healthy", {
+				latency: health.latency_ms,
+				model: health.model,
+			})
+		: !health.reachable
+			? t("profileHealth.unreachable")
+			: health.auth_valid === false
+				? t("profileHealth.authInvalid")
+				: health.model_available === false
+					? t("profileHealth.modelUnavailable", { model: health.model })
+					: t("profileHealth.failed", { status: health.status });
+
+	return (
+		<div
+			className="flex items-center gap-1 text-[10px] text-muted-foreground mt-0.5 min-w-0"
+			title={health.error ?? health.url}
+		>
+			<div
+				className={cn(
+					"w-1.5 h-1.5 rounded-full shrink-0",
+					healthy ? "bg-green-500" : "bg-red-500",
+				)}
+			/>
+			<span className="truncate">{summary}</span>
+		</div>
+	);
+}
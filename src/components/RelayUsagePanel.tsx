@@ -0,0 +1,271 @@
+import { ChevronDownIcon, ChevronRightIcon, Trash2Icon } from "lucide-react";
+import { useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Button } from "@/components/ui/button";
+import {
+	type RelayLogEntry,
+	type RelayMetrics,
+	useClearRelayLog,
+	useRelayLog,
+	useRelayLogEntry,
+	useRelayMetrics,
+	useRelayUsage,
+} from "@/lib/query";
+import { cn, formatLargeNumber } from "@/lib/utils";
+
+// Requests and token usage of the profiles used through the local relay
+export function RelayUsagePanel() {
+	const { t } = useTranslation();
+	const { data: usage } = useRelayUsage();
+	const { data: log } = useRelayLog();
+	const { data: metrics } = useRelayMetrics();
+	const clearLog = useClearRelayLog();
+	const [expanded, setExpanded] = useState<number>();
+
+	if (!usage?.length && !log?.length) {
+		return null;
+	}
+
+	return (
+		<div className="space-y-3">
+			<div className="flex items-center justify-between">
+				<h4 className="text-sm font-medium">{t("usage.relay.title")}</h4>
+				<div className="flex items-center gap-1">
+					<Button
+						variant="ghost"
+						size="sm"
+						className="text-muted-foreground h-8"
+						onClick={() => clearLog.mutate({ bodiesOnly: true })}
+						disabled={clearLog.isPending}
+					>
+						{t("usage.relay.clearBodies")}
+					</Button>
+					<Button
+						variant="ghost"
+						size="sm"
+						className="text-muted-foreground h-8"
+						onClick={() => clearLog.mutate({ bodiesOnly: false })}
+						disabled={clearLog.isPending}
+					>
+						<Trash2Icon className="w-4 h-4" />
+						{t("usage.relay.clear")}
+					</Button>
+				</div>
+			</div>
+
+			<div className="bg-card rounded-lg border overflow-x-auto">
+				<table className="w-full text-xs">
+					<thead className="text-muted-foreground">
+						<tr className="border-b">
+							<th className="text-left font-medium px-3 py-2">
+								{t("usage.relay.profile")}
+							</th>
+							<th className="text-left font-medium px-3 py-2">
+								{t("usage.relay.model")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.requests")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.inputTokens")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.outputTokens")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.cacheReadTokens")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.cacheWriteTokens")}
+							</th>
+						</tr>
+					</thead>
+					<tbody className="divide-y">
+						{usage?.map((row) => (
+							<tr key={`${row.store_id}-${row.model}`}>
+								<td className="px-3 py-2">
+									{row.store_title ?? row.store_id ?? "-"}
+								</td>
+								<td className="px-3 py-2">{row.model ?? "-"}</td>
+								<td className="px-3 py-2 text-right">
+									{row.requests}
+									{row.errors > 0 && (
+										<span className="text-red-600 ml-1">
+											{t("usage.relay.errors", { count: row.errors })}
+										</span>
+									)}
+								</td>
+								<td className="px-3 py-2 text-right">
+									{formatLargeNumber(row.input_tokens)}
+								</td>
+								<td className="px-3 py-2 text-right">
+									{formatLargeNumber(row.output_tokens)}
+								</td>
+								<td className="px-3 py-2 text-right">
+									{formatLargeNumber(row.cache_read_input_tokens)}
+								</td>
+								<td className="px-3 py-2 text-right">
+									{formatLargeNumber(row.cache_creation_input_tokens)}
+								</td>
+							</tr>
+						))}
+					</tbody>
+				</table>
+			</div>
+
+			{!!metrics?.length && <RelayTrafficTable metrics={metrics} />}
+
+			<h4 className="text-sm font-medium">{t("usage.relay.recent")}</h4>
+			<div className="bg-card rounded-lg border divide-y">
+				{log?.map((entry) => (
+					<RelayLogRow
+						key={entry.id}
+						entry={entry}
+						expanded={expanded === entry.id}
+						onToggle={() =>
+							setExpanded(expanded === entry.id ? undefined : entry.id)
+						}
+					/>
+				))}
+			</div>
+		</div>
+	);
+}
+
+// Live counters of the relay's per-profile policies
+function RelayTrafficTable({ metrics }: { metrics: RelayMetrics[] }) {
+	const { t } = useTranslation();
+	const circuitLabels = {
+		closed: t("usage.relay.circuitClosed"),
+		open: t("usage.relay.circuitOpen"),
+		half_open: t("usage.relay.circuitHalfOpen"),
+	};
+
+	return (
+		<>
+			<h4 className="text-sm font-medium">{t("usage.relay.traffic")}</h4>
+			<div className="bg-card rounded-lg border overflow-x-auto">
+				<table className="w-full text-xs">
+					<thead className="text-muted-foreground">
+						<tr className="border-b">
+							<th className="text-left font-medium px-3 py-2">
+								{t("usage.relay.profile")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.active")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.waiting")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.requests")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.retries")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.throttled")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.turnedAway")}
+							</th>
+							<th className="text-right font-medium px-3 py-2">
+								{t("usage.relay.failures")}
+							</th>
+							<th className="text-left font-medium px-3 py-2">
+								{t("usage.relay.circuit")}
+							</th>
+						</tr>
+					</thead>
+					<tbody className="divide-y">
+						{metrics.map((row) => (
+							<tr key={row.store_id} title={row.last_error ?? undefined}>
+								<td className="px-3 py-2">{row.store_title ?? row.store_id}</td>
+								<td className="px-3 py-2 text-right">{row.in_flight}</td>
+								<td className="px-3 py-2 text-right">{row.queued}</td>
+								<td className="px-3 py-2 text-right">{row.requests}</td>
+								<td className="px-3 py-2 text-right">{row.retries}</td>
+								<td className="px-3 py-2 text-right">{row.throttled}</td>
+								<td className="px-3 py-2 text-right">
+									{row.rate_limited + row.rejected}
+								</td>
+								<td className="px-3 py-2 text-right">{row.failures}</td>
+								<td
+									className={cn(
+										"px-3 py-2",
+										row.circuit !== "closed" && "text-red-600",
+									)}
+								>
+									{circuitLabels[row.circuit]}
+								</td>
+							</tr>
+						))}
+					</tbody>
+				</table>
+			</div>
+		</>
+	);
+}
+
+function RelayLogRow(props: {
+	entry: RelayLogEntry;
+	expanded: boolean;
+	onToggle: () => void;
+}) {
+	const { t } = useTranslation();
+	const { entry, expanded, onToggle } = props;
+	const { data: detail } = useRelayLogEntry(expanded ? entry.id : undefined);
+	const failed = !entry.status || entry.status >= 400 || !!entry.error;
+
+	return (
+		<div className="px-3 py-2 text-xs">
+			<button
+				type="button"
+				className="flex w-full items-center justify-between gap-2 text-left"
+				onClick={onToggle}
+			>
+				<span className="flex items-center gap-1 min-w-0">
+					{expanded ? (
+						<ChevronDownIcon className="w-3 h-3 shrink-0" />
+					) : (
+						<ChevronRightIcon className="w-3 h-3 shrink-0" />
+					)}
+					<span className={cn("font-medium", failed && "text-red-600")}>
+						{entry.status ?? "-"}
+					</span>
+					<span className="truncate">
+						{entry.store_title ?? entry.store_id ?? "-"} ·{" "}
+						{entry.model ?? entry.path}
+					</span>
+				</span>
+				<span className="text-muted-foreground shrink-0">
+					{t("usage.relay.tokens", {
+						input: formatLargeNumber(entry.input_tokens),
+						output: formatLargeNumber(entry.output_tokens),
+					})}{" "}
+					· {entry.duration_ms} ms ·{" "}
+					{new Date(entry.timestamp).toLocaleString()}
+				</span>
+			</button>
+			{expanded && (
+				<div className="mt-2 space-y-2">
+					{entry.error && <p className="text-red-600">{entry.error}</p>}
+					{detail?.request_body || detail?.response_body ? (
+						<>
+							<pre className="bg-muted rounded p-2 max-h-48 overflow-auto whitespace-pre-wrap break-all">
+								{detail.request_body}
+							</pre>
+							<pre className="bg-muted rounded p-2 max-h-48 overflow-auto whitespace-pre-wrap break-all">
+								{detail.response_body}
+							</pre>
+						</>
+					) : (
+						<p className="text-muted-foreground">
+							{t("usage.relay.noBodies")}
+						</p>
+					)}
+				</div>
+			)}
+		</div>
+	);
+}
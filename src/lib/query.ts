@@ -5,7 +5,9 @@ import {
 	useSuspenseQuery,
 } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { nanoid } from "nanoid";
+import { useEffect } from "react";
 import { toast } from "sonner";
 import i18n from "../i18n";
 
@@ -37,6 +39,7 @@ export interface ConfigStore {
 	createdAt: number;
 	settings: ClaudeSettings;
 	using: boolean;
+	parent?: string;
 }
 
 export interface McpServer {
@@ -128,6 +131,15 @@ export const useStore = (storeId: string) => {
 	});
 };
 
+export const useResolvedStoreSettings = (storeId: string) => {
+	return useQuery({
+		queryKey: ["resolved-store-settings", storeId],
+		queryFn: () =>
+			invoke<ClaudeSettings>("get_resolved_store_settings", { storeId }),
+		enabled: !!storeId,
+	});
+};
+
 export const useCurrentStore = () => {
 	return useSuspenseQuery({
 		queryKey: ["current-store"],
@@ -142,12 +154,19 @@ export const useCreateConfig = () => {
 		mutationFn: async ({
 			title,
 			settings,
+			parent,
 		}: {
 			title: string;
 			settings: unknown;
+			parent?: string;
 		}) => {
 			const id = nanoid(6);
-			return invoke<ConfigStore>("create_config", { id, title, settings });
+			return invoke<ConfigStore>("create_config", {
+				id,
+				title,
+				settings,
+				parent,
+			});
 		},
 		onSuccess: async () => {
 			toast.success(i18n.t("toast.storeCreated"));
@@ -175,6 +194,7 @@ export const useDeleteConfig = () => {
 			toast.success(i18n.t("toast.storeDeleted"));
 			queryClient.invalidateQueries({ queryKey: ["stores"] });
 			queryClient.invalidateQueries({ queryKey: ["current-store"] });
+			queryClient.invalidateQueries({ queryKey: ["project-bindings"] });
 			await rebuildTrayMenu();
 		},
 		onError: (error) => {
@@ -240,11 +260,19 @@ export const useUpdateConfig = () => {
 			storeId,
 			title,
 			settings,
+			parent,
 		}: {
 			storeId: string;
 			title: string;
 			settings: unknown;
-		}) => invoke<ConfigStore>("update_config", { storeId, title, settings }),
+			parent?: string;
+		}) =>
+			invoke<ConfigStore>("update_config", {
+				storeId,
+				title,
+				settings,
+				parent,
+			}),
 		onSuccess: async (data) => {
 			if (data.using) {
 				toast.success(
@@ -256,9 +284,9 @@ export const useUpdateConfig = () => {
 			queryClient.invalidateQueries({ queryKey: ["stores"] });
 			queryClient.invalidateQueries({ queryKey: ["store", data.id] });
 			queryClient.invalidateQueries({ queryKey: ["current-store"] });
-			if (data.using) {
-				queryClient.invalidateQueries({ queryKey: ["config-file", "user"] });
-			}
+			queryClient.invalidateQueries({ queryKey: ["resolved-store-settings"] });
+			// A child of this store may be the active one
+			queryClient.invalidateQueries({ queryKey: ["config-file", "user"] });
 			await rebuildTrayMenu();
 		},
 		onError: (error) => {
@@ -269,6 +297,56 @@ export const useUpdateConfig = () => {
 	});
 };
 
+export interface SettingsIssue {
+	pointer: string;
+	message: string;
+	severity: "error" | "warning";
+}
+
+export interface SettingsValidation {
+	valid: boolean;
+	errors: SettingsIssue[];
+	warnings: SettingsIssue[];
+}
+
+// Check store settings against the Claude Code settings schema before saving
+export const useValidateStoreSettings = () => {
+	return useMutation({
+		mutationFn: (settings: unknown) =>
+			invoke<SettingsValidation>("validate_store_settings", { settings }),
+	});
+};
+
+export type MergeStrategy = "replace" | "deep-merge" | "array-union";
+
+// Strategies keyed by JSON pointer, e.g. { "/env": "deep-merge" }
+export type MergeStrategies = Record<string, MergeStrategy>;
+
+export const useMergeStrategies = () => {
+	return useQuery({
+		queryKey: ["merge-strategies"],
+		queryFn: () => invoke<MergeStrategies>("get_merge_strategies"),
+	});
+};
+
+export const useUpdateMergeStrategies = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (strategies: MergeStrategies) =>
+			invoke<void>("update_merge_strategies", { strategies }),
+		onSuccess: () => {
+			toast.success("Merge strategies updated");
+			queryClient.invalidateQueries({ queryKey: ["merge-strategies"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to update merge strategies: ${errorMessage}`);
+		},
+	});
+};
+
 export interface UpdateInfo {
 	available: boolean;
 	version?: string;
@@ -460,6 +538,63 @@ export const useClaudeProjects = () => {
 	});
 };
 
+export interface ProjectBinding {
+	project_path: string;
+	store_id: string;
+	bound_at: number;
+}
+
+export const useProjectBindings = () => {
+	return useQuery({
+		queryKey: ["project-bindings"],
+		queryFn: () => invoke<ProjectBinding[]>("get_project_bindings"),
+	});
+};
+
+export const useBindProjectConfig = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: ({
+			projectPath,
+			storeId,
+		}: {
+			projectPath: string;
+			storeId: string;
+		}) =>
+			invoke<ProjectBinding>("bind_project_config", { projectPath, storeId }),
+		onSuccess: async () => {
+			toast.success("Project config updated");
+			queryClient.invalidateQueries({ queryKey: ["project-bindings"] });
+			await rebuildTrayMenu();
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to bind project config: ${errorMessage}`);
+		},
+	});
+};
+
+export const useUnbindProjectConfig = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (projectPath: string) =>
+			invoke<void>("unbind_project_config", { projectPath }),
+		onSuccess: async () => {
+			toast.success("Project config removed");
+			queryClient.invalidateQueries({ queryKey: ["project-bindings"] });
+			await rebuildTrayMenu();
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to unbind project config: ${errorMessage}`);
+		},
+	});
+};
+
 export interface ClaudeConfigFile {
 	path: string;
 	content: unknown;
@@ -512,6 +647,8 @@ export const useUpdateNotificationSettings = () => {
 		onSuccess: () => {
 			toast.success("Notification settings updated successfully");
 			queryClient.invalidateQueries({ queryKey: ["notification-settings"] });
+			// Hooks are installed in settings.json for the enabled events only
+			queryClient.invalidateQueries({ queryKey: ["config-file", "user"] });
 		},
 		onError: (error) => {
 			const errorMessage =
@@ -521,6 +658,355 @@ export const useUpdateNotificationSettings = () => {
 	});
 };
 
+// Tool use approval hooks
+
+export type PermissionDecision = "allow" | "deny" | "ask";
+
+export interface ApprovalSettings {
+	enabled: boolean;
+	timeout_secs: number;
+	timeout_decision: PermissionDecision;
+	tools: string[];
+}
+
+export interface PendingApproval {
+	id: string;
+	session_id: string;
+	cwd: string;
+	tool_name: string;
+	tool_input: Record<string, unknown> | null;
+	summary: string;
+	requested_at: number;
+	expires_at: number;
+	timeout_decision: PermissionDecision;
+}
+
+export const useApprovalSettings = () => {
+	return useQuery({
+		queryKey: ["approval-settings"],
+		queryFn: () => invoke<ApprovalSettings>("get_approval_settings"),
+	});
+};
+
+export const useUpdateApprovalSettings = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (settings: ApprovalSettings) =>
+			invoke<ApprovalSettings>("update_approval_settings", { settings }),
+		onSuccess: (settings) => {
+			queryClient.setQueryData(["approval-settings"], settings);
+			// Approvals need the PreToolUse hook in settings.json
+			queryClient.invalidateQueries({ queryKey: ["config-file", "user"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to update approval settings: ${errorMessage}`);
+		},
+	});
+};
+
+// Tool calls held by the hook server until the user answers them
+export const usePendingApprovals = () => {
+	const queryClient = useQueryClient();
+
+	useEffect(() => {
+		const unlistenRequested = listen("approval-requested", () => {
+			queryClient.invalidateQueries({ queryKey: ["pending-approvals"] });
+		});
+		const unlistenResolved = listen("approval-resolved", () => {
+			queryClient.invalidateQueries({ queryKey: ["pending-approvals"] });
+		});
+		return () => {
+			unlistenRequested.then((fn) => fn());
+			unlistenResolved.then((fn) => fn());
+		};
+	}, [queryClient]);
+
+	return useQuery({
+		queryKey: ["pending-approvals"],
+		queryFn: () => invoke<PendingApproval[]>("get_pending_approvals"),
+	});
+};
+
+export const useResolveApproval = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: ({
+			id,
+			decision,
+		}: {
+			id: string;
+			decision: PermissionDecision;
+		}) => invoke<void>("resolve_approval", { id, decision }),
+		onSettled: () => {
+			queryClient.invalidateQueries({ queryKey: ["pending-approvals"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to answer approval: ${errorMessage}`);
+		},
+	});
+};
+
+// Tool rule hooks
+
+export type RuleAction = "allow" | "deny" | "ask" | "approve";
+
+export type ToolCondition =
+	| { type: "always" }
+	| { type: "input_matches"; field: string; pattern: string }
+	| { type: "outside_cwd" };
+
+export interface ToolRule {
+	id: string;
+	name: string;
+	enabled: boolean;
+	tools: string[];
+	condition: ToolCondition;
+	action: RuleAction;
+	reason?: string;
+}
+
+export interface ToolRuleSettings {
+	enabled: boolean;
+	dry_run: boolean;
+	rules: ToolRule[];
+}
+
+export interface RuleMatch {
+	rule_id: string;
+	rule_name: string;
+	action: RuleAction;
+	reason: string;
+}
+
+export const useToolRules = () => {
+	return useQuery({
+		queryKey: ["tool-rules"],
+		queryFn: () => invoke<ToolRuleSettings>("get_tool_rules"),
+	});
+};
+
+export const useUpdateToolRules = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (settings: ToolRuleSettings) =>
+			invoke<ToolRuleSettings>("update_tool_rules", { settings }),
+		onSuccess: (settings) => {
+			queryClient.setQueryData(["tool-rules"], settings);
+			// Rules need the PreToolUse hook in settings.json
+			queryClient.invalidateQueries({ queryKey: ["config-file", "user"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to save tool rules: ${errorMessage}`);
+		},
+	});
+};
+
+export const useEvaluateToolRules = () => {
+	return useMutation({
+		mutationFn: ({
+			toolName,
+			toolInput,
+			cwd,
+		}: {
+			toolName: string;
+			toolInput: unknown;
+			cwd: string;
+		}) =>
+			invoke<RuleMatch | null>("evaluate_tool_rules", {
+				toolName,
+				toolInput,
+				cwd,
+			}),
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to test tool rules: ${errorMessage}`);
+		},
+	});
+};
+
+export interface RuleDecisionRecord {
+	id: number;
+	timestamp: number;
+	session_id: string;
+	cwd: string;
+	tool_name: string;
+	summary: string;
+	rule_id: string;
+	rule_name: string;
+	action: RuleAction;
+	reason: string;
+	dry_run: boolean;
+}
+
+// What the rules decided, or would have in dry-run mode, refreshed as the hook server records it
+export const useToolRuleDecisions = (limit = 50) => {
+	const queryClient = useQueryClient();
+
+	useEffect(() => {
+		const unlisten = listen<string>("tool-rule-decided", () => {
+			queryClient.invalidateQueries({ queryKey: ["tool-rule-decisions"] });
+		});
+		return () => {
+			unlisten.then((fn) => fn());
+		};
+	}, [queryClient]);
+
+	return useQuery({
+		queryKey: ["tool-rule-decisions", limit],
+		queryFn: () =>
+			invoke<RuleDecisionRecord[]>("get_tool_rule_decisions", { limit }),
+	});
+};
+
+// Hook event log hooks
+
+export interface HookEventRecord {
+	id: number;
+	timestamp: number;
+	session_id: string;
+	cwd: string;
+	hook_event_name: string;
+	tool_name: string | null;
+	tool_input: Record<string, unknown> | null;
+	payload: Record<string, unknown>;
+}
+
+export interface HookSession {
+	session_id: string;
+	cwd: string;
+	started_at: number;
+	last_event_at: number;
+	last_event_name: string;
+	events: number;
+	tool_uses: number;
+}
+
+export interface HookEventFilter {
+	session_id?: string;
+	project?: string;
+	tool_name?: string;
+	hook_event_name?: string;
+	since?: number;
+	until?: number;
+	limit?: number;
+}
+
+// Refresh the hook event queries whenever the hook server records an event
+const useHookEventsListener = () => {
+	const queryClient = useQueryClient();
+
+	useEffect(() => {
+		const unlisten = listen<string>("hook-event-recorded", () => {
+			queryClient.invalidateQueries({ queryKey: ["hook-sessions"] });
+			queryClient.invalidateQueries({ queryKey: ["hook-session-timeline"] });
+			queryClient.invalidateQueries({ queryKey: ["hook-events"] });
+		});
+		return () => {
+			unlisten.then((fn) => fn());
+		};
+	}, [queryClient]);
+};
+
+export const useHookSessions = (filter: HookEventFilter) => {
+	useHookEventsListener();
+
+	return useQuery({
+		queryKey: ["hook-sessions", filter],
+		queryFn: () => invoke<HookSession[]>("list_hook_sessions", { filter }),
+	});
+};
+
+export const useHookSessionTimeline = (sessionId?: string) => {
+	return useQuery({
+		queryKey: ["hook-session-timeline", sessionId],
+		queryFn: () =>
+			invoke<HookEventRecord[]>("get_hook_session_timeline", { sessionId }),
+		enabled: !!sessionId,
+	});
+};
+
+export const useHookEvents = (filter: HookEventFilter) => {
+	useHookEventsListener();
+
+	return useQuery({
+		queryKey: ["hook-events", filter],
+		queryFn: () => invoke<HookEventRecord[]>("get_hook_events", { filter }),
+	});
+};
+
+export const useHookToolNames = () => {
+	return useQuery({
+		queryKey: ["hook-tool-names"],
+		queryFn: () => invoke<string[]>("get_hook_tool_names"),
+	});
+};
+
+export const useClearHookEvents = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (before?: number) =>
+			invoke<number>("clear_hook_events", { before }),
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["hook-sessions"] });
+			queryClient.invalidateQueries({ queryKey: ["hook-session-timeline"] });
+			queryClient.invalidateQueries({ queryKey: ["hook-events"] });
+			queryClient.invalidateQueries({ queryKey: ["hook-tool-names"] });
+			queryClient.invalidateQueries({ queryKey: ["tool-rule-decisions"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to clear hook events: ${errorMessage}`);
+		},
+	});
+};
+
+export type SessionStatus = "running" | "waiting_for_input" | "stopped";
+
+export interface LiveSession {
+	session_id: string;
+	cwd: string;
+	status: SessionStatus;
+	current_tool: string | null;
+	last_message: string | null;
+	started_at: number;
+	status_since: number;
+	updated_at: number;
+}
+
+// Claude Code sessions that are still going, kept current by the hook server
+export const useActiveSessions = () => {
+	const queryClient = useQueryClient();
+
+	useEffect(() => {
+		const unlisten = listen<LiveSession[]>(
+			"active-sessions-changed",
+			(event) => {
+				queryClient.setQueryData(["active-sessions"], event.payload);
+			},
+		);
+		return () => {
+			unlisten.then((fn) => fn());
+		};
+	}, [queryClient]);
+
+	return useQuery({
+		queryKey: ["active-sessions"],
+		queryFn: () => invoke<LiveSession[]>("get_active_sessions"),
+	});
+};
+
 // Command management hooks
 export const useClaudeCommands = () =>
 	useQuery({
@@ -760,6 +1246,761 @@ export const useUpdateCodexGlobalSettings = () => {
 	});
 };
 
+// Config history hooks
+export interface HistoryFile {
+	key: string;
+	name: string;
+	path: string;
+	exists: boolean;
+	versions: number;
+	latest_at?: number;
+}
+
+export interface HistoryVersion {
+	id: string;
+	createdAt: number;
+	size: number;
+}
+
+export interface HistoryDiffLine {
+	kind: "equal" | "added" | "removed";
+	text: string;
+}
+
+export interface HistoryDiff {
+	key: string;
+	path: string;
+	from: string;
+	to?: string;
+	added: number;
+	removed: number;
+	lines: HistoryDiffLine[];
+}
+
+export interface HistorySettings {
+	max_versions: number;
+}
+
+export const useHistoryFiles = () => {
+	return useQuery({
+		queryKey: ["history-files"],
+		queryFn: () => invoke<HistoryFile[]>("list_history_files"),
+	});
+};
+
+export const useHistoryVersions = (key: string) => {
+	return useQuery({
+		queryKey: ["history-versions", key],
+		queryFn: () => invoke<HistoryVersion[]>("list_history_versions", { key }),
+		enabled: !!key,
+	});
+};
+
+export const useHistoryVersion = (key: string, versionId: string) => {
+	return useQuery({
+		queryKey: ["history-version", key, versionId],
+		queryFn: () =>
+			invoke<string>("read_history_version", { key, versionId }),
+		enabled: !!key && !!versionId,
+	});
+};
+
+export const useHistoryDiff = (key: string, from: string, to?: string) => {
+	return useQuery({
+		queryKey: ["history-diff", key, from, to],
+		queryFn: () =>
+			invoke<HistoryDiff>("diff_history_version", { key, from, to }),
+		enabled: !!key && !!from,
+	});
+};
+
+export const useRestoreHistoryVersion = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: ({ key, versionId }: { key: string; versionId: string }) =>
+			invoke<void>("restore_history_version", { key, versionId }),
+		onSuccess: async () => {
+			toast.success("Version restored");
+			// A restore can touch any managed file, so refresh everything
+			await queryClient.invalidateQueries();
+			await rebuildTrayMenu();
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to restore version: ${errorMessage}`);
+		},
+	});
+};
+
+export const useHistorySettings = () => {
+	return useQuery({
+		queryKey: ["history-settings"],
+		queryFn: () => invoke<HistorySettings>("get_history_settings"),
+	});
+};
+
+export const useUpdateHistorySettings = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (settings: HistorySettings) =>
+			invoke<void>("update_history_settings", { settings }),
+		onSuccess: () => {
+			toast.success("History settings updated");
+			queryClient.invalidateQueries({ queryKey: ["history-settings"] });
+			queryClient.invalidateQueries({ queryKey: ["history-files"] });
+			queryClient.invalidateQueries({ queryKey: ["history-versions"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to update settings: ${errorMessage}`);
+		},
+	});
+};
+
+// Profile health check hooks
+
+export interface ProfileHealth {
+	api: "anthropic_messages" | "openai_responses" | "openai_chat";
+	url: string;
+	model: string;
+	reachable: boolean;
+	status?: number;
+	auth_valid?: boolean;
+	model_available?: boolean;
+	latency_ms: number;
+	error?: string;
+}
+
+// Sends a real, minimal request with the profile's credentials
+export const useCheckProfileHealth = () => {
+	return useMutation({
+		mutationFn: ({
+			storeId,
+			target,
+		}: {
+			storeId: string;
+			target: "claude" | "codex";
+		}) => invoke<ProfileHealth>("check_profile_health", { storeId, target }),
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to check profile: ${errorMessage}`);
+		},
+	});
+};
+
+export const isHealthy = (health: ProfileHealth) =>
+	health.status !== undefined && health.status >= 200 && health.status < 300;
+
+// Automatic switching hooks
+
+export type SwitchTarget = "claude" | "codex";
+
+export type RuleCondition =
+	| { type: "schedule"; cron: string }
+	| { type: "public_ip"; addresses: string[] }
+	| { type: "latency"; url?: string; threshold_ms: number }
+	| { type: "health_check"; url?: string };
+
+export interface SwitchRule {
+	id: string;
+	name: string;
+	enabled: boolean;
+	target: SwitchTarget;
+	store_id: string;
+	condition: RuleCondition;
+}
+
+export interface AutoSwitchSettings {
+	enabled: boolean;
+	interval_secs: number;
+	public_ip_url: string;
+	rules: SwitchRule[];
+}
+
+export interface AuditEntry {
+	timestamp: number;
+	rule_id: string;
+	rule_name: string;
+	target: SwitchTarget;
+	from_store?: string;
+	to_store: string;
+	to_title?: string;
+	reason: string;
+	success: boolean;
+	error?: string;
+}
+
+export const useAutoSwitchSettings = () => {
+	return useQuery({
+		queryKey: ["auto-switch-settings"],
+		queryFn: () => invoke<AutoSwitchSettings>("get_auto_switch_settings"),
+	});
+};
+
+export const useUpdateAutoSwitchSettings = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (settings: AutoSwitchSettings) =>
+			invoke<AutoSwitchSettings>("update_auto_switch_settings", { settings }),
+		onSuccess: (settings) => {
+			queryClient.setQueryData(["auto-switch-settings"], settings);
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to save rules: ${errorMessage}`);
+		},
+	});
+};
+
+export const useAutoSwitchLog = () => {
+	const queryClient = useQueryClient();
+
+	// New entries arrive whenever a rule switches profiles
+	useEffect(() => {
+		const unlisten = listen<AuditEntry>("auto-switched", () => {
+			queryClient.invalidateQueries({ queryKey: ["auto-switch-log"] });
+		});
+		return () => {
+			unlisten.then((fn) => fn());
+		};
+	}, [queryClient]);
+
+	return useQuery({
+		queryKey: ["auto-switch-log"],
+		queryFn: () => invoke<AuditEntry[]>("get_auto_switch_log", { limit: 50 }),
+	});
+};
+
+// Failover group hooks
+
+export interface FailoverSettings {
+	enabled: boolean;
+	interval_secs: number;
+	failure_threshold: number;
+	switch_back: boolean;
+	members: string[];
+}
+
+export const useFailoverSettings = () => {
+	return useQuery({
+		queryKey: ["failover-settings"],
+		queryFn: () => invoke<FailoverSettings>("get_failover_settings"),
+	});
+};
+
+export const useUpdateFailoverSettings = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (settings: FailoverSettings) =>
+			invoke<FailoverSettings>("update_failover_settings", { settings }),
+		onSuccess: (settings) => {
+			queryClient.setQueryData(["failover-settings"], settings);
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to save failover group: ${errorMessage}`);
+		},
+	});
+};
+
+// Local relay hooks
+
+export interface RelaySettings {
+	enabled: boolean;
+	port: number;
+	token: string;
+	log_bodies: boolean;
+	wire_apis?: Record<string, WireApi>;
+}
+
+// API a Claude Code profile's endpoint speaks, translated by the relay
+export type WireApi = "anthropic" | "chat" | "responses";
+
+export const useRelaySettings = () => {
+	return useQuery({
+		queryKey: ["relay-settings"],
+		queryFn: () => invoke<RelaySettings>("get_relay_settings"),
+	});
+};
+
+export const useUpdateRelaySettings = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (settings: Omit<RelaySettings, "token">) =>
+			invoke<RelaySettings>("update_relay_settings", {
+				enabled: settings.enabled,
+				port: settings.port,
+				logBodies: settings.log_bodies,
+			}),
+		onSuccess: (settings) => {
+			queryClient.setQueryData(["relay-settings"], settings);
+			queryClient.invalidateQueries({ queryKey: ["stores"] });
+			queryClient.invalidateQueries({ queryKey: ["active-drift"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to update relay: ${errorMessage}`);
+		},
+	});
+};
+
+export const useSetRelayWireApi = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: ({
+			storeId,
+			wireApi,
+		}: {
+			storeId: string;
+			wireApi: WireApi;
+		}) => invoke<RelaySettings>("set_relay_wire_api", { storeId, wireApi }),
+		onSuccess: (settings) => {
+			queryClient.setQueryData(["relay-settings"], settings);
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to save endpoint API: ${errorMessage}`);
+		},
+	});
+};
+
+// Model alias hooks
+
+export interface ModelAlias {
+	from: string;
+	to: string;
+}
+
+export const useModelAliases = (storeId: string) => {
+	return useQuery({
+		queryKey: ["model-aliases", storeId],
+		queryFn: () => invoke<ModelAlias[]>("get_model_aliases", { storeId }),
+		enabled: !!storeId,
+	});
+};
+
+export const useUpdateModelAliases = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: ({
+			storeId,
+			aliases,
+		}: {
+			storeId: string;
+			aliases: ModelAlias[];
+		}) => invoke<ModelAlias[]>("update_model_aliases", { storeId, aliases }),
+		onSuccess: (aliases, { storeId }) => {
+			queryClient.setQueryData(["model-aliases", storeId], aliases);
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to save model aliases: ${errorMessage}`);
+		},
+	});
+};
+
+// Relay policy hooks
+
+export interface RelayPolicy {
+	max_concurrent: number | null;
+	requests_per_minute: number | null;
+	burst: number | null;
+	max_retries: number;
+	retry_base_ms: number;
+	retry_max_ms: number;
+	breaker_threshold: number | null;
+	breaker_cooldown_secs: number;
+}
+
+export interface RelayMetrics {
+	store_id: string;
+	store_title: string | null;
+	in_flight: number;
+	queued: number;
+	requests: number;
+	throttled: number;
+	rate_limited: number;
+	retries: number;
+	failures: number;
+	rejected: number;
+	circuit: "closed" | "open" | "half_open";
+	circuit_opened: number;
+	last_error: string | null;
+}
+
+export const useRelayPolicy = (storeId: string) => {
+	return useQuery({
+		queryKey: ["relay-policy", storeId],
+		queryFn: () => invoke<RelayPolicy>("get_relay_policy", { storeId }),
+		enabled: !!storeId,
+	});
+};
+
+export const useUpdateRelayPolicy = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: ({
+			storeId,
+			policy,
+		}: {
+			storeId: string;
+			policy: RelayPolicy;
+		}) => invoke<RelayPolicy>("update_relay_policy", { storeId, policy }),
+		onSuccess: (policy, { storeId }) => {
+			queryClient.setQueryData(["relay-policy", storeId], policy);
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to save relay policy: ${errorMessage}`);
+		},
+	});
+};
+
+export const useRelayMetrics = () => {
+	return useQuery({
+		queryKey: ["relay-metrics"],
+		queryFn: () => invoke<RelayMetrics[]>("get_relay_metrics"),
+		refetchInterval: 1000 * 5,
+	});
+};
+
+// Relay log hooks
+
+export interface RelayTokenUsage {
+	input_tokens: number;
+	output_tokens: number;
+	cache_creation_input_tokens: number;
+	cache_read_input_tokens: number;
+}
+
+export interface RelayLogEntry extends RelayTokenUsage {
+	id: number;
+	timestamp: number;
+	store_id?: string;
+	store_title?: string;
+	method: string;
+	path: string;
+	model?: string;
+	status?: number;
+	latency_ms: number;
+	duration_ms: number;
+	streamed: boolean;
+	error?: string;
+	request_body?: string;
+	response_body?: string;
+}
+
+export interface RelayUsage extends RelayTokenUsage {
+	store_id?: string;
+	store_title?: string;
+	model?: string;
+	requests: number;
+	errors: number;
+}
+
+export const useRelayLog = (storeId?: string) => {
+	return useQuery({
+		queryKey: ["relay-log", storeId],
+		queryFn: () =>
+			invoke<RelayLogEntry[]>("get_relay_log", { limit: 100, storeId }),
+	});
+};
+
+export const useRelayLogEntry = (id?: number) => {
+	return useQuery({
+		queryKey: ["relay-log-entry", id],
+		queryFn: () => invoke<RelayLogEntry>("get_relay_log_entry", { id }),
+		enabled: id !== undefined,
+	});
+};
+
+export const useRelayUsage = (since?: number) => {
+	return useQuery({
+		queryKey: ["relay-usage", since],
+		queryFn: () => invoke<RelayUsage[]>("get_relay_usage", { since }),
+	});
+};
+
+export const useClearRelayLog = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: ({ bodiesOnly }: { bodiesOnly: boolean }) =>
+			bodiesOnly
+				? invoke("clear_relay_log_bodies")
+				: invoke("clear_relay_log"),
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["relay-log"] });
+			queryClient.invalidateQueries({ queryKey: ["relay-log-entry"] });
+			queryClient.invalidateQueries({ queryKey: ["relay-usage"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to clear relay log: ${errorMessage}`);
+		},
+	});
+};
+
+// Secret vault hooks
+
+export interface VaultStatus {
+	initialized: boolean;
+	key_source?: "passphrase" | "keyring" | "file";
+	locked: boolean;
+	secrets: number;
+	keyring_available: boolean;
+}
+
+export const useVaultStatus = () => {
+	return useQuery({
+		queryKey: ["vault-status"],
+		queryFn: () => invoke<VaultStatus>("get_vault_status"),
+	});
+};
+
+export const useUnlockVault = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (passphrase: string) =>
+			invoke<void>("unlock_vault", { passphrase }),
+		onSuccess: () => {
+			toast.success("Vault unlocked");
+			queryClient.invalidateQueries({ queryKey: ["vault-status"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to unlock vault: ${errorMessage}`);
+		},
+	});
+};
+
+export const useLockVault = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: () => invoke<void>("lock_vault"),
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["vault-status"] });
+		},
+	});
+};
+
+export const useSetVaultPassphrase = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: (passphrase: string | null) =>
+			invoke<void>("set_vault_passphrase", { passphrase }),
+		onSuccess: (_data, passphrase) => {
+			toast.success(
+				passphrase ? "Vault passphrase set" : "Vault passphrase removed",
+			);
+			queryClient.invalidateQueries({ queryKey: ["vault-status"] });
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to update vault: ${errorMessage}`);
+		},
+	});
+};
+
+// External edit hooks
+
+export interface ActiveDrift {
+	store_id: string;
+	store_title: string;
+	pointers: string[];
+}
+
+interface FilesChanged {
+	files: string[];
+	drift?: ActiveDrift | null;
+}
+
+export const useActiveDrift = () => {
+	return useQuery({
+		queryKey: ["active-drift"],
+		queryFn: () => invoke<ActiveDrift | null>("get_active_drift"),
+	});
+};
+
+export interface DriftEntry {
+	pointer: string;
+	kind: "added" | "removed" | "changed";
+	// expected/actual are one item of the list at pointer
+	item?: boolean;
+	expected?: unknown;
+	actual?: unknown;
+}
+
+export interface ProfileDiff {
+	store_id: string;
+	store_title: string;
+	entries: DriftEntry[];
+}
+
+export const useProfileDiff = (enabled = true) => {
+	return useQuery({
+		queryKey: ["active-diff"],
+		queryFn: () => invoke<ProfileDiff | null>("diff_active_profile"),
+		enabled,
+	});
+};
+
+export const useReapplyActiveProfile = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: () => invoke<void>("reapply_active_profile"),
+		onSuccess: async () => {
+			toast.success("Profile re-applied to settings.json");
+			await queryClient.invalidateQueries();
+			await rebuildTrayMenu();
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to re-apply profile: ${errorMessage}`);
+		},
+	});
+};
+
+export const useAbsorbActiveProfile = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: () => invoke<ProfileDiff | null>("absorb_active_profile"),
+		onSuccess: async (diff) => {
+			if (diff?.entries.length) {
+				toast.warning(
+					`Saved edits into the profile, ${diff.entries.length} inherited value(s) could not be changed`,
+				);
+			} else {
+				toast.success("Saved settings.json edits into the profile");
+			}
+			await queryClient.invalidateQueries();
+			await rebuildTrayMenu();
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to save edits into profile: ${errorMessage}`);
+		},
+	});
+};
+
+// Refresh everything when the watcher reports managed files edited on disk
+export const useConfigFileEvents = () => {
+	const queryClient = useQueryClient();
+
+	useEffect(() => {
+		const unlisten = listen<FilesChanged>("config-files-changed", (event) => {
+			queryClient.setQueryData(["active-drift"], event.payload.drift ?? null);
+			queryClient.invalidateQueries({
+				predicate: (query) => query.queryKey[0] !== "active-drift",
+			});
+		});
+		return () => {
+			unlisten.then((fn) => fn());
+		};
+	}, [queryClient]);
+};
+
+// Profile bundle hooks
+
+export type ConflictStrategy = "skip" | "rename" | "overwrite";
+
+export interface ImportItem {
+	kind: "claude_store" | "codex_store" | "mcp_server" | "command" | "agent";
+	name: string;
+	action: "create" | "skip" | "rename" | "overwrite";
+	renamed_to?: string;
+}
+
+export interface ImportReport {
+	dry_run: boolean;
+	items: ImportItem[];
+}
+
+export const useExportProfiles = () => {
+	return useMutation({
+		mutationFn: ({
+			filePath,
+			passphrase,
+		}: {
+			filePath: string;
+			passphrase?: string;
+		}) => invoke<void>("export_profiles", { filePath, passphrase }),
+		onSuccess: () => {
+			toast.success("Profiles exported");
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to export profiles: ${errorMessage}`);
+		},
+	});
+};
+
+export const useImportProfiles = () => {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: ({
+			filePath,
+			passphrase,
+			strategy,
+			dryRun,
+		}: {
+			filePath: string;
+			passphrase?: string;
+			strategy: ConflictStrategy;
+			dryRun: boolean;
+		}) =>
+			invoke<ImportReport>("import_profiles", {
+				filePath,
+				passphrase,
+				strategy,
+				dryRun,
+			}),
+		onSuccess: async (report) => {
+			if (report.dry_run) {
+				return;
+			}
+			toast.success("Profiles imported");
+			// Stores, MCP servers, commands and agents may all have changed
+			await queryClient.invalidateQueries();
+			await rebuildTrayMenu();
+		},
+		onError: (error) => {
+			const errorMessage =
+				error instanceof Error ? error.message : String(error);
+			toast.error(`Failed to import profiles: ${errorMessage}`);
+		},
+	});
+};
+
 // Helper function to rebuild tray menu
 const rebuildTrayMenu = async () => {
 	try {
@@ -0,0 +1,496 @@
+import { ArrowUpIcon, PlusIcon, Trash2Icon, XIcon } from "lucide-react";
+import { useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import {
+	Select,
+	SelectContent,
+	SelectItem,
+	SelectTrigger,
+	SelectValue,
+} from "@/components/ui/select";
+import { Switch } from "@/components/ui/switch";
+import {
+	type AutoSwitchSettings,
+	type FailoverSettings,
+	type RuleCondition,
+	type SwitchRule,
+	type SwitchTarget,
+	useAutoSwitchLog,
+	useAutoSwitchSettings,
+	useCodexStores,
+	useFailoverSettings,
+	useStores,
+	useUpdateAutoSwitchSettings,
+	useUpdateFailoverSettings,
+} from "@/lib/query";
+
+type ConditionType = RuleCondition["type"];
+
+export function AutoSwitchPage() {
+	const { t } = useTranslation();
+	const { data: settings } = useAutoSwitchSettings();
+	const { data: log } = useAutoSwitchLog();
+	const { data: claudeStores } = useStores();
+	const { data: codexStores } = useCodexStores();
+	const updateSettings = useUpdateAutoSwitchSettings();
+
+	const [name, setName] = useState("");
+	const [target, setTarget] = useState<SwitchTarget>("claude");
+	const [storeId, setStoreId] = useState("");
+	const [conditionType, setConditionType] =
+		useState<ConditionType>("schedule");
+	const [conditionValue, setConditionValue] = useState("");
+	const [thresholdMs, setThresholdMs] = useState("2000");
+
+	const targetStores = target === "claude" ? claudeStores : codexStores;
+
+	const storeTitle = (rule: SwitchRule) =>
+		(rule.target === "claude" ? claudeStores : codexStores).find(
+			(store) => store.id === rule.store_id,
+		)?.title ?? rule.store_id;
+
+	const save = (changes: Partial<AutoSwitchSettings>) => {
+		if (!settings) return;
+		updateSettings.mutate({ ...settings, ...changes });
+	};
+
+	const describeCondition = (condition: RuleCondition) => {
+		switch (condition.type) {
+			case "schedule":
+				return t("autoSwitch.condition.scheduleSummary", {
+					cron: condition.cron,
+				});
+			case "public_ip":
+				return t("autoSwitch.condition.publicIpSummary", {
+					addresses: condition.addresses.join(", "),
+				});
+			case "latency":
+				return t("autoSwitch.condition.latencySummary", {
+					url: condition.url || t("autoSwitch.activeEndpoint"),
+					threshold: condition.threshold_ms,
+				});
+			case "health_check":
+				return t("autoSwitch.condition.healthCheckSummary", {
+					url: condition.url || t("autoSwitch.activeEndpoint"),
+				});
+		}
+	};
+
+	const buildCondition = (): RuleCondition => {
+		const value = conditionValue.trim();
+		switch (conditionType) {
+			case "schedule":
+				return { type: "schedule", cron: value };
+			case "public_ip":
+				return {
+					type: "public_ip",
+					addresses: value
+						.split(",")
+						.map((address) => address.trim())
+						.filter(Boolean),
+				};
+			case "latency":
+				return {
+					type: "latency",
+					url: value || undefined,
+					threshold_ms: Number(thresholdMs),
+				};
+			case "health_check":
+				return { type: "health_check", url: value || undefined };
+		}
+	};
+
+	const handleAddRule = () => {
+		if (!settings) return;
+		const rule: SwitchRule = {
+			id: "",
+			name: name.trim(),
+			enabled: true,
+			target,
+			store_id: storeId,
+			condition: buildCondition(),
+		};
+		updateSettings.mutate(
+			{ ...settings, rules: [...settings.rules, rule] },
+			{
+				onSuccess: () => {
+					setName("");
+					setConditionValue("");
+				},
+			},
+		);
+	};
+
+	const handleToggleRule = (ruleId: string, enabled: boolean) => {
+		if (!settings) return;
+		save({
+			rules: settings.rules.map((rule) =>
+				rule.id === ruleId ? { ...rule, enabled } : rule,
+			),
+		});
+	};
+
+	const handleDeleteRule = (ruleId: string) => {
+		if (!settings) return;
+		save({ rules: settings.rules.filter((rule) => rule.id !== ruleId) });
+	};
+
+	const conditionPlaceholder = {
+		schedule: "* 20-23 * * 1-5",
+		public_ip: "203.0.113.7, 198.51.100.0/24",
+		latency: t("autoSwitch.urlPlaceholder"),
+		health_check: t("autoSwitch.urlPlaceholder"),
+	}[conditionType];
+
+	return (
+		<div className="">
+			<div
+				className="flex items-center p-3 border-b px-3 justify-between sticky top-0 bg-background z-10 mb-4"
+				data-tauri-drag-region
+			>
+				<div data-tauri-drag-region>
+					<h3 className="font-bold" data-tauri-drag-region>
+						{t("autoSwitch.title")}
+					</h3>
+					<p className="text-sm text-muted-foreground" data-tauri-drag-region>
+						{t("autoSwitch.description")}
+					</p>
+				</div>
+			</div>
+
+			<div className="px-4 flex flex-col bg-card mx-4 rounded-lg py-1 border">
+				<div className="border-b px-1 py-3">
+					<div className="flex items-center justify-between">
+						<Label htmlFor="auto-switch">{t("autoSwitch.enable")}</Label>
+						<Switch
+							id="auto-switch"
+							checked={settings?.enabled || false}
+							onCheckedChange={(enabled) => save({ enabled })}
+						/>
+					</div>
+					<div className="text-muted-foreground text-sm">
+						{t("autoSwitch.enableDescription")}
+					</div>
+				</div>
+				<div className="px-1 py-3 flex items-center justify-between">
+					<Label htmlFor="auto-switch-interval">
+						{t("autoSwitch.interval")}
+					</Label>
+					<Input
+						id="auto-switch-interval"
+						type="number"
+						min={15}
+						className="w-[120px]"
+						key={settings?.interval_secs}
+						defaultValue={settings?.interval_secs}
+						onBlur={(e) => {
+							const interval_secs = Number(e.target.value);
+							if (interval_secs && interval_secs !== settings?.interval_secs) {
+								save({ interval_secs });
+							}
+						}}
+					/>
+				</div>
+			</div>
+
+			<FailoverGroup />
+
+			<div className="mx-4 mt-6">
+				<h4 className="text-sm font-medium mb-2 mx-1">
+					{t("autoSwitch.rules")}
+				</h4>
+				<div className="bg-card rounded-lg border divide-y">
+					{settings?.rules.length ? (
+						settings.rules.map((rule) => (
+							<div
+								key={rule.id}
+								className="flex items-center gap-3 px-4 py-3"
+							>
+								<div className="flex-1 min-w-0">
+									<p className="text-sm font-medium truncate">{rule.name}</p>
+									<p className="text-xs text-muted-foreground truncate">
+										{describeCondition(rule.condition)} →{" "}
+										{rule.target === "codex" ? "Codex: " : ""}
+										{storeTitle(rule)}
+									</p>
+								</div>
+								<Switch
+									checked={rule.enabled}
+									onCheckedChange={(enabled) =>
+										handleToggleRule(rule.id, enabled)
+									}
+								/>
+								<Button
+									variant="ghost"
+									size="icon"
+									onClick={() => handleDeleteRule(rule.id)}
+								>
+									<Trash2Icon size={14} />
+								</Button>
+							</div>
+						))
+					) : (
+						<p className="text-sm text-muted-foreground px-4 py-3">
+							{t("autoSwitch.noRules")}
+						</p>
+					)}
+
+					<div className="flex flex-wrap items-center gap-2 px-4 py-3">
+						<Input
+							className="w-[160px]"
+							value={name}
+							onChange={(e) => setName(e.target.value)}
+							placeholder={t("autoSwitch.namePlaceholder")}
+						/>
+						<Select
+							value={conditionType}
+							onValueChange={(value) => setConditionType(value as ConditionType)}
+						>
+							<SelectTrigger className="w-[150px]">
+								<SelectValue />
+							</SelectTrigger>
+							<SelectContent>
+								<SelectItem value="schedule">
+									{t("autoSwitch.condition.schedule")}
+								</SelectItem>
+								<SelectItem value="public_ip">
+									{t("autoSwitch.condition.publicIp")}
+								</SelectItem>
+								<SelectItem value="latency">
+									{t("autoSwitch.condition.latency")}
+								</SelectItem>
+								<SelectItem value="health_check">
+									{t("autoSwitch.condition.healthCheck")}
+								</SelectItem>
+							</SelectContent>
+						</Select>
+						<Input
+							className="w-[220px]"
+							value={conditionValue}
+							onChange={(e) => setConditionValue(e.target.value)}
+							placeholder={conditionPlaceholder}
+						/>
+						{conditionType === "latency" && (
+							<Input
+								type="number"
+								className="w-[100px]"
+								value={thresholdMs}
+								onChange={(e) => setThresholdMs(e.target.value)}
+								placeholder="ms"
+							/>
+						)}
+						<Select
+							value={target}
+							onValueChange={(value) => {
+								setTarget(value as SwitchTarget);
+								setStoreId("");
+							}}
+						>
+							<SelectTrigger className="w-[120px]">
+								<SelectValue />
+							</SelectTrigger>
+							<SelectContent>
+								<SelectItem value="claude">Claude Code</SelectItem>
+								<SelectItem value="codex">Codex</SelectItem>
+							</SelectContent>
+						</Select>
+						<Select value={storeId} onValueChange={setStoreId}>
+							<SelectTrigger className="w-[180px]">
+								<SelectValue placeholder={t("autoSwitch.switchTo")} />
+							</SelectTrigger>
+							<SelectContent>
+								{targetStores.map((store) => (
+									<SelectItem key={store.id} value={store.id}>
+										{store.title}
+									</SelectItem>
+								))}
+							</SelectContent>
+						</Select>
+						<Button
+							variant="outline"
+							size="sm"
+							onClick={handleAddRule}
+							disabled={!name.trim() || !storeId || updateSettings.isPending}
+						>
+							<PlusIcon className="w-4 h-4 mr-1" />
+							{t("autoSwitch.addRule")}
+						</Button>
+					</div>
+				</div>
+			</div>
+
+			<div className="mx-4 mt-6 mb-8">
+				<h4 className="text-sm font-medium mb-2 mx-1">
+					{t("autoSwitch.log")}
+				</h4>
+				<div className="bg-card rounded-lg border divide-y">
+					{log?.length ? (
+						log.map((entry) => (
+							<div
+								key={`${entry.timestamp}-${entry.rule_id}`}
+								className="px-4 py-2 text-xs"
+							>
+								<div className="flex justify-between gap-2">
+									<span className="font-medium">
+										{entry.rule_name} → {entry.to_title ?? entry.to_store}
+									</span>
+									<span className="text-muted-foreground shrink-0">
+										{new Date(entry.timestamp).toLocaleString()}
+									</span>
+								</div>
+								<p
+									className={
+										entry.success ? "text-muted-foreground" : "text-red-600"
+									}
+								>
+									{entry.error ?? entry.reason}
+								</p>
+							</div>
+						))
+					) : (
+						<p className="text-sm text-muted-foreground px-4 py-3">
+							{t("autoSwitch.noLog")}
+						</p>
+					)}
+				</div>
+			</div>
+		</div>
+	);
+}
+
+function FailoverGroup() {
+	const { t } = useTranslation();
+	const { data: settings } = useFailoverSettings();
+	const { data: stores } = useStores();
+	const updateSettings = useUpdateFailoverSettings();
+	const [candidate, setCandidate] = useState("");
+
+	const save = (changes: Partial<FailoverSettings>) => {
+		if (!settings) return;
+		updateSettings.mutate({ ...settings, ...changes });
+	};
+
+	const members = settings?.members ?? [];
+	const storeTitle = (storeId: string) =>
+		stores.find((store) => store.id === storeId)?.title ?? storeId;
+	const available = stores.filter((store) => !members.includes(store.id));
+
+	const moveUp = (index: number) => {
+		const reordered = [...members];
+		[reordered[index - 1], reordered[index]] = [
+			reordered[index],
+			reordered[index - 1],
+		];
+		save({ members: reordered });
+	};
+
+	return (
+		<div className="mx-4 mt-6">
+			<h4 className="text-sm font-medium mb-2 mx-1">
+				{t("autoSwitch.failover.title")}
+			</h4>
+			<div className="px-4 flex flex-col bg-card rounded-lg py-1 border">
+				<div className="border-b px-1 py-3">
+					<div className="flex items-center justify-between">
+						<Label htmlFor="failover">{t("autoSwitch.failover.enable")}</Label>
+						<Switch
+							id="failover"
+							checked={settings?.enabled || false}
+							onCheckedChange={(enabled) => save({ enabled })}
+						/>
+					</div>
+					<div className="text-muted-foreground text-sm">
+						{t("autoSwitch.failover.description")}
+					</div>
+				</div>
+				<div className="border-b px-1 py-3 flex items-center justify-between">
+					<Label htmlFor="failover-threshold">
+						{t("autoSwitch.failover.threshold")}
+					</Label>
+					<Input
+						id="failover-threshold"
+						type="number"
+						min={1}
+						className="w-[120px]"
+						key={settings?.failure_threshold}
+						defaultValue={settings?.failure_threshold}
+						onBlur={(e) => {
+							const failure_threshold = Number(e.target.value);
+							if (
+								failure_threshold &&
+								failure_threshold !== settings?.failure_threshold
+							) {
+								save({ failure_threshold });
+							}
+						}}
+					/>
+				</div>
+				<div className="border-b px-1 py-3 flex items-center justify-between">
+					<Label htmlFor="failover-switch-back">
+						{t("autoSwitch.failover.switchBack")}
+					</Label>
+					<Switch
+						id="failover-switch-back"
+						checked={settings?.switch_back || false}
+						onCheckedChange={(switch_back) => save({ switch_back })}
+					/>
+				</div>
+				<div className="px-1 py-3 space-y-2">
+					{members.map((storeId, index) => (
+						<div key={storeId} className="flex items-center gap-2 text-sm">
+							<span className="w-5 text-muted-foreground">{index + 1}.</span>
+							<span className="flex-1 truncate">{storeTitle(storeId)}</span>
+							<Button
+								variant="ghost"
+								size="icon"
+								className="h-7 w-7"
+								disabled={index === 0}
+								onClick={() => moveUp(index)}
+							>
+								<ArrowUpIcon size={14} />
+							</Button>
+							<Button
+								variant="ghost"
+								size="icon"
+								className="h-7 w-7"
+								onClick={() =>
+									save({ members: members.filter((id) => id !== storeId) })
+								}
+							>
+								<XIcon size={14} />
+							</Button>
+						</div>
+					))}
+					<div className="flex items-center gap-2">
+						<Select value={candidate} onValueChange={setCandidate}>
+							<SelectTrigger className="w-[220px]">
+								<SelectValue placeholder={t("autoSwitch.failover.addMember")} />
+							</SelectTrigger>
+							<SelectContent>
+								{available.map((store) => (
+									<SelectItem key={store.id} value={store.id}>
+										{store.title}
+									</SelectItem>
+								))}
+							</SelectContent>
+						</Select>
+						<Button
+							variant="outline"
+							size="sm"
+							disabled={!candidate}
+							onClick={() => {
+								save({ members: [...members, candidate] });
+								setCandidate("");
+							}}
+						>
+							<PlusIcon className="w-4 h-4" />
+						</Button>
+					</div>
+				</div>
+			</div>
+		</div>
+	);
+}
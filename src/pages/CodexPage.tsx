@@ -11,8 +11,9 @@ import {
     AlertDialogTrigger,
 } from "@/components/ui/alert-dialog";
 import { cn } from "@/lib/utils";
-import { useCodexGlobalSettings, useCodexStores, useDeleteCodexStore, useSetUsingCodexStore } from "@/lib/query";
-import { CheckIcon, DownloadIcon, PencilLineIcon, PlusIcon, ServerIcon, Trash2Icon } from "lucide-react";
+import { type ProfileHealth, useCheckProfileHealth, useCodexGlobalSettings, useCodexStores, useDeleteCodexStore, useSetUsingCodexStore } from "@/lib/query";
+import { ProfileHealthStatus } from "@/components/ProfileHealthStatus";
+import { ActivityIcon, CheckIcon, DownloadIcon, PencilLineIcon, PlusIcon, ServerIcon, Trash2Icon } from "lucide-react";
 import { useNavigate } from "react-router-dom";
 import { toast } from "sonner";
 import { invoke } from "@tauri-apps/api/core";
@@ -41,13 +42,15 @@ function CodexConfigList() {
 
     const [statuses, setStatuses] = useState<Record<string, ConnectionStatus>>({});
     const [checking, setChecking] = useState<Record<string, boolean>>({});
+    const [health, setHealth] = useState<Record<string, ProfileHealth>>({});
+    const checkHealthMutation = useCheckProfileHealth();
     const [codexInstalled, setCodexInstalled] = useState<boolean | null>(null);
     const [installing, setInstalling] = useState(false);
 
     useEffect(() => {
         if (stores) {
             stores.forEach(store => {
-                checkStatus(store.id, store.config);
+                checkStatus(store.id);
             });
         }
     }, [stores]);
@@ -66,15 +69,13 @@ function CodexConfigList() {
         }
     };
 
-    const checkStatus = async (id: string, config: any) => {
+    const checkStatus = async (id: string) => {
         if (checking[id]) return;
 
         setChecking(prev => ({ ...prev, [id]: true }));
         try {
             const status = await invoke<ConnectionStatus>("check_codex_connection", {
-                url: config.url || "",
-                apiKey: config.api_key || "",
-                model: config.model || ""
+                storeId: id
             });
             setStatuses(prev => ({ ...prev, [id]: status }));
         } catch (error) {
@@ -85,6 +86,17 @@ function CodexConfigList() {
         }
     };
 
+    // Real request with the store's key, replaces the reachability check above
+    const checkHealth = async (id: string) => {
+        setChecking(prev => ({ ...prev, [id]: true }));
+        try {
+            const result = await checkHealthMutation.mutateAsync({ storeId: id, target: "codex" });
+            setHealth(prev => ({ ...prev, [id]: result }));
+        } finally {
+            setChecking(prev => ({ ...prev, [id]: false }));
+        }
+    };
+
     const handleStoreClick = (storeId: string, isCurrentStore: boolean) => {
         if (!isCurrentStore) {
             if (!settings?.enabled) {
@@ -244,6 +256,8 @@ function CodexConfigList() {
                                     <div className="flex items-center gap-2 text-xs text-muted-foreground mt-0.5">
                                         {isChecking ? (
                                             <span className="text-[10px] animate-pulse">Checking...</span>
+                                        ) : health[store.id] ? (
+                                            <ProfileHealthStatus health={health[store.id]} />
                                         ) : (
                                             <div className="flex items-center gap-1">
                                                 <div className={cn("w-1.5 h-1.5 rounded-full", isAvailable ? "bg-green-500" : "bg-red-500")} />
@@ -269,6 +283,20 @@ function CodexConfigList() {
 
                                 {/* Actions (Hover Overlay) */}
                                 <div className="absolute right-2 top-1/2 -translate-y-1/2 flex items-center gap-1 opacity-0 group-hover:opacity-100 transition-all bg-background/95 backdrop-blur shadow-sm border rounded-md p-1 pl-1.5">
+                                    <Button
+                                        variant="ghost"
+                                        size="icon"
+                                        className="h-7 w-7 hover:text-primary"
+                                        title="Check with API key"
+                                        disabled={isChecking}
+                                        onClick={(e) => {
+                                            e.stopPropagation();
+                                            checkHealth(store.id);
+                                        }}
+                                    >
+                                        <ActivityIcon size={14} />
+                                    </Button>
+
                                     <Button
                                         variant="ghost"
                                         size="icon"
@@ -1,9 +1,25 @@
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
+import { NativeSelect, NativeSelectOption } from "@/components/ui/native-select";
 import { cn } from "@/lib/utils";
-import { useCreateConfig, useStore, useUpdateConfig, useSystemEnvConfig } from "@/lib/query";
-import { ArrowLeftIcon, CheckCircle2, Code2, Globe, SaveIcon, Server, ShieldCheck, Sparkles } from "lucide-react";
+import {
+	type ModelAlias,
+	type RelayPolicy,
+	type WireApi,
+	useCreateConfig,
+	useModelAliases,
+	useRelayPolicy,
+	useRelaySettings,
+	useSetRelayWireApi,
+	useStore,
+	useStores,
+	useUpdateConfig,
+	useUpdateModelAliases,
+	useUpdateRelayPolicy,
+	useSystemEnvConfig,
+} from "@/lib/query";
+import { ArrowLeftIcon, ArrowRightIcon, CheckCircle2, Code2, Gauge, Globe, PlusIcon, Repeat, SaveIcon, Server, ShieldCheck, Sparkles, XIcon } from "lucide-react";
 import { useEffect, useMemo, useState } from "react";
 import { useNavigate, useParams } from "react-router-dom";
 import { toast } from "sonner";
@@ -62,8 +78,15 @@ export function ConfigEditorPage() {
 
 	const { data: existingStore, isLoading } = useStore(isEditMode ? storeId : "");
 	const { data: systemEnvConfig } = useSystemEnvConfig();
+	const { data: stores } = useStores();
+	const { data: savedAliases } = useModelAliases(isEditMode ? storeId : "");
 	const createConfigMutation = useCreateConfig();
 	const updateConfigMutation = useUpdateConfig();
+	const updateAliasesMutation = useUpdateModelAliases();
+	const { data: relaySettings } = useRelaySettings();
+	const setWireApiMutation = useSetRelayWireApi();
+	const { data: savedPolicy } = useRelayPolicy(isEditMode ? storeId : "");
+	const updatePolicyMutation = useUpdateRelayPolicy();
 
 	// State
 	const [provider, setProvider] = useState<ProviderType>("zhipu");
@@ -74,11 +97,16 @@ export function ConfigEditorPage() {
 	const [haikuModel, setHaikuModel] = useState("glm-4.5-air");
 	const [sonnetModel, setSonnetModel] = useState("glm-4.6");
 	const [opusModel, setOpusModel] = useState("glm-4.6");
+	const [parent, setParent] = useState("");
+	const [aliases, setAliases] = useState<ModelAlias[]>([]);
+	const [wireApi, setWireApi] = useState<WireApi>("anthropic");
+	const [policy, setPolicy] = useState<RelayPolicy>();
 
 	// Load existing data if in edit mode
 	useEffect(() => {
 		if (isEditMode && existingStore) {
 			setName(existingStore.title);
+			setParent(existingStore.parent || "");
 			const env = existingStore.settings?.env || {};
 
 			// Determine provider type based on URL
@@ -102,6 +130,38 @@ export function ConfigEditorPage() {
 		}
 	}, [isEditMode, existingStore]);
 
+	useEffect(() => {
+		if (isEditMode && relaySettings) {
+			setWireApi(relaySettings.wire_apis?.[storeId] ?? "anthropic");
+		}
+	}, [isEditMode, relaySettings, storeId]);
+
+	useEffect(() => {
+		if (savedPolicy) {
+			setPolicy(savedPolicy);
+		}
+	}, [savedPolicy]);
+
+	useEffect(() => {
+		if (savedAliases) {
+			setAliases(savedAliases);
+		}
+	}, [savedAliases]);
+
+	const updateAlias = (index: number, changes: Partial<ModelAlias>) => {
+		setAliases(aliases.map((alias, i) => (i === index ? { ...alias, ...changes } : alias)));
+	};
+
+	// One alias per Claude model family, from the models configured above
+	const fillAliasesFromModels = () => {
+		const families: [string, string][] = [
+			["claude-opus-*", opusModel || mainModel],
+			["claude-sonnet-*", sonnetModel || mainModel],
+			["claude-haiku-*", haikuModel || mainModel],
+		];
+		setAliases(families.filter(([, to]) => to).map(([from, to]) => ({ from, to })));
+	};
+
 	// Load system default config from environment variables
 	useEffect(() => {
 		if (isSystemDefault && systemEnvConfig?.has_config) {
@@ -148,14 +208,15 @@ export function ConfigEditorPage() {
 			return;
 		}
 
-		if (!url.trim()) {
+		// Child configs inherit the endpoint from their parent
+		if (!url.trim() && !parent) {
 			toast.error("Please enter API endpoint");
 			return;
 		}
 
 		const settings = {
 			env: {
-				ANTHROPIC_BASE_URL: url,
+				...(url ? { ANTHROPIC_BASE_URL: url } : {}),
 				...(apiKey ? { ANTHROPIC_AUTH_TOKEN: apiKey } : {}),
 				...(mainModel ? { ANTHROPIC_MODEL: mainModel } : {}),
 				...(haikuModel ? { ANTHROPIC_DEFAULT_HAIKU_MODEL: haikuModel } : {}),
@@ -170,11 +231,23 @@ export function ConfigEditorPage() {
 					storeId,
 					title: name,
 					settings,
+					parent: parent || undefined,
 				});
+				await updateAliasesMutation.mutateAsync({
+					storeId,
+					aliases: aliases.filter((alias) => alias.from.trim() && alias.to.trim()),
+				});
+				if (wireApi !== (relaySettings?.wire_apis?.[storeId] ?? "anthropic")) {
+					await setWireApiMutation.mutateAsync({ storeId, wireApi });
+				}
+				if (policy && JSON.stringify(policy) !== JSON.stringify(savedPolicy)) {
+					await updatePolicyMutation.mutateAsync({ storeId, policy });
+				}
 			} else {
 				await createConfigMutation.mutateAsync({
 					title: name,
 					settings,
+					parent: parent || undefined,
 				});
 			}
 			navigate("/claude");
@@ -277,6 +350,31 @@ export function ConfigEditorPage() {
 								/>
 							</div>
 
+							<div className="group">
+								<Label className="text-xs font-normal text-muted-foreground mb-1.5 block group-focus-within:text-primary transition-colors">
+									INHERIT FROM (OPTIONAL)
+								</Label>
+								<NativeSelect
+									value={parent}
+									onChange={(e) => setParent(e.target.value)}
+									className="w-full font-mono text-sm"
+								>
+									<NativeSelectOption value="">None</NativeSelectOption>
+									{stores
+										.filter((store) => store.id !== storeId)
+										.map((store) => (
+											<NativeSelectOption key={store.id} value={store.id}>
+												{store.title}
+											</NativeSelectOption>
+										))}
+								</NativeSelect>
+								{parent && (
+									<p className="text-xs text-muted-foreground mt-1.5">
+										Empty fields are inherited from the parent configuration.
+									</p>
+								)}
+							</div>
+
 							<div className="group">
 								<Label className="text-xs font-normal text-muted-foreground mb-1.5 block group-focus-within:text-primary transition-colors">
 									API ENDPOINT
@@ -289,6 +387,28 @@ export function ConfigEditorPage() {
 								/>
 							</div>
 
+							{isEditMode && (
+								<div className="group">
+									<Label className="text-xs font-normal text-muted-foreground mb-1.5 block group-focus-within:text-primary transition-colors">
+										ENDPOINT API
+									</Label>
+									<NativeSelect
+										value={wireApi}
+										onChange={(e) => setWireApi(e.target.value as WireApi)}
+										className="w-full font-mono text-sm"
+									>
+										<NativeSelectOption value="anthropic">Anthropic Messages</NativeSelectOption>
+										<NativeSelectOption value="chat">OpenAI Chat Completions</NativeSelectOption>
+										<NativeSelectOption value="responses">OpenAI Responses</NativeSelectOption>
+									</NativeSelect>
+									{wireApi !== "anthropic" && (
+										<p className="text-xs text-muted-foreground mt-1.5">
+											Requests are translated by the local relay, which has to be on for Claude Code to use this profile.
+										</p>
+									)}
+								</div>
+							)}
+
 							<div className="group">
 								<Label className="text-xs font-normal text-muted-foreground mb-1.5 block group-focus-within:text-primary transition-colors">
 									API KEY (OPTIONAL)
@@ -361,6 +481,101 @@ export function ConfigEditorPage() {
 						</div>
 					</section>
 
+					{/* Model aliases, applied by the local relay */}
+					{isEditMode && (
+						<section className="space-y-4">
+							<div className="flex items-center justify-between">
+								<div className="flex items-center gap-2 text-sm font-medium text-muted-foreground uppercase tracking-wider">
+									<Repeat size={14} />
+									<span>Model Aliases</span>
+								</div>
+								<Button variant="ghost" size="sm" onClick={fillAliasesFromModels}>
+									Fill from models above
+								</Button>
+							</div>
+							<p className="text-xs text-muted-foreground">
+								With the local relay on, requests for a model matching the left side are sent with the model on the right, and
+								responses keep the name Claude Code asked for. Use * to match any text, e.g. claude-sonnet-*.
+							</p>
+							<div className="space-y-2">
+								{aliases.map((alias, index) => (
+									<div key={index} className="flex items-center gap-2">
+										<Input
+											value={alias.from}
+											onChange={(e) => updateAlias(index, { from: e.target.value })}
+											placeholder="claude-sonnet-*"
+											className="h-9 font-mono text-sm"
+										/>
+										<ArrowRightIcon size={14} className="shrink-0 text-muted-foreground" />
+										<Input
+											value={alias.to}
+											onChange={(e) => updateAlias(index, { to: e.target.value })}
+											placeholder="glm-4.6"
+											className="h-9 font-mono text-sm"
+										/>
+										<Button
+											variant="ghost"
+											size="icon"
+											className="shrink-0"
+											onClick={() => setAliases(aliases.filter((_, i) => i !== index))}
+										>
+											<XIcon size={14} />
+										</Button>
+									</div>
+								))}
+								<Button variant="outline" size="sm" onClick={() => setAliases([...aliases, { from: "", to: "" }])}>
+									<PlusIcon size={14} />
+									Add alias
+								</Button>
+							</div>
+						</section>
+					)}
+
+					{/* Traffic policy, applied by the local relay */}
+					{isEditMode && policy && (
+						<section className="space-y-4">
+							<div className="flex items-center gap-2 text-sm font-medium text-muted-foreground uppercase tracking-wider">
+								<Gauge size={14} />
+								<span>Relay Policy</span>
+							</div>
+							<p className="text-xs text-muted-foreground">
+								With the local relay on, requests to this profile are limited and retried as set here. Leave a limit empty for
+								none. The circuit breaker stops sending requests for the cooldown once as many requests failed in a row.
+							</p>
+							<div className="grid grid-cols-2 sm:grid-cols-4 gap-4">
+								{(
+									[
+										["max_concurrent", "Concurrent requests", true],
+										["requests_per_minute", "Requests / minute", true],
+										["burst", "Burst", true],
+										["max_retries", "Retries", false],
+										["retry_base_ms", "First retry after (ms)", false],
+										["retry_max_ms", "Longest retry wait (ms)", false],
+										["breaker_threshold", "Failures to break", true],
+										["breaker_cooldown_secs", "Cooldown (s)", false],
+									] as [keyof RelayPolicy, string, boolean][]
+								).map(([key, label, optional]) => (
+									<div key={key}>
+										<Label className="text-xs font-normal text-muted-foreground mb-1.5 block">{label}</Label>
+										<Input
+											type="number"
+											min={optional ? 1 : 0}
+											value={policy[key] ?? ""}
+											placeholder={optional ? "No limit" : undefined}
+											onChange={(e) =>
+												setPolicy({
+													...policy,
+													[key]: e.target.value === "" && optional ? null : Number(e.target.value),
+												})
+											}
+											className="h-9 font-mono text-sm"
+										/>
+									</div>
+								))}
+							</div>
+						</section>
+					)}
+
 					{/* Action Buttons */}
 					<div className="flex items-center gap-4 pt-4">
 						<Button onClick={handleSave} className="gap-2">
@@ -1,5 +1,5 @@
 import { Kimi, Minimax, ZAI } from "@lobehub/icons";
-import { CheckIcon, DownloadIcon, EllipsisVerticalIcon, PencilLineIcon, PlusIcon, ServerIcon, Trash2Icon } from "lucide-react";
+import { ActivityIcon, AlertTriangleIcon, CheckIcon, DownloadIcon, EllipsisVerticalIcon, PencilLineIcon, PlusIcon, ServerIcon, Trash2Icon } from "lucide-react";
 import { useTranslation } from "react-i18next";
 import { useNavigate } from "react-router-dom";
 import { useEffect, useState } from "react";
@@ -8,6 +8,8 @@ import { toast } from "sonner";
 import { GLMDialog } from "@/components/GLMBanner";
 import { KimiDialog } from "@/components/KimiDialog";
 import { MiniMaxDialog } from "@/components/MiniMaxDialog";
+import { ProfileDiffDialog } from "@/components/ProfileDiffDialog";
+import { ProfileHealthStatus } from "@/components/ProfileHealthStatus";
 import { Button } from "@/components/ui/button";
 import {
 	AlertDialog,
@@ -28,6 +30,9 @@ import {
 } from "@/components/ui/dropdown-menu";
 import { cn } from "@/lib/utils";
 import {
+	type ProfileHealth,
+	useActiveDrift,
+	useCheckProfileHealth,
 	useDeleteConfig,
 	useSetCurrentConfig,
 	useStores,
@@ -46,10 +51,15 @@ function ConfigStores() {
 	const { t } = useTranslation();
 	const { data: stores } = useStores();
 	const { data: systemEnvConfig } = useSystemEnvConfig();
+	const { data: activeDrift } = useActiveDrift();
 	const setCurrentStoreMutation = useSetCurrentConfig();
 	const deleteConfigMutation = useDeleteConfig();
+	const checkHealthMutation = useCheckProfileHealth();
 	const navigate = useNavigate();
 
+	const [health, setHealth] = useState<Record<string, ProfileHealth>>({});
+	const [checking, setChecking] = useState<Record<string, boolean>>({});
+
 	const [claudeInstalled, setClaudeInstalled] = useState<boolean | null>(null);
 	const [installing, setInstalling] = useState(false);
 
@@ -71,6 +81,19 @@ function ConfigStores() {
 		checkClaudeInstallation();
 	}, []);
 
+	const checkHealth = async (storeId: string) => {
+		setChecking((prev) => ({ ...prev, [storeId]: true }));
+		try {
+			const result = await checkHealthMutation.mutateAsync({
+				storeId,
+				target: "claude",
+			});
+			setHealth((prev) => ({ ...prev, [storeId]: result }));
+		} finally {
+			setChecking((prev) => ({ ...prev, [storeId]: false }));
+		}
+	};
+
 	const checkClaudeInstallation = async () => {
 		try {
 			const installed = await invoke<boolean>("check_command_exists", { command: "claude" });
@@ -209,6 +232,26 @@ function ConfigStores() {
 			</div>
 
 			<div className="flex-1 overflow-y-auto p-6 scrollbar-hide">
+				{activeDrift && (
+					<div className="flex items-start gap-2 rounded-lg border border-amber-500/40 bg-amber-500/10 p-3 mb-4 text-xs max-w-[1920px] mx-auto">
+						<AlertTriangleIcon size={14} className="text-amber-600 shrink-0 mt-0.5" />
+						<div>
+							<p className="font-medium">
+								{t("configSwitcher.drift", { title: activeDrift.store_title })}
+							</p>
+							<p className="text-muted-foreground mt-0.5 font-mono">
+								{activeDrift.pointers.join(", ")}
+							</p>
+						</div>
+						<ProfileDiffDialog
+							trigger={
+								<Button variant="outline" size="sm" className="ml-auto h-7 text-xs">
+									{t("configSwitcher.diff.review")}
+								</Button>
+							}
+						/>
+					</div>
+				)}
 				<div className="grid grid-cols-1 sm:grid-cols-2 lg:grid-cols-3 xl:grid-cols-4 2xl:grid-cols-5 gap-4 max-w-[1920px] mx-auto">
 					{/* New Config Card */}
 					<div
@@ -323,6 +366,13 @@ function ConfigStores() {
 											{store.settings.env.ANTHROPIC_BASE_URL}
 										</p>
 									)}
+									{checking[store.id] ? (
+										<span className="text-[10px] text-muted-foreground animate-pulse mt-0.5">
+											{t("profileHealth.checking")}
+										</span>
+									) : (
+										health[store.id] && <ProfileHealthStatus health={health[store.id]} />
+									)}
 								</div>
 
 								{/* Active Indicator */}
@@ -337,6 +387,20 @@ function ConfigStores() {
 
 								{/* Actions (Hover Overlay) */}
 								<div className="absolute right-2 top-1/2 -translate-y-1/2 flex items-center gap-1 opacity-0 group-hover:opacity-100 transition-all bg-background/95 backdrop-blur shadow-sm border rounded-md p-1 pl-1.5">
+									<Button
+										variant="ghost"
+										size="icon"
+										className="h-7 w-7 hover:text-primary"
+										title={t("profileHealth.check")}
+										disabled={checking[store.id]}
+										onClick={(e) => {
+											e.stopPropagation();
+											checkHealth(store.id);
+										}}
+									>
+										<ActivityIcon size={14} />
+									</Button>
+
 									<Button
 										variant="ghost"
 										size="icon"
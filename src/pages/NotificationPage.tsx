@@ -1,11 +1,38 @@
+import { useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
+import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
+import {
+	Select,
+	SelectContent,
+	SelectItem,
+	SelectTrigger,
+	SelectValue,
+} from "@/components/ui/select";
 import { Skeleton } from "@/components/ui/skeleton";
 import { Switch } from "@/components/ui/switch";
 import {
+	type ApprovalSettings,
+	type PermissionDecision,
+	useApprovalSettings,
 	useNotificationSettings,
+	useUpdateApprovalSettings,
 	useUpdateNotificationSettings,
 } from "@/lib/query";
+import { cn } from "@/lib/utils";
+
+// Hook events that can be turned on, with the i18n key of their label
+const HOOKS = [
+	{ name: "Notification", label: "notifications.waiting" },
+	{ name: "PreToolUse", label: "notifications.toolUse" },
+	{ name: "PostToolUse", label: "notifications.toolResult" },
+	{ name: "UserPromptSubmit", label: "notifications.prompt" },
+	{ name: "Stop", label: "notifications.completion" },
+	{ name: "SubagentStop", label: "notifications.subagentCompletion" },
+	{ name: "PreCompact", label: "notifications.compact" },
+	{ name: "SessionStart", label: "notifications.sessionStart" },
+	{ name: "SessionEnd", label: "notifications.sessionEnd" },
+];
 
 export function NotificationPage() {
 	const { t } = useTranslation();
@@ -115,41 +142,119 @@ export function NotificationPage() {
 						{t("notifications.generalDescription")}
 					</div>
 				</div>
-				<div className="border-b px-1 py-3">
-					<div className="flex items-center justify-between">
-						<Label htmlFor="preToolUse" className="">
-							{t("notifications.toolUse")}
-						</Label>
-						<Switch
-							id="preToolUse"
-							checked={isHookEnabled("PreToolUse")}
-							onCheckedChange={(checked) =>
-								handleHookToggle("PreToolUse", checked)
-							}
-							disabled={!notificationSettings?.enable}
-						/>
-					</div>
-					<div className="text-muted-foreground text-sm">
-						{t("notifications.toolUseDescription")}
+				{HOOKS.map((hook, index) => (
+					<div
+						key={hook.name}
+						className={cn("px-1 py-3", index < HOOKS.length - 1 && "border-b")}
+					>
+						<div className="flex items-center justify-between">
+							<Label htmlFor={hook.name} className="">
+								{t(hook.label)}
+							</Label>
+							<Switch
+								id={hook.name}
+								checked={isHookEnabled(hook.name)}
+								onCheckedChange={(checked) =>
+									handleHookToggle(hook.name, checked)
+								}
+								disabled={!notificationSettings?.enable}
+							/>
+						</div>
+						<div className="text-muted-foreground text-sm">
+							{t(`${hook.label}Description`)}
+						</div>
 					</div>
+				))}
+			</div>
+			<ApprovalSettingsCard />
+		</div>
+	);
+}
+
+const DECISIONS: PermissionDecision[] = ["ask", "allow", "deny"];
+
+// Holding tool calls until they are allowed or denied in the app
+function ApprovalSettingsCard() {
+	const { t } = useTranslation();
+	const { data: settings } = useApprovalSettings();
+	const updateSettings = useUpdateApprovalSettings();
+	const [timeoutSecs, setTimeoutSecs] = useState("");
+	const [tools, setTools] = useState("");
+
+	useEffect(() => {
+		if (!settings) return;
+		setTimeoutSecs(String(settings.timeout_secs));
+		setTools(settings.tools.join(", "));
+	}, [settings]);
+
+	if (!settings) return null;
+
+	const save = (changes: Partial<ApprovalSettings>) =>
+		updateSettings.mutate({ ...settings, ...changes });
+
+	return (
+		<div className="px-4 flex flex-col bg-card mx-4 my-4 rounded-lg py-1 border">
+			<div className="border-b px-1 py-3">
+				<div className="flex items-center justify-between">
+					<Label htmlFor="approvals">{t("notifications.approvals")}</Label>
+					<Switch
+						id="approvals"
+						checked={settings.enabled}
+						onCheckedChange={(enabled) => save({ enabled })}
+					/>
 				</div>
-				<div className="px-1 py-3">
-					<div className="flex items-center justify-between">
-						<Label htmlFor="stop" className="">
-							{t("notifications.completion")}
-						</Label>
-						<Switch
-							id="stop"
-							checked={isHookEnabled("Stop")}
-							onCheckedChange={(checked) => handleHookToggle("Stop", checked)}
-							disabled={!notificationSettings?.enable}
-						/>
-					</div>
-					<div className="text-muted-foreground text-sm">
-						{t("notifications.completionDescription")}
-					</div>
+				<div className="text-muted-foreground text-sm">
+					{t("notifications.approvalsDescription")}
 				</div>
 			</div>
+			<div className="border-b px-1 py-3 flex items-center justify-between gap-4">
+				<Label htmlFor="approvalTools">{t("notifications.approvalTools")}</Label>
+				<Input
+					id="approvalTools"
+					value={tools}
+					onChange={(e) => setTools(e.target.value)}
+					onBlur={() => save({ tools: tools.split(",") })}
+					placeholder={t("notifications.approvalToolsPlaceholder")}
+					className="max-w-[320px] font-mono text-sm"
+					disabled={!settings.enabled}
+				/>
+			</div>
+			<div className="border-b px-1 py-3 flex items-center justify-between gap-4">
+				<Label htmlFor="approvalTimeout">
+					{t("notifications.approvalTimeout")}
+				</Label>
+				<Input
+					id="approvalTimeout"
+					type="number"
+					min={5}
+					value={timeoutSecs}
+					onChange={(e) => setTimeoutSecs(e.target.value)}
+					onBlur={() => save({ timeout_secs: Number(timeoutSecs) })}
+					className="max-w-[120px]"
+					disabled={!settings.enabled}
+				/>
+			</div>
+			<div className="px-1 py-3 flex items-center justify-between gap-4">
+				<Label>{t("notifications.approvalTimeoutDecision")}</Label>
+				<Select
+					value={settings.timeout_decision}
+					onValueChange={(decision) =>
+						save({ timeout_decision: decision as PermissionDecision })
+					}
+					disabled={!settings.enabled}
+				>
+					<SelectTrigger className="w-[200px]">
+						<SelectValue />
+					</SelectTrigger>
+					<SelectContent>
+						{DECISIONS.map((decision) => (
+							<SelectItem key={decision} value={decision}>
+								{t(`approvals.${decision}`)}
+							</SelectItem>
+						))}
+					</SelectContent>
+				</Select>
+			</div>
 		</div>
 	);
 }
@@ -0,0 +1,323 @@
+import { ChevronDownIcon, ChevronRightIcon, Trash2Icon } from "lucide-react";
+import { useEffect, useMemo, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import {
+	Select,
+	SelectContent,
+	SelectItem,
+	SelectTrigger,
+	SelectValue,
+} from "@/components/ui/select";
+import {
+	type HookEventRecord,
+	type LiveSession,
+	type SessionStatus,
+	useActiveSessions,
+	useClearHookEvents,
+	useHookSessions,
+	useHookSessionTimeline,
+	useHookToolNames,
+} from "@/lib/query";
+import { cn } from "@/lib/utils";
+
+const ALL = "all";
+const HOUR = 60 * 60 * 1000;
+const RANGES: Record<string, number | undefined> = {
+	day: 24 * HOUR,
+	week: 7 * 24 * HOUR,
+	month: 30 * 24 * HOUR,
+	[ALL]: undefined,
+};
+const STATUS_STYLES: Record<SessionStatus, string> = {
+	running: "bg-green-500",
+	waiting_for_input: "bg-amber-500",
+	stopped: "bg-muted-foreground",
+};
+
+// What Claude Code did, from the hook events it sent the app
+export function SessionsPage() {
+	const { t } = useTranslation();
+	const [project, setProject] = useState("");
+	const [toolName, setToolName] = useState(ALL);
+	const [range, setRange] = useState("week");
+	const [selected, setSelected] = useState<string>();
+	const { data: toolNames } = useHookToolNames();
+	const clearEvents = useClearHookEvents();
+
+	const filter = useMemo(() => {
+		const span = RANGES[range];
+		return {
+			project: project.trim() || undefined,
+			tool_name: toolName === ALL ? undefined : toolName,
+			since: span ? Date.now() - span : undefined,
+			limit: 200,
+		};
+	}, [project, toolName, range]);
+	const { data: sessions } = useHookSessions(filter);
+	const { data: timeline } = useHookSessionTimeline(selected);
+	const { data: activeSessions } = useActiveSessions();
+
+	return (
+		<div className="">
+			<div
+				className="flex items-center p-3 border-b px-3 justify-between sticky top-0 bg-background z-10 mb-4"
+				data-tauri-drag-region
+			>
+				<div data-tauri-drag-region>
+					<h3 className="font-bold" data-tauri-drag-region>
+						{t("sessions.title")}
+					</h3>
+					<p className="text-sm text-muted-foreground" data-tauri-drag-region>
+						{t("sessions.description")}
+					</p>
+				</div>
+				<Button
+					variant="ghost"
+					size="sm"
+					className="text-muted-foreground"
+					onClick={() => {
+						clearEvents.mutate(undefined);
+						setSelected(undefined);
+					}}
+					disabled={clearEvents.isPending}
+				>
+					<Trash2Icon className="w-4 h-4" />
+					{t("sessions.clear")}
+				</Button>
+			</div>
+
+			{!!activeSessions?.length && (
+				<div className="mx-4 mb-4">
+					<h4 className="text-sm font-medium mb-2">{t("sessions.live")}</h4>
+					<div className="grid grid-cols-1 md:grid-cols-2 xl:grid-cols-3 gap-2">
+						{activeSessions.map((session) => (
+							<LiveSessionCard
+								key={session.session_id}
+								session={session}
+								selected={selected === session.session_id}
+								onSelect={() => setSelected(session.session_id)}
+							/>
+						))}
+					</div>
+				</div>
+			)}
+
+			<div className="mx-4 flex flex-wrap items-center gap-2">
+				<Input
+					value={project}
+					onChange={(e) => setProject(e.target.value)}
+					placeholder={t("sessions.projectPlaceholder")}
+					className="flex-1 min-w-[200px] font-mono text-sm"
+				/>
+				<Select value={toolName} onValueChange={setToolName}>
+					<SelectTrigger className="w-[160px]">
+						<SelectValue />
+					</SelectTrigger>
+					<SelectContent>
+						<SelectItem value={ALL}>{t("sessions.allTools")}</SelectItem>
+						{toolNames?.map((name) => (
+							<SelectItem key={name} value={name}>
+								{name}
+							</SelectItem>
+						))}
+					</SelectContent>
+				</Select>
+				<Select value={range} onValueChange={setRange}>
+					<SelectTrigger className="w-[160px]">
+						<SelectValue />
+					</SelectTrigger>
+					<SelectContent>
+						{Object.keys(RANGES).map((key) => (
+							<SelectItem key={key} value={key}>
+								{t(`sessions.range.${key}`)}
+							</SelectItem>
+						))}
+					</SelectContent>
+				</Select>
+			</div>
+
+			<div className="mx-4 mt-4 mb-6 grid grid-cols-1 lg:grid-cols-[minmax(0,2fr)_minmax(0,3fr)] gap-4">
+				<div className="bg-card rounded-lg border divide-y self-start">
+					{sessions?.length ? (
+						sessions.map((session) => (
+							<button
+								key={session.session_id}
+								type="button"
+								className={cn(
+									"w-full text-left px-4 py-3 hover:bg-accent/50",
+									selected === session.session_id && "bg-accent",
+								)}
+								onClick={() => setSelected(session.session_id)}
+							>
+								<p className="text-sm font-medium truncate font-mono">
+									{session.cwd || session.session_id}
+								</p>
+								<p className="text-xs text-muted-foreground truncate">
+									{t("sessions.summary", {
+										events: session.events,
+										tools: session.tool_uses,
+									})}{" "}
+									· {session.last_event_name} ·{" "}
+									{new Date(session.last_event_at).toLocaleString()}
+								</p>
+							</button>
+						))
+					) : (
+						<p className="px-4 py-3 text-sm text-muted-foreground">
+							{t("sessions.empty")}
+						</p>
+					)}
+				</div>
+
+				{selected && (
+					<div className="bg-card rounded-lg border divide-y self-start">
+						<p className="px-4 py-2 text-xs text-muted-foreground font-mono truncate">
+							{selected}
+						</p>
+						{timeline?.map((event) => (
+							<TimelineEvent key={event.id} event={event} />
+						))}
+					</div>
+				)}
+			</div>
+		</div>
+	);
+}
+
+function formatDuration(ms: number) {
+	const seconds = Math.max(0, Math.floor(ms / 1000));
+	const hours = Math.floor(seconds / 3600);
+	const minutes = Math.floor((seconds % 3600) / 60);
+	if (hours > 0) return `${hours}h ${minutes}m`;
+	if (minutes > 0) return `${minutes}m ${seconds % 60}s`;
+	return `${seconds}s`;
+}
+
+// Re-render every second so elapsed times keep counting
+function useNow() {
+	const [now, setNow] = useState(Date.now());
+	useEffect(() => {
+		const timer = setInterval(() => setNow(Date.now()), 1000);
+		return () => clearInterval(timer);
+	}, []);
+	return now;
+}
+
+function LiveSessionCard({
+	session,
+	selected,
+	onSelect,
+}: {
+	session: LiveSession;
+	selected: boolean;
+	onSelect: () => void;
+}) {
+	const { t } = useTranslation();
+	const now = useNow();
+
+	return (
+		<button
+			type="button"
+			className={cn(
+				"bg-card rounded-lg border px-4 py-3 text-left hover:bg-accent/50",
+				selected && "bg-accent",
+			)}
+			onClick={onSelect}
+		>
+			<div className="flex items-center gap-2">
+				<span
+					className={cn(
+						"w-2 h-2 rounded-full shrink-0",
+						STATUS_STYLES[session.status],
+					)}
+				/>
+				<p className="text-sm font-medium truncate font-mono">
+					{session.cwd || session.session_id}
+				</p>
+			</div>
+			<p className="text-xs text-muted-foreground mt-1">
+				{t("sessions.statusFor", {
+					status: t(`sessions.status.${session.status}`),
+					duration: formatDuration(now - session.status_since),
+				})}{" "}
+				·{" "}
+				{t("sessions.elapsed", {
+					duration: formatDuration(now - session.started_at),
+				})}
+				{session.current_tool && (
+					<span className="ml-2 rounded bg-muted px-1.5 text-foreground">
+						{session.current_tool}
+					</span>
+				)}
+			</p>
+			{session.last_message && (
+				<p className="text-xs text-muted-foreground mt-1 truncate">
+					{session.last_message}
+				</p>
+			)}
+		</button>
+	);
+}
+
+// The part of an event worth showing at a glance
+function eventSummary(event: HookEventRecord) {
+	const input = event.tool_input ?? {};
+	const fields = [
+		input.command,
+		input.file_path,
+		input.pattern,
+		input.url,
+		input.description,
+		event.payload.prompt,
+		event.payload.message,
+		event.payload.reason,
+	];
+	const summary = fields.find((field) => typeof field === "string");
+	return typeof summary === "string" ? summary : undefined;
+}
+
+function TimelineEvent({ event }: { event: HookEventRecord }) {
+	const [expanded, setExpanded] = useState(false);
+	const summary = eventSummary(event);
+
+	return (
+		<div className="px-4 py-2 text-xs">
+			<button
+				type="button"
+				className="flex w-full items-start gap-2 text-left"
+				onClick={() => setExpanded(!expanded)}
+			>
+				{expanded ? (
+					<ChevronDownIcon className="w-3 h-3 mt-0.5 shrink-0" />
+				) : (
+					<ChevronRightIcon className="w-3 h-3 mt-0.5 shrink-0" />
+				)}
+				<span className="text-muted-foreground shrink-0">
+					{new Date(event.timestamp).toLocaleTimeString()}
+				</span>
+				<span className="font-medium shrink-0">{event.hook_event_name}</span>
+				{event.tool_name && (
+					<span className="rounded bg-muted px-1.5 shrink-0">
+						{event.tool_name}
+					</span>
+				)}
+				{summary && (
+					<span className="truncate font-mono text-muted-foreground">
+						{summary}
+					</span>
+				)}
+			</button>
+			{expanded && (
+				<pre className="mt-2 bg-muted rounded p-2 max-h-64 overflow-auto whitespace-pre-wrap break-all">
+					{JSON.stringify(
+						{ cwd: event.cwd, tool_input: event.tool_input, ...event.payload },
+						null,
+						2,
+					)}
+				</pre>
+			)}
+		</div>
+	);
+}
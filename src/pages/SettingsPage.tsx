@@ -1,9 +1,18 @@
 import { getVersion } from "@tauri-apps/api/app";
-import { DownloadIcon, RotateCwIcon } from "lucide-react";
+import { ask, message, open, save } from "@tauri-apps/plugin-dialog";
+import {
+	DownloadIcon,
+	FileDownIcon,
+	FileUpIcon,
+	LockIcon,
+	RotateCwIcon,
+	UnlockIcon,
+} from "lucide-react";
 import { useTheme } from "next-themes";
 import { useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
 import {
 	Select,
 	SelectContent,
@@ -11,7 +20,20 @@ import {
 	SelectTrigger,
 	SelectValue,
 } from "@/components/ui/select";
-import { useCheckForUpdates, useInstallAndRestart } from "@/lib/query";
+import { Switch } from "@/components/ui/switch";
+import {
+	type ConflictStrategy,
+	useCheckForUpdates,
+	useExportProfiles,
+	useImportProfiles,
+	useInstallAndRestart,
+	useLockVault,
+	useRelaySettings,
+	useSetVaultPassphrase,
+	useUnlockVault,
+	useUpdateRelaySettings,
+	useVaultStatus,
+} from "@/lib/query";
 
 export function SettingsPage() {
 	const { t, i18n } = useTranslation();
@@ -22,10 +44,36 @@ export function SettingsPage() {
 		useInstallAndRestart();
 	const [version, setVersion] = useState<string>("");
 
+	const { data: vaultStatus } = useVaultStatus();
+	const { mutate: unlockVault, isPending: unlockingVault } = useUnlockVault();
+	const { mutate: lockVault } = useLockVault();
+	const { mutate: setVaultPassphrase, isPending: settingPassphrase } =
+		useSetVaultPassphrase();
+	const [passphrase, setPassphrase] = useState("");
+	const usesPassphrase = vaultStatus?.key_source === "passphrase";
+
+	const { mutate: exportProfiles, isPending: exporting } = useExportProfiles();
+	const { mutateAsync: importProfiles, isPending: importing } =
+		useImportProfiles();
+	const [bundlePassphrase, setBundlePassphrase] = useState("");
+	const [conflictStrategy, setConflictStrategy] =
+		useState<ConflictStrategy>("skip");
+
+	const { data: relaySettings } = useRelaySettings();
+	const { mutate: updateRelay, isPending: updatingRelay } =
+		useUpdateRelaySettings();
+	const [relayPort, setRelayPort] = useState("");
+
 	useEffect(() => {
 		getVersion().then(setVersion);
 	}, []);
 
+	useEffect(() => {
+		if (relaySettings) {
+			setRelayPort(String(relaySettings.port));
+		}
+	}, [relaySettings]);
+
 	const handleLanguageChange = (language: string) => {
 		i18n.changeLanguage(language);
 	};
@@ -34,6 +82,82 @@ export function SettingsPage() {
 		installUpdate();
 	};
 
+	const handleUnlockVault = () => {
+		unlockVault(passphrase, { onSuccess: () => setPassphrase("") });
+	};
+
+	const handleSetVaultPassphrase = () => {
+		setVaultPassphrase(passphrase, { onSuccess: () => setPassphrase("") });
+	};
+
+	const relayPortValue = Number(relayPort);
+	const relayPortValid =
+		Number.isInteger(relayPortValue) &&
+		relayPortValue > 0 &&
+		relayPortValue <= 65535;
+
+	const handleToggleRelay = (enabled: boolean) => {
+		if (!relaySettings) return;
+		updateRelay({ ...relaySettings, enabled });
+	};
+
+	const handleToggleRelayBodies = (log_bodies: boolean) => {
+		if (!relaySettings) return;
+		updateRelay({ ...relaySettings, log_bodies });
+	};
+
+	const handleApplyRelayPort = () => {
+		if (!relaySettings || !relayPortValid) return;
+		updateRelay({ ...relaySettings, port: relayPortValue });
+	};
+
+	const handleExportProfiles = async () => {
+		const filePath = await save({
+			defaultPath: "tvcbuddy-profiles.json",
+			filters: [{ name: "JSON", extensions: ["json"] }],
+		});
+		if (!filePath) return;
+		exportProfiles({ filePath, passphrase: bundlePassphrase || undefined });
+	};
+
+	const handleImportProfiles = async () => {
+		const filePath = await open({
+			multiple: false,
+			filters: [{ name: "JSON", extensions: ["json"] }],
+		});
+		if (!filePath) return;
+
+		const request = {
+			filePath,
+			passphrase: bundlePassphrase || undefined,
+			strategy: conflictStrategy,
+		};
+		// Preview what the import would do before touching anything
+		const preview = await importProfiles({ ...request, dryRun: true });
+		if (preview.items.length === 0) {
+			await message(t("settings.bundle.empty"), {
+				title: t("settings.bundle.importTitle"),
+			});
+			return;
+		}
+
+		const summary = preview.items
+			.map(
+				(item) =>
+					`${t(`settings.bundle.action.${item.action}`)}: ${item.name}${
+						item.renamed_to ? ` → ${item.renamed_to}` : ""
+					}`,
+			)
+			.join("\n");
+		const confirmed = await ask(summary, {
+			title: t("settings.bundle.importTitle"),
+			kind: "info",
+		});
+		if (confirmed) {
+			await importProfiles({ ...request, dryRun: false });
+		}
+	};
+
 	return (
 		<div className="">
 			<div
@@ -82,6 +206,181 @@ export function SettingsPage() {
 					</Select>
 				</div>
 
+				<div>
+					<label className="block text-sm font-medium mb-2 mx-2">
+						{t("settings.vault")}
+					</label>
+					<p className="text-xs text-muted-foreground mx-2 mb-2">
+						{!vaultStatus?.initialized
+							? t("settings.vault.empty")
+							: usesPassphrase
+								? t("settings.vault.passphrase", {
+										count: vaultStatus.secrets,
+									})
+								: vaultStatus.key_source === "keyring"
+									? t("settings.vault.keyring", {
+											count: vaultStatus.secrets,
+										})
+									: t("settings.vault.file", {
+											count: vaultStatus.secrets,
+										})}
+					</p>
+					<div className="flex items-center gap-2 mx-2">
+						<Input
+							type="password"
+							className="w-[220px]"
+							value={passphrase}
+							onChange={(e) => setPassphrase(e.target.value)}
+							placeholder={t("settings.vault.passphrasePlaceholder")}
+						/>
+						{usesPassphrase && vaultStatus?.locked ? (
+							<Button
+								variant="outline"
+								size="sm"
+								onClick={handleUnlockVault}
+								disabled={!passphrase || unlockingVault}
+							>
+								<UnlockIcon className="w-4 h-4 mr-1" />
+								{t("settings.vault.unlock")}
+							</Button>
+						) : (
+							<Button
+								variant="outline"
+								size="sm"
+								onClick={handleSetVaultPassphrase}
+								disabled={passphrase.length < 8 || settingPassphrase}
+							>
+								{t("settings.vault.setPassphrase")}
+							</Button>
+						)}
+						{usesPassphrase && !vaultStatus?.locked && (
+							<>
+								<Button variant="ghost" size="sm" onClick={() => lockVault()}>
+									<LockIcon className="w-4 h-4 mr-1" />
+									{t("settings.vault.lock")}
+								</Button>
+								<Button
+									variant="ghost"
+									size="sm"
+									onClick={() => setVaultPassphrase(null)}
+									disabled={settingPassphrase}
+								>
+									{t("settings.vault.removePassphrase")}
+								</Button>
+							</>
+						)}
+					</div>
+				</div>
+
+				<div>
+					<label className="block text-sm font-medium mb-2 mx-2">
+						{t("settings.bundle")}
+					</label>
+					<p className="text-xs text-muted-foreground mx-2 mb-2">
+						{t("settings.bundle.description")}
+					</p>
+					<div className="flex flex-wrap items-center gap-2 mx-2">
+						<Input
+							type="password"
+							className="w-[220px]"
+							value={bundlePassphrase}
+							onChange={(e) => setBundlePassphrase(e.target.value)}
+							placeholder={t("settings.bundle.passphrasePlaceholder")}
+						/>
+						<Select
+							value={conflictStrategy}
+							onValueChange={(value) =>
+								setConflictStrategy(value as ConflictStrategy)
+							}
+						>
+							<SelectTrigger className="w-[150px]">
+								<SelectValue />
+							</SelectTrigger>
+							<SelectContent>
+								<SelectItem value="skip">
+									{t("settings.bundle.onConflict.skip")}
+								</SelectItem>
+								<SelectItem value="rename">
+									{t("settings.bundle.onConflict.rename")}
+								</SelectItem>
+								<SelectItem value="overwrite">
+									{t("settings.bundle.onConflict.overwrite")}
+								</SelectItem>
+							</SelectContent>
+						</Select>
+						<Button
+							variant="outline"
+							size="sm"
+							onClick={handleExportProfiles}
+							disabled={exporting}
+						>
+							<FileDownIcon className="w-4 h-4 mr-1" />
+							{t("settings.bundle.export")}
+						</Button>
+						<Button
+							variant="outline"
+							size="sm"
+							onClick={handleImportProfiles}
+							disabled={importing}
+						>
+							<FileUpIcon className="w-4 h-4 mr-1" />
+							{t("settings.bundle.import")}
+						</Button>
+					</div>
+				</div>
+
+				<div>
+					<label className="block text-sm font-medium mb-2 mx-2">
+						{t("settings.relay")}
+					</label>
+					<p className="text-xs text-muted-foreground mx-2 mb-2">
+						{t("settings.relay.description", {
+							url: `http://127.0.0.1:${relaySettings?.port ?? relayPort}`,
+						})}
+					</p>
+					<div className="flex flex-wrap items-center gap-2 mx-2">
+						<Switch
+							checked={relaySettings?.enabled ?? false}
+							onCheckedChange={handleToggleRelay}
+							disabled={!relaySettings || updatingRelay}
+						/>
+						<span className="text-sm">{t("settings.relay.enabled")}</span>
+					</div>
+					<div className="flex flex-wrap items-center gap-2 mx-2 mt-2">
+						<Switch
+							checked={relaySettings?.log_bodies ?? false}
+							onCheckedChange={handleToggleRelayBodies}
+							disabled={!relaySettings || updatingRelay}
+						/>
+						<span className="text-sm">{t("settings.relay.logBodies")}</span>
+					</div>
+					<div className="flex items-center gap-2 mx-2 mt-2">
+						<span className="text-sm text-muted-foreground">
+							{t("settings.relay.port")}
+						</span>
+						<Input
+							type="number"
+							className="w-[110px]"
+							min={1}
+							max={65535}
+							value={relayPort}
+							onChange={(e) => setRelayPort(e.target.value)}
+						/>
+						<Button
+							variant="outline"
+							size="sm"
+							onClick={handleApplyRelayPort}
+							disabled={
+								!relayPortValid ||
+								relayPortValue === relaySettings?.port ||
+								updatingRelay
+							}
+						>
+							{t("settings.relay.applyPort")}
+						</Button>
+					</div>
+				</div>
+
 				<div>
 					<label className="block text-sm font-medium mb-2 mx-2">
 						{t("settings.version")}
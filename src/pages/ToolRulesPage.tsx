@@ -0,0 +1,438 @@
+import { ArrowUpIcon, PlayIcon, PlusIcon, Trash2Icon } from "lucide-react";
+import { useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import {
+	Select,
+	SelectContent,
+	SelectItem,
+	SelectTrigger,
+	SelectValue,
+} from "@/components/ui/select";
+import { Switch } from "@/components/ui/switch";
+import {
+	type RuleAction,
+	type ToolCondition,
+	type ToolRule,
+	type ToolRuleSettings,
+	useEvaluateToolRules,
+	useToolRuleDecisions,
+	useToolRules,
+	useUpdateToolRules,
+} from "@/lib/query";
+
+type ConditionType = ToolCondition["type"];
+
+const ACTIONS: RuleAction[] = ["deny", "allow", "ask", "approve"];
+
+const splitTools = (tools: string) =>
+	tools
+		.split(",")
+		.map((tool) => tool.trim())
+		.filter(Boolean);
+
+export function ToolRulesPage() {
+	const { t } = useTranslation();
+	const { data: settings } = useToolRules();
+	const updateSettings = useUpdateToolRules();
+
+	const [name, setName] = useState("");
+	const [tools, setTools] = useState("");
+	const [conditionType, setConditionType] =
+		useState<ConditionType>("input_matches");
+	const [field, setField] = useState("command");
+	const [pattern, setPattern] = useState("");
+	const [action, setAction] = useState<RuleAction>("deny");
+	const [reason, setReason] = useState("");
+
+	const save = (changes: Partial<ToolRuleSettings>) => {
+		if (!settings) return;
+		updateSettings.mutate({ ...settings, ...changes });
+	};
+
+	const describeCondition = (condition: ToolCondition) => {
+		switch (condition.type) {
+			case "always":
+				return t("toolRules.condition.always");
+			case "input_matches":
+				return t("toolRules.condition.inputMatchesSummary", {
+					field: condition.field,
+					pattern: condition.pattern,
+				});
+			case "outside_cwd":
+				return t("toolRules.condition.outsideCwdSummary");
+		}
+	};
+
+	const buildCondition = (): ToolCondition => {
+		switch (conditionType) {
+			case "always":
+				return { type: "always" };
+			case "input_matches":
+				return { type: "input_matches", field: field.trim(), pattern };
+			case "outside_cwd":
+				return { type: "outside_cwd" };
+		}
+	};
+
+	const handleAddRule = () => {
+		if (!settings) return;
+		const rule: ToolRule = {
+			id: "",
+			name: name.trim(),
+			enabled: true,
+			tools: splitTools(tools),
+			condition: buildCondition(),
+			action,
+			reason: reason.trim() || undefined,
+		};
+		updateSettings.mutate(
+			{ ...settings, rules: [...settings.rules, rule] },
+			{
+				onSuccess: () => {
+					setName("");
+					setTools("");
+					setPattern("");
+					setReason("");
+				},
+			},
+		);
+	};
+
+	const handleToggleRule = (ruleId: string, enabled: boolean) => {
+		if (!settings) return;
+		save({
+			rules: settings.rules.map((rule) =>
+				rule.id === ruleId ? { ...rule, enabled } : rule,
+			),
+		});
+	};
+
+	const handleMoveUp = (index: number) => {
+		if (!settings) return;
+		const reordered = [...settings.rules];
+		[reordered[index - 1], reordered[index]] = [
+			reordered[index],
+			reordered[index - 1],
+		];
+		save({ rules: reordered });
+	};
+
+	const handleDeleteRule = (ruleId: string) => {
+		if (!settings) return;
+		save({ rules: settings.rules.filter((rule) => rule.id !== ruleId) });
+	};
+
+	return (
+		<div className="">
+			<div
+				className="flex items-center p-3 border-b px-3 justify-between sticky top-0 bg-background z-10 mb-4"
+				data-tauri-drag-region
+			>
+				<div data-tauri-drag-region>
+					<h3 className="font-bold" data-tauri-drag-region>
+						{t("toolRules.title")}
+					</h3>
+					<p className="text-sm text-muted-foreground" data-tauri-drag-region>
+						{t("toolRules.description")}
+					</p>
+				</div>
+			</div>
+
+			<div className="px-4 flex flex-col bg-card mx-4 rounded-lg py-1 border">
+				<div className="border-b px-1 py-3">
+					<div className="flex items-center justify-between">
+						<Label htmlFor="tool-rules">{t("toolRules.enable")}</Label>
+						<Switch
+							id="tool-rules"
+							checked={settings?.enabled || false}
+							onCheckedChange={(enabled) => save({ enabled })}
+						/>
+					</div>
+					<div className="text-muted-foreground text-sm">
+						{t("toolRules.enableDescription")}
+					</div>
+				</div>
+				<div className="px-1 py-3">
+					<div className="flex items-center justify-between">
+						<Label htmlFor="tool-rules-dry-run">{t("toolRules.dryRun")}</Label>
+						<Switch
+							id="tool-rules-dry-run"
+							checked={settings?.dry_run || false}
+							onCheckedChange={(dry_run) => save({ dry_run })}
+						/>
+					</div>
+					<div className="text-muted-foreground text-sm">
+						{t("toolRules.dryRunDescription")}
+					</div>
+				</div>
+			</div>
+
+			<div className="mx-4 mt-6">
+				<h4 className="text-sm font-medium mb-2 mx-1">
+					{t("toolRules.rules")}
+				</h4>
+				<div className="bg-card rounded-lg border divide-y">
+					{settings?.rules.length ? (
+						settings.rules.map((rule, index) => (
+							<div
+								key={rule.id}
+								className="flex items-center gap-3 px-4 py-3"
+							>
+								<div className="flex-1 min-w-0">
+									<p className="text-sm font-medium truncate">{rule.name}</p>
+									<p className="text-xs text-muted-foreground truncate">
+										{rule.tools.length
+											? rule.tools.join(", ")
+											: t("toolRules.allTools")}{" "}
+										· {describeCondition(rule.condition)} →{" "}
+										{t(`toolRules.action.${rule.action}`)}
+									</p>
+								</div>
+								<Switch
+									checked={rule.enabled}
+									onCheckedChange={(enabled) =>
+										handleToggleRule(rule.id, enabled)
+									}
+								/>
+								<Button
+									variant="ghost"
+									size="icon"
+									disabled={index === 0}
+									onClick={() => handleMoveUp(index)}
+								>
+									<ArrowUpIcon size={14} />
+								</Button>
+								<Button
+									variant="ghost"
+									size="icon"
+									onClick={() => handleDeleteRule(rule.id)}
+								>
+									<Trash2Icon size={14} />
+								</Button>
+							</div>
+						))
+					) : (
+						<p className="text-sm text-muted-foreground px-4 py-3">
+							{t("toolRules.noRules")}
+						</p>
+					)}
+
+					<div className="flex flex-wrap items-center gap-2 px-4 py-3">
+						<Input
+							className="w-[160px]"
+							value={name}
+							onChange={(e) => setName(e.target.value)}
+							placeholder={t("toolRules.namePlaceholder")}
+						/>
+						<Input
+							className="w-[180px] font-mono"
+							value={tools}
+							onChange={(e) => setTools(e.target.value)}
+							placeholder={t("toolRules.toolsPlaceholder")}
+						/>
+						<Select
+							value={conditionType}
+							onValueChange={(value) => setConditionType(value as ConditionType)}
+						>
+							<SelectTrigger className="w-[160px]">
+								<SelectValue />
+							</SelectTrigger>
+							<SelectContent>
+								<SelectItem value="input_matches">
+									{t("toolRules.condition.inputMatches")}
+								</SelectItem>
+								<SelectItem value="outside_cwd">
+									{t("toolRules.condition.outsideCwd")}
+								</SelectItem>
+								<SelectItem value="always">
+									{t("toolRules.condition.always")}
+								</SelectItem>
+							</SelectContent>
+						</Select>
+						{conditionType === "input_matches" && (
+							<>
+								<Input
+									className="w-[120px] font-mono"
+									value={field}
+									onChange={(e) => setField(e.target.value)}
+									placeholder={t("toolRules.fieldPlaceholder")}
+								/>
+								<Input
+									className="w-[200px] font-mono"
+									value={pattern}
+									onChange={(e) => setPattern(e.target.value)}
+									placeholder={t("toolRules.patternPlaceholder")}
+								/>
+							</>
+						)}
+						<Select
+							value={action}
+							onValueChange={(value) => setAction(value as RuleAction)}
+						>
+							<SelectTrigger className="w-[180px]">
+								<SelectValue />
+							</SelectTrigger>
+							<SelectContent>
+								{ACTIONS.map((value) => (
+									<SelectItem key={value} value={value}>
+										{t(`toolRules.action.${value}`)}
+									</SelectItem>
+								))}
+							</SelectContent>
+						</Select>
+						<Input
+							className="w-[220px]"
+							value={reason}
+							onChange={(e) => setReason(e.target.value)}
+							placeholder={t("toolRules.reasonPlaceholder")}
+						/>
+						<Button
+							variant="outline"
+							size="sm"
+							onClick={handleAddRule}
+							disabled={
+								!name.trim() ||
+								(conditionType === "input_matches" &&
+									(!field.trim() || !pattern)) ||
+								updateSettings.isPending
+							}
+						>
+							<PlusIcon className="w-4 h-4 mr-1" />
+							{t("toolRules.addRule")}
+						</Button>
+					</div>
+				</div>
+			</div>
+
+			<RuleTester />
+			<RuleDecisions />
+		</div>
+	);
+}
+
+// Tool calls the rules matched, with what they decided or would have in dry-run mode
+function RuleDecisions() {
+	const { t } = useTranslation();
+	const { data: decisions } = useToolRuleDecisions();
+
+	return (
+		<div className="mx-4 mb-8">
+			<h4 className="text-sm font-medium mb-2 mx-1">
+				{t("toolRules.decisions")}
+			</h4>
+			<div className="bg-card rounded-lg border divide-y">
+				{decisions?.length ? (
+					decisions.map((decision) => (
+						<div key={decision.id} className="px-4 py-2">
+							<div className="flex items-center gap-2 text-sm">
+								<span className="font-medium truncate">
+									{decision.rule_name}
+								</span>
+								<span>→ {t(`toolRules.action.${decision.action}`)}</span>
+								{decision.dry_run && (
+									<span className="text-xs rounded bg-secondary px-1.5 py-0.5">
+										{t("toolRules.decisionsDryRun")}
+									</span>
+								)}
+								<span className="ml-auto text-xs text-muted-foreground">
+									{new Date(decision.timestamp).toLocaleString()}
+								</span>
+							</div>
+							<p className="text-xs font-mono text-muted-foreground truncate">
+								{decision.summary}
+							</p>
+							<p className="text-xs text-muted-foreground truncate">
+								{decision.reason}
+							</p>
+						</div>
+					))
+				) : (
+					<p className="text-sm text-muted-foreground px-4 py-3">
+						{t("toolRules.decisionsEmpty")}
+					</p>
+				)}
+			</div>
+		</div>
+	);
+}
+
+// Try a tool call against the saved rules, whether or not they are on
+function RuleTester() {
+	const { t } = useTranslation();
+	const evaluateRules = useEvaluateToolRules();
+	const [toolName, setToolName] = useState("Bash");
+	const [toolInput, setToolInput] = useState("");
+	const [cwd, setCwd] = useState("");
+	const [invalidInput, setInvalidInput] = useState(false);
+
+	const handleTest = () => {
+		let parsed: unknown;
+		try {
+			parsed = toolInput.trim() ? JSON.parse(toolInput) : {};
+		} catch {
+			setInvalidInput(true);
+			return;
+		}
+		setInvalidInput(false);
+		evaluateRules.mutate({ toolName: toolName.trim(), toolInput: parsed, cwd });
+	};
+
+	const result = evaluateRules.data;
+
+	return (
+		<div className="mx-4 my-6">
+			<h4 className="text-sm font-medium mb-2 mx-1">
+				{t("toolRules.testTitle")}
+			</h4>
+			<div className="bg-card rounded-lg border px-4 py-3 space-y-2">
+				<div className="flex flex-wrap items-center gap-2">
+					<Input
+						className="w-[140px] font-mono"
+						value={toolName}
+						onChange={(e) => setToolName(e.target.value)}
+						placeholder={t("toolRules.testToolPlaceholder")}
+					/>
+					<Input
+						className="flex-1 min-w-[240px] font-mono"
+						value={toolInput}
+						onChange={(e) => setToolInput(e.target.value)}
+						placeholder='{"command": "rm -rf build"}'
+					/>
+					<Input
+						className="w-[200px] font-mono"
+						value={cwd}
+						onChange={(e) => setCwd(e.target.value)}
+						placeholder={t("toolRules.testCwdPlaceholder")}
+					/>
+					<Button
+						variant="outline"
+						size="sm"
+						onClick={handleTest}
+						disabled={!toolName.trim() || evaluateRules.isPending}
+					>
+						<PlayIcon className="w-4 h-4 mr-1" />
+						{t("toolRules.test")}
+					</Button>
+				</div>
+				{invalidInput ? (
+					<p className="text-xs text-red-600">{t("toolRules.invalidJson")}</p>
+				) : (
+					evaluateRules.isSuccess && (
+						<p className="text-xs text-muted-foreground">
+							{result
+								? t("toolRules.testMatch", {
+										rule: result.rule_name,
+										action: t(`toolRules.action.${result.action}`),
+										reason: result.reason,
+									})
+								: t("toolRules.testNoMatch")}
+						</p>
+					)
+				)}
+			</div>
+		</div>
+	);
+}
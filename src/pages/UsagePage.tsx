@@ -7,6 +7,7 @@ import {
 import { useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { ActivityGrid } from "@/components/ActivityGrid";
+import { RelayUsagePanel } from "@/components/RelayUsagePanel";
 import { TokenUsageChart } from "@/components/TokenUsageChart";
 import { Button } from "@/components/ui/button";
 import { TooltipProvider } from "@/components/ui/tooltip";
@@ -179,6 +180,7 @@ export function UsagePage() {
 				) : (
 					<p>{t("usage.noData")}</p>
 				)}
+				<RelayUsagePanel />
 			</div>
 		</TooltipProvider>
 	);
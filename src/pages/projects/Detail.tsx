@@ -14,6 +14,10 @@ import { useNavigate, useParams } from "react-router-dom";
 import { toast } from "sonner";
 import { Alert, AlertDescription } from "../../components/ui/alert";
 import { Button } from "../../components/ui/button";
+import {
+	NativeSelect,
+	NativeSelectOption,
+} from "../../components/ui/native-select";
 import {
 	Command,
 	CommandEmpty,
@@ -28,8 +32,12 @@ import {
 	PopoverTrigger,
 } from "../../components/ui/popover";
 import {
+	useBindProjectConfig,
 	useClaudeConfigFile,
 	useClaudeProjects,
+	useProjectBindings,
+	useStores,
+	useUnbindProjectConfig,
 	useWriteClaudeConfigFile,
 } from "../../lib/query";
 import { useCodeMirrorTheme } from "../../lib/use-codemirror-theme";
@@ -50,6 +58,10 @@ export function Detail() {
 		error: configError,
 	} = useClaudeConfigFile();
 	const writeClaudeConfig = useWriteClaudeConfigFile();
+	const { data: stores } = useStores();
+	const { data: bindings } = useProjectBindings();
+	const bindProjectConfig = useBindProjectConfig();
+	const unbindProjectConfig = useUnbindProjectConfig();
 	const [jsonContent, setJsonContent] = useState("");
 	const [hasChanges, setHasChanges] = useState(false);
 	const [comboboxOpen, setComboboxOpen] = useState(false);
@@ -112,6 +124,22 @@ export function Detail() {
 		[hasChanges, handleSave, navigate, t],
 	);
 
+	const handleBindingChange = useCallback(
+		(projectPath: string, storeId: string) => {
+			if (storeId) {
+				bindProjectConfig.mutate({ projectPath, storeId });
+			} else {
+				unbindProjectConfig.mutate(projectPath);
+			}
+		},
+		[bindProjectConfig, unbindProjectConfig],
+	);
+
+	const storeTitleFor = (projectPath: string) => {
+		const binding = bindings?.find((b) => b.project_path === projectPath);
+		return stores?.find((store) => store.id === binding?.store_id)?.title;
+	};
+
 	// Create save keymap for CodeMirror
 	const saveKeymap = keymap.of([
 		{
@@ -250,6 +278,11 @@ export function Detail() {
 											>
 												<FolderIcon className="mr-2 h-4 w-4" />
 												<span className="truncate">{proj.path}</span>
+												{storeTitleFor(proj.path) && (
+													<span className="ml-2 shrink-0 text-xs text-muted-foreground">
+														{storeTitleFor(proj.path)}
+													</span>
+												)}
 												<Check
 													className={cn(
 														"ml-auto h-4 w-4",
@@ -268,6 +301,29 @@ export function Detail() {
 				</div>
 
 				<div className="flex items-center gap-2">
+					<span className="text-xs text-muted-foreground">
+						{t("projects.detail.boundConfig")}
+					</span>
+					<NativeSelect
+						className="h-8 text-xs"
+						value={
+							bindings?.find((b) => b.project_path === project.path)
+								?.store_id ?? ""
+						}
+						disabled={
+							bindProjectConfig.isPending || unbindProjectConfig.isPending
+						}
+						onChange={(e) => handleBindingChange(project.path, e.target.value)}
+					>
+						<NativeSelectOption value="">
+							{t("projects.detail.globalConfig")}
+						</NativeSelectOption>
+						{stores?.map((store) => (
+							<NativeSelectOption key={store.id} value={store.id}>
+								{store.title}
+							</NativeSelectOption>
+						))}
+					</NativeSelect>
 					<Button
 						onClick={handleSave}
 						disabled={!hasChanges || writeClaudeConfig.isPending}
@@ -2,6 +2,7 @@ import { createBrowserRouter, RouterProvider } from "react-router-dom";
 import { Layout } from "./components/Layout";
 import { RouteWrapper } from "./components/RouteWrapper";
 import { AgentsPage } from "./pages/AgentsPage";
+import { AutoSwitchPage } from "./pages/AutoSwitchPage";
 import { CodexPage } from "./pages/CodexPage";
 import { CodexConfigPage } from "./pages/CodexConfigPage";
 import { CodexSettingsPage } from "./pages/CodexSettingsPage";
@@ -12,7 +13,9 @@ import { MCPPage } from "./pages/MCPPage";
 import { MemoryPage } from "./pages/MemoryPage";
 import { NotificationPage } from "./pages/NotificationPage";
 import { ProxyPage } from "./pages/ProxyPage";
+import { SessionsPage } from "./pages/SessionsPage";
 import { SettingsPage } from "./pages/SettingsPage";
+import { ToolRulesPage } from "./pages/ToolRulesPage";
 import { UsagePage } from "./pages/UsagePage";
 import { WelcomePage } from "./pages/WelcomePage";
 import { Detail } from "./pages/projects/Detail";
@@ -115,6 +118,14 @@ const router = createBrowserRouter([
 					</RouteWrapper>
 				),
 			},
+			{
+				path: "auto-switch",
+				element: (
+					<RouteWrapper>
+						<AutoSwitchPage />
+					</RouteWrapper>
+				),
+			},
 			{
 				path: "usage",
 				element: (
@@ -131,6 +142,14 @@ const router = createBrowserRouter([
 					</RouteWrapper>
 				),
 			},
+			{
+				path: "sessions",
+				element: (
+					<RouteWrapper>
+						<SessionsPage />
+					</RouteWrapper>
+				),
+			},
 			{
 				path: "notification",
 				element: (
@@ -139,6 +158,14 @@ const router = createBrowserRouter([
 					</RouteWrapper>
 				),
 			},
+			{
+				path: "tool-rules",
+				element: (
+					<RouteWrapper>
+						<ToolRulesPage />
+					</RouteWrapper>
+				),
+			},
 			{
 				path: "commands",
 				element: (